@@ -0,0 +1,205 @@
+//! Drive the C API with arbitrary call sequences and weights over live, freed, forged and
+//! foreign handles
+//!
+//! The input is a stream of operation/operand byte pairs; operations that load or swap weights
+//! take their blob from the bytes that follow (see `next_weights`). Handles are never
+//! dereferenced by the runtime, so forged values are fair game. Fuzz builds abort on panic, so
+//! any crash, abort, sanitizer report or unexpected code is a bug in the FFI layer or a parser.
+
+#![no_main]
+
+use leanrl_core::{error::ffi::*, ffi::*};
+use libfuzzer_sys::fuzz_target;
+use std::ptr;
+
+/// Valid 4x2 blob of algorithm `algorithm % 3`, so call sequences reach live handles
+fn seed_weights(algorithm: u8) -> Vec<u8> {
+    let algorithm = algorithm % 3;
+    let mut blob = vec![algorithm];
+    let params = match algorithm {
+        // TabularQ: 2 states x 2 actions
+        0 => {
+            blob.extend(2u32.to_le_bytes());
+            blob.extend(2u32.to_le_bytes());
+            blob.extend(0.1f32.to_le_bytes());
+            blob.extend(0.9f32.to_le_bytes());
+            2 * 2
+        }
+        // LinearFA: learning rate, 2x4 weights, 2 biases
+        1 => 1 + 2 * 4 + 2,
+        // TinyNN: 4 -> 64 -> 32 -> 2 with ReLU, ReLU, Tanh
+        _ => {
+            blob.extend(4u16.to_le_bytes());
+            blob.extend([0, 0, 1]);
+            4 * 64 + 64 + 64 * 32 + 32 + 32 * 2 + 2
+        }
+    };
+    blob.extend((0..params).flat_map(|i| (i as f32 * 0.001).to_le_bytes()));
+    blob
+}
+
+/// Split `len` bytes (fewer at the end of the input) off the front of `input`
+fn take<'a>(input: &mut &'a [u8], len: usize) -> &'a [u8] {
+    let (head, tail) = input.split_at(len.min(input.len()));
+    *input = tail;
+    head
+}
+
+/// Weights for a load or swap; `selector` picks the algorithm and how the blob is built from
+/// the following input bytes
+fn next_weights(selector: u8, input: &mut &[u8]) -> Vec<u8> {
+    let mut blob = seed_weights(selector);
+    match selector / 3 % 4 {
+        // Valid blob
+        0 => {}
+        // Truncated blob
+        1 => {
+            let len = take(input, 2);
+            let len = len.iter().fold(0usize, |acc, &b| acc << 8 | b as usize);
+            blob.truncate(len);
+        }
+        // Fuzzer bytes spliced over the valid blob: corrupt headers, sizes, values and
+        // trailing data
+        2 => {
+            let at = take(input, 2)
+                .iter()
+                .fold(0usize, |acc, &b| acc << 8 | b as usize);
+            let at = at % (blob.len() + 1);
+            let len = take(input, 1).first().copied().unwrap_or(0) as usize;
+            let patch = take(input, len);
+            let end = (at + patch.len()).min(blob.len());
+            blob.splice(at..end, patch.iter().copied());
+        }
+        // Raw fuzzer bytes behind the algorithm byte
+        _ => {
+            let len = take(input, 1).first().copied().unwrap_or(0) as usize;
+            blob.truncate(1);
+            blob.extend_from_slice(take(input, len));
+        }
+    }
+    blob
+}
+
+fuzz_target!(|data: &[u8]| {
+    let obs = [0.5f32; LR_OBS_DIM];
+    let mut action = [0.0f32; LR_ACTION_DIM];
+    // (handle, still live)
+    let mut envs: Vec<(*mut lr_env, bool)> = Vec::new();
+    let mut ctxs: Vec<(*mut lr_ctx, bool)> = Vec::new();
+    let mut policies: Vec<*mut lr_policy> = Vec::new();
+
+    let mut input = data;
+    while let [code, operand, rest @ ..] = input {
+        input = rest;
+        let (code, operand) = (*code, *operand);
+        let env = envs.get(operand as usize % envs.len().max(1)).copied();
+        let ctx = ctxs.get(operand as usize % ctxs.len().max(1)).copied();
+        let policy = policies
+            .get(operand as usize % policies.len().max(1))
+            .copied();
+
+        match (code % 11, env, ctx) {
+            (0, _, _) | (_, None, _) => {
+                // Any blob either loads into a working env or fails cleanly
+                let weights = next_weights(operand, &mut input);
+                let mut out: *mut lr_env = ptr::null_mut();
+                match lr_init(weights.as_ptr(), weights.len(), &mut out) {
+                    LR_OK => {
+                        let code = lr_step(out, obs.as_ptr(), action.as_mut_ptr());
+                        assert_eq!(code, LR_OK);
+                        envs.push((out, true));
+                    }
+                    code => assert!(code != LR_EINTERNAL && out.is_null(), "code {}", code),
+                }
+            }
+            (1, Some((env, _)), _) => {
+                // Freeing twice is a no-op
+                lr_free(env);
+                let i = operand as usize % envs.len();
+                envs[i].1 = false;
+                let code = lr_step(env, obs.as_ptr(), action.as_mut_ptr());
+                assert_eq!(code, LR_EBADHANDLE);
+            }
+            (2, Some((env, live)), _) => {
+                let code = lr_step(env, obs.as_ptr(), action.as_mut_ptr());
+                assert_eq!(code == LR_OK, live, "code {}", code);
+            }
+            (3, Some((env, live)), _) => {
+                // A rejected swap leaves the live policy stepping
+                let weights = next_weights(operand, &mut input);
+                let code = lr_update_weights(env, weights.as_ptr(), weights.len());
+                assert!(code != LR_EINTERNAL, "code {}", code);
+                assert!(live || code == LR_EBADHANDLE);
+                let code = lr_step(env, obs.as_ptr(), action.as_mut_ptr());
+                assert_eq!(code == LR_OK, live, "code {}", code);
+            }
+            (4, Some((env, live)), _) => {
+                lr_rollback_weights(env);
+                let mut info = std::mem::MaybeUninit::<lr_info>::uninit();
+                let code = lr_get_info(env, info.as_mut_ptr());
+                assert_eq!(code == LR_OK, live);
+            }
+            (5, Some((env, live)), _) => {
+                let mut policy: *mut lr_policy = ptr::null_mut();
+                let code = lr_policy_share(env, &mut policy);
+                assert_eq!(code == LR_OK, live);
+                if code == LR_OK {
+                    let mut ctx: *mut lr_ctx = ptr::null_mut();
+                    assert_eq!(lr_ctx_new(policy, &mut ctx), LR_OK);
+                    ctxs.push((ctx, true));
+                    policies.push(policy);
+                }
+            }
+            (6, _, Some((ctx, live))) => {
+                let code = lr_ctx_step(ctx, obs.as_ptr(), action.as_mut_ptr());
+                assert_eq!(code == LR_OK, live);
+            }
+            (7, _, Some((ctx, _))) => {
+                lr_ctx_free(ctx);
+                let i = operand as usize % ctxs.len();
+                ctxs[i].1 = false;
+            }
+            (8, Some((env, _)), Some((ctx, _))) => {
+                // Handles of the wrong kind
+                let code = lr_step(ctx.cast(), obs.as_ptr(), action.as_mut_ptr());
+                assert_eq!(code, LR_EBADHANDLE);
+                let code = lr_ctx_step(env.cast(), obs.as_ptr(), action.as_mut_ptr());
+                assert_eq!(code, LR_EBADHANDLE);
+            }
+            (9, _, _) if policy.is_some() => {
+                // Shared policies are never freed before the end, so every swap must be
+                // handled; contexts keep stepping whatever the outcome
+                let weights = next_weights(operand, &mut input);
+                let code =
+                    lr_policy_update_weights(policy.unwrap(), weights.as_ptr(), weights.len());
+                assert!(
+                    code != LR_EINTERNAL && code != LR_EBADHANDLE,
+                    "code {}",
+                    code
+                );
+                for &(ctx, live) in &ctxs {
+                    let code = lr_ctx_step(ctx, obs.as_ptr(), action.as_mut_ptr());
+                    assert_eq!(code == LR_OK, live, "code {}", code);
+                }
+            }
+            (_, Some((env, _)), _) => {
+                let forged: *mut lr_env =
+                    ptr::without_provenance_mut(env.addr() ^ (operand as usize + 1));
+                let mut generation = 0u64;
+                let code = lr_get_generation(forged, &mut generation);
+                let live = envs.iter().any(|&(env, live)| live && env == forged);
+                assert_eq!(code == LR_OK, live);
+            }
+        }
+    }
+
+    for (env, _) in envs {
+        lr_free(env);
+    }
+    for (ctx, _) in ctxs {
+        lr_ctx_free(ctx);
+    }
+    for policy in policies {
+        lr_policy_free(policy);
+    }
+});
@@ -2,6 +2,7 @@ use crate::{
     action::Action,
     algorithms::{utils, Policy},
     error::{Error, Result},
+    info::Architecture,
     obs::Obs,
 };
 
@@ -39,19 +40,19 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> LinearFA<OBS_DIM, ACTION_DIM
     pub fn from_weights(weights: &[u8]) -> Result<Self> {
         let header_size = 4; // alpha (f32)
         if weights.len() < header_size {
-            return Err(Error::InvalidWeights(
-                "Insufficient weights for LinearFA".to_string(),
-            ));
+            return Err(Error::MalformedWeights {
+                offset: weights.len(),
+                reason: "Insufficient weights for LinearFA".to_string(),
+            });
         }
 
         let alpha = f32::from_le_bytes([weights[0], weights[1], weights[2], weights[3]]);
 
+        // Exactly one weight per (action, obs) pair and one bias per action: a blob exported
+        // for other dimensions is rejected rather than read in part
         let expected_size = header_size + (OBS_DIM * ACTION_DIM + ACTION_DIM) * 4; // 4 bytes per f32
-        if weights.len() < expected_size {
-            return Err(Error::InvalidWeights(
-                "Insufficient weights for LinearFA".to_string(),
-            ));
-        }
+        utils::check_parameter_count(&weights[header_size..], OBS_DIM * ACTION_DIM + ACTION_DIM)
+            .map_err(|err| err.shifted(header_size))?;
 
         let mut lfa = Self::new();
         lfa.alpha = alpha;
@@ -169,25 +170,8 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> Policy<OBS_DIM, ACTION_DIM>
     }
 
     fn update_weights(&mut self, weights: &[u8]) -> Result<()> {
-        let header_size = 4; // alpha (f32)
-        if weights.len() < header_size {
-            return Err(Error::InvalidWeights(
-                "Insufficient weights for LinearFA".to_string(),
-            ));
-        }
-
-        let alpha = f32::from_le_bytes([weights[0], weights[1], weights[2], weights[3]]);
-        self.alpha = alpha;
-
-        let expected_size = header_size + (OBS_DIM * ACTION_DIM + ACTION_DIM) * 4;
-        if weights.len() >= expected_size {
-            let weights_data = &weights[header_size..header_size + OBS_DIM * ACTION_DIM * 4];
-            self.load_weights_matrix(weights_data)?;
-
-            let bias_data = &weights[header_size + OBS_DIM * ACTION_DIM * 4..expected_size];
-            self.load_bias_vector(bias_data)?;
-        }
-
+        // Parse into a shadow first so a rejected blob leaves alpha, weights and bias untouched
+        *self = Self::from_weights(weights)?;
         Ok(())
     }
 
@@ -215,6 +199,26 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> Policy<OBS_DIM, ACTION_DIM>
     fn algorithm_name(&self) -> &'static str {
         "LinearFA"
     }
+
+    fn parameter_count(&self) -> usize {
+        self.weights.iter().map(Vec::len).sum::<usize>() + self.bias.len()
+    }
+
+    fn architecture(&self) -> Architecture {
+        Architecture::Linear
+    }
+
+    fn dims(&self) -> (usize, usize) {
+        (self.weights.first().map_or(0, Vec::len), self.bias.len())
+    }
+
+    fn memory_footprint(&self) -> usize {
+        let rows: usize = self.weights.iter().map(|row| row.capacity() * 4).sum();
+        core::mem::size_of::<Self>()
+            + self.weights.capacity() * core::mem::size_of::<Vec<f32>>()
+            + rows
+            + self.bias.capacity() * 4
+    }
 }
 
 impl<const OBS_DIM: usize, const ACTION_DIM: usize> Default for LinearFA<OBS_DIM, ACTION_DIM> {
@@ -260,6 +264,33 @@ mod tests {
         assert_eq!(lfa.bias[1], 0.2);
     }
 
+    #[test]
+    fn test_linear_fa_rejects_other_dims() {
+        // 8 obs x 4 actions: alpha, 32 weights, 4 biases
+        let weights: Vec<u8> = (0..1 + 32 + 4).flat_map(|_| 0.1f32.to_le_bytes()).collect();
+        assert_eq!(
+            LinearFA::<4, 2>::from_weights(&weights).err(),
+            Some(Error::ParameterCount {
+                expected: 10,
+                actual: 36
+            })
+        );
+
+        let mut lfa = LinearFA::<4, 2>::new();
+        assert!(Policy::update_weights(&mut lfa, &weights).is_err());
+        assert_eq!(lfa.alpha, 0.01);
+
+        // A ragged tail is malformed at the first byte past the parameters
+        let mut weights: Vec<u8> = (0..1 + 8 + 2).flat_map(|_| 0.1f32.to_le_bytes()).collect();
+        weights.push(0);
+        assert_eq!(
+            LinearFA::<4, 2>::from_weights(&weights)
+                .err()
+                .and_then(|err| err.weights_offset()),
+            Some(44)
+        );
+    }
+
     #[test]
     fn test_linear_fa_action() {
         let lfa = LinearFA::<4, 2>::new();
@@ -1,4 +1,4 @@
-use crate::{action::Action, algorithms::Policy, error::Result, obs::Obs};
+use crate::{action::Action, algorithms::Policy, error::Result, info::Architecture, obs::Obs};
 
 /// Mock policy for testing purposes
 pub struct MockPolicy<const OBS_DIM: usize, const ACTION_DIM: usize> {
@@ -51,6 +51,22 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> Policy<OBS_DIM, ACTION_DIM>
     fn algorithm_name(&self) -> &'static str {
         "MockPolicy"
     }
+
+    fn parameter_count(&self) -> usize {
+        0
+    }
+
+    fn architecture(&self) -> Architecture {
+        Architecture::None
+    }
+
+    fn dims(&self) -> (usize, usize) {
+        (OBS_DIM, ACTION_DIM)
+    }
+
+    fn memory_footprint(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
 }
 
 #[cfg(test)]
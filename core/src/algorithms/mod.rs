@@ -13,6 +13,7 @@ pub use crate::Policy;
 /// Common utilities for algorithms
 pub mod utils {
     use crate::action::Action;
+    use crate::error::{Error, Result};
     use crate::obs::Obs;
 
     /// Apply ReLU activation function
@@ -60,6 +61,29 @@ pub mod utils {
     ) -> Action<OUT> {
         crate::simd::matrix_vector_mul(input, weights, bias)
     }
+
+    /// Check that `data` holds exactly `expected` f32 parameters
+    ///
+    /// A whole number of parameters that is not `expected` was exported for other
+    /// dimensions and fails with `ParameterCount`; a ragged tail is `MalformedWeights`.
+    pub fn check_parameter_count(data: &[u8], expected: usize) -> Result<()> {
+        if data.len() == expected * 4 {
+            return Ok(());
+        }
+        if !data.len().is_multiple_of(4) {
+            return Err(Error::MalformedWeights {
+                offset: data.len().min(expected * 4),
+                reason: format!(
+                    "{} bytes is not a whole number of f32 parameters",
+                    data.len()
+                ),
+            });
+        }
+        Err(Error::ParameterCount {
+            expected,
+            actual: data.len() / 4,
+        })
+    }
 }
 
 #[cfg(test)]
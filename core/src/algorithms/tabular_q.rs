@@ -2,8 +2,20 @@ use crate::{
     action::Action,
     algorithms::Policy,
     error::{Error, Result},
+    info::Architecture,
     obs::Obs,
 };
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Default exploration seed, so runs are reproducible unless `set_seed` is called
+const DEFAULT_SEED: u32 = 0x9E37_79B9;
+
+/// Bytes of the `[num_states, num_actions, alpha, gamma]` header
+const HEADER_SIZE: usize = 16;
+
+/// Largest Q-table (in Q-values, 4 MiB) a header-only blob may ask to zero-initialize; a
+/// serialized table is bounded by the blob itself
+const MAX_ZERO_TABLE_ENTRIES: usize = 1 << 20;
 
 /// Tabular Q-Learning implementation
 pub struct TabularQLearning<const OBS_DIM: usize, const ACTION_DIM: usize> {
@@ -19,6 +31,8 @@ pub struct TabularQLearning<const OBS_DIM: usize, const ACTION_DIM: usize> {
     num_states: usize,
     /// Number of discrete actions
     num_actions: usize,
+    /// xorshift32 state for exploration; no clock or OS entropy, so it runs on wasm32 and no_std
+    rng_state: AtomicU32,
 }
 
 impl<const OBS_DIM: usize, const ACTION_DIM: usize> TabularQLearning<OBS_DIM, ACTION_DIM> {
@@ -31,18 +45,25 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> TabularQLearning<OBS_DIM, AC
             epsilon: 0.1,
             num_states,
             num_actions,
+            rng_state: AtomicU32::new(DEFAULT_SEED),
         }
     }
 
     /// Create from weights
+    ///
+    /// The blob is a 16-byte header `[num_states, num_actions, alpha, gamma]`, optionally
+    /// followed by `num_states * num_actions` Q-values. A header-only blob starts from a zeroed
+    /// table of at most `MAX_ZERO_TABLE_ENTRIES`; a table cut short is rejected. Bytes past the
+    /// table are ignored. Sizes are checked before the table is allocated.
     pub fn from_weights(weights: &[u8]) -> Result<Self> {
-        if weights.len() < 16 {
-            return Err(Error::InvalidWeights(
-                "Insufficient weights for TabularQLearning".to_string(),
-            ));
+        if weights.len() < HEADER_SIZE {
+            return Err(Error::MalformedWeights {
+                offset: weights.len(),
+                reason: "Insufficient weights for TabularQLearning".to_string(),
+            });
         }
 
-        // Parse header: [num_states, num_actions, alpha, gamma, epsilon] (4 bytes each)
+        // Parse header: [num_states, num_actions, alpha, gamma] (4 bytes each)
         let num_states =
             u32::from_le_bytes([weights[0], weights[1], weights[2], weights[3]]) as usize;
         let num_actions =
@@ -50,15 +71,63 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> TabularQLearning<OBS_DIM, AC
         let alpha = f32::from_le_bytes([weights[8], weights[9], weights[10], weights[11]]);
         let gamma = f32::from_le_bytes([weights[12], weights[13], weights[14], weights[15]]);
 
+        if num_states == 0 {
+            return Err(Error::MalformedWeights {
+                offset: 0,
+                reason: "TabularQLearning needs at least one state".to_string(),
+            });
+        }
+        if num_actions == 0 {
+            return Err(Error::MalformedWeights {
+                offset: 4,
+                reason: "TabularQLearning needs at least one action".to_string(),
+            });
+        }
+
+        // 4 bytes per f32
+        let entries =
+            num_states
+                .checked_mul(num_actions)
+                .ok_or_else(|| Error::MalformedWeights {
+                    offset: 0,
+                    reason: "Q-table size overflows".to_string(),
+                })?;
+        let q_table_size = entries
+            .checked_mul(4)
+            .ok_or_else(|| Error::MalformedWeights {
+                offset: 0,
+                reason: "Q-table size overflows".to_string(),
+            })?;
+        let q_table_data = &weights[HEADER_SIZE..];
+        if q_table_data.is_empty() {
+            if entries > MAX_ZERO_TABLE_ENTRIES {
+                return Err(Error::MalformedWeights {
+                    offset: HEADER_SIZE,
+                    reason: format!(
+                        "Q-table of {} states x {} actions is too large to zero-initialize; \
+                         serialize it",
+                        num_states, num_actions
+                    ),
+                });
+            }
+        } else if q_table_data.len() < q_table_size {
+            return Err(Error::MalformedWeights {
+                offset: weights.len(),
+                reason: format!(
+                    "Q-table of {} states x {} actions needs {} bytes, got {}",
+                    num_states,
+                    num_actions,
+                    q_table_size,
+                    q_table_data.len()
+                ),
+            });
+        }
+
         let mut ql = Self::new(num_states, num_actions);
         ql.alpha = alpha;
         ql.gamma = gamma;
-
-        // Load Q-table if provided
-        let expected_q_table_size = num_states * num_actions * 4; // 4 bytes per f32
-        if weights.len() >= 16 + expected_q_table_size {
-            let q_table_data = &weights[16..16 + expected_q_table_size];
-            ql.load_q_table(q_table_data)?;
+        if !q_table_data.is_empty() {
+            ql.load_q_table(&q_table_data[..q_table_size])?;
         }
 
         Ok(ql)
@@ -89,25 +158,35 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> TabularQLearning<OBS_DIM, AC
         state.clamp(0, self.num_states - 1)
     }
 
+    /// Advance the exploration PRNG (xorshift32) and return the new value
+    fn next_random(&self) -> u32 {
+        let xorshift = |mut x: u32| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            x
+        };
+        let previous = self
+            .rng_state
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| Some(xorshift(x)))
+            .unwrap_or(DEFAULT_SEED);
+        xorshift(previous)
+    }
+
     /// Epsilon-greedy action selection
     fn select_action(&self, state: usize) -> usize {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        // Use current time as seed for exploration
-        let mut hasher = DefaultHasher::new();
-        std::time::SystemTime::now().hash(&mut hasher);
-        let random_value = (hasher.finish() % 1000) as f32 / 1000.0;
+        let random_value = (self.next_random() % 1000) as f32 / 1000.0;
 
         if random_value < self.epsilon {
             // Explore: random action
-            (hasher.finish() % self.num_actions as u64) as usize
+            self.next_random() as usize % self.num_actions
         } else {
-            // Exploit: best action
+            // Exploit: best action; NaN Q-values are never the best, and never panic
             self.q_table[state]
                 .iter()
                 .enumerate()
-                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                .filter(|(_, q)| !q.is_nan())
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
                 .map(|(action, _)| action)
                 .unwrap_or(0)
         }
@@ -128,6 +207,13 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> TabularQLearning<OBS_DIM, AC
         self.epsilon = epsilon.clamp(0.0, 1.0);
     }
 
+    /// Reseed exploration; the same seed replays the same explore/exploit decisions
+    pub fn set_seed(&mut self, seed: u32) {
+        // xorshift never leaves the all-zero state
+        let seed = if seed == 0 { DEFAULT_SEED } else { seed };
+        *self.rng_state.get_mut() = seed;
+    }
+
     /// Get Q-value for state-action pair
     pub fn get_q_value(&self, state: usize, action: usize) -> f32 {
         self.q_table[state][action]
@@ -151,35 +237,17 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> Policy<OBS_DIM, ACTION_DIM>
     }
 
     fn update_weights(&mut self, weights: &[u8]) -> Result<()> {
-        if weights.len() < 16 {
-            return Err(Error::InvalidWeights(
-                "Insufficient weights for TabularQLearning".to_string(),
-            ));
-        }
-
-        let num_states =
-            u32::from_le_bytes([weights[0], weights[1], weights[2], weights[3]]) as usize;
-        let num_actions =
-            u32::from_le_bytes([weights[4], weights[5], weights[6], weights[7]]) as usize;
-
-        if num_states != self.num_states || num_actions != self.num_actions {
+        // Parse into a shadow table first so a bad blob leaves `self` untouched
+        let shadow = Self::from_weights(weights)?;
+        if shadow.num_states != self.num_states || shadow.num_actions != self.num_actions {
             return Err(Error::InvalidWeights(
                 "State/action dimensions mismatch".to_string(),
             ));
         }
 
-        let alpha = f32::from_le_bytes([weights[8], weights[9], weights[10], weights[11]]);
-        let gamma = f32::from_le_bytes([weights[12], weights[13], weights[14], weights[15]]);
-
-        self.alpha = alpha;
-        self.gamma = gamma;
-
-        // Update Q-table if provided
-        let expected_q_table_size = num_states * num_actions * 4;
-        if weights.len() >= 16 + expected_q_table_size {
-            let q_table_data = &weights[16..16 + expected_q_table_size];
-            self.load_q_table(q_table_data)?;
-        }
+        self.alpha = shadow.alpha;
+        self.gamma = shadow.gamma;
+        self.q_table = shadow.q_table;
 
         Ok(())
     }
@@ -206,6 +274,30 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> Policy<OBS_DIM, ACTION_DIM>
     fn algorithm_name(&self) -> &'static str {
         "TabularQLearning"
     }
+
+    fn parameter_count(&self) -> usize {
+        self.num_states * self.num_actions
+    }
+
+    fn architecture(&self) -> Architecture {
+        Architecture::Tabular {
+            num_states: self.num_states,
+            num_actions: self.num_actions,
+        }
+    }
+
+    fn dims(&self) -> (usize, usize) {
+        // Observations are discretized, so any width maps onto the table; actions are one-hot,
+        // so a table with fewer actions fits a wider action vector
+        (OBS_DIM, self.num_actions.max(ACTION_DIM))
+    }
+
+    fn memory_footprint(&self) -> usize {
+        let rows: usize = self.q_table.iter().map(|row| row.capacity() * 4).sum();
+        core::mem::size_of::<Self>()
+            + self.q_table.capacity() * core::mem::size_of::<Vec<f32>>()
+            + rows
+    }
 }
 
 #[cfg(test)]
@@ -221,6 +313,15 @@ mod tests {
         assert_eq!(ql.q_table[0].len(), 3);
     }
 
+    fn header(num_states: u32, num_actions: u32) -> Vec<u8> {
+        let mut weights = Vec::new();
+        weights.extend(num_states.to_le_bytes());
+        weights.extend(num_actions.to_le_bytes());
+        weights.extend((0.1f32).to_le_bytes()); // alpha
+        weights.extend((0.9f32).to_le_bytes()); // gamma
+        weights
+    }
+
     #[test]
     fn test_tabular_q_from_weights() {
         let mut weights = Vec::new();
@@ -239,6 +340,67 @@ mod tests {
         assert_eq!(ql.gamma, 0.9);
     }
 
+    #[test]
+    fn test_tabular_q_loads_table_and_rejects_truncated_one() {
+        let mut weights = header(5, 2);
+        for i in 0..10 {
+            weights.extend((i as f32).to_le_bytes());
+        }
+        let ql = TabularQLearning::<4, 2>::from_weights(&weights).unwrap();
+        assert_eq!(ql.get_q_value(4, 1), 9.0);
+        assert_eq!(ql.get_weights().unwrap(), weights);
+
+        // Bytes past the table are ignored
+        let mut trailing = weights.clone();
+        trailing.extend([0u8; 4]);
+        let ql = TabularQLearning::<4, 2>::from_weights(&trailing).unwrap();
+        assert_eq!(ql.get_weights().unwrap(), weights);
+
+        // A table one value short
+        let truncated = &weights[..weights.len() - 4];
+        let err = TabularQLearning::<4, 2>::from_weights(truncated)
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, Error::MalformedWeights { offset, .. } if offset == truncated.len()),
+            "{err}"
+        );
+
+        // A live table is left untouched by a truncated update
+        let mut ql = TabularQLearning::<4, 2>::new(5, 2);
+        ql.update_q_value(0, 1, 1.0, 1);
+        assert!(ql.update_weights(truncated).is_err());
+        assert!(ql.get_q_value(0, 1) > 0.0);
+    }
+
+    #[test]
+    fn test_tabular_q_rejects_zero_dims() {
+        for (num_states, num_actions, offset) in [(0, 2, 0), (5, 0, 4), (0, 0, 0)] {
+            let err = TabularQLearning::<4, 2>::from_weights(&header(num_states, num_actions))
+                .err()
+                .unwrap();
+            assert!(
+                matches!(err, Error::MalformedWeights { offset: o, .. } if o == offset),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tabular_q_rejects_oversized_header_without_allocating() {
+        // Claims u32::MAX x u32::MAX states; must fail on size, not abort on allocation
+        let mut weights = header(u32::MAX, u32::MAX);
+        weights.push(0);
+        assert!(matches!(
+            TabularQLearning::<4, 2>::from_weights(&weights),
+            Err(Error::MalformedWeights { .. })
+        ));
+        assert!(matches!(
+            TabularQLearning::<4, 2>::from_weights(&header(u32::MAX, 2)),
+            Err(Error::MalformedWeights { offset: 16, .. })
+        ));
+    }
+
     #[test]
     fn test_tabular_q_action() {
         let ql = TabularQLearning::<4, 2>::new(10, 2);
@@ -264,4 +426,33 @@ mod tests {
 
         assert!(new_q > old_q); // Q-value should increase with positive reward
     }
+
+    #[test]
+    fn test_tabular_q_skips_nan_q_values() {
+        let mut weights = header(1, 3);
+        for q in [f32::NAN, 0.5, f32::NAN] {
+            weights.extend(q.to_le_bytes());
+        }
+        let mut ql = TabularQLearning::<4, 3>::from_weights(&weights).unwrap();
+        ql.set_epsilon(0.0);
+        assert_eq!(ql.act(&Obs::new([0.0; 4])).as_slice(), [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_tabular_q_exploration_is_seeded() {
+        let obs = Obs::new([0.5, 0.0, 0.0, 0.0]);
+        let run = |seed| {
+            let mut ql = TabularQLearning::<4, 4>::new(10, 4);
+            ql.set_epsilon(1.0);
+            ql.set_seed(seed);
+            (0..32).map(|_| ql.act(&obs)).collect::<Vec<_>>()
+        };
+
+        let actions = run(7);
+        assert_eq!(actions, run(7));
+        assert_ne!(actions, run(8));
+        // Pure exploration still visits more than one action
+        assert!(actions.iter().any(|a| a != &actions[0]));
+        assert_eq!(run(0), run(DEFAULT_SEED));
+    }
 }
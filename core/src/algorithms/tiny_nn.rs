@@ -2,6 +2,7 @@ use crate::{
     action::Action,
     algorithms::{utils, Policy},
     error::{Error, Result},
+    info::Architecture,
     obs::Obs,
 };
 
@@ -124,44 +125,59 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> TinyNN<OBS_DIM, ACTION_DIM>
     /// Create from weights
     pub fn from_weights(weights: &[u8]) -> Result<Self> {
         if weights.len() < 8 {
-            return Err(Error::InvalidWeights(
-                "Insufficient weights for TinyNN".to_string(),
-            ));
+            return Err(Error::MalformedWeights {
+                offset: weights.len(),
+                reason: "Insufficient weights for TinyNN".to_string(),
+            });
         }
 
         // Parse header: [num_layers, activation1, activation2, ...] (2 bytes each)
         let num_layers = u16::from_le_bytes([weights[0], weights[1]]) as usize;
         if !(2..=5).contains(&num_layers) {
-            return Err(Error::InvalidWeights(
-                "Invalid number of layers".to_string(),
-            ));
+            return Err(Error::MalformedWeights {
+                offset: 0,
+                reason: "Invalid number of layers".to_string(),
+            });
         }
 
         // 2-byte layer count + one activation byte per inter-layer connection
         let header_size = 2 + (num_layers - 1);
         if weights.len() < header_size {
-            return Err(Error::InvalidWeights(
-                "Insufficient header for TinyNN".to_string(),
-            ));
+            return Err(Error::MalformedWeights {
+                offset: weights.len(),
+                reason: "Insufficient header for TinyNN".to_string(),
+            });
         }
 
         let mut activations = Vec::new();
         for i in 0..num_layers - 1 {
-            let activation = ActivationFunction::from_u8(weights[2 + i])?;
+            let activation = ActivationFunction::from_u8(weights[2 + i]).map_err(|err| {
+                Error::MalformedWeights {
+                    offset: 2 + i,
+                    reason: err.to_string(),
+                }
+            })?;
             activations.push(activation);
         }
 
         // Parse layer sizes (assuming fixed architecture for now)
         let layer_sizes = vec![OBS_DIM, 64, 32, ACTION_DIM];
         if layer_sizes.len() != num_layers {
-            return Err(Error::InvalidWeights("Layer size mismatch".to_string()));
+            return Err(Error::MalformedWeights {
+                offset: 0,
+                reason: "Layer size mismatch".to_string(),
+            });
         }
 
         let mut nn = Self::with_architecture(layer_sizes, activations);
 
-        // Load weights and biases
+        // Load weights and biases; a network exported for other dimensions has a different
+        // parameter count, so it is rejected rather than read in part
         let weights_data = &weights[header_size..];
-        nn.load_weights_and_biases(weights_data)?;
+        utils::check_parameter_count(weights_data, nn.parameter_count())
+            .map_err(|err| err.shifted(header_size))?;
+        nn.load_weights_and_biases(weights_data)
+            .map_err(|err| err.shifted(header_size))?;
 
         Ok(nn)
     }
@@ -177,9 +193,10 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> TinyNN<OBS_DIM, ACTION_DIM>
             // Load weights
             let weights_size = input_size * output_size * 4;
             if offset + weights_size > data.len() {
-                return Err(Error::InvalidWeights(
-                    "Insufficient data for weights".to_string(),
-                ));
+                return Err(Error::MalformedWeights {
+                    offset: data.len(),
+                    reason: "Insufficient data for weights".to_string(),
+                });
             }
 
             let weights_data = &data[offset..offset + weights_size];
@@ -194,9 +211,10 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> TinyNN<OBS_DIM, ACTION_DIM>
             // Load biases
             let biases_size = output_size * 4;
             if offset + biases_size > data.len() {
-                return Err(Error::InvalidWeights(
-                    "Insufficient data for biases".to_string(),
-                ));
+                return Err(Error::MalformedWeights {
+                    offset: data.len(),
+                    reason: "Insufficient data for biases".to_string(),
+                });
             }
 
             let biases_data = &data[offset..offset + biases_size];
@@ -269,20 +287,14 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> Policy<OBS_DIM, ACTION_DIM>
     }
 
     fn update_weights(&mut self, weights: &[u8]) -> Result<()> {
-        if weights.len() < 8 {
-            return Err(Error::InvalidWeights(
-                "Insufficient weights for TinyNN".to_string(),
-            ));
-        }
-
-        let num_layers = u16::from_le_bytes([weights[0], weights[1]]) as usize;
-        let header_size = 2 + num_layers;
-
-        if weights.len() >= header_size {
-            let weights_data = &weights[header_size..];
-            self.load_weights_and_biases(weights_data)?;
+        // Parse into a shadow network first so a truncated blob cannot leave `self`
+        // with some layers overwritten and others stale
+        let shadow = Self::from_weights(weights)?;
+        if shadow.layer_sizes != self.layer_sizes {
+            return Err(Error::InvalidWeights("Layer size mismatch".to_string()));
         }
 
+        *self = shadow;
         Ok(())
     }
 
@@ -316,6 +328,47 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> Policy<OBS_DIM, ACTION_DIM>
     fn algorithm_name(&self) -> &'static str {
         "TinyNN"
     }
+
+    fn parameter_count(&self) -> usize {
+        let weights: usize = self.weights.iter().flatten().map(Vec::len).sum();
+        let biases: usize = self.biases.iter().map(Vec::len).sum();
+        weights + biases
+    }
+
+    fn architecture(&self) -> Architecture {
+        Architecture::Network {
+            layer_sizes: self.layer_sizes.clone(),
+            activations: self.activations.clone(),
+        }
+    }
+
+    fn dims(&self) -> (usize, usize) {
+        (
+            self.layer_sizes.first().copied().unwrap_or(0),
+            self.layer_sizes.last().copied().unwrap_or(0),
+        )
+    }
+
+    fn memory_footprint(&self) -> usize {
+        use core::mem::size_of;
+
+        let weights: usize = self
+            .weights
+            .iter()
+            .map(|layer| {
+                layer.capacity() * size_of::<Vec<f32>>()
+                    + layer.iter().map(|row| row.capacity() * 4).sum::<usize>()
+            })
+            .sum();
+        let biases: usize = self.biases.iter().map(|bias| bias.capacity() * 4).sum();
+        size_of::<Self>()
+            + self.layer_sizes.capacity() * size_of::<usize>()
+            + self.weights.capacity() * size_of::<Vec<Vec<f32>>>()
+            + weights
+            + self.biases.capacity() * size_of::<Vec<f32>>()
+            + biases
+            + self.activations.capacity() * size_of::<ActivationFunction>()
+    }
 }
 
 impl<const OBS_DIM: usize, const ACTION_DIM: usize> Default for TinyNN<OBS_DIM, ACTION_DIM> {
@@ -354,6 +407,18 @@ mod tests {
         assert!(loaded.is_ok());
     }
 
+    #[test]
+    fn test_tiny_nn_truncated_update_leaves_network_intact() {
+        let mut nn = TinyNN::<4, 2>::new();
+        let before = nn.get_weights().expect("serialize");
+
+        // Cut the blob mid-way through the second layer
+        let truncated = &before[..before.len() / 2];
+        assert!(nn.update_weights(truncated).is_err());
+
+        assert_eq!(nn.get_weights().expect("serialize"), before);
+    }
+
     #[test]
     fn test_tiny_nn_forward() {
         let nn = TinyNN::<4, 2>::new();
@@ -0,0 +1,369 @@
+//! Runtime observation/action dimensions over the const-generic core.
+//!
+//! `Env<OBS_DIM, ACTION_DIM>` is erased behind the slice-based `DynEnv`/`DynPolicy` traits,
+//! which the C API holds its environments as. The dispatch that monomorphizes `Env` for every
+//! supported pair (`new_env`, `new_signed_env`, `new_policy`) requires the `dyn-dims` feature.
+
+#[cfg(feature = "dyn-dims")]
+use crate::{env::AlgorithmType, signed, Policy};
+use crate::{env::EnvState, Action, Env, Error, Obs, PolicyInfo, Result};
+use std::any::Any;
+
+/// Largest observation dimension the dispatch is compiled for
+pub const MAX_OBS_DIM: usize = 16;
+
+/// Largest action dimension the dispatch is compiled for
+pub const MAX_ACTION_DIM: usize = 8;
+
+/// Expand `$f::<OBS, ACT>$args` for the matching `(obs_dim, action_dim)` pair, or `None`
+#[cfg(feature = "dyn-dims")]
+macro_rules! dispatch_dims {
+    ($obs_dim:expr, $action_dim:expr, $f:ident $args:tt) => {
+        dispatch_dims!(@obs $obs_dim, $action_dim, $f $args;
+            1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16; [1 2 3 4 5 6 7 8])
+    };
+    (@obs $obs_dim:expr, $action_dim:expr, $f:ident $args:tt; $($o:literal)*; $acts:tt) => {
+        match $obs_dim {
+            $($o => dispatch_dims!(@act $o, $action_dim, $f $args; $acts),)*
+            _ => None,
+        }
+    };
+    (@act $o:literal, $action_dim:expr, $f:ident $args:tt; [$($a:literal)*]) => {
+        match $action_dim {
+            $($a => Some($f::<$o, $a> $args),)*
+            _ => None,
+        }
+    };
+}
+
+/// Check `obs_dim`/`action_dim` against the compiled range
+///
+/// An out-of-range dimension is a size error whose `expected` is the largest supported one.
+pub fn check_dims(obs_dim: usize, action_dim: usize) -> Result<()> {
+    if !(1..=MAX_OBS_DIM).contains(&obs_dim) {
+        return Err(Error::InvalidObsSize {
+            expected: MAX_OBS_DIM,
+            actual: obs_dim,
+        });
+    }
+    if !(1..=MAX_ACTION_DIM).contains(&action_dim) {
+        return Err(Error::InvalidActionSize {
+            expected: MAX_ACTION_DIM,
+            actual: action_dim,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "dyn-dims")]
+fn unsupported_dims(obs_dim: usize, action_dim: usize) -> Error {
+    check_dims(obs_dim, action_dim)
+        .err()
+        .unwrap_or_else(|| Error::Internal(format!("No dispatch for {}x{}", obs_dim, action_dim)))
+}
+
+/// Check a flat row-major batch against the row width, returning the number of rows
+fn batch_rows(values: &[f32], width: usize) -> Result<usize> {
+    if !values.len().is_multiple_of(width) {
+        return Err(Error::InvalidObsSize {
+            expected: width,
+            actual: values.len(),
+        });
+    }
+    Ok(values.len() / width)
+}
+
+/// Object-safe view of `Env<OBS_DIM, ACTION_DIM>` with dimensions chosen at runtime
+pub trait DynEnv: Send {
+    /// `(obs_dim, action_dim)`
+    fn dims(&self) -> (usize, usize);
+    /// Reset with `obs` and write the first action into `action`
+    fn reset(&mut self, obs: &[f32], action: &mut [f32]) -> Result<()>;
+    /// Step with `obs` and write the action into `action`
+    fn step(&mut self, obs: &[f32], action: &mut [f32]) -> Result<()>;
+    fn check_invariant(&self, obs: &[f32], action: &[f32]) -> Result<()>;
+    fn update_weights(&mut self, weights: &[u8]) -> Result<()>;
+    fn rollback_weights(&mut self) -> Result<()>;
+    fn get_weights(&self) -> Result<Vec<u8>>;
+    fn state(&self) -> &EnvState;
+    fn algorithm_name(&self) -> &'static str;
+    fn parameter_count(&self) -> usize;
+    fn info(&self) -> PolicyInfo;
+    /// The concrete `Env`, for callers that need its const-generic API
+    fn as_any(&self) -> &dyn Any;
+
+    /// Step once per row of a row-major `(batch, obs_dim)` buffer, writing `(batch, action_dim)`
+    ///
+    /// Both buffers are checked before the first row, so an error steps no row.
+    fn step_batch(&mut self, obs: &[f32], actions: &mut [f32]) -> Result<()> {
+        let (obs_dim, action_dim) = self.dims();
+        let rows = batch_rows(obs, obs_dim)?;
+        if actions.len() != rows * action_dim {
+            return Err(Error::InvalidActionSize {
+                expected: rows * action_dim,
+                actual: actions.len(),
+            });
+        }
+        for (row, action) in obs
+            .chunks_exact(obs_dim)
+            .zip(actions.chunks_exact_mut(action_dim))
+        {
+            self.step(row, action)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_action<const ACTION_DIM: usize>(
+    action: Action<ACTION_DIM>,
+    out: &mut [f32],
+) -> Result<()> {
+    if out.len() != ACTION_DIM {
+        return Err(Error::InvalidActionSize {
+            expected: ACTION_DIM,
+            actual: out.len(),
+        });
+    }
+    out.copy_from_slice(action.as_slice());
+    Ok(())
+}
+
+impl<const OBS_DIM: usize, const ACTION_DIM: usize> DynEnv for Env<OBS_DIM, ACTION_DIM> {
+    fn dims(&self) -> (usize, usize) {
+        (OBS_DIM, ACTION_DIM)
+    }
+
+    fn reset(&mut self, obs: &[f32], action: &mut [f32]) -> Result<()> {
+        let obs = Obs::from_slice(obs)?;
+        write_action(Env::reset(self, &obs), action)
+    }
+
+    fn step(&mut self, obs: &[f32], action: &mut [f32]) -> Result<()> {
+        let obs = Obs::from_slice(obs)?;
+        write_action(Env::step(self, &obs), action)
+    }
+
+    fn check_invariant(&self, obs: &[f32], action: &[f32]) -> Result<()> {
+        Env::check_invariant(self, &Obs::from_slice(obs)?, &Action::from_slice(action)?)
+    }
+
+    fn update_weights(&mut self, weights: &[u8]) -> Result<()> {
+        Env::update_weights(self, weights)
+    }
+
+    fn rollback_weights(&mut self) -> Result<()> {
+        Env::rollback_weights(self)
+    }
+
+    fn get_weights(&self) -> Result<Vec<u8>> {
+        Env::get_weights(self)
+    }
+
+    fn state(&self) -> &EnvState {
+        Env::state(self)
+    }
+
+    fn algorithm_name(&self) -> &'static str {
+        Env::algorithm_name(self)
+    }
+
+    fn parameter_count(&self) -> usize {
+        Env::parameter_count(self)
+    }
+
+    fn info(&self) -> PolicyInfo {
+        Env::info(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(feature = "dyn-dims")]
+fn env_from_weights<const OBS_DIM: usize, const ACTION_DIM: usize>(
+    weights: &[u8],
+) -> Result<Box<dyn DynEnv>> {
+    Ok(Box::new(Env::<OBS_DIM, ACTION_DIM>::from_weights(weights)?))
+}
+
+#[cfg(feature = "dyn-dims")]
+fn env_from_signed_weights<const OBS_DIM: usize, const ACTION_DIM: usize>(
+    container: &[u8],
+    public_key: &[u8; signed::PUBLIC_KEY_LEN],
+) -> Result<Box<dyn DynEnv>> {
+    Ok(Box::new(Env::<OBS_DIM, ACTION_DIM>::from_signed_weights(
+        container, public_key,
+    )?))
+}
+
+#[cfg(feature = "dyn-dims")]
+/// Create an environment for `obs_dim`/`action_dim` from a weights blob
+pub fn new_env(weights: &[u8], obs_dim: usize, action_dim: usize) -> Result<Box<dyn DynEnv>> {
+    dispatch_dims!(obs_dim, action_dim, env_from_weights(weights))
+        .unwrap_or_else(|| Err(unsupported_dims(obs_dim, action_dim)))
+}
+
+#[cfg(feature = "dyn-dims")]
+/// Create an environment from a signed weights container, verified against `public_key`
+pub fn new_signed_env(
+    container: &[u8],
+    public_key: &[u8; signed::PUBLIC_KEY_LEN],
+    obs_dim: usize,
+    action_dim: usize,
+) -> Result<Box<dyn DynEnv>> {
+    dispatch_dims!(
+        obs_dim,
+        action_dim,
+        env_from_signed_weights(container, public_key)
+    )
+    .unwrap_or_else(|| Err(unsupported_dims(obs_dim, action_dim)))
+}
+
+/// Object-safe view of a standalone `Policy<OBS_DIM, ACTION_DIM>`, for pure inference
+pub trait DynPolicy {
+    /// `(obs_dim, action_dim)`
+    fn dims(&self) -> (usize, usize);
+    /// Write the action for `obs` into `action`
+    fn act(&self, obs: &[f32], action: &mut [f32]) -> Result<()>;
+    /// Full weights blob, algorithm byte included
+    fn get_weights(&self) -> Result<Vec<u8>>;
+    fn algorithm_name(&self) -> &'static str;
+    fn parameter_count(&self) -> usize;
+
+    /// Act on every row of a row-major `(batch, obs_dim)` buffer, writing `(batch, action_dim)`
+    fn act_batch(&self, obs: &[f32], actions: &mut [f32]) -> Result<()> {
+        let (obs_dim, action_dim) = self.dims();
+        let rows = batch_rows(obs, obs_dim)?;
+        if actions.len() != rows * action_dim {
+            return Err(Error::InvalidActionSize {
+                expected: rows * action_dim,
+                actual: actions.len(),
+            });
+        }
+        for (row, action) in obs
+            .chunks_exact(obs_dim)
+            .zip(actions.chunks_exact_mut(action_dim))
+        {
+            self.act(row, action)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "dyn-dims")]
+/// A parsed policy together with the algorithm byte it was loaded from
+struct StandalonePolicy<const OBS_DIM: usize, const ACTION_DIM: usize> {
+    algorithm: AlgorithmType,
+    policy: Box<dyn Policy<OBS_DIM, ACTION_DIM>>,
+}
+
+#[cfg(feature = "dyn-dims")]
+impl<const OBS_DIM: usize, const ACTION_DIM: usize> DynPolicy
+    for StandalonePolicy<OBS_DIM, ACTION_DIM>
+{
+    fn dims(&self) -> (usize, usize) {
+        (OBS_DIM, ACTION_DIM)
+    }
+
+    fn act(&self, obs: &[f32], action: &mut [f32]) -> Result<()> {
+        write_action(self.policy.act(&Obs::from_slice(obs)?), action)
+    }
+
+    fn get_weights(&self) -> Result<Vec<u8>> {
+        let mut weights = vec![self.algorithm.to_u8()];
+        weights.extend(self.policy.get_weights()?);
+        Ok(weights)
+    }
+
+    fn algorithm_name(&self) -> &'static str {
+        self.policy.algorithm_name()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.policy.parameter_count()
+    }
+}
+
+#[cfg(feature = "dyn-dims")]
+fn policy_from_weights<const OBS_DIM: usize, const ACTION_DIM: usize>(
+    weights: &[u8],
+) -> Result<Box<dyn DynPolicy>> {
+    let policy = Env::<OBS_DIM, ACTION_DIM>::policy_from_weights(weights)?;
+    Ok(Box::new(StandalonePolicy {
+        algorithm: AlgorithmType::from_u8(weights[0])?,
+        policy,
+    }))
+}
+
+#[cfg(feature = "dyn-dims")]
+/// Parse a weights blob into a standalone policy for `obs_dim`/`action_dim`
+pub fn new_policy(weights: &[u8], obs_dim: usize, action_dim: usize) -> Result<Box<dyn DynPolicy>> {
+    dispatch_dims!(obs_dim, action_dim, policy_from_weights(weights))
+        .unwrap_or_else(|| Err(unsupported_dims(obs_dim, action_dim)))
+}
+
+#[cfg(all(test, feature = "dyn-dims"))]
+mod tests {
+    use super::*;
+    use crate::weights::linear_fa_weights;
+
+    #[test]
+    fn test_dispatch_matches_const_generic_env() {
+        let weights = linear_fa_weights(3, 2, &[0.1; 6], &[0.0, 0.2], 0.01).unwrap();
+        let mut env = new_env(&weights, 3, 2).unwrap();
+        assert_eq!(env.dims(), (3, 2));
+
+        let mut expected = Env::<3, 2>::from_weights(&weights).unwrap();
+        let obs = [1.0, 1.0, 1.0];
+        let mut action = [0.0; 2];
+        env.reset(&obs, &mut action).unwrap();
+        assert_eq!(
+            &action,
+            expected.reset(&Obs::new(obs)).as_array(),
+            "dispatched env must run the exact same inference"
+        );
+        assert_eq!(env.state().episode_count, 1);
+        assert!(env.step(&[1.0; 4], &mut action).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_dims_are_rejected() {
+        let weights = linear_fa_weights(4, 2, &[0.0; 8], &[0.0; 2], 0.01).unwrap();
+        assert!(matches!(
+            new_env(&weights, 0, 2),
+            Err(Error::InvalidObsSize { actual: 0, .. })
+        ));
+        assert!(matches!(
+            new_env(&weights, MAX_OBS_DIM + 1, 2),
+            Err(Error::InvalidObsSize { .. })
+        ));
+        assert!(matches!(
+            new_policy(&weights, 4, MAX_ACTION_DIM + 1),
+            Err(Error::InvalidActionSize { .. })
+        ));
+        assert!(new_env(&weights, MAX_OBS_DIM, MAX_ACTION_DIM).is_err()); // too short
+    }
+
+    #[test]
+    fn test_batches_step_every_row() {
+        let weights = linear_fa_weights(2, 1, &[0.5, 0.0], &[0.0], 0.01).unwrap();
+        let mut env = new_env(&weights, 2, 1).unwrap();
+        let policy = new_policy(&weights, 2, 1).unwrap();
+
+        let obs = [0.0, 0.0, 1.0, 0.0, -1.0, 0.0];
+        let mut stepped = [0.0; 3];
+        let mut acted = [0.0; 3];
+        env.step_batch(&obs, &mut stepped).unwrap();
+        policy.act_batch(&obs, &mut acted).unwrap();
+
+        assert_eq!(stepped, acted);
+        assert_eq!(stepped[0], 0.0);
+        assert!((stepped[1] - 0.5f32.tanh()).abs() < 1e-6);
+        assert_eq!(env.state().step_count, 3);
+        assert_eq!(policy.get_weights().unwrap(), weights);
+
+        assert!(env.step_batch(&obs[..5], &mut stepped).is_err());
+        assert!(policy.act_batch(&obs, &mut acted[..2]).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! AEAD-encrypted weights containers (requires the `encryption` feature).
+//!
+//! An encrypted container protects a weights blob at rest:
+//!
+//! ```text
+//! magic "LREN" (4) | version (1) | cipher (1) | nonce (12) | ciphertext + tag (16)
+//! ```
+//!
+//! The header is bound to the ciphertext as associated data. Keys are supplied by the host
+//! for each load and are never stored in the environment; decrypted plaintext is zeroized
+//! as soon as the policy has been parsed from it.
+
+use crate::error::{Error, Result};
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::aead::{AeadInPlace, KeyInit};
+use chacha20poly1305::ChaCha20Poly1305;
+use zeroize::Zeroizing;
+
+/// Container magic bytes
+pub const MAGIC: [u8; 4] = *b"LREN";
+
+/// Current container format version
+pub const VERSION: u8 = 1;
+
+/// AEAD nonce length (both supported ciphers use 96-bit nonces)
+pub const NONCE_LEN: usize = 12;
+
+/// AEAD key length (256-bit keys for both ciphers)
+pub const KEY_LEN: usize = 32;
+
+/// AEAD authentication tag length
+pub const TAG_LEN: usize = 16;
+
+/// Bytes before the ciphertext: magic, version, cipher id and nonce
+pub const HEADER_LEN: usize = 4 + 1 + 1 + NONCE_LEN;
+
+/// Supported AEAD ciphers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cipher {
+    ChaCha20Poly1305,
+    Aes256Gcm,
+}
+
+impl Cipher {
+    pub fn from_u8(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::ChaCha20Poly1305),
+            1 => Ok(Self::Aes256Gcm),
+            _ => Err(Error::InvalidWeights(format!(
+                "Unknown weights cipher: {}",
+                value
+            ))),
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::ChaCha20Poly1305 => 0,
+            Self::Aes256Gcm => 1,
+        }
+    }
+}
+
+/// Check whether a blob starts with the encrypted-container magic
+pub fn is_encrypted(blob: &[u8]) -> bool {
+    blob.len() >= MAGIC.len() && blob[..MAGIC.len()] == MAGIC
+}
+
+/// Decrypt and authenticate a container, returning the plaintext weights blob
+///
+/// The returned buffer is zeroized when dropped. Any integrity failure (wrong key, modified
+/// header or ciphertext, truncation) is reported as `Error::InvalidWeights`.
+pub fn decrypt(container: &[u8], key: &[u8; KEY_LEN]) -> Result<Zeroizing<Vec<u8>>> {
+    if !is_encrypted(container) {
+        return Err(Error::InvalidWeights(
+            "Weights are not an encrypted container".to_string(),
+        ));
+    }
+
+    if container.len() < HEADER_LEN + TAG_LEN {
+        return Err(Error::InvalidWeights(
+            "Truncated encrypted container".to_string(),
+        ));
+    }
+
+    if container[4] != VERSION {
+        return Err(Error::InvalidWeights(format!(
+            "Unsupported encrypted container version: {}",
+            container[4]
+        )));
+    }
+
+    let cipher = Cipher::from_u8(container[5])?;
+    let (header, ciphertext) = container.split_at(HEADER_LEN);
+    let nonce = &header[6..HEADER_LEN];
+
+    let mut buffer = Zeroizing::new(ciphertext.to_vec());
+    let result = match cipher {
+        Cipher::ChaCha20Poly1305 => {
+            ChaCha20Poly1305::new(key.into()).decrypt_in_place(nonce.into(), header, &mut *buffer)
+        }
+        Cipher::Aes256Gcm => {
+            Aes256Gcm::new(key.into()).decrypt_in_place(nonce.into(), header, &mut *buffer)
+        }
+    };
+    result.map_err(|_| Error::InvalidWeights("Weights decryption failed".to_string()))?;
+
+    Ok(buffer)
+}
+
+/// Encrypt a weights blob into a container
+///
+/// `nonce` must never be reused with the same key; tooling should draw it from an OS RNG.
+pub fn encrypt(
+    plaintext: &[u8],
+    key: &[u8; KEY_LEN],
+    cipher: Cipher,
+    nonce: &[u8; NONCE_LEN],
+) -> Result<Vec<u8>> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(&MAGIC);
+    header.push(VERSION);
+    header.push(cipher.to_u8());
+    header.extend_from_slice(nonce);
+
+    // Zeroized so the plaintext copy never outlives this call, even on error; sized for the
+    // tag up front so appending it cannot reallocate and leave plaintext behind
+    let mut buffer = Zeroizing::new(Vec::with_capacity(plaintext.len() + TAG_LEN));
+    buffer.extend_from_slice(plaintext);
+    let result = match cipher {
+        Cipher::ChaCha20Poly1305 => {
+            ChaCha20Poly1305::new(key.into()).encrypt_in_place(nonce.into(), &header, &mut *buffer)
+        }
+        Cipher::Aes256Gcm => {
+            Aes256Gcm::new(key.into()).encrypt_in_place(nonce.into(), &header, &mut *buffer)
+        }
+    };
+    result.map_err(|_| Error::Internal("Weights encryption failed".to_string()))?;
+
+    header.extend_from_slice(&buffer);
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; KEY_LEN] = [0x42; KEY_LEN];
+    const NONCE: [u8; NONCE_LEN] = [0x24; NONCE_LEN];
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let plaintext = [1u8, 2, 3, 4, 5, 6];
+        for cipher in [Cipher::ChaCha20Poly1305, Cipher::Aes256Gcm] {
+            let container = encrypt(&plaintext, &KEY, cipher, &NONCE).unwrap();
+            assert!(is_encrypted(&container));
+            assert_eq!(container.len(), HEADER_LEN + plaintext.len() + TAG_LEN);
+            assert_eq!(decrypt(&container, &KEY).unwrap().as_slice(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_decrypt_rejects_wrong_key() {
+        let container = encrypt(&[1u8, 2, 3], &KEY, Cipher::ChaCha20Poly1305, &NONCE).unwrap();
+        let result = decrypt(&container, &[0u8; KEY_LEN]);
+        assert!(matches!(result, Err(Error::InvalidWeights(_))));
+    }
+
+    #[test]
+    fn test_decrypt_rejects_tampered_header_and_body() {
+        let container = encrypt(&[1u8, 2, 3], &KEY, Cipher::Aes256Gcm, &NONCE).unwrap();
+
+        let mut tampered_nonce = container.clone();
+        tampered_nonce[7] ^= 0x01;
+        assert!(decrypt(&tampered_nonce, &KEY).is_err());
+
+        let mut tampered_body = container.clone();
+        tampered_body[HEADER_LEN] ^= 0x01;
+        assert!(decrypt(&tampered_body, &KEY).is_err());
+
+        assert!(decrypt(&container[..HEADER_LEN + 4], &KEY).is_err());
+    }
+}
@@ -1,9 +1,13 @@
 use crate::{
     action::Action,
     algorithms::{LinearFA, Policy, TabularQLearning, TinyNN},
-    error::{Error, Result},
+    error::{Error, Invariant, Result},
+    info::{PolicyInfo, WEIGHTS_FORMAT_VERSION},
     obs::Obs,
+    signed,
 };
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
 
 /// Environment state for tracking internal state
 #[derive(Debug, Clone, PartialEq)]
@@ -16,8 +20,10 @@ pub struct EnvState {
     pub episode_count: u64,
     /// Algorithm type
     pub algorithm: AlgorithmType,
-    /// Policy weights hash for verification
+    /// SHA-256 of the live policy's weights blob
     pub weights_hash: [u8; 32],
+    /// Number of policy swaps (hot-swaps and rollbacks) since construction
+    pub weights_generation: u64,
 }
 
 /// Supported RL algorithms
@@ -50,10 +56,27 @@ impl AlgorithmType {
     }
 }
 
+/// Policy held in the shadow or rollback buffer, together with the hash of its weights
+struct PolicySlot<const OBS_DIM: usize, const ACTION_DIM: usize> {
+    policy: Arc<dyn Policy<OBS_DIM, ACTION_DIM>>,
+    weights_hash: [u8; 32],
+}
+
 /// Main environment struct implementing the RL interface
+///
+/// Weight updates are double-buffered: a new blob is parsed and validated into a shadow
+/// policy, and only swapped in at a step boundary once it is known to be good. The policy
+/// it replaces is retained so the swap can be rolled back.
 pub struct Env<const OBS_DIM: usize, const ACTION_DIM: usize> {
     state: EnvState,
-    policy: Box<dyn Policy<OBS_DIM, ACTION_DIM>>,
+    /// Live policy; reference-counted so `SharedPolicy::from_env` shares it without a copy
+    policy: Arc<dyn Policy<OBS_DIM, ACTION_DIM>>,
+    /// Validated policy waiting to be swapped in at the next `reset`/`step`
+    staged: Option<PolicySlot<OBS_DIM, ACTION_DIM>>,
+    /// Previously live policy, kept for `rollback_weights`
+    previous: Option<PolicySlot<OBS_DIM, ACTION_DIM>>,
+    /// Loaded from an encrypted container: `get_weights` never exports plaintext
+    encrypted: bool,
 }
 
 impl<const OBS_DIM: usize, const ACTION_DIM: usize> Env<OBS_DIM, ACTION_DIM> {
@@ -64,35 +87,136 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> Env<OBS_DIM, ACTION_DIM> {
         }
 
         let algorithm = AlgorithmType::from_u8(weights[0])?;
-        let policy_weights = &weights[1..];
+        let policy = Self::build_policy(algorithm, &weights[1..])?;
+
+        let state = EnvState {
+            current_obs: vec![0.0; OBS_DIM],
+            step_count: 0,
+            episode_count: 0,
+            algorithm,
+            weights_hash: Self::weights_hash(weights),
+            weights_generation: 0,
+        };
+
+        Ok(Self {
+            state,
+            policy: Arc::from(policy),
+            staged: None,
+            previous: None,
+            encrypted: false,
+        })
+    }
+
+    /// Create environment from a signed weights container
+    ///
+    /// The Ed25519 signature is checked against `public_key` before any weights are parsed.
+    pub fn from_signed_weights(
+        container: &[u8],
+        public_key: &[u8; signed::PUBLIC_KEY_LEN],
+    ) -> Result<Self> {
+        let weights = signed::verify(container, public_key)?;
+        Self::from_weights(weights)
+    }
+
+    /// Create environment from an AEAD-encrypted weights container
+    ///
+    /// `key` is used only for this call and is not retained; the decrypted blob is zeroized
+    /// once the policy has been parsed. Integrity failures surface as `Error::InvalidWeights`.
+    /// The environment never exports its weights, including ones swapped in later:
+    /// `get_weights` fails with `Error::UnsupportedAlgorithm`.
+    #[cfg(feature = "encryption")]
+    pub fn from_encrypted_weights(
+        container: &[u8],
+        key: &[u8; crate::encrypted::KEY_LEN],
+    ) -> Result<Self> {
+        let weights = crate::encrypted::decrypt(container, key)?;
+        let mut env = Self::from_weights(&weights)?;
+        env.mark_encrypted();
+        Ok(env)
+    }
 
-        let policy: Box<dyn Policy<OBS_DIM, ACTION_DIM>> = match algorithm {
+    /// Record that the weights came from an encrypted container, so they are never exported
+    #[cfg(feature = "encryption")]
+    pub(crate) fn mark_encrypted(&mut self) {
+        self.encrypted = true;
+    }
+
+    /// Whether the weights came from an encrypted container and are never exported
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+
+    /// Parse algorithm-specific weights into a fresh policy
+    ///
+    /// `MalformedWeights` offsets count the algorithm byte that precedes `policy_weights`.
+    /// A policy shaped for other dimensions (a TabularQ table with more actions than
+    /// `ACTION_DIM`, say) fails with `InvalidObsSize`/`InvalidActionSize`.
+    pub fn build_policy(
+        algorithm: AlgorithmType,
+        policy_weights: &[u8],
+    ) -> Result<Box<dyn Policy<OBS_DIM, ACTION_DIM>>> {
+        let policy = Self::parse_policy(algorithm, policy_weights).map_err(|err| err.shifted(1))?;
+
+        let (obs_dim, action_dim) = policy.dims();
+        if obs_dim != OBS_DIM {
+            return Err(Error::InvalidObsSize {
+                expected: OBS_DIM,
+                actual: obs_dim,
+            });
+        }
+        if action_dim != ACTION_DIM {
+            return Err(Error::InvalidActionSize {
+                expected: ACTION_DIM,
+                actual: action_dim,
+            });
+        }
+
+        Ok(policy)
+    }
+
+    fn parse_policy(
+        algorithm: AlgorithmType,
+        policy_weights: &[u8],
+    ) -> Result<Box<dyn Policy<OBS_DIM, ACTION_DIM>>> {
+        Ok(match algorithm {
             AlgorithmType::TabularQLearning => {
                 Box::new(TabularQLearning::from_weights(policy_weights)?)
             }
             AlgorithmType::LinearFA => Box::new(LinearFA::from_weights(policy_weights)?),
             AlgorithmType::TinyNN => Box::new(TinyNN::from_weights(policy_weights)?),
-        };
+        })
+    }
 
-        // Calculate weights hash for verification
-        let mut weights_hash = [0u8; 32];
-        if weights.len() >= 32 {
-            weights_hash.copy_from_slice(&weights[..32]);
-        }
+    /// Parse a full weights blob (algorithm byte, then algorithm-specific weights) into a
+    /// standalone policy, for inference outside an environment
+    pub fn policy_from_weights(weights: &[u8]) -> Result<Box<dyn Policy<OBS_DIM, ACTION_DIM>>> {
+        let (&algorithm, policy_weights) = weights
+            .split_first()
+            .ok_or_else(|| Error::InvalidWeights("Empty weights data".to_string()))?;
+        Self::build_policy(AlgorithmType::from_u8(algorithm)?, policy_weights)
+    }
 
-        let state = EnvState {
-            current_obs: vec![0.0; OBS_DIM],
-            step_count: 0,
-            episode_count: 0,
-            algorithm,
-            weights_hash,
-        };
+    /// SHA-256 of a weights blob, as listed in bundle manifests
+    fn weights_hash(weights: &[u8]) -> [u8; 32] {
+        Sha256::digest(weights).into()
+    }
 
-        Ok(Self { state, policy })
+    /// Swap a staged policy in, keeping the outgoing one for rollback
+    fn swap_staged(&mut self) {
+        if let Some(staged) = self.staged.take() {
+            let outgoing = PolicySlot {
+                policy: core::mem::replace(&mut self.policy, staged.policy),
+                weights_hash: self.state.weights_hash,
+            };
+            self.previous = Some(outgoing);
+            self.state.weights_hash = staged.weights_hash;
+            self.state.weights_generation += 1;
+        }
     }
 
     /// Reset environment with initial observation
     pub fn reset(&mut self, obs: &Obs<OBS_DIM>) -> Action<ACTION_DIM> {
+        self.swap_staged();
         self.state.current_obs = obs.as_slice().to_vec();
         self.state.step_count = 0;
         self.state.episode_count += 1;
@@ -103,6 +227,7 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> Env<OBS_DIM, ACTION_DIM> {
 
     /// Step environment with new observation
     pub fn step(&mut self, obs: &Obs<OBS_DIM>) -> Action<ACTION_DIM> {
+        self.swap_staged();
         self.state.current_obs = obs.as_slice().to_vec();
         self.state.step_count += 1;
 
@@ -115,34 +240,141 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> Env<OBS_DIM, ACTION_DIM> {
         &self.state
     }
 
+    /// Name of the live policy's algorithm
+    pub fn algorithm_name(&self) -> &'static str {
+        self.policy.algorithm_name()
+    }
+
+    /// Number of learned parameters in the live policy
+    pub fn parameter_count(&self) -> usize {
+        self.policy.parameter_count()
+    }
+
+    /// The live policy itself, for sharing it across threads
+    pub(crate) fn live_policy(&self) -> Arc<dyn Policy<OBS_DIM, ACTION_DIM>> {
+        Arc::clone(&self.policy)
+    }
+
+    /// `(obs_dim, action_dim)` of the live policy, as read from its weights
+    pub fn dims(&self) -> (usize, usize) {
+        self.policy.dims()
+    }
+
+    /// Describe the live policy: algorithm, shape, size and weights hash
+    pub fn info(&self) -> PolicyInfo {
+        let (obs_dim, action_dim) = self.dims();
+        PolicyInfo {
+            algorithm: self.state.algorithm,
+            algorithm_name: self.policy.algorithm_name(),
+            obs_dim,
+            action_dim,
+            architecture: self.policy.architecture(),
+            parameter_count: self.policy.parameter_count(),
+            memory_bytes: self.policy.memory_footprint(),
+            weights_sha256: self.state.weights_hash,
+            format_version: WEIGHTS_FORMAT_VERSION,
+        }
+    }
+
     /// Set environment state (for testing/debugging)
     pub fn set_state(&mut self, state: EnvState) {
         self.state = state;
     }
 
-    /// Update policy weights
-    pub fn update_weights(&mut self, weights: &[u8]) -> Result<()> {
+    /// Parse and validate new weights into the shadow buffer
+    ///
+    /// The live policy is untouched; the staged policy is swapped in at the start of the
+    /// next `reset`/`step`. Staging again before that replaces the pending weights.
+    pub fn stage_weights(&mut self, weights: &[u8]) -> Result<()> {
+        let policy = Self::validate_weights(self.state.algorithm, weights)?;
+
+        self.staged = Some(PolicySlot {
+            policy: Arc::from(policy),
+            weights_hash: Self::weights_hash(weights),
+        });
+
+        Ok(())
+    }
+
+    /// Parse a replacement for a live `algorithm` policy, rejecting it unless it is safe to swap in
+    ///
+    /// The blob must be for the same algorithm, and the parsed policy is probed once so weights
+    /// that parse but produce NaN or infinite actions are rejected.
+    pub fn validate_weights(
+        algorithm: AlgorithmType,
+        weights: &[u8],
+    ) -> Result<Box<dyn Policy<OBS_DIM, ACTION_DIM>>> {
         if weights.is_empty() {
             return Err(Error::InvalidWeights("Empty weights data".to_string()));
         }
 
-        let algorithm = AlgorithmType::from_u8(weights[0])?;
-        if algorithm != self.state.algorithm {
-            return Err(Error::InvalidWeights("Algorithm type mismatch".to_string()));
+        let incoming = AlgorithmType::from_u8(weights[0])?;
+        if incoming != algorithm {
+            return Err(Error::WeightsMismatch(format!(
+                "Algorithm type mismatch: loaded {:?}, got {:?}",
+                algorithm, incoming
+            )));
         }
 
-        self.policy.update_weights(weights)?;
+        let policy = Self::build_policy(incoming, &weights[1..])?;
 
-        // Update weights hash
-        if weights.len() >= 32 {
-            self.state.weights_hash.copy_from_slice(&weights[..32]);
+        // Probe the shadow policy once so a blob that parses but produces garbage is rejected
+        let probe = policy.act(&Obs::default());
+        if probe.as_slice().iter().any(|x| !x.is_finite()) {
+            return Err(Error::InvalidWeights(
+                "Staged policy produces NaN or infinite actions".to_string(),
+            ));
         }
 
+        Ok(policy)
+    }
+
+    /// Update policy weights
+    ///
+    /// Transactional: the blob is fully validated before the swap, so on error the live
+    /// policy is unchanged. Any previously staged weights are discarded.
+    pub fn update_weights(&mut self, weights: &[u8]) -> Result<()> {
+        self.staged = None;
+        self.stage_weights(weights)?;
+        self.swap_staged();
+        Ok(())
+    }
+
+    /// Update policy weights from a signed weights container
+    pub fn update_signed_weights(
+        &mut self,
+        container: &[u8],
+        public_key: &[u8; signed::PUBLIC_KEY_LEN],
+    ) -> Result<()> {
+        let weights = signed::verify(container, public_key)?;
+        self.update_weights(weights)
+    }
+
+    /// Whether validated weights are waiting for the next step boundary
+    pub fn has_staged_weights(&self) -> bool {
+        self.staged.is_some()
+    }
+
+    /// Restore the policy that was live before the most recent swap
+    ///
+    /// Pending staged weights are discarded. Rolling back twice returns to the newer policy.
+    pub fn rollback_weights(&mut self) -> Result<()> {
+        let previous = self.previous.take().ok_or(Error::NoRollbackTarget)?;
+
+        self.staged = Some(previous);
+        self.swap_staged();
+
         Ok(())
     }
 
     /// Get policy weights for serialization
+    ///
+    /// Fails with `Error::UnsupportedAlgorithm` on an environment loaded from an encrypted
+    /// container.
     pub fn get_weights(&self) -> Result<Vec<u8>> {
+        if self.encrypted {
+            return Err(encrypted_export_error());
+        }
         let mut weights = vec![self.state.algorithm.to_u8()];
         weights.extend(self.policy.get_weights()?);
         Ok(weights)
@@ -150,28 +382,38 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> Env<OBS_DIM, ACTION_DIM> {
 
     /// Verify safety invariant
     pub fn check_invariant(&self, obs: &Obs<OBS_DIM>, action: &Action<ACTION_DIM>) -> Result<()> {
-        // Basic safety checks
-        if !action.is_within_bounds(-1.0, 1.0) {
-            return Err(Error::InvariantViolation(
-                "Action out of bounds".to_string(),
-            ));
-        }
+        check_invariant(obs, action)
+    }
+}
 
-        // Check for NaN or infinite values
-        if obs.as_slice().iter().any(|x| !x.is_finite()) {
-            return Err(Error::InvariantViolation(
-                "Observation contains NaN or infinite values".to_string(),
-            ));
-        }
+/// Error for exporting the weights of a policy loaded from an encrypted container
+pub(crate) fn encrypted_export_error() -> Error {
+    Error::UnsupportedAlgorithm(
+        "Weights loaded from an encrypted container are not exported".to_string(),
+    )
+}
 
-        if action.as_slice().iter().any(|x| !x.is_finite()) {
-            return Err(Error::InvariantViolation(
-                "Action contains NaN or infinite values".to_string(),
-            ));
-        }
+/// Runtime safety invariant shared by `Env` and `StepContext`: actions in [-1, 1] and no NaN or
+/// infinite observations or actions
+pub fn check_invariant<const OBS_DIM: usize, const ACTION_DIM: usize>(
+    obs: &Obs<OBS_DIM>,
+    action: &Action<ACTION_DIM>,
+) -> Result<()> {
+    // Basic safety checks
+    if !action.is_within_bounds(-1.0, 1.0) {
+        return Err(Error::InvariantViolation(Invariant::ActionBounds));
+    }
 
-        Ok(())
+    // Check for NaN or infinite values
+    if obs.as_slice().iter().any(|x| !x.is_finite()) {
+        return Err(Error::InvariantViolation(Invariant::FiniteObservation));
+    }
+
+    if action.as_slice().iter().any(|x| !x.is_finite()) {
+        return Err(Error::InvariantViolation(Invariant::FiniteAction));
     }
+
+    Ok(())
 }
 
 impl<const OBS_DIM: usize, const ACTION_DIM: usize> crate::Environment<OBS_DIM, ACTION_DIM>
@@ -197,6 +439,19 @@ impl<const OBS_DIM: usize, const ACTION_DIM: usize> crate::Environment<OBS_DIM,
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn linear_fa_weights(bias: [f32; 2]) -> Vec<u8> {
+        let mut weights = vec![1u8]; // LinearFA
+        weights.extend_from_slice(&0.01f32.to_le_bytes());
+        for _ in 0..8 {
+            weights.extend_from_slice(&0.0f32.to_le_bytes());
+        }
+        for b in bias {
+            weights.extend_from_slice(&b.to_le_bytes());
+        }
+        weights
+    }
+
     #[test]
     fn test_env_creation() {
         let mut weights = vec![0u8]; // TabularQLearning
@@ -209,6 +464,35 @@ mod tests {
         assert!(env.is_ok());
     }
 
+    #[test]
+    fn test_env_reports_policy_shape() {
+        let env = Env::<4, 2>::from_weights(&linear_fa_weights([0.0, 0.0])).unwrap();
+        assert_eq!(env.algorithm_name(), "LinearFA");
+        assert_eq!(env.parameter_count(), 4 * 2 + 2);
+    }
+
+    #[test]
+    fn test_env_info() {
+        let live = linear_fa_weights([0.0, 0.0]);
+        let mut env = Env::<4, 2>::from_weights(&live).unwrap();
+        let info = env.info();
+        assert_eq!(info.algorithm, AlgorithmType::LinearFA);
+        assert_eq!((info.obs_dim, info.action_dim), (4, 2));
+        assert_eq!(info.architecture, crate::Architecture::Linear);
+        assert_eq!(info.parameter_count, env.parameter_count());
+        assert!(info.memory_bytes >= info.parameter_count * 4);
+        assert_eq!(info.weights_sha256, <[u8; 32]>::from(Sha256::digest(&live)));
+        assert_eq!(info.format_version, WEIGHTS_FORMAT_VERSION);
+
+        // The hash follows hot-swaps
+        let new = linear_fa_weights([0.5, 0.5]);
+        env.update_weights(&new).unwrap();
+        assert_eq!(
+            env.info().weights_sha256,
+            <[u8; 32]>::from(Sha256::digest(&new))
+        );
+    }
+
     #[test]
     fn test_env_reset_and_step() {
         let mut weights = vec![0u8]; // TabularQLearning
@@ -245,4 +529,109 @@ mod tests {
         let bad_action = Action::new([1.5, -0.3]);
         assert!(env.check_invariant(&obs, &bad_action).is_err());
     }
+
+    #[test]
+    fn test_env_staged_weights_swap_at_step_boundary() {
+        let mut env = Env::<4, 2>::from_weights(&linear_fa_weights([0.0, 0.0])).unwrap();
+        let obs = Obs::new([1.0, 2.0, 3.0, 4.0]);
+
+        env.stage_weights(&linear_fa_weights([0.5, -0.5])).unwrap();
+        assert!(env.has_staged_weights());
+        assert_eq!(env.state().weights_generation, 0);
+
+        let action = env.step(&obs);
+        assert!(!env.has_staged_weights());
+        assert_eq!(env.state().weights_generation, 1);
+        assert!((action.as_slice()[0] - 0.5f32.tanh()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_env_failed_update_keeps_live_policy() {
+        let live = linear_fa_weights([0.25, 0.0]);
+        let mut env = Env::<4, 2>::from_weights(&live).unwrap();
+
+        let truncated = &linear_fa_weights([0.5, 0.5])[..10];
+        assert!(matches!(
+            env.update_weights(truncated),
+            Err(Error::MalformedWeights { offset: 10, .. })
+        ));
+
+        let mut tabular = vec![0u8];
+        tabular.extend_from_slice(&1u32.to_le_bytes());
+        tabular.extend_from_slice(&1u32.to_le_bytes());
+        tabular.extend_from_slice(&0.1f32.to_le_bytes());
+        tabular.extend_from_slice(&0.9f32.to_le_bytes());
+        assert!(matches!(
+            env.update_weights(&tabular),
+            Err(Error::WeightsMismatch(_))
+        ));
+
+        assert_eq!(env.state().weights_generation, 0);
+        assert_eq!(env.get_weights().unwrap(), live);
+    }
+
+    #[test]
+    fn test_env_rollback_weights() {
+        let old = linear_fa_weights([0.1, 0.2]);
+        let new = linear_fa_weights([0.3, 0.4]);
+        let mut env = Env::<4, 2>::from_weights(&old).unwrap();
+
+        assert_eq!(env.rollback_weights(), Err(Error::NoRollbackTarget));
+
+        env.update_weights(&new).unwrap();
+        assert_eq!(env.get_weights().unwrap(), new);
+
+        env.rollback_weights().unwrap();
+        assert_eq!(env.get_weights().unwrap(), old);
+        assert_eq!(env.state().weights_generation, 2);
+    }
+
+    #[test]
+    fn test_env_from_signed_weights() {
+        let secret = [3u8; 32];
+        let container = signed::sign(&linear_fa_weights([0.1, 0.2]), &secret).unwrap();
+        let public_key = signed::public_key(&secret);
+
+        assert!(Env::<4, 2>::from_signed_weights(&container, &public_key).is_ok());
+
+        let mut tampered = container.clone();
+        tampered[signed::HEADER_LEN + 1] ^= 0x80;
+        assert!(matches!(
+            Env::<4, 2>::from_signed_weights(&tampered, &public_key),
+            Err(Error::SignatureInvalid(_))
+        ));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_env_from_encrypted_weights() {
+        use crate::encrypted::{self, Cipher};
+
+        let key = [0x11u8; encrypted::KEY_LEN];
+        let nonce = [0x22u8; encrypted::NONCE_LEN];
+        let plaintext = linear_fa_weights([0.1, 0.2]);
+        let container = encrypted::encrypt(&plaintext, &key, Cipher::Aes256Gcm, &nonce).unwrap();
+
+        let mut env = Env::<4, 2>::from_encrypted_weights(&container, &key).unwrap();
+        assert!(env.is_encrypted());
+        assert!((env.step(&Obs::new([1.0; 4])).as_slice()[0]).is_finite());
+
+        // The plaintext is never exported, not even after a swap or through a shared policy
+        for _ in 0..2 {
+            assert!(matches!(
+                env.get_weights(),
+                Err(Error::UnsupportedAlgorithm(_))
+            ));
+            assert!(matches!(
+                crate::SharedPolicy::from_env(&env).get_weights(),
+                Err(Error::UnsupportedAlgorithm(_))
+            ));
+            env.update_weights(&plaintext).unwrap();
+        }
+
+        assert!(matches!(
+            Env::<4, 2>::from_encrypted_weights(&container, &[0u8; encrypted::KEY_LEN]),
+            Err(Error::InvalidWeights(_))
+        ));
+    }
 }
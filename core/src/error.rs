@@ -1,19 +1,73 @@
+use core::fmt;
 use thiserror::Error;
 
+/// Runtime safety invariant checked by `Env::check_invariant`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invariant {
+    /// Every action component lies in [-1, 1]
+    ActionBounds,
+    /// No observation component is NaN or infinite
+    FiniteObservation,
+    /// No action component is NaN or infinite
+    FiniteAction,
+}
+
+impl Invariant {
+    /// Stable snake_case name, as reported by `lr_last_error`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ActionBounds => "action_bounds",
+            Self::FiniteObservation => "finite_observation",
+            Self::FiniteAction => "finite_action",
+        }
+    }
+}
+
+impl fmt::Display for Invariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ActionBounds => "Action out of bounds",
+            Self::FiniteObservation => "Observation contains NaN or infinite values",
+            Self::FiniteAction => "Action contains NaN or infinite values",
+        })
+    }
+}
+
 /// Error types for LeanEdge-RL
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum Error {
     #[error("Invalid weights data: {0}")]
     InvalidWeights(String),
 
+    /// Weights that fail to parse at a known byte offset (counting the algorithm byte)
+    #[error("Invalid weights data at byte {offset}: {reason}")]
+    MalformedWeights { offset: usize, reason: String },
+
     #[error("Invalid observation size: expected {expected}, got {actual}")]
     InvalidObsSize { expected: usize, actual: usize },
 
     #[error("Invalid action size: expected {expected}, got {actual}")]
     InvalidActionSize { expected: usize, actual: usize },
 
+    /// Weights holding a different number of parameters than the policy's dimensions need
+    #[error("Invalid parameter count: expected {expected}, got {actual}")]
+    ParameterCount { expected: usize, actual: usize },
+
+    #[error("Weights signature invalid: {0}")]
+    SignatureInvalid(String),
+
+    #[error("Weights do not match the loaded policy: {0}")]
+    WeightsMismatch(String),
+
+    #[error("Buffer too small: {required} bytes needed, {available} given")]
+    BufferTooSmall { required: usize, available: usize },
+
     #[error("Safety invariant violation: {0}")]
-    InvariantViolation(String),
+    InvariantViolation(Invariant),
+
+    /// `rollback_weights` with no earlier policy to restore (nothing swapped in yet)
+    #[error("No previous weights to roll back to")]
+    NoRollbackTarget,
 
     #[error("Out of memory: {0}")]
     OutOfMemory(String),
@@ -27,10 +81,61 @@ pub enum Error {
     #[error("SIMD feature not available: {0}")]
     SimdNotAvailable(String),
 
+    #[error("Null pointer: {0}")]
+    NullPointer(String),
+
+    #[error("Invalid handle: {0}")]
+    BadHandle(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
 
+impl Error {
+    /// Expected and actual element counts of an observation or action size error, or the
+    /// required and available bytes of a too-small output buffer
+    pub fn sizes(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::InvalidObsSize { expected, actual }
+            | Self::InvalidActionSize { expected, actual }
+            | Self::ParameterCount { expected, actual } => Some((*expected, *actual)),
+            Self::BufferTooSmall {
+                required,
+                available,
+            } => Some((*required, *available)),
+            _ => None,
+        }
+    }
+
+    /// Byte offset in the weights blob where parsing failed
+    pub fn weights_offset(&self) -> Option<usize> {
+        match self {
+            Self::MalformedWeights { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Move a `MalformedWeights` offset `by` bytes further in, for a parser that was handed
+    /// a sub-slice of the blob
+    pub fn shifted(self, by: usize) -> Self {
+        match self {
+            Self::MalformedWeights { offset, reason } => Self::MalformedWeights {
+                offset: offset + by,
+                reason,
+            },
+            other => other,
+        }
+    }
+
+    /// The violated runtime invariant
+    pub fn invariant(&self) -> Option<Invariant> {
+        match self {
+            Self::InvariantViolation(invariant) => Some(*invariant),
+            _ => None,
+        }
+    }
+}
+
 /// Result type for LeanEdge-RL operations
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -42,32 +147,81 @@ pub mod ffi {
     pub const LR_EINVARIANT: i32 = -3;
     pub const LR_EOUTOFMEM: i32 = -4;
     pub const LR_EINTERNAL: i32 = -5;
+    pub const LR_EMISMATCH: i32 = -6;
+    pub const LR_ESIGNATURE: i32 = -7;
+    /// Encrypted weights failed authentication (wrong key or tampered container)
+    pub const LR_EDECRYPT: i32 = -8;
+    /// A required pointer argument was NULL
+    pub const LR_ENULL: i32 = -9;
+    /// Handle already freed, forged, or of another kind (`lr_ctx_t *` passed as `lr_env_t *`)
+    pub const LR_EBADHANDLE: i32 = -10;
+    /// Unknown algorithm byte in the weights
+    pub const LR_EUNSUPPORTED: i32 = -11;
+    /// Weights could not be serialized
+    pub const LR_ESERIALIZATION: i32 = -12;
+    /// Requested SIMD path not available on this CPU
+    pub const LR_ESIMD: i32 = -13;
+    /// Output buffer smaller than the data; the required size was still reported
+    pub const LR_EBUFSMALL: i32 = -14;
+    /// Rollback requested but no earlier weights were ever swapped out; state is unchanged
+    pub const LR_ENOROLLBACK: i32 = -15;
 
     /// Convert Rust error to FFI error code
     pub fn error_to_code(err: &crate::Error) -> i32 {
         match err {
-            crate::Error::InvalidWeights(_) => LR_EBADWEIGHTS,
-            crate::Error::InvalidObsSize { .. } | crate::Error::InvalidActionSize { .. } => {
-                LR_EINVSIZE
+            crate::Error::InvalidWeights(_) | crate::Error::MalformedWeights { .. } => {
+                LR_EBADWEIGHTS
             }
+            crate::Error::InvalidObsSize { .. }
+            | crate::Error::InvalidActionSize { .. }
+            | crate::Error::ParameterCount { .. } => LR_EINVSIZE,
+            crate::Error::WeightsMismatch(_) => LR_EMISMATCH,
+            crate::Error::SignatureInvalid(_) => LR_ESIGNATURE,
             crate::Error::InvariantViolation(_) => LR_EINVARIANT,
             crate::Error::OutOfMemory(_) => LR_EOUTOFMEM,
-            _ => LR_EINTERNAL,
+            crate::Error::Serialization(_) => LR_ESERIALIZATION,
+            crate::Error::UnsupportedAlgorithm(_) => LR_EUNSUPPORTED,
+            crate::Error::SimdNotAvailable(_) => LR_ESIMD,
+            crate::Error::BufferTooSmall { .. } => LR_EBUFSMALL,
+            crate::Error::NoRollbackTarget => LR_ENOROLLBACK,
+            crate::Error::NullPointer(_) => LR_ENULL,
+            crate::Error::BadHandle(_) => LR_EBADHANDLE,
+            crate::Error::Internal(_) => LR_EINTERNAL,
         }
     }
 
     /// Convert FFI error code to Rust error
+    ///
+    /// Returns the error the failed call recorded on this thread (`ffi::last_error`) when its
+    /// code matches. Otherwise the error is generic; size, buffer and invariant errors carry
+    /// data a bare code cannot supply, so they come back as `Internal`.
     pub fn code_to_error(code: i32) -> crate::Error {
+        if let Some((last_code, err)) = crate::ffi::last_error() {
+            if last_code == code {
+                return err;
+            }
+        }
         match code {
             LR_EBADWEIGHTS => crate::Error::InvalidWeights("FFI: Bad weights".to_string()),
-            LR_EINVSIZE => crate::Error::InvalidObsSize {
-                expected: 0,
-                actual: 0,
-            },
+            LR_EINVSIZE => crate::Error::Internal("FFI: Invalid size (no detail)".to_string()),
+            LR_EBUFSMALL => crate::Error::Internal("FFI: Buffer too small (no detail)".to_string()),
             LR_EINVARIANT => {
-                crate::Error::InvariantViolation("FFI: Invariant violation".to_string())
+                crate::Error::Internal("FFI: Invariant violation (no detail)".to_string())
             }
             LR_EOUTOFMEM => crate::Error::OutOfMemory("FFI: Out of memory".to_string()),
+            LR_EMISMATCH => crate::Error::WeightsMismatch("FFI: Weights mismatch".to_string()),
+            LR_ESIGNATURE => {
+                crate::Error::SignatureInvalid("FFI: Signature verification failed".to_string())
+            }
+            LR_ENULL => crate::Error::NullPointer("FFI: Null pointer argument".to_string()),
+            LR_EBADHANDLE => crate::Error::BadHandle("FFI: Stale or invalid handle".to_string()),
+            LR_EDECRYPT => crate::Error::InvalidWeights("FFI: Decryption failed".to_string()),
+            LR_EUNSUPPORTED => {
+                crate::Error::UnsupportedAlgorithm("FFI: Unsupported algorithm".to_string())
+            }
+            LR_ESERIALIZATION => crate::Error::Serialization("FFI: Serialization".to_string()),
+            LR_ESIMD => crate::Error::SimdNotAvailable("FFI: SIMD not available".to_string()),
+            LR_ENOROLLBACK => crate::Error::NoRollbackTarget,
             _ => crate::Error::Internal(format!("FFI: Unknown error code {}", code)),
         }
     }
@@ -85,5 +239,51 @@ mod tests {
 
         let converted_err = ffi::code_to_error(code);
         assert!(matches!(converted_err, Error::InvalidWeights(_)));
+
+        for code in [
+            ffi::LR_ENULL,
+            ffi::LR_EBADHANDLE,
+            ffi::LR_EUNSUPPORTED,
+            ffi::LR_ESERIALIZATION,
+            ffi::LR_ESIMD,
+            ffi::LR_ENOROLLBACK,
+        ] {
+            assert_eq!(ffi::error_to_code(&ffi::code_to_error(code)), code);
+        }
+    }
+
+    #[test]
+    fn test_structured_detail() {
+        let err = Error::InvalidActionSize {
+            expected: 2,
+            actual: 3,
+        };
+        assert_eq!(err.sizes(), Some((2, 3)));
+        assert_eq!(err.weights_offset(), None);
+
+        let err = Error::ParameterCount {
+            expected: 10,
+            actual: 36,
+        };
+        assert_eq!(err.sizes(), Some((10, 36)));
+        assert_eq!(ffi::error_to_code(&err), ffi::LR_EINVSIZE);
+
+        let err = Error::MalformedWeights {
+            offset: 17,
+            reason: "Truncated Q-table".to_string(),
+        };
+        assert_eq!(err.weights_offset(), Some(17));
+        assert_eq!(ffi::error_to_code(&err), ffi::LR_EBADWEIGHTS);
+        assert_eq!(
+            err.to_string(),
+            "Invalid weights data at byte 17: Truncated Q-table"
+        );
+
+        let err = Error::InvariantViolation(Invariant::FiniteAction);
+        assert_eq!(err.invariant().map(Invariant::name), Some("finite_action"));
+        assert_eq!(
+            err.to_string(),
+            "Safety invariant violation: Action contains NaN or infinite values"
+        );
     }
 }
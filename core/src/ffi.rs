@@ -1,125 +1,707 @@
 // This is the only file allowed to use unsafe code in the entire crate
 // All unsafe code must be audited and documented
+//
+// Handles given to C are generation-tagged tokens from `handles::HandleTable`, never pointers
+// to Rust memory, so the only raw pointers dereferenced here are caller buffers. Every entry
+// point runs inside `guard`, which records each failure for `lr_last_error` and turns a panic
+// into `LR_EINTERNAL` instead of unwinding into C. That conversion only exists in unwinding
+// builds (debug, tests, hosts built with `panic = "unwind"`): the release profile sets
+// `panic = "abort"`, so there a panic still aborts the process. Weight parsing and stepping
+// must therefore never panic on any input; `core/fuzz` checks that with arbitrary blobs.
 
-use crate::{action::Action, env::Env, error::ffi as error_ffi, obs::Obs};
+use crate::{
+    action::Action,
+    dims::{DynEnv, MAX_OBS_DIM},
+    env::{AlgorithmType, Env},
+    error::{ffi as error_ffi, Error, Invariant},
+    handles::HandleTable,
+    info::{Architecture, PolicyInfo},
+    obs::Obs,
+    shared::{SharedPolicy, StepContext},
+    signed,
+};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// Observation dimension `lr_init` loads policies at (`lr_init_dims` takes any)
+pub const LR_OBS_DIM: usize = 4;
+
+/// Action dimension `lr_init` loads policies at (`lr_init_dims` takes any)
+pub const LR_ACTION_DIM: usize = 2;
+
+/// Most layers (input and output included) a TinyNN policy has
+pub const LR_MAX_LAYERS: usize = 5;
 
 /// Opaque environment handle for C API
+///
+/// Calls on one handle are serialized, so it may be used from any thread; for several control
+/// loops running concurrently on one policy, share it with `lr_policy_share`.
 #[allow(non_camel_case_types)]
 pub struct lr_env {
-    env: Option<Env<4, 2>>, // Using fixed dimensions for C API
-    weights: Vec<u8>,       // Retained for provenance / future reloads
+    env: Box<dyn DynEnv>, // `LR_OBS_DIM` x `LR_ACTION_DIM`, or the dims given to `lr_init_dims`
+    weights: Vec<u8>,     // Retained for provenance / future reloads
+    previous_weights: Vec<u8>, // Blob of the policy `lr_rollback_weights` would restore
+    public_key: Option<[u8; signed::PUBLIC_KEY_LEN]>, // Key every weight update must verify with
+    encrypted: bool,      // From `lr_init_encrypted`: plaintext of swapped-in blobs is not retained
+    last_error: Option<Failure>, // Last failed call on this handle, for `lr_last_error`
 }
 
-/// C API: Initialize environment with weights
-#[no_mangle]
-pub extern "C" fn lr_init(weights: *const u8, len: usize, out: *mut *mut lr_env) -> i32 {
-    // Safety: Check for null pointers
-    if weights.is_null() || out.is_null() {
-        return error_ffi::LR_EBADWEIGHTS;
+/// Opaque shared policy handle for C API; every `lr_policy_*` call is thread-safe
+#[allow(non_camel_case_types)]
+pub struct lr_policy {
+    shared: SharedPolicy<LR_OBS_DIM, LR_ACTION_DIM>,
+    public_key: Option<[u8; signed::PUBLIC_KEY_LEN]>,
+    last_error: Option<Failure>,
+}
+
+/// Opaque per-thread step context over a shared policy
+///
+/// Each control loop owns its context; calls on one context are serialized, but contexts of
+/// the same policy never contend with each other.
+#[allow(non_camel_case_types)]
+pub struct lr_ctx {
+    ctx: StepContext<LR_OBS_DIM, LR_ACTION_DIM>,
+}
+
+/// Structured detail of the last failed call, filled by `lr_last_error`
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct lr_error {
+    /// Status code the call returned; `LR_OK` when nothing has failed
+    pub code: i32,
+    /// Expected element count of an `LR_EINVSIZE` failure (required bytes for
+    /// `LR_EBUFSMALL`), else 0
+    pub expected: usize,
+    /// Actual element count of an `LR_EINVSIZE` failure (bytes given for `LR_EBUFSMALL`),
+    /// else 0
+    pub actual: usize,
+    /// Byte offset in the weights blob where parsing failed, or `SIZE_MAX` when unknown
+    pub weights_offset: usize,
+    /// Name of the invariant an `LR_EINVARIANT` failure violated (static), else ""
+    pub invariant: *const c_char,
+}
+
+/// Description of the loaded policy, filled by `lr_get_info`
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct lr_info {
+    /// Algorithm byte of the weights: 0 TabularQLearning, 1 LinearFA, 2 TinyNN
+    pub algorithm: u8,
+    /// Algorithm name (static string)
+    pub algorithm_name: *const c_char,
+    pub obs_dim: usize,
+    pub action_dim: usize,
+    /// Q-table shape of a TabularQLearning policy, else 0
+    pub num_states: usize,
+    pub num_actions: usize,
+    /// Layer count of a TinyNN policy (input and output included), else 0
+    pub num_layers: usize,
+    /// First `num_layers` entries are the layer sizes
+    pub layer_sizes: [usize; LR_MAX_LAYERS],
+    /// First `num_layers - 1` entries are activation ids (0 ReLU, 1 Tanh, 2 Sigmoid, 3 Linear)
+    pub activations: [u8; LR_MAX_LAYERS],
+    /// Learned parameters (Q-values, weights and biases)
+    pub parameter_count: usize,
+    /// Bytes the policy occupies in memory
+    pub memory_bytes: usize,
+    /// SHA-256 of the weights blob the policy was loaded from
+    pub weights_sha256: [u8; 32],
+    /// Weights format version
+    pub format_version: u32,
+}
+
+static ENVS: HandleTable<lr_env> = HandleTable::new(0xE1A5_0E17);
+static POLICIES: HandleTable<lr_policy> = HandleTable::new(0x9C13_7A05);
+static CTXS: HandleTable<lr_ctx> = HandleTable::new(0x35D7_C4E9);
+
+/// A failed call: the status code returned to C and the error behind it
+#[derive(Debug, Clone)]
+struct Failure {
+    code: i32,
+    error: Error,
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        Self {
+            code: error_ffi::error_to_code(&error),
+            error,
+        }
     }
+}
 
-    // Safety: Validate input slice
-    let weights_slice = unsafe { std::slice::from_raw_parts(weights, len) };
+type FfiResult<T = ()> = Result<T, Failure>;
 
-    // Create weights vector to keep data alive
-    let weights_vec = weights_slice.to_vec();
+thread_local! {
+    /// Last failed call on this thread, whatever handle it was made on
+    static LAST_ERROR: RefCell<Option<Failure>> = const { RefCell::new(None) };
+}
 
-    // Create environment
-    let env_result = Env::<4, 2>::from_weights(&weights_vec);
-    match env_result {
-        Ok(env) => {
-            // Allocate environment handle
-            let env_handle = Box::new(lr_env {
-                env: Some(env),
-                weights: weights_vec,
-            });
+/// Last failure recorded by a C API call on this thread, as its code and error
+pub fn last_error() -> Option<(i32, Error)> {
+    LAST_ERROR
+        .try_with(|last| {
+            last.borrow()
+                .as_ref()
+                .map(|failure| (failure.code, failure.error.clone()))
+        })
+        .ok()
+        .flatten()
+}
 
-            // Safety: Write pointer to output
-            unsafe {
-                *out = Box::into_raw(env_handle);
-            }
+/// Object behind a C handle
+trait Handle {
+    /// Name of the handle kind, for error messages
+    const KIND: &'static str;
 
-            error_ffi::LR_OK
+    /// Slot recording the last failed call on this handle, if the kind keeps one
+    fn last_error(&mut self) -> Option<&mut Option<Failure>> {
+        None
+    }
+}
+
+impl Handle for lr_env {
+    const KIND: &'static str = "env";
+
+    fn last_error(&mut self) -> Option<&mut Option<Failure>> {
+        Some(&mut self.last_error)
+    }
+}
+
+impl Handle for lr_policy {
+    const KIND: &'static str = "policy";
+
+    fn last_error(&mut self) -> Option<&mut Option<Failure>> {
+        Some(&mut self.last_error)
+    }
+}
+
+impl Handle for lr_ctx {
+    const KIND: &'static str = "ctx";
+}
+
+/// Run an entry point and return its status code
+///
+/// A failure is recorded as this thread's last error. A panic becomes `LR_EINTERNAL` in
+/// unwinding builds only; under the release profile's `panic = "abort"` it aborts, so this is
+/// a last line of defence, not error handling.
+fn guard(body: impl FnOnce() -> FfiResult) -> i32 {
+    let result = panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown payload");
+        Err(Error::Internal(format!("panic: {}", message)).into())
+    });
+
+    match result {
+        Ok(()) => error_ffi::LR_OK,
+        Err(failure) => {
+            let code = failure.code;
+            let _ = LAST_ERROR.try_with(|last| *last.borrow_mut() = Some(failure));
+            code
         }
-        Err(_) => error_ffi::LR_EBADWEIGHTS,
     }
 }
 
-/// C API: Reset environment with initial observation
-#[no_mangle]
-pub extern "C" fn lr_reset(env: *mut lr_env, obs: *const f32, action: *mut f32) -> i32 {
-    // Safety: Check for null pointers
-    if env.is_null() || obs.is_null() || action.is_null() {
-        return error_ffi::LR_EINVSIZE;
+/// Run a last-error query: like `guard`, but never records, so a failed query cannot
+/// overwrite the error it was asked about
+fn query(body: impl FnOnce() -> FfiResult) -> i32 {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => error_ffi::LR_OK,
+        Ok(Err(failure)) => failure.code,
+        Err(_) => error_ffi::LR_EINTERNAL,
     }
+}
 
-    // Safety: Dereference environment handle
-    let env_handle = unsafe { &mut *env };
-    let env_ref = match &mut env_handle.env {
-        Some(env) => env,
-        None => return error_ffi::LR_EINTERNAL,
-    };
+/// `LR_ENULL` if `ptr` is NULL
+fn non_null<T>(ptr: *const T, name: &str) -> FfiResult {
+    if ptr.is_null() {
+        return Err(Error::NullPointer(format!("{} is NULL", name)).into());
+    }
+    Ok(())
+}
 
-    // Safety: Create observation from C array
-    let obs_slice = unsafe { std::slice::from_raw_parts(obs, 4) };
-    let obs_result = Obs::<4>::from_slice(obs_slice);
-    let obs = match obs_result {
-        Ok(obs) => obs,
-        Err(_) => return error_ffi::LR_EINVSIZE,
-    };
+/// Run `f` on the object behind a C handle, recording a failure of `f` on that object:
+/// `LR_ENULL` for NULL, `LR_EBADHANDLE` when the handle is stale, forged or of another kind
+fn with_handle<T: Handle, H, R>(
+    table: &HandleTable<T>,
+    handle: *const H,
+    f: impl FnOnce(&mut T) -> FfiResult<R>,
+) -> FfiResult<R> {
+    non_null(handle, T::KIND)?;
+    table
+        .with(handle.addr(), |object| {
+            let result = f(object);
+            if let (Err(failure), Some(last)) = (&result, object.last_error()) {
+                *last = Some(failure.clone());
+            }
+            result
+        })
+        .unwrap_or_else(|| {
+            Err(Error::BadHandle(format!("{} handle is stale or invalid", T::KIND)).into())
+        })
+}
 
-    // Reset environment
-    let action_result = env_ref.reset(&obs);
+/// Store `object` and write its handle to `out`
+fn install<T, H>(table: &HandleTable<T>, object: T, out: *mut *mut H) -> FfiResult {
+    let token = table
+        .insert(object)
+        .ok_or_else(|| Error::OutOfMemory("Handle table is full".to_string()))?;
 
-    // Safety: Write action to output array
-    let action_slice = unsafe { std::slice::from_raw_parts_mut(action, 2) };
-    action_slice.copy_from_slice(action_result.as_slice());
+    // Safety: `out` was checked non-null by the caller
+    unsafe {
+        *out = ptr::without_provenance_mut(token);
+    }
 
-    error_ffi::LR_OK
+    Ok(())
 }
 
-/// C API: Step environment with new observation
-#[no_mangle]
-pub extern "C" fn lr_step(env: *mut lr_env, obs: *const f32, action: *mut f32) -> i32 {
-    // Safety: Check for null pointers
-    if env.is_null() || obs.is_null() || action.is_null() {
-        return error_ffi::LR_EINVSIZE;
+/// Invalidate a C handle; NULL, stale and foreign handles are ignored
+fn release<T, H>(table: &HandleTable<T>, handle: *mut H) {
+    if !handle.is_null() {
+        // Dropped outside the table lock; calls still inside the object finish first
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| table.remove(handle.addr())));
+    }
+}
+
+/// Borrow a caller's weights buffer; NULL with `len == 0` is an empty (invalid) blob, as an
+/// empty `std::vector` passes
+fn weights_slice<'a>(weights: *const u8, len: usize) -> FfiResult<&'a [u8]> {
+    if weights.is_null() {
+        return if len == 0 {
+            Ok(&[])
+        } else {
+            Err(Error::NullPointer("weights is NULL".to_string()).into())
+        };
+    }
+    if len > isize::MAX as usize {
+        return Err(Error::InvalidWeights("Weights length exceeds isize::MAX".to_string()).into());
+    }
+
+    // Safety: Caller guarantees `weights` points to `len` readable bytes
+    Ok(unsafe { std::slice::from_raw_parts(weights, len) })
+}
+
+/// Copy an observation out of a caller's `obs_dim` floats into `buf`
+///
+/// The copy keeps the observation apart from the action buffer, which C may alias with it.
+fn read_obs(obs: *const f32, obs_dim: usize, buf: &mut [f32; MAX_OBS_DIM]) -> FfiResult<&[f32]> {
+    non_null(obs, "obs")?;
+
+    // Safety: Caller guarantees `obs` points to `obs_dim` readable floats; every handle's
+    // `obs_dim` is at most MAX_OBS_DIM
+    let obs_slice = unsafe { std::slice::from_raw_parts(obs, obs_dim) };
+    let values = &mut buf[..obs_dim];
+    values.copy_from_slice(obs_slice);
+    Ok(values)
+}
+
+/// Borrow a caller's `action_dim` floats (checked non-null up front)
+fn action_slice<'a>(action: *mut f32, action_dim: usize) -> &'a mut [f32] {
+    // Safety: Caller guarantees `action` points to `action_dim` writable floats
+    unsafe { std::slice::from_raw_parts_mut(action, action_dim) }
+}
+
+/// Write a value through a caller's output pointer (checked non-null up front)
+fn write_out<T>(out: *mut T, value: T) {
+    // Safety: Caller guarantees `out` points to a writable `T`
+    unsafe {
+        *out = value;
     }
+}
 
-    // Safety: Dereference environment handle
-    let env_handle = unsafe { &mut *env };
-    let env_ref = match &mut env_handle.env {
-        Some(env) => env,
-        None => return error_ffi::LR_EINTERNAL,
+/// Build an `LR_OBS_DIM` x `LR_ACTION_DIM` environment from a weights blob, verifying its
+/// signature when a key is given
+fn load_env(
+    weights: &[u8],
+    public_key: Option<&[u8; 32]>,
+) -> crate::Result<Env<LR_OBS_DIM, LR_ACTION_DIM>> {
+    match public_key {
+        Some(key) => Env::from_signed_weights(weights, key),
+        None => Env::from_weights(weights),
+    }
+}
+
+/// Allocate a handle for a freshly loaded environment and write it to `out`
+///
+/// `public_key` is the key the weights were verified with; updates on the handle must be
+/// signed by it. `encrypted` marks a handle whose `weights_vec` is ciphertext.
+fn install_env(
+    env: Box<dyn DynEnv>,
+    weights_vec: Vec<u8>,
+    public_key: Option<[u8; signed::PUBLIC_KEY_LEN]>,
+    encrypted: bool,
+    out: *mut *mut lr_env,
+) -> FfiResult {
+    let env_handle = lr_env {
+        env,
+        weights: weights_vec,
+        previous_weights: Vec::new(),
+        public_key,
+        encrypted,
+        last_error: None,
     };
+    install(&ENVS, env_handle, out)
+}
+
+/// Step `env` or a context: read `obs`, run `act`, which writes `action`
+///
+/// `dims` gives the handle's `(obs_dim, action_dim)`: how many floats are read and written.
+fn act_on<T: Handle, H>(
+    table: &HandleTable<T>,
+    handle: *mut H,
+    obs: *const f32,
+    action: *mut f32,
+    dims: impl FnOnce(&T) -> (usize, usize),
+    act: impl FnOnce(&mut T, &[f32], &mut [f32]) -> crate::Result<()>,
+) -> i32 {
+    guard(|| {
+        with_handle(table, handle, |object| {
+            non_null(action, "action")?;
+            let (obs_dim, action_dim) = dims(object);
+            let mut buf = [0.0; MAX_OBS_DIM];
+            let obs = read_obs(obs, obs_dim, &mut buf)?;
+            Ok(act(object, obs, action_slice(action, action_dim))?)
+        })
+    })
+}
 
-    // Safety: Create observation from C array
-    let obs_slice = unsafe { std::slice::from_raw_parts(obs, 4) };
-    let obs_result = Obs::<4>::from_slice(obs_slice);
-    let obs = match obs_result {
-        Ok(obs) => obs,
-        Err(_) => return error_ffi::LR_EINVSIZE,
+/// `act_on` for a fixed `LR_OBS_DIM` x `LR_ACTION_DIM` context
+fn act_on_ctx(
+    ctx: *mut lr_ctx,
+    obs: *const f32,
+    action: *mut f32,
+    act: impl FnOnce(
+        &mut StepContext<LR_OBS_DIM, LR_ACTION_DIM>,
+        &Obs<LR_OBS_DIM>,
+    ) -> Action<LR_ACTION_DIM>,
+) -> i32 {
+    act_on(
+        &CTXS,
+        ctx,
+        obs,
+        action,
+        |_| (LR_OBS_DIM, LR_ACTION_DIM),
+        |ctx, obs, out| {
+            out.copy_from_slice(act(&mut ctx.ctx, &Obs::from_slice(obs)?).as_slice());
+            Ok(())
+        },
+    )
+}
+
+/// Run `f` on the last failure recorded on a handle, or on this thread when `handle` is NULL
+fn last_failure<T: Handle, H, R>(
+    table: &HandleTable<T>,
+    handle: *const H,
+    f: impl FnOnce(Option<&Failure>) -> R,
+) -> FfiResult<R> {
+    if handle.is_null() {
+        return Ok(LAST_ERROR.with(|last| f(last.borrow().as_ref())));
+    }
+    table
+        .with(handle.addr(), |object| {
+            f(object.last_error().and_then(|last| last.as_ref()))
+        })
+        .ok_or_else(|| Error::BadHandle(format!("{} handle is stale or invalid", T::KIND)).into())
+}
+
+/// `lr_error_t::invariant` of a violated invariant
+fn invariant_name(invariant: Option<Invariant>) -> &'static CStr {
+    match invariant {
+        Some(Invariant::ActionBounds) => c"action_bounds",
+        Some(Invariant::FiniteObservation) => c"finite_observation",
+        Some(Invariant::FiniteAction) => c"finite_action",
+        None => c"",
+    }
+}
+
+/// `lr_info_t` of a policy
+fn info_to_c(info: &PolicyInfo) -> lr_info {
+    let mut out = lr_info {
+        algorithm: info.algorithm.to_u8(),
+        algorithm_name: match info.algorithm {
+            AlgorithmType::TabularQLearning => c"TabularQLearning",
+            AlgorithmType::LinearFA => c"LinearFA",
+            AlgorithmType::TinyNN => c"TinyNN",
+        }
+        .as_ptr(),
+        obs_dim: info.obs_dim,
+        action_dim: info.action_dim,
+        num_states: 0,
+        num_actions: 0,
+        num_layers: 0,
+        layer_sizes: [0; LR_MAX_LAYERS],
+        activations: [0; LR_MAX_LAYERS],
+        parameter_count: info.parameter_count,
+        memory_bytes: info.memory_bytes,
+        weights_sha256: info.weights_sha256,
+        format_version: info.format_version,
     };
+    match &info.architecture {
+        Architecture::Tabular {
+            num_states,
+            num_actions,
+        } => {
+            out.num_states = *num_states;
+            out.num_actions = *num_actions;
+        }
+        Architecture::Network {
+            layer_sizes,
+            activations,
+        } => {
+            // Parsers cap networks at LR_MAX_LAYERS layers
+            out.num_layers = layer_sizes.len().min(LR_MAX_LAYERS);
+            for (slot, size) in out.layer_sizes.iter_mut().zip(layer_sizes) {
+                *slot = *size;
+            }
+            for (slot, activation) in out.activations.iter_mut().zip(activations) {
+                *slot = activation.to_u8();
+            }
+        }
+        Architecture::Linear | Architecture::None => {}
+    }
+    out
+}
 
-    // Step environment
-    let action_result = env_ref.step(&obs);
+/// Structured detail of a failure; `code == LR_OK` when there is none
+fn describe(failure: Option<&Failure>) -> lr_error {
+    let error = failure.map(|failure| &failure.error);
+    let (expected, actual) = error.and_then(Error::sizes).unwrap_or((0, 0));
+    lr_error {
+        code: failure.map_or(error_ffi::LR_OK, |failure| failure.code),
+        expected,
+        actual,
+        weights_offset: error.and_then(Error::weights_offset).unwrap_or(usize::MAX),
+        invariant: invariant_name(error.and_then(Error::invariant)).as_ptr(),
+    }
+}
 
-    // Safety: Write action to output array
-    let action_slice = unsafe { std::slice::from_raw_parts_mut(action, 2) };
-    action_slice.copy_from_slice(action_result.as_slice());
+/// Two-phase copy of `data` into a caller's buffer of `len` bytes
+///
+/// `*actual_len` always receives `data.len()`. NULL with `len == 0` only queries that size;
+/// a buffer shorter than `data` is left untouched and fails with `LR_EBUFSMALL`.
+fn copy_out(data: &[u8], buf: *mut u8, len: usize, actual_len: *mut usize) -> FfiResult {
+    non_null(actual_len, "actual_len")?;
+    write_out(actual_len, data.len());
+    if buf.is_null() && len == 0 {
+        return Ok(());
+    }
+    non_null(buf, "buffer")?;
+    if len < data.len() {
+        return Err(Error::BufferTooSmall {
+            required: data.len(),
+            available: len,
+        }
+        .into());
+    }
 
-    error_ffi::LR_OK
+    // Safety: Caller guarantees `buf` points to `len` writable bytes
+    unsafe {
+        ptr::copy_nonoverlapping(data.as_ptr(), buf, data.len());
+    }
+    Ok(())
 }
 
-/// C API: Free environment handle
+/// Copy a failure's message, NUL-terminated, with `copy_out` (empty when there is none)
+fn write_message(
+    failure: Option<&Failure>,
+    buf: *mut c_char,
+    len: usize,
+    actual_len: *mut usize,
+) -> FfiResult {
+    let mut message = failure
+        .map(|failure| failure.error.to_string())
+        .unwrap_or_default()
+        .into_bytes();
+    message.push(0);
+    copy_out(&message, buf.cast(), len, actual_len)
+}
+
+/// C API: Initialize environment with weights shaped for `LR_OBS_DIM` x `LR_ACTION_DIM`
+///
+/// If the library was built with an embedded verification key (`LEANRL_WEIGHTS_PUBKEY`),
+/// `weights` must be a signed container and is verified against that key. Returns
+/// `LR_EBADWEIGHTS` for malformed weights and `LR_EUNSUPPORTED` for an unknown algorithm.
+/// On error `*out` is set to NULL.
 #[no_mangle]
-pub extern "C" fn lr_free(env: *mut lr_env) {
-    if !env.is_null() {
-        // Safety: Drop the environment handle
+pub extern "C" fn lr_init(weights: *const u8, len: usize, out: *mut *mut lr_env) -> i32 {
+    guard(|| {
+        non_null(out, "out")?;
+        write_out(out, ptr::null_mut());
+        let weights_slice = weights_slice(weights, len)?;
+
+        // Create weights vector to keep data alive
+        let env = load_env(weights_slice, signed::EMBEDDED_PUBLIC_KEY.as_ref())?;
+        install_env(
+            Box::new(env),
+            weights_slice.to_vec(),
+            signed::EMBEDDED_PUBLIC_KEY,
+            false,
+            out,
+        )
+    })
+}
+
+/// C API: Initialize environment with weights shaped for `obs_dim` x `action_dim`
+///
+/// Like `lr_init`, embedded verification key included, for any shape from 1x1 up to 16x8;
+/// `lr_get_dims` reports the dims, and `lr_reset`, `lr_step` and `lr_check_invariant` read
+/// `obs_dim` observations and write or read `action_dim` actions. Returns `LR_EINVSIZE` for
+/// dims out of range or weights shaped for other dims. On error `*out` is set to NULL.
+#[cfg(feature = "dyn-dims")]
+#[no_mangle]
+pub extern "C" fn lr_init_dims(
+    weights: *const u8,
+    len: usize,
+    obs_dim: usize,
+    action_dim: usize,
+    out: *mut *mut lr_env,
+) -> i32 {
+    use crate::dims;
+
+    guard(|| {
+        non_null(out, "out")?;
+        write_out(out, ptr::null_mut());
+        let weights_slice = weights_slice(weights, len)?;
+
+        let env = match &signed::EMBEDDED_PUBLIC_KEY {
+            Some(key) => dims::new_signed_env(weights_slice, key, obs_dim, action_dim)?,
+            None => dims::new_env(weights_slice, obs_dim, action_dim)?,
+        };
+        install_env(
+            env,
+            weights_slice.to_vec(),
+            signed::EMBEDDED_PUBLIC_KEY,
+            false,
+            out,
+        )
+    })
+}
+
+/// C API: Initialize environment from a signed weights container
+///
+/// `public_key` points to a 32-byte Ed25519 public key, or is NULL to use the key embedded
+/// at build time. Returns `LR_ESIGNATURE` if the signature does not verify, or if NULL is
+/// passed and no key was embedded. On error `*out` is set to NULL.
+#[no_mangle]
+pub extern "C" fn lr_init_signed(
+    weights: *const u8,
+    len: usize,
+    public_key: *const u8,
+    out: *mut *mut lr_env,
+) -> i32 {
+    guard(|| {
+        non_null(out, "out")?;
+        write_out(out, ptr::null_mut());
+        let weights_slice = weights_slice(weights, len)?;
+
+        let key = if public_key.is_null() {
+            signed::EMBEDDED_PUBLIC_KEY.ok_or_else(|| {
+                Error::SignatureInvalid("No public key given and none embedded".to_string())
+            })?
+        } else {
+            // Safety: Caller guarantees `public_key` points to PUBLIC_KEY_LEN readable bytes
+            let mut key = [0u8; signed::PUBLIC_KEY_LEN];
+            unsafe {
+                ptr::copy_nonoverlapping(public_key, key.as_mut_ptr(), signed::PUBLIC_KEY_LEN);
+            }
+            key
+        };
+
+        let env = load_env(weights_slice, Some(&key))?;
+        install_env(Box::new(env), weights_slice.to_vec(), Some(key), false, out)
+    })
+}
+
+/// C API: Initialize environment from an AEAD-encrypted weights container
+///
+/// `key` points to a 32-byte key that is used only for this call. Returns `LR_EDECRYPT`
+/// if the container fails authentication. The handle keeps the ciphertext but never the
+/// decrypted weights: `lr_get_weights` on it returns `LR_EUNSUPPORTED`, and plaintext blobs
+/// later swapped in with `lr_update_weights` are not retained. On error `*out` is set to NULL.
+#[cfg(feature = "encryption")]
+#[no_mangle]
+pub extern "C" fn lr_init_encrypted(
+    weights: *const u8,
+    len: usize,
+    key: *const u8,
+    out: *mut *mut lr_env,
+) -> i32 {
+    use crate::encrypted;
+    use zeroize::Zeroizing;
+
+    guard(|| {
+        non_null(out, "out")?;
+        non_null(key, "key")?;
+        write_out(out, ptr::null_mut());
+        let weights_slice = weights_slice(weights, len)?;
+
+        // Safety: Caller guarantees `key` points to KEY_LEN readable bytes
+        let mut key_bytes = Zeroizing::new([0u8; encrypted::KEY_LEN]);
         unsafe {
-            let _ = Box::from_raw(env);
+            ptr::copy_nonoverlapping(key, key_bytes.as_mut_ptr(), encrypted::KEY_LEN);
         }
-    }
+
+        let plaintext = encrypted::decrypt(weights_slice, &key_bytes).map_err(|error| Failure {
+            code: error_ffi::LR_EDECRYPT,
+            error,
+        })?;
+
+        let mut env = load_env(&plaintext, signed::EMBEDDED_PUBLIC_KEY.as_ref())?;
+        env.mark_encrypted();
+        install_env(
+            Box::new(env),
+            weights_slice.to_vec(),
+            signed::EMBEDDED_PUBLIC_KEY,
+            true,
+            out,
+        )
+    })
+}
+
+/// C API: Reset environment with initial observation
+///
+/// Reads `obs_dim` floats from `obs` and writes `action_dim` floats to `action` (`lr_get_dims`).
+#[no_mangle]
+pub extern "C" fn lr_reset(env: *mut lr_env, obs: *const f32, action: *mut f32) -> i32 {
+    act_on(
+        &ENVS,
+        env,
+        obs,
+        action,
+        |env_handle| env_handle.env.dims(),
+        |env_handle, obs, action| env_handle.env.reset(obs, action),
+    )
+}
+
+/// C API: Step environment with new observation
+///
+/// Reads `obs_dim` floats from `obs` and writes `action_dim` floats to `action` (`lr_get_dims`).
+#[no_mangle]
+pub extern "C" fn lr_step(env: *mut lr_env, obs: *const f32, action: *mut f32) -> i32 {
+    act_on(
+        &ENVS,
+        env,
+        obs,
+        action,
+        |env_handle| env_handle.env.dims(),
+        |env_handle, obs, action| env_handle.env.step(obs, action),
+    )
+}
+
+/// C API: Free environment handle
+///
+/// NULL, already freed and foreign handles are ignored. Calls running on `env` in other
+/// threads finish before the environment is dropped.
+#[no_mangle]
+pub extern "C" fn lr_free(env: *mut lr_env) {
+    release(&ENVS, env);
 }
 
 /// C API: Get environment state (for debugging/testing)
@@ -129,100 +711,131 @@ pub extern "C" fn lr_get_state(
     step_count: *mut u64,
     episode_count: *mut u64,
 ) -> i32 {
-    // Safety: Check for null pointers
-    if env.is_null() || step_count.is_null() || episode_count.is_null() {
-        return error_ffi::LR_EINTERNAL;
-    }
-
-    // Safety: Dereference environment handle
-    let env_handle = unsafe { &*env };
-    let env_ref = match &env_handle.env {
-        Some(env) => env,
-        None => return error_ffi::LR_EINTERNAL,
-    };
-
-    let state = env_ref.state();
-
-    // Safety: Write state to output pointers
-    unsafe {
-        *step_count = state.step_count;
-        *episode_count = state.episode_count;
-    }
+    guard(|| {
+        with_handle(&ENVS, env, |env_handle| {
+            non_null(step_count, "step_count")?;
+            non_null(episode_count, "episode_count")?;
+            let state = env_handle.env.state();
+            write_out(step_count, state.step_count);
+            write_out(episode_count, state.episode_count);
+            Ok(())
+        })
+    })
+}
 
-    error_ffi::LR_OK
+/// C API: Get the observation and action dimensions of the loaded policy
+///
+/// The dimensions are those the handle was created with: `LR_OBS_DIM` x `LR_ACTION_DIM` for
+/// `lr_init`, or the ones given to `lr_init_dims`. Init and the weight swaps reject blobs
+/// shaped for other dims with `LR_EINVSIZE`. `lr_reset`, `lr_step` and `lr_check_invariant`
+/// read `obs_dim` observations and write or read `action_dim` actions.
+#[no_mangle]
+pub extern "C" fn lr_get_dims(
+    env: *const lr_env,
+    obs_dim: *mut usize,
+    action_dim: *mut usize,
+) -> i32 {
+    guard(|| {
+        with_handle(&ENVS, env, |env_handle| {
+            non_null(obs_dim, "obs_dim")?;
+            non_null(action_dim, "action_dim")?;
+            let (obs, action) = env_handle.env.dims();
+            write_out(obs_dim, obs);
+            write_out(action_dim, action);
+            Ok(())
+        })
+    })
 }
 
 /// C API: Check safety invariant
+///
+/// Returns `LR_EINVARIANT` on a violation; `lr_last_error` names the invariant.
 #[no_mangle]
 pub extern "C" fn lr_check_invariant(
     env: *const lr_env,
     obs: *const f32,
     action: *const f32,
 ) -> i32 {
-    // Safety: Check for null pointers
-    if env.is_null() || obs.is_null() || action.is_null() {
-        return error_ffi::LR_EINVARIANT;
-    }
-
-    // Safety: Dereference environment handle
-    let env_handle = unsafe { &*env };
-    let env_ref = match &env_handle.env {
-        Some(env) => env,
-        None => return error_ffi::LR_EINTERNAL,
-    };
+    guard(|| {
+        with_handle(&ENVS, env, |env_handle| {
+            let (obs_dim, action_dim) = env_handle.env.dims();
+            let mut buf = [0.0; MAX_OBS_DIM];
+            let obs = read_obs(obs, obs_dim, &mut buf)?;
+            non_null(action, "action")?;
 
-    // Safety: Create observation and action from C arrays
-    let obs_slice = unsafe { std::slice::from_raw_parts(obs, 4) };
-    let obs_result = Obs::<4>::from_slice(obs_slice);
-    let obs = match obs_result {
-        Ok(obs) => obs,
-        Err(_) => return error_ffi::LR_EINVSIZE,
-    };
-
-    let action_slice = unsafe { std::slice::from_raw_parts(action, 2) };
-    let action_result = Action::<2>::from_slice(action_slice);
-    let action = match action_result {
-        Ok(action) => action,
-        Err(_) => return error_ffi::LR_EINVSIZE,
-    };
+            // Safety: Caller guarantees `action` points to `action_dim` readable floats
+            let action_slice = unsafe { std::slice::from_raw_parts(action, action_dim) };
 
-    // Check invariant
-    match env_ref.check_invariant(&obs, &action) {
-        Ok(_) => error_ffi::LR_OK,
-        Err(_) => error_ffi::LR_EINVARIANT,
-    }
+            Ok(env_handle.env.check_invariant(obs, action_slice)?)
+        })
+    })
 }
 
 /// C API: Update environment weights
+///
+/// The new blob is validated into a shadow policy before being swapped in, so on any error
+/// the live policy keeps running unchanged. On a handle whose weights were verified at init
+/// (`lr_init_signed`, or any init with an embedded key) the blob must be a container signed
+/// by that same key, else `LR_ESIGNATURE`. Returns `LR_EBADWEIGHTS` for malformed weights,
+/// `LR_EUNSUPPORTED` for an unknown algorithm and `LR_EMISMATCH` when the blob is for a
+/// different algorithm than the loaded one.
 #[no_mangle]
 pub extern "C" fn lr_update_weights(env: *mut lr_env, weights: *const u8, len: usize) -> i32 {
-    // Safety: Check for null pointers
-    if env.is_null() || weights.is_null() {
-        return error_ffi::LR_EBADWEIGHTS;
-    }
+    guard(|| {
+        with_handle(&ENVS, env, |env_handle| {
+            let weights_slice = weights_slice(weights, len)?;
 
-    // Safety: Dereference environment handle
-    let env_handle = unsafe { &mut *env };
-    let env_ref = match &mut env_handle.env {
-        Some(env) => env,
-        None => return error_ffi::LR_EINTERNAL,
-    };
+            // Update weights, requiring a signature by the key the handle was verified with
+            match env_handle.public_key {
+                Some(key) => env_handle
+                    .env
+                    .update_weights(signed::verify(weights_slice, &key)?)?,
+                None => env_handle.env.update_weights(weights_slice)?,
+            }
 
-    // Safety: Create weights slice
-    let weights_slice = unsafe { std::slice::from_raw_parts(weights, len) };
+            // Update stored weights, keeping the outgoing blob for rollback; an encrypted
+            // handle keeps its ciphertext but drops the plaintext of a swapped-in blob
+            let retained = if env_handle.encrypted {
+                Vec::new()
+            } else {
+                weights_slice.to_vec()
+            };
+            env_handle.previous_weights = std::mem::replace(&mut env_handle.weights, retained);
+            Ok(())
+        })
+    })
+}
 
-    // Update weights
-    match env_ref.update_weights(weights_slice) {
-        Ok(_) => {
-            // Update stored weights
-            env_handle.weights = weights_slice.to_vec();
-            error_ffi::LR_OK
-        }
-        Err(_) => error_ffi::LR_EBADWEIGHTS,
-    }
+/// C API: Restore the weights that were live before the last successful update
+#[no_mangle]
+pub extern "C" fn lr_rollback_weights(env: *mut lr_env) -> i32 {
+    guard(|| {
+        with_handle(&ENVS, env, |env_handle| {
+            env_handle.env.rollback_weights()?;
+            std::mem::swap(&mut env_handle.weights, &mut env_handle.previous_weights);
+            Ok(())
+        })
+    })
+}
+
+/// C API: Get the weights generation (number of swaps and rollbacks since init)
+#[no_mangle]
+pub extern "C" fn lr_get_generation(env: *const lr_env, generation: *mut u64) -> i32 {
+    guard(|| {
+        with_handle(&ENVS, env, |env_handle| {
+            non_null(generation, "generation")?;
+            write_out(generation, env_handle.env.state().weights_generation);
+            Ok(())
+        })
+    })
 }
 
 /// C API: Get environment weights
+///
+/// Two-phase: `*actual_len` always receives the blob size, so call with `weights == NULL`
+/// and `max_len == 0` to query it, then with a buffer of that size. A smaller buffer is left
+/// untouched and the call returns `LR_EBUFSMALL`. A handle from `lr_init_encrypted` never
+/// exports its weights: the call returns `LR_EUNSUPPORTED` and writes nothing.
 #[no_mangle]
 pub extern "C" fn lr_get_weights(
     env: *const lr_env,
@@ -230,33 +843,233 @@ pub extern "C" fn lr_get_weights(
     max_len: usize,
     actual_len: *mut usize,
 ) -> i32 {
-    // Safety: Check for null pointers
-    if env.is_null() || weights.is_null() || actual_len.is_null() {
-        return error_ffi::LR_EBADWEIGHTS;
-    }
+    guard(|| {
+        with_handle(&ENVS, env, |env_handle| {
+            // `Env::get_weights` refuses an environment loaded from an encrypted container
+            let weights_vec = env_handle.env.get_weights()?;
+            copy_out(&weights_vec, weights, max_len, actual_len)
+        })
+    })
+}
 
-    // Safety: Dereference environment handle
-    let env_handle = unsafe { &*env };
-    let env_ref = match &env_handle.env {
-        Some(env) => env,
-        None => return error_ffi::LR_EINTERNAL,
-    };
+/// C API: Describe the policy loaded in `env`: algorithm, dims, shape, size and weights hash
+#[no_mangle]
+pub extern "C" fn lr_get_info(env: *const lr_env, out: *mut lr_info) -> i32 {
+    guard(|| {
+        with_handle(&ENVS, env, |env_handle| {
+            non_null(out, "out")?;
+            write_out(out, info_to_c(&env_handle.env.info()));
+            Ok(())
+        })
+    })
+}
 
-    // Get weights
-    let weights_result = env_ref.get_weights();
-    let weights_vec = match weights_result {
-        Ok(weights) => weights,
-        Err(_) => return error_ffi::LR_EBADWEIGHTS,
-    };
+/// C API: Get the last failure on `env`, or on the calling thread when `env` is NULL
+///
+/// Every failing call records its error on this thread and, when made on a live handle, on
+/// that handle; a later success does not clear it. With nothing recorded, `out->code` is
+/// `LR_OK`. Returns `LR_EBADHANDLE` for a stale `env`; this call never records a failure.
+#[no_mangle]
+pub extern "C" fn lr_last_error(env: *const lr_env, out: *mut lr_error) -> i32 {
+    query(|| {
+        non_null(out, "out")?;
+        last_failure(&ENVS, env, |failure| write_out(out, describe(failure)))
+    })
+}
 
-    // Safety: Write weights to output buffer
-    let actual_size = weights_vec.len().min(max_len);
-    unsafe {
-        std::ptr::copy_nonoverlapping(weights_vec.as_ptr(), weights, actual_size);
-        *actual_len = weights_vec.len();
-    }
+/// C API: Get the message of the last failure on `env`, or on the calling thread when `env`
+/// is NULL
+///
+/// Two-phase like `lr_get_weights`: `*actual_len` receives the message size including its
+/// NUL; `buf == NULL` with `len == 0` queries it, and a smaller buffer gets `LR_EBUFSMALL`.
+/// The message is empty when nothing has failed. This call never records a failure.
+#[no_mangle]
+pub extern "C" fn lr_last_error_message(
+    env: *const lr_env,
+    buf: *mut c_char,
+    len: usize,
+    actual_len: *mut usize,
+) -> i32 {
+    query(|| {
+        last_failure(&ENVS, env, |failure| {
+            write_message(failure, buf, len, actual_len)
+        })?
+    })
+}
+
+/// C API: Share the policy loaded in `env` with several threads
+///
+/// The returned handle starts on the very policy `env` has live (verified or decrypted, never
+/// re-parsed) and requires updates signed by the same key as `env`; otherwise it is
+/// independent of `env`, which keeps running and can be freed. Step
+/// it from each control loop through its own `lr_ctx_new` context; the policy is freed once
+/// the handle and every context created from it are freed. Only `LR_OBS_DIM` x
+/// `LR_ACTION_DIM` policies can be shared; others fail with `LR_EINVSIZE`. On error `*out` is
+/// set to NULL.
+#[no_mangle]
+pub extern "C" fn lr_policy_share(env: *const lr_env, out: *mut *mut lr_policy) -> i32 {
+    guard(|| {
+        non_null(out, "out")?;
+        write_out(out, ptr::null_mut());
+
+        let (shared, public_key) = with_handle(&ENVS, env, |env_handle| {
+            let (obs_dim, action_dim) = env_handle.env.dims();
+            let fixed = env_handle
+                .env
+                .as_any()
+                .downcast_ref::<Env<LR_OBS_DIM, LR_ACTION_DIM>>()
+                .ok_or(if obs_dim != LR_OBS_DIM {
+                    Error::InvalidObsSize {
+                        expected: LR_OBS_DIM,
+                        actual: obs_dim,
+                    }
+                } else {
+                    Error::InvalidActionSize {
+                        expected: LR_ACTION_DIM,
+                        actual: action_dim,
+                    }
+                })?;
+            Ok((SharedPolicy::from_env(fixed), env_handle.public_key))
+        })?;
+        let policy = lr_policy {
+            shared,
+            public_key,
+            last_error: None,
+        };
+        install(&POLICIES, policy, out)
+    })
+}
+
+/// C API: Free a shared policy handle (contexts created from it keep working)
+#[no_mangle]
+pub extern "C" fn lr_policy_free(policy: *mut lr_policy) {
+    release(&POLICIES, policy);
+}
+
+/// C API: Hot-swap a shared policy; thread-safe
+///
+/// Validated like `lr_update_weights`, signature included, before the swap, so on error every
+/// context keeps the live policy. Contexts switch to the new policy at their next `lr_ctx_reset`/`lr_ctx_step`.
+#[no_mangle]
+pub extern "C" fn lr_policy_update_weights(
+    policy: *const lr_policy,
+    weights: *const u8,
+    len: usize,
+) -> i32 {
+    guard(|| {
+        with_handle(&POLICIES, policy, |policy| {
+            let weights_slice = weights_slice(weights, len)?;
+
+            // Update weights, requiring a signature by the key `env` was verified with
+            match policy.public_key {
+                Some(key) => {
+                    policy
+                        .shared
+                        .update_weights(signed::verify(weights_slice, &key)?)?;
+                }
+                None => policy.shared.update_weights(weights_slice)?,
+            }
+            Ok(())
+        })
+    })
+}
+
+/// C API: Restore the shared policy that was live before the last swap; thread-safe
+#[no_mangle]
+pub extern "C" fn lr_policy_rollback_weights(policy: *const lr_policy) -> i32 {
+    guard(|| {
+        with_handle(&POLICIES, policy, |policy| {
+            Ok(policy.shared.rollback_weights()?)
+        })
+    })
+}
+
+/// C API: Get the shared policy generation (number of swaps and rollbacks); thread-safe
+#[no_mangle]
+pub extern "C" fn lr_policy_get_generation(policy: *const lr_policy, generation: *mut u64) -> i32 {
+    guard(|| {
+        with_handle(&POLICIES, policy, |policy| {
+            non_null(generation, "generation")?;
+            write_out(generation, policy.shared.generation());
+            Ok(())
+        })
+    })
+}
+
+/// C API: `lr_last_error` for a shared policy; NULL reads the calling thread's last failure
+#[no_mangle]
+pub extern "C" fn lr_policy_last_error(policy: *const lr_policy, out: *mut lr_error) -> i32 {
+    query(|| {
+        non_null(out, "out")?;
+        last_failure(&POLICIES, policy, |failure| {
+            write_out(out, describe(failure))
+        })
+    })
+}
+
+/// C API: `lr_last_error_message` for a shared policy
+#[no_mangle]
+pub extern "C" fn lr_policy_last_error_message(
+    policy: *const lr_policy,
+    buf: *mut c_char,
+    len: usize,
+    actual_len: *mut usize,
+) -> i32 {
+    query(|| {
+        last_failure(&POLICIES, policy, |failure| {
+            write_message(failure, buf, len, actual_len)
+        })?
+    })
+}
+
+/// C API: Create a step context for one control loop over a shared policy; thread-safe
+///
+/// On error `*out` is set to NULL.
+#[no_mangle]
+pub extern "C" fn lr_ctx_new(policy: *const lr_policy, out: *mut *mut lr_ctx) -> i32 {
+    guard(|| {
+        non_null(out, "out")?;
+        write_out(out, ptr::null_mut());
 
-    error_ffi::LR_OK
+        let ctx = with_handle(&POLICIES, policy, |policy| Ok(policy.shared.context()))?;
+        install(&CTXS, lr_ctx { ctx }, out)
+    })
+}
+
+/// C API: Free a step context
+#[no_mangle]
+pub extern "C" fn lr_ctx_free(ctx: *mut lr_ctx) {
+    release(&CTXS, ctx);
+}
+
+/// C API: Reset a step context with an initial observation
+#[no_mangle]
+pub extern "C" fn lr_ctx_reset(ctx: *mut lr_ctx, obs: *const f32, action: *mut f32) -> i32 {
+    act_on_ctx(ctx, obs, action, |ctx, obs| ctx.reset(obs))
+}
+
+/// C API: Step a context with a new observation, picking up any policy swap first
+#[no_mangle]
+pub extern "C" fn lr_ctx_step(ctx: *mut lr_ctx, obs: *const f32, action: *mut f32) -> i32 {
+    act_on_ctx(ctx, obs, action, |ctx, obs| ctx.step(obs))
+}
+
+/// C API: Get the step and episode counters of a context
+#[no_mangle]
+pub extern "C" fn lr_ctx_get_state(
+    ctx: *const lr_ctx,
+    step_count: *mut u64,
+    episode_count: *mut u64,
+) -> i32 {
+    guard(|| {
+        with_handle(&CTXS, ctx, |ctx| {
+            non_null(step_count, "step_count")?;
+            non_null(episode_count, "episode_count")?;
+            write_out(step_count, ctx.ctx.step_count());
+            write_out(episode_count, ctx.ctx.episode_count());
+            Ok(())
+        })
+    })
 }
 
 #[cfg(test)]
@@ -306,11 +1119,923 @@ mod tests {
         lr_free(env_ptr);
     }
 
+    #[test]
+    fn test_ffi_get_dims() {
+        let weights = minimal_tabular_weights();
+
+        let mut env_ptr: *mut lr_env = ptr::null_mut();
+        lr_init(weights.as_ptr(), weights.len(), &mut env_ptr);
+
+        let (mut obs_dim, mut action_dim) = (0usize, 0usize);
+        let result = lr_get_dims(env_ptr, &mut obs_dim, &mut action_dim);
+        assert_eq!(result, error_ffi::LR_OK);
+        assert_eq!((obs_dim, action_dim), (LR_OBS_DIM, LR_ACTION_DIM));
+
+        let result = lr_get_dims(ptr::null(), &mut obs_dim, &mut action_dim);
+        assert_eq!(result, error_ffi::LR_ENULL);
+
+        lr_free(env_ptr);
+    }
+
+    #[test]
+    fn test_ffi_init_rejects_other_dims() {
+        // LinearFA exported for 8 observations x 4 actions: rate, 32 weights, 4 biases
+        let mut weights = vec![1u8];
+        weights.extend((0..1 + 32 + 4).flat_map(|_| 0.1f32.to_le_bytes()));
+        let mut env_ptr: *mut lr_env = ptr::null_mut();
+        assert_eq!(
+            lr_init(weights.as_ptr(), weights.len(), &mut env_ptr),
+            error_ffi::LR_EINVSIZE
+        );
+        assert!(env_ptr.is_null());
+        let mut detail = describe(None);
+        assert_eq!(lr_last_error(ptr::null(), &mut detail), error_ffi::LR_OK);
+        assert_eq!((detail.expected, detail.actual), (10, 36));
+
+        // TabularQ table with one action too many
+        let mut weights = vec![0u8];
+        weights.extend(1u32.to_le_bytes());
+        weights.extend(((LR_ACTION_DIM + 1) as u32).to_le_bytes());
+        weights.extend([0u8; 8]);
+        weights.extend(vec![0u8; (LR_ACTION_DIM + 1) * 4]);
+        assert_eq!(
+            lr_init(weights.as_ptr(), weights.len(), &mut env_ptr),
+            error_ffi::LR_EINVSIZE
+        );
+        assert_eq!(lr_last_error(ptr::null(), &mut detail), error_ffi::LR_OK);
+        assert_eq!(
+            (detail.expected, detail.actual),
+            (LR_ACTION_DIM, LR_ACTION_DIM + 1)
+        );
+
+        // Swaps are held to the same dimensions
+        let env = new_env();
+        assert_eq!(
+            lr_update_weights(env, weights.as_ptr(), weights.len()),
+            error_ffi::LR_EINVSIZE
+        );
+        lr_free(env);
+    }
+
+    #[cfg(feature = "dyn-dims")]
+    #[test]
+    fn test_ffi_init_dims() {
+        // LinearFA for 8 observations x 4 actions: rate, 32 weights, 4 biases
+        let mut weights = vec![1u8];
+        weights.extend((0..1 + 32 + 4).flat_map(|_| 0.1f32.to_le_bytes()));
+        let mut env_ptr: *mut lr_env = ptr::null_mut();
+        assert_eq!(
+            lr_init_dims(weights.as_ptr(), weights.len(), 8, 4, &mut env_ptr),
+            error_ffi::LR_OK
+        );
+
+        let (mut obs_dim, mut action_dim) = (0usize, 0usize);
+        assert_eq!(
+            lr_get_dims(env_ptr, &mut obs_dim, &mut action_dim),
+            error_ffi::LR_OK
+        );
+        assert_eq!((obs_dim, action_dim), (8, 4));
+
+        // Steps read 8 floats and write exactly 4
+        let obs = [1.0f32; 8];
+        let mut action = [f32::NAN; 5];
+        assert_eq!(
+            lr_step(env_ptr, obs.as_ptr(), action.as_mut_ptr()),
+            error_ffi::LR_OK
+        );
+        assert!(action[..4].iter().all(|a| (a - 0.9f32.tanh()).abs() < 1e-6));
+        assert!(action[4].is_nan());
+        assert_eq!(
+            lr_check_invariant(env_ptr, obs.as_ptr(), action.as_ptr()),
+            error_ffi::LR_OK
+        );
+
+        // Swaps stay at 8x4, and only LR_OBS_DIM x LR_ACTION_DIM policies can be shared
+        let mut fixed = vec![1u8];
+        fixed.extend((0..1 + 8 + 2).flat_map(|_| 0.1f32.to_le_bytes()));
+        assert_eq!(
+            lr_update_weights(env_ptr, fixed.as_ptr(), fixed.len()),
+            error_ffi::LR_EINVSIZE
+        );
+        let mut policy: *mut lr_policy = ptr::null_mut();
+        assert_eq!(
+            lr_policy_share(env_ptr, &mut policy),
+            error_ffi::LR_EINVSIZE
+        );
+        assert!(policy.is_null());
+        lr_free(env_ptr);
+
+        // Out-of-range dims and weights shaped for other dims
+        for (obs_dim, action_dim) in [(0, 4), (17, 4), (8, 9), (4, 2)] {
+            assert_eq!(
+                lr_init_dims(
+                    weights.as_ptr(),
+                    weights.len(),
+                    obs_dim,
+                    action_dim,
+                    &mut env_ptr
+                ),
+                error_ffi::LR_EINVSIZE
+            );
+            assert!(env_ptr.is_null());
+        }
+    }
+
+    fn new_env() -> *mut lr_env {
+        let weights = minimal_tabular_weights();
+        let mut env_ptr: *mut lr_env = ptr::null_mut();
+        assert_eq!(
+            lr_init(weights.as_ptr(), weights.len(), &mut env_ptr),
+            error_ffi::LR_OK
+        );
+        env_ptr
+    }
+
     #[test]
     fn test_ffi_null_pointer_handling() {
         let result = lr_init(ptr::null(), 0, ptr::null_mut());
+        assert_eq!(result, error_ffi::LR_ENULL);
+        let mut env_ptr: *mut lr_env = ptr::null_mut();
+        let result = lr_init(ptr::null(), 0, &mut env_ptr);
         assert_eq!(result, error_ffi::LR_EBADWEIGHTS);
 
         lr_free(ptr::null_mut()); // Should not crash
+        lr_policy_free(ptr::null_mut());
+        lr_ctx_free(ptr::null_mut());
+    }
+
+    #[test]
+    fn test_ffi_null_arguments_return_enull() {
+        let env = new_env();
+        let weights = minimal_tabular_weights();
+        let obs = [1.0f32; LR_OBS_DIM];
+        let mut action = [0.0f32; LR_ACTION_DIM];
+        let (mut a, mut b) = (0u64, 0u64);
+        let (mut x, mut y) = (0usize, 0usize);
+        let mut info = std::mem::MaybeUninit::<lr_info>::uninit();
+        let info = info.as_mut_ptr();
+        let mut env_out: *mut lr_env = ptr::null_mut();
+        let mut policy: *mut lr_policy = ptr::null_mut();
+        assert_eq!(lr_policy_share(env, &mut policy), error_ffi::LR_OK);
+        let mut ctx: *mut lr_ctx = ptr::null_mut();
+        assert_eq!(lr_ctx_new(policy, &mut ctx), error_ffi::LR_OK);
+
+        let codes = [
+            lr_init(ptr::null(), weights.len(), &mut env_out),
+            lr_init(weights.as_ptr(), weights.len(), ptr::null_mut()),
+            lr_init_signed(ptr::null(), 1, ptr::null(), &mut env_out),
+            lr_reset(ptr::null_mut(), obs.as_ptr(), action.as_mut_ptr()),
+            lr_reset(env, ptr::null(), action.as_mut_ptr()),
+            lr_step(env, obs.as_ptr(), ptr::null_mut()),
+            lr_get_state(ptr::null(), &mut a, &mut b),
+            lr_get_state(env, ptr::null_mut(), &mut b),
+            lr_get_dims(env, &mut x, ptr::null_mut()),
+            lr_check_invariant(env, obs.as_ptr(), ptr::null()),
+            lr_check_invariant(ptr::null(), obs.as_ptr(), action.as_ptr()),
+            lr_update_weights(env, ptr::null(), 1),
+            lr_rollback_weights(ptr::null_mut()),
+            lr_get_generation(env, ptr::null_mut()),
+            lr_get_weights(env, ptr::null_mut(), 1, &mut y),
+            lr_get_info(env, ptr::null_mut()),
+            lr_get_info(ptr::null(), info),
+            lr_last_error(env, ptr::null_mut()),
+            lr_last_error_message(env, ptr::null_mut(), 1, &mut y),
+            lr_last_error_message(env, ptr::null_mut(), 0, ptr::null_mut()),
+            lr_policy_last_error(policy, ptr::null_mut()),
+            lr_policy_last_error_message(policy, ptr::null_mut(), 1, &mut y),
+            lr_policy_share(ptr::null(), &mut policy),
+            lr_policy_update_weights(ptr::null(), weights.as_ptr(), weights.len()),
+            lr_policy_rollback_weights(ptr::null()),
+            lr_policy_get_generation(ptr::null(), &mut a),
+            lr_ctx_new(ptr::null(), &mut ctx),
+            lr_ctx_step(ptr::null_mut(), obs.as_ptr(), action.as_mut_ptr()),
+            lr_ctx_get_state(ptr::null(), &mut a, &mut b),
+        ];
+        for (i, code) in codes.into_iter().enumerate() {
+            assert_eq!(code, error_ffi::LR_ENULL, "call {}", i);
+        }
+        // Constructors clear `*out` on failure
+        assert!(env_out.is_null() && policy.is_null() && ctx.is_null());
+        lr_free(env);
+    }
+
+    #[test]
+    fn test_ffi_stale_forged_and_foreign_handles() {
+        let obs = [1.0f32; LR_OBS_DIM];
+        let mut action = [0.0f32; LR_ACTION_DIM];
+        let env = new_env();
+        let mut policy: *mut lr_policy = ptr::null_mut();
+        lr_policy_share(env, &mut policy);
+        let mut ctx: *mut lr_ctx = ptr::null_mut();
+        lr_ctx_new(policy, &mut ctx);
+
+        // Handles of another kind
+        assert_eq!(
+            lr_step(ctx.cast(), obs.as_ptr(), action.as_mut_ptr()),
+            error_ffi::LR_EBADHANDLE
+        );
+        assert_eq!(
+            lr_ctx_step(env.cast(), obs.as_ptr(), action.as_mut_ptr()),
+            error_ffi::LR_EBADHANDLE
+        );
+        assert_eq!(
+            lr_policy_rollback_weights(env.cast()),
+            error_ffi::LR_EBADHANDLE
+        );
+
+        // Forged handles, including a real pointer
+        let mut generation = 0u64;
+        for forged in [
+            1usize,
+            usize::MAX,
+            env.addr() ^ (1 << (usize::BITS / 2)),
+            action.as_ptr().addr(),
+        ] {
+            let forged: *mut lr_env = ptr::without_provenance_mut(forged);
+            assert_eq!(
+                lr_get_generation(forged, &mut generation),
+                error_ffi::LR_EBADHANDLE
+            );
+            lr_free(forged);
+        }
+
+        // Stale handles, even once their slot is reused
+        lr_free(env);
+        let reused = new_env();
+        assert_ne!(reused, env);
+        assert_eq!(
+            lr_step(env, obs.as_ptr(), action.as_mut_ptr()),
+            error_ffi::LR_EBADHANDLE
+        );
+        lr_free(env); // double free is a no-op
+        assert_eq!(
+            lr_step(reused, obs.as_ptr(), action.as_mut_ptr()),
+            error_ffi::LR_OK
+        );
+
+        lr_policy_free(policy);
+        assert_eq!(
+            lr_policy_get_generation(policy, &mut generation),
+            error_ffi::LR_EBADHANDLE
+        );
+        assert_eq!(
+            lr_ctx_step(ctx, obs.as_ptr(), action.as_mut_ptr()),
+            error_ffi::LR_OK
+        );
+        lr_ctx_free(ctx);
+        lr_ctx_free(ctx);
+        assert_eq!(
+            lr_ctx_step(ctx, obs.as_ptr(), action.as_mut_ptr()),
+            error_ffi::LR_EBADHANDLE
+        );
+        lr_free(reused);
+    }
+
+    #[test]
+    fn test_ffi_guard_catches_panics() {
+        let code = guard(|| panic!("panic inside an entry point"));
+        assert_eq!(code, error_ffi::LR_EINTERNAL);
+        assert!(matches!(
+            last_error(),
+            Some((error_ffi::LR_EINTERNAL, Error::Internal(message)))
+                if message == "panic: panic inside an entry point"
+        ));
+        assert_eq!(guard(|| Ok(())), error_ffi::LR_OK);
+    }
+
+    #[test]
+    fn test_ffi_concurrent_calls_and_free_on_one_handle() {
+        const THREADS: usize = 4;
+        const STEPS: u64 = if cfg!(miri) { 20 } else { 500 };
+        let env = new_env().addr();
+
+        std::thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(move || {
+                    let env: *mut lr_env = ptr::without_provenance_mut(env);
+                    let obs = [1.0f32; LR_OBS_DIM];
+                    let mut action = [0.0f32; LR_ACTION_DIM];
+                    for _ in 0..STEPS {
+                        let code = lr_step(env, obs.as_ptr(), action.as_mut_ptr());
+                        assert_eq!(code, error_ffi::LR_OK);
+                    }
+                });
+            }
+        });
+        let env: *mut lr_env = ptr::without_provenance_mut(env);
+        let (mut steps, mut episodes) = (0u64, 0u64);
+        lr_get_state(env, &mut steps, &mut episodes);
+        assert_eq!(steps, THREADS as u64 * STEPS);
+
+        // Freed while other threads step: each call either runs or sees a stale handle
+        let token = env.addr();
+        std::thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(move || {
+                    let env: *mut lr_env = ptr::without_provenance_mut(token);
+                    let obs = [1.0f32; LR_OBS_DIM];
+                    let mut action = [0.0f32; LR_ACTION_DIM];
+                    let mut freed = false;
+                    for _ in 0..STEPS {
+                        match lr_step(env, obs.as_ptr(), action.as_mut_ptr()) {
+                            error_ffi::LR_OK => assert!(!freed),
+                            error_ffi::LR_EBADHANDLE => freed = true,
+                            code => panic!("unexpected code {}", code),
+                        }
+                    }
+                });
+            }
+            lr_free(env);
+        });
+    }
+
+    #[test]
+    fn test_ffi_random_call_sequences() {
+        // Deterministic fuzzing of handle lifetimes; core/fuzz/ drives the same calls from
+        // libFuzzer, and CI runs this suite under Miri
+        let ops = if cfg!(miri) { 200 } else { 5000 };
+        let mut seed = 0x2545_F491u32;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as usize
+        };
+        let weights = minimal_tabular_weights();
+        let obs = [0.5f32; LR_OBS_DIM];
+        let mut action = [0.0f32; LR_ACTION_DIM];
+        let mut handles: Vec<(*mut lr_env, bool)> = Vec::new();
+
+        for _ in 0..ops {
+            let pick = match handles.len() {
+                0 => None,
+                len => Some(next() % len),
+            };
+            match (next() % 6, pick) {
+                (0, _) | (_, None) => {
+                    let mut env: *mut lr_env = ptr::null_mut();
+                    let len = if next() % 4 == 0 { 5 } else { weights.len() };
+                    match lr_init(weights.as_ptr(), len, &mut env) {
+                        error_ffi::LR_OK => handles.push((env, true)),
+                        code => assert!(code == error_ffi::LR_EBADWEIGHTS && env.is_null()),
+                    }
+                }
+                (1, Some(i)) => {
+                    lr_free(handles[i].0);
+                    handles[i].1 = false;
+                }
+                (2, Some(i)) => {
+                    let expected = if handles[i].1 {
+                        error_ffi::LR_OK
+                    } else {
+                        error_ffi::LR_EBADHANDLE
+                    };
+                    let code = lr_step(handles[i].0, obs.as_ptr(), action.as_mut_ptr());
+                    assert_eq!(code, expected);
+                }
+                (3, Some(i)) => {
+                    let code = lr_update_weights(handles[i].0, weights.as_ptr(), weights.len());
+                    assert!(code == error_ffi::LR_OK || !handles[i].1);
+                }
+                (4, Some(i)) => {
+                    let code = lr_rollback_weights(handles[i].0);
+                    assert!(code != error_ffi::LR_EBADHANDLE || !handles[i].1);
+                }
+                (_, Some(i)) => {
+                    // Other generations of a slot we hold (other tests share the tables, so
+                    // only our own slots have a known occupant)
+                    let (env, live) = handles[i];
+                    let forged: *mut lr_env = if live {
+                        ptr::without_provenance_mut(
+                            env.addr() ^ ((1 + next() % 7) << (usize::BITS / 2)),
+                        )
+                    } else {
+                        env
+                    };
+                    let mut generation = 0u64;
+                    let code = lr_get_generation(forged, &mut generation);
+                    assert_eq!(code, error_ffi::LR_EBADHANDLE);
+                }
+            }
+        }
+        for (env, _) in handles {
+            lr_free(env);
+        }
+    }
+
+    #[test]
+    fn test_ffi_update_weights_and_rollback() {
+        let weights = minimal_tabular_weights();
+
+        let mut env_ptr: *mut lr_env = ptr::null_mut();
+        lr_init(weights.as_ptr(), weights.len(), &mut env_ptr);
+
+        let mut generation = u64::MAX;
+        assert_eq!(
+            lr_get_generation(env_ptr, &mut generation),
+            error_ffi::LR_OK
+        );
+        assert_eq!(generation, 0);
+
+        // Truncated blob is rejected and leaves the live policy in place
+        let result = lr_update_weights(env_ptr, weights.as_ptr(), 5);
+        assert_eq!(result, error_ffi::LR_EBADWEIGHTS);
+
+        // Blob for a different algorithm
+        let mut linear = vec![1u8];
+        linear.extend_from_slice(&[0u8; 44]);
+        let result = lr_update_weights(env_ptr, linear.as_ptr(), linear.len());
+        assert_eq!(result, error_ffi::LR_EMISMATCH);
+
+        // Nothing to roll back to yet: a state error, not a weights error
+        assert_eq!(lr_rollback_weights(env_ptr), error_ffi::LR_ENOROLLBACK);
+        lr_get_generation(env_ptr, &mut generation);
+        assert_eq!(generation, 0);
+
+        let result = lr_update_weights(env_ptr, weights.as_ptr(), weights.len());
+        assert_eq!(result, error_ffi::LR_OK);
+        assert_eq!(lr_rollback_weights(env_ptr), error_ffi::LR_OK);
+
+        lr_get_generation(env_ptr, &mut generation);
+        assert_eq!(generation, 2);
+
+        lr_free(env_ptr);
+    }
+
+    fn message_of(read: impl Fn(*mut c_char, usize, *mut usize) -> i32) -> String {
+        let mut buf = [0 as c_char; 128];
+        let mut len = 0usize;
+        assert_eq!(
+            read(buf.as_mut_ptr(), buf.len(), &mut len),
+            error_ffi::LR_OK
+        );
+        assert!(len >= 1 && len <= buf.len() && buf[len - 1] == 0);
+        // Safety: the buffer was NUL-terminated by the call
+        unsafe { CStr::from_ptr(buf.as_ptr()) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_ffi_last_error_detail() {
+        let weights = minimal_tabular_weights();
+        let env = new_env();
+        let mut detail = describe(None);
+
+        // Nothing failed on this handle yet
+        assert_eq!(lr_last_error(env, &mut detail), error_ffi::LR_OK);
+        assert_eq!(detail.code, error_ffi::LR_OK);
+        assert_eq!(detail.weights_offset, usize::MAX);
+        assert_eq!(
+            message_of(|buf, len, out| lr_last_error_message(env, buf, len, out)),
+            ""
+        );
+
+        // Truncated blob: the offset counts the algorithm byte
+        assert_eq!(
+            lr_update_weights(env, weights.as_ptr(), 5),
+            error_ffi::LR_EBADWEIGHTS
+        );
+        assert_eq!(lr_last_error(env, &mut detail), error_ffi::LR_OK);
+        assert_eq!(
+            (detail.code, detail.weights_offset),
+            (error_ffi::LR_EBADWEIGHTS, 5)
+        );
+        let message = message_of(|buf, len, out| lr_last_error_message(env, buf, len, out));
+        assert_eq!(
+            message,
+            "Invalid weights data at byte 5: Insufficient weights for TabularQLearning"
+        );
+
+        // A success does not clear it, and the thread saw the same failure
+        let obs = [0.5f32; LR_OBS_DIM];
+        let mut action = [0.0f32; LR_ACTION_DIM];
+        assert_eq!(
+            lr_step(env, obs.as_ptr(), action.as_mut_ptr()),
+            error_ffi::LR_OK
+        );
+        assert_eq!(
+            message_of(|buf, len, out| lr_last_error_message(env, buf, len, out)),
+            message
+        );
+        assert_eq!(
+            message_of(|buf, len, out| lr_last_error_message(ptr::null(), buf, len, out)),
+            message
+        );
+
+        // Invariant violations are named
+        let bad_obs = [f32::INFINITY; LR_OBS_DIM];
+        assert_eq!(
+            lr_check_invariant(env, bad_obs.as_ptr(), action.as_ptr()),
+            error_ffi::LR_EINVARIANT
+        );
+        lr_last_error(env, &mut detail);
+        assert_eq!(detail.code, error_ffi::LR_EINVARIANT);
+        // Safety: `invariant` points to a static C string
+        let invariant = unsafe { CStr::from_ptr(detail.invariant) };
+        assert_eq!(invariant.to_str(), Ok("finite_observation"));
+
+        // Two-phase: the size query counts the NUL; a short buffer is left untouched
+        let mut required = 0usize;
+        assert_eq!(
+            lr_last_error_message(env, ptr::null_mut(), 0, &mut required),
+            error_ffi::LR_OK
+        );
+        let message = detail_message(env);
+        assert_eq!(required, message.len() + 1);
+        let mut small = vec![0x7f as c_char; required - 1];
+        assert_eq!(
+            lr_last_error_message(env, small.as_mut_ptr(), small.len(), &mut required),
+            error_ffi::LR_EBUFSMALL
+        );
+        assert!(small.iter().all(|&byte| byte == 0x7f));
+        assert_eq!(required, message.len() + 1);
+        assert_eq!(
+            lr_last_error_message(env, ptr::null_mut(), 8, &mut required),
+            error_ffi::LR_ENULL
+        );
+        assert_eq!(
+            lr_last_error_message(env, small.as_mut_ptr(), small.len(), ptr::null_mut()),
+            error_ffi::LR_ENULL
+        );
+
+        // Failed queries do not overwrite the thread's last error
+        assert_eq!(lr_last_error(env, ptr::null_mut()), error_ffi::LR_ENULL);
+        lr_last_error(ptr::null(), &mut detail);
+        assert_eq!(detail.code, error_ffi::LR_EINVARIANT);
+
+        // Other handles keep their own record; stale ones have none
+        let other = new_env();
+        lr_last_error(other, &mut detail);
+        assert_eq!(detail.code, error_ffi::LR_OK);
+        lr_free(other);
+        assert_eq!(lr_last_error(other, &mut detail), error_ffi::LR_EBADHANDLE);
+
+        // Init failures are only recorded on the thread
+        let mut env_out: *mut lr_env = ptr::null_mut();
+        let unknown = [9u8; 20];
+        assert_eq!(
+            lr_init(unknown.as_ptr(), unknown.len(), &mut env_out),
+            error_ffi::LR_EUNSUPPORTED
+        );
+        lr_last_error(ptr::null(), &mut detail);
+        assert_eq!(detail.code, error_ffi::LR_EUNSUPPORTED);
+        assert!(matches!(
+            error_ffi::code_to_error(error_ffi::LR_EUNSUPPORTED),
+            Error::UnsupportedAlgorithm(message) if message.contains('9')
+        ));
+
+        lr_free(env);
+    }
+
+    fn detail_message(env: *const lr_env) -> String {
+        message_of(|buf, len, out| lr_last_error_message(env, buf, len, out))
+    }
+
+    #[test]
+    fn test_ffi_get_info() {
+        let env = new_env();
+        let mut info = std::mem::MaybeUninit::<lr_info>::uninit();
+        assert_eq!(lr_get_info(env, info.as_mut_ptr()), error_ffi::LR_OK);
+        // Safety: filled by the successful call
+        let info = unsafe { info.assume_init() };
+        assert_eq!(info.algorithm, 0);
+        // Safety: `algorithm_name` points to a static C string
+        let name = unsafe { CStr::from_ptr(info.algorithm_name) };
+        assert_eq!(name.to_str(), Ok("TabularQLearning"));
+        assert_eq!((info.obs_dim, info.action_dim), (LR_OBS_DIM, LR_ACTION_DIM));
+        assert_eq!(
+            (info.num_states, info.num_actions, info.num_layers),
+            (1, 1, 0)
+        );
+        assert_eq!(info.parameter_count, 1);
+        let expected = Env::<LR_OBS_DIM, LR_ACTION_DIM>::from_weights(&minimal_tabular_weights())
+            .unwrap()
+            .info();
+        assert_eq!(info.weights_sha256, expected.weights_sha256);
+        assert_eq!(info.format_version, expected.format_version);
+        lr_free(env);
+
+        // TinyNN: 4 -> 64 -> 32 -> 2 with ReLU, ReLU, Tanh
+        let mut weights = vec![2u8, 4, 0, 0, 0, 1];
+        let params = 4 * 64 + 64 + 64 * 32 + 32 + 32 * 2 + 2;
+        weights.resize(weights.len() + params * 4, 0);
+        let mut env: *mut lr_env = ptr::null_mut();
+        assert_eq!(
+            lr_init(weights.as_ptr(), weights.len(), &mut env),
+            error_ffi::LR_OK
+        );
+        let mut info = std::mem::MaybeUninit::<lr_info>::uninit();
+        assert_eq!(lr_get_info(env, info.as_mut_ptr()), error_ffi::LR_OK);
+        // Safety: filled by the successful call
+        let info = unsafe { info.assume_init() };
+        assert_eq!(info.num_layers, 4);
+        assert_eq!(&info.layer_sizes[..4], &[4, 64, 32, 2]);
+        assert_eq!(&info.activations[..3], &[0, 0, 1]);
+        assert_eq!(info.parameter_count, params);
+        lr_free(env);
+    }
+
+    #[test]
+    fn test_ffi_get_weights_two_phase() {
+        // The serialized form includes the (zeroed) Q-table
+        let weights = Env::<LR_OBS_DIM, LR_ACTION_DIM>::from_weights(&minimal_tabular_weights())
+            .unwrap()
+            .get_weights()
+            .unwrap();
+        let env = new_env();
+
+        // Size query
+        let mut actual_len = 0usize;
+        assert_eq!(
+            lr_get_weights(env, ptr::null_mut(), 0, &mut actual_len),
+            error_ffi::LR_OK
+        );
+        assert_eq!(actual_len, weights.len());
+
+        // One byte short: nothing is written, the required size is still reported
+        let mut short = vec![0xAAu8; weights.len() - 1];
+        actual_len = 0;
+        assert_eq!(
+            lr_get_weights(env, short.as_mut_ptr(), short.len(), &mut actual_len),
+            error_ffi::LR_EBUFSMALL
+        );
+        assert!(short.iter().all(|&byte| byte == 0xAA));
+        assert_eq!(actual_len, weights.len());
+        let mut detail = describe(None);
+        lr_last_error(env, &mut detail);
+        assert_eq!(
+            (detail.code, detail.expected, detail.actual),
+            (error_ffi::LR_EBUFSMALL, weights.len(), weights.len() - 1)
+        );
+
+        // Exact and oversized buffers
+        let mut exact = vec![0u8; weights.len()];
+        assert_eq!(
+            lr_get_weights(env, exact.as_mut_ptr(), exact.len(), &mut actual_len),
+            error_ffi::LR_OK
+        );
+        assert_eq!(exact, weights);
+        let mut large = vec![0xAAu8; weights.len() + 4];
+        assert_eq!(
+            lr_get_weights(env, large.as_mut_ptr(), large.len(), &mut actual_len),
+            error_ffi::LR_OK
+        );
+        assert_eq!(
+            (&large[..weights.len()], actual_len),
+            (&weights[..], weights.len())
+        );
+        assert!(large[weights.len()..].iter().all(|&byte| byte == 0xAA));
+
+        // NULL with a non-zero length, and a NULL size pointer
+        assert_eq!(
+            lr_get_weights(env, ptr::null_mut(), 4, &mut actual_len),
+            error_ffi::LR_ENULL
+        );
+        assert_eq!(
+            lr_get_weights(env, ptr::null_mut(), 0, ptr::null_mut()),
+            error_ffi::LR_ENULL
+        );
+
+        lr_free(env);
+    }
+
+    #[test]
+    fn test_ffi_policy_last_error() {
+        let env = new_env();
+        let mut policy: *mut lr_policy = ptr::null_mut();
+        assert_eq!(lr_policy_share(env, &mut policy), error_ffi::LR_OK);
+        lr_free(env);
+
+        let unknown = [9u8; 20];
+        assert_eq!(
+            lr_policy_update_weights(policy, unknown.as_ptr(), unknown.len()),
+            error_ffi::LR_EUNSUPPORTED
+        );
+        let mut detail = describe(None);
+        assert_eq!(lr_policy_last_error(policy, &mut detail), error_ffi::LR_OK);
+        assert_eq!(detail.code, error_ffi::LR_EUNSUPPORTED);
+        let message =
+            message_of(|buf, len, out| lr_policy_last_error_message(policy, buf, len, out));
+        assert!(
+            message.starts_with("Algorithm not supported"),
+            "{}",
+            message
+        );
+
+        lr_policy_free(policy);
+        assert_eq!(
+            lr_policy_last_error(policy, &mut detail),
+            error_ffi::LR_EBADHANDLE
+        );
+    }
+
+    #[test]
+    fn test_ffi_invariant_names_match() {
+        for invariant in [
+            Invariant::ActionBounds,
+            Invariant::FiniteObservation,
+            Invariant::FiniteAction,
+        ] {
+            assert_eq!(
+                invariant_name(Some(invariant)).to_str(),
+                Ok(invariant.name())
+            );
+        }
+    }
+
+    #[test]
+    fn test_ffi_shared_policy_contexts() {
+        let weights = minimal_tabular_weights();
+        let mut env_ptr: *mut lr_env = ptr::null_mut();
+        lr_init(weights.as_ptr(), weights.len(), &mut env_ptr);
+
+        let mut policy_ptr: *mut lr_policy = ptr::null_mut();
+        assert_eq!(lr_policy_share(env_ptr, &mut policy_ptr), error_ffi::LR_OK);
+        lr_free(env_ptr);
+
+        let mut ctx_ptrs = [ptr::null_mut::<lr_ctx>(); 2];
+        for ctx_ptr in &mut ctx_ptrs {
+            assert_eq!(lr_ctx_new(policy_ptr, ctx_ptr), error_ffi::LR_OK);
+        }
+
+        // Freeing the policy handle leaves the contexts running
+        let mut generation = u64::MAX;
+        assert_eq!(
+            lr_policy_update_weights(policy_ptr, weights.as_ptr(), weights.len()),
+            error_ffi::LR_OK
+        );
+        assert_eq!(
+            lr_policy_get_generation(policy_ptr, &mut generation),
+            error_ffi::LR_OK
+        );
+        assert_eq!(generation, 1);
+        assert_eq!(
+            lr_policy_update_weights(policy_ptr, weights.as_ptr(), 5),
+            error_ffi::LR_EBADWEIGHTS
+        );
+        assert_eq!(lr_policy_rollback_weights(policy_ptr), error_ffi::LR_OK);
+        lr_policy_free(policy_ptr);
+        assert_eq!(
+            lr_policy_rollback_weights(policy_ptr),
+            error_ffi::LR_EBADHANDLE
+        );
+
+        let obs = [1.0f32, 2.0, 3.0, 4.0];
+        let mut action = [0.0f32; 2];
+        let (mut steps, mut episodes) = (0u64, 0u64);
+        assert_eq!(
+            lr_ctx_reset(ctx_ptrs[0], obs.as_ptr(), action.as_mut_ptr()),
+            error_ffi::LR_OK
+        );
+        assert_eq!(
+            lr_ctx_step(ctx_ptrs[0], obs.as_ptr(), action.as_mut_ptr()),
+            error_ffi::LR_OK
+        );
+        assert_eq!(
+            lr_ctx_get_state(ctx_ptrs[0], &mut steps, &mut episodes),
+            error_ffi::LR_OK
+        );
+        assert_eq!((steps, episodes), (1, 1));
+        lr_ctx_get_state(ctx_ptrs[1], &mut steps, &mut episodes);
+        assert_eq!((steps, episodes), (0, 0));
+
+        assert_eq!(
+            lr_ctx_step(ptr::null_mut(), obs.as_ptr(), action.as_mut_ptr()),
+            error_ffi::LR_ENULL
+        );
+        for ctx_ptr in ctx_ptrs {
+            lr_ctx_free(ctx_ptr);
+        }
+    }
+
+    #[test]
+    fn test_ffi_init_signed() {
+        let secret = [5u8; 32];
+        let container = signed::sign(&minimal_tabular_weights(), &secret).unwrap();
+        let public_key = signed::public_key(&secret);
+
+        let mut env_ptr: *mut lr_env = ptr::null_mut();
+        let result = lr_init_signed(
+            container.as_ptr(),
+            container.len(),
+            public_key.as_ptr(),
+            &mut env_ptr,
+        );
+        assert_eq!(result, error_ffi::LR_OK);
+        assert!(!env_ptr.is_null());
+
+        // Updates must be signed by the key the handle was verified with
+        let unsigned = minimal_tabular_weights();
+        assert_eq!(
+            lr_update_weights(env_ptr, unsigned.as_ptr(), unsigned.len()),
+            error_ffi::LR_ESIGNATURE
+        );
+        let other_key = signed::sign(&unsigned, &[6u8; 32]).unwrap();
+        assert_eq!(
+            lr_update_weights(env_ptr, other_key.as_ptr(), other_key.len()),
+            error_ffi::LR_ESIGNATURE
+        );
+        assert_eq!(
+            lr_update_weights(env_ptr, container.as_ptr(), container.len()),
+            error_ffi::LR_OK
+        );
+
+        let mut policy_ptr: *mut lr_policy = ptr::null_mut();
+        assert_eq!(lr_policy_share(env_ptr, &mut policy_ptr), error_ffi::LR_OK);
+        assert_eq!(
+            lr_policy_update_weights(policy_ptr, unsigned.as_ptr(), unsigned.len()),
+            error_ffi::LR_ESIGNATURE
+        );
+        assert_eq!(
+            lr_policy_update_weights(policy_ptr, container.as_ptr(), container.len()),
+            error_ffi::LR_OK
+        );
+        lr_policy_free(policy_ptr);
+        lr_free(env_ptr);
+
+        let mut tampered = container.clone();
+        tampered[signed::HEADER_LEN] ^= 0x01;
+        let mut env_ptr: *mut lr_env = ptr::null_mut();
+        let result = lr_init_signed(
+            tampered.as_ptr(),
+            tampered.len(),
+            public_key.as_ptr(),
+            &mut env_ptr,
+        );
+        assert_eq!(result, error_ffi::LR_ESIGNATURE);
+        assert!(env_ptr.is_null());
+
+        if signed::EMBEDDED_PUBLIC_KEY.is_none() {
+            let result = lr_init_signed(
+                container.as_ptr(),
+                container.len(),
+                ptr::null(),
+                &mut env_ptr,
+            );
+            assert_eq!(result, error_ffi::LR_ESIGNATURE);
+        }
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_ffi_init_encrypted() {
+        use crate::encrypted::{self, Cipher};
+
+        let key = [0x5au8; encrypted::KEY_LEN];
+        let nonce = [0xa5u8; encrypted::NONCE_LEN];
+        let container = encrypted::encrypt(
+            &minimal_tabular_weights(),
+            &key,
+            Cipher::ChaCha20Poly1305,
+            &nonce,
+        )
+        .unwrap();
+
+        let mut env_ptr: *mut lr_env = ptr::null_mut();
+        let result = lr_init_encrypted(
+            container.as_ptr(),
+            container.len(),
+            key.as_ptr(),
+            &mut env_ptr,
+        );
+        assert_eq!(result, error_ffi::LR_OK);
+
+        // Neither the loaded nor a swapped-in policy is ever exported in plaintext
+        let plaintext = minimal_tabular_weights();
+        for _ in 0..2 {
+            let mut buf = [0xeeu8; 256];
+            let mut actual_len = usize::MAX;
+            assert_eq!(
+                lr_get_weights(env_ptr, ptr::null_mut(), 0, &mut actual_len),
+                error_ffi::LR_EUNSUPPORTED
+            );
+            assert_eq!(
+                lr_get_weights(env_ptr, buf.as_mut_ptr(), buf.len(), &mut actual_len),
+                error_ffi::LR_EUNSUPPORTED
+            );
+            assert_eq!(actual_len, usize::MAX);
+            assert!(buf.iter().all(|&byte| byte == 0xee));
+            if signed::EMBEDDED_PUBLIC_KEY.is_some() {
+                break;
+            }
+            assert_eq!(
+                lr_update_weights(env_ptr, plaintext.as_ptr(), plaintext.len()),
+                error_ffi::LR_OK
+            );
+        }
+        lr_free(env_ptr);
+
+        let wrong_key = [0u8; encrypted::KEY_LEN];
+        let mut env_ptr: *mut lr_env = ptr::null_mut();
+        let result = lr_init_encrypted(
+            container.as_ptr(),
+            container.len(),
+            wrong_key.as_ptr(),
+            &mut env_ptr,
+        );
+        assert_eq!(result, error_ffi::LR_EDECRYPT);
+        assert!(env_ptr.is_null());
     }
 }
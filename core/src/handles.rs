@@ -0,0 +1,182 @@
+//! Generation-tagged handle tables behind the C API
+//!
+//! A C handle (`lr_env_t *`, `lr_policy_t *`, `lr_ctx_t *`) is an opaque token naming a table
+//! slot and the generation of the object in it, masked with a per-table magic; it never
+//! addresses Rust memory. Freeing bumps the slot's generation, so stale, double-freed, forged
+//! and wrong-kind handles fail lookup instead of reaching freed memory. Each object sits behind
+//! its own mutex: concurrent calls on one handle are serialized, and a handle freed by another
+//! thread stays alive until calls already inside it return.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Low bits of a token name the slot, high bits its generation
+const INDEX_BITS: u32 = usize::BITS / 2;
+const INDEX_MASK: usize = (1 << INDEX_BITS) - 1;
+const GENERATION_MASK: usize = usize::MAX >> INDEX_BITS;
+
+struct Slot<T> {
+    generation: usize,
+    object: Option<Arc<Mutex<T>>>,
+}
+
+struct Slots<T> {
+    slots: Vec<Slot<T>>,
+    /// Vacant slots, reused oldest first so a slot's generation advances as slowly as possible
+    free: VecDeque<usize>,
+}
+
+/// Objects addressed by generation-tagged tokens; usable as a `static`
+pub struct HandleTable<T> {
+    magic: usize,
+    slots: RwLock<Slots<T>>,
+}
+
+impl<T> HandleTable<T> {
+    /// Empty table whose tokens are masked with `magic` (distinct per handle kind)
+    pub const fn new(magic: usize) -> Self {
+        Self {
+            magic,
+            slots: RwLock::new(Slots {
+                slots: Vec::new(),
+                free: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Store `object` and return its token (never 0), or `None` when every slot is taken
+    pub fn insert(&self, object: T) -> Option<usize> {
+        let mut slots = self.write();
+        let index = match slots.free.pop_front() {
+            Some(index) => index,
+            None if slots.slots.len() < INDEX_MASK => {
+                slots.slots.push(Slot {
+                    generation: 0,
+                    object: None,
+                });
+                slots.slots.len() - 1
+            }
+            None => return None,
+        };
+
+        let slot = &mut slots.slots[index];
+        // Token 0 would read as NULL to C callers
+        if self.token(index, slot.generation) == 0 {
+            slot.generation = (slot.generation + 1) & GENERATION_MASK;
+        }
+        slot.object = Some(Arc::new(Mutex::new(object)));
+        Some(self.token(index, slot.generation))
+    }
+
+    /// The live object `token` names
+    pub fn get(&self, token: usize) -> Option<Arc<Mutex<T>>> {
+        let (index, generation) = self.decode(token);
+        let slots = self.read();
+        let slot = slots.slots.get(index)?;
+        if slot.generation != generation {
+            return None;
+        }
+        slot.object.clone()
+    }
+
+    /// Run `f` on the live object `token` names, holding that object's lock
+    pub fn with<R>(&self, token: usize, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let object = self.get(token)?;
+        let mut guard = object.lock().unwrap_or_else(PoisonError::into_inner);
+        Some(f(&mut guard))
+    }
+
+    /// Invalidate `token` and hand back its object; `None` for a stale or unknown token
+    pub fn remove(&self, token: usize) -> Option<Arc<Mutex<T>>> {
+        let (index, generation) = self.decode(token);
+        let mut slots = self.write();
+        let slot = slots
+            .slots
+            .get_mut(index)
+            .filter(|slot| slot.generation == generation)?;
+        let object = slot.object.take()?;
+        slot.generation = (slot.generation + 1) & GENERATION_MASK;
+        slots.free.push_back(index);
+        Some(object)
+    }
+
+    /// Number of live objects
+    pub fn len(&self) -> usize {
+        let slots = self.read();
+        slots.slots.len() - slots.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn token(&self, index: usize, generation: usize) -> usize {
+        ((generation << INDEX_BITS) | index) ^ self.magic
+    }
+
+    fn decode(&self, token: usize) -> (usize, usize) {
+        let raw = token ^ self.magic;
+        (raw & INDEX_MASK, raw >> INDEX_BITS)
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Slots<T>> {
+        self.slots.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Slots<T>> {
+        self.slots.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_tokens_fail_after_slot_reuse() {
+        let table = HandleTable::new(0x1234_5678);
+        let first = table.insert("first").unwrap();
+        assert_eq!(table.with(first, |value| *value), Some("first"));
+
+        assert!(table.remove(first).is_some());
+        assert!(table.remove(first).is_none());
+        let second = table.insert("second").unwrap();
+        assert_ne!(second, first);
+        assert!(table.get(first).is_none());
+        assert_eq!(table.with(second, |value| *value), Some("second"));
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_foreign_and_forged_tokens_are_rejected() {
+        let envs = HandleTable::new(0xE1A5_0E17);
+        let ctxs = HandleTable::new(0x35D7_C4E9);
+        let env = envs.insert(1u8).unwrap();
+        let ctx = ctxs.insert(2u8).unwrap();
+
+        assert!(envs.get(ctx).is_none());
+        assert!(ctxs.get(env).is_none());
+        for forged in [0, 1, usize::MAX, env ^ 1, env.wrapping_add(1 << INDEX_BITS)] {
+            assert!(envs.get(forged).is_none());
+        }
+    }
+
+    #[test]
+    fn test_token_is_never_zero() {
+        // A magic that makes slot 0, generation 0 encode to 0
+        let table = HandleTable::new(0);
+        let token = table.insert(()).unwrap();
+        assert_ne!(token, 0);
+        assert!(table.get(token).is_some());
+    }
+
+    #[test]
+    fn test_removed_object_outlives_callers_holding_it() {
+        let table = HandleTable::new(0x1234_5678);
+        let token = table.insert(vec![1, 2, 3]).unwrap();
+        let held = table.get(token).unwrap();
+        table.remove(token);
+        assert!(table.is_empty());
+        assert_eq!(held.lock().unwrap().len(), 3);
+    }
+}
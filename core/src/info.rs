@@ -0,0 +1,123 @@
+//! Introspection of a loaded policy: what algorithm, shape and weights are deployed
+//!
+//! `Env::info` reports a [`PolicyInfo`]; the C API exposes the same through `lr_get_info`, and
+//! `leanrl inspect` prints it for a weights file.
+
+use crate::{algorithms::tiny_nn::ActivationFunction, env::AlgorithmType};
+use core::fmt;
+
+/// Version of the weights blob layout (algorithm byte, then the algorithm's payload) this
+/// build reads and writes
+pub const WEIGHTS_FORMAT_VERSION: u32 = 1;
+
+/// Algorithm-specific shape of a policy
+#[derive(Debug, Clone, PartialEq)]
+pub enum Architecture {
+    /// Q-table of `num_states` × `num_actions` values
+    Tabular {
+        num_states: usize,
+        num_actions: usize,
+    },
+    /// Dense `action_dim` × `obs_dim` matrix plus bias
+    Linear,
+    /// Dense layers; `activations[i]` follows the layer from `layer_sizes[i]` to
+    /// `layer_sizes[i + 1]`
+    Network {
+        layer_sizes: Vec<usize>,
+        activations: Vec<ActivationFunction>,
+    },
+    /// No learned structure (test doubles)
+    None,
+}
+
+/// What a loaded policy is, so a field engineer can confirm what is deployed
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyInfo {
+    pub algorithm: AlgorithmType,
+    /// `Policy::algorithm_name`
+    pub algorithm_name: &'static str,
+    pub obs_dim: usize,
+    pub action_dim: usize,
+    pub architecture: Architecture,
+    /// Learned parameters (Q-values, weights and biases)
+    pub parameter_count: usize,
+    /// Bytes the policy occupies in memory, including its heap allocations
+    pub memory_bytes: usize,
+    /// SHA-256 of the weights blob the policy was loaded from (the plain blob, for signed
+    /// and encrypted containers)
+    pub weights_sha256: [u8; 32],
+    /// `WEIGHTS_FORMAT_VERSION` of the blob
+    pub format_version: u32,
+}
+
+impl fmt::Display for PolicyInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "algorithm:       {}", self.algorithm_name)?;
+        writeln!(f, "format version:  {}", self.format_version)?;
+        writeln!(
+            f,
+            "dims:            {} obs -> {} actions",
+            self.obs_dim, self.action_dim
+        )?;
+        match &self.architecture {
+            Architecture::Tabular {
+                num_states,
+                num_actions,
+            } => writeln!(
+                f,
+                "q-table:         {} states x {} actions",
+                num_states, num_actions
+            )?,
+            Architecture::Linear => writeln!(f, "layers:          linear")?,
+            Architecture::Network {
+                layer_sizes,
+                activations,
+            } => {
+                write!(f, "layers:          {}", layer_sizes[0])?;
+                for (size, activation) in layer_sizes[1..].iter().zip(activations) {
+                    write!(f, " -> {} ({:?})", size, activation)?;
+                }
+                writeln!(f)?;
+            }
+            Architecture::None => {}
+        }
+        writeln!(f, "parameters:      {}", self.parameter_count)?;
+        writeln!(f, "memory:          {} bytes", self.memory_bytes)?;
+        write!(f, "weights sha256:  ")?;
+        for byte in self.weights_sha256 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{algorithms::TinyNN, Policy};
+
+    #[test]
+    fn test_network_info_display() {
+        let policy = TinyNN::<4, 2>::new();
+        let info = PolicyInfo {
+            algorithm: AlgorithmType::TinyNN,
+            algorithm_name: policy.algorithm_name(),
+            obs_dim: 4,
+            action_dim: 2,
+            architecture: policy.architecture(),
+            parameter_count: policy.parameter_count(),
+            memory_bytes: policy.memory_footprint(),
+            weights_sha256: [0xab; 32],
+            format_version: WEIGHTS_FORMAT_VERSION,
+        };
+
+        assert_eq!(
+            info.parameter_count,
+            4 * 64 + 64 + 64 * 32 + 32 + 32 * 2 + 2
+        );
+        assert!(info.memory_bytes > info.parameter_count * 4);
+        let text = info.to_string();
+        assert!(text.contains("layers:          4 -> 64 (ReLU) -> 32 (ReLU) -> 2 (Tanh)"));
+        assert!(text.ends_with(&"ab".repeat(32)));
+    }
+}
@@ -15,17 +15,27 @@ const _ENV_STATE_SIZE_CHECK: () = {
 
 pub mod action;
 pub mod algorithms;
+pub mod dims;
+#[cfg(feature = "encryption")]
+pub mod encrypted;
 pub mod env;
 pub mod error;
 pub mod ffi;
+pub mod handles;
+pub mod info;
 pub mod obs;
+pub mod shared;
+pub mod signed;
 pub mod simd;
+pub mod weights;
 
 // Re-export main types
 pub use action::Action;
 pub use env::Env;
 pub use error::{Error, Result};
+pub use info::{Architecture, PolicyInfo};
 pub use obs::Obs;
+pub use shared::{SharedPolicy, StepContext};
 
 // Type aliases for common dimensions
 pub type Dim = usize;
@@ -46,7 +56,10 @@ pub trait Environment<const OBS_DIM: usize, const ACTION_DIM: usize> {
 }
 
 /// Policy trait for different RL algorithms
-pub trait Policy<const OBS_DIM: usize, const ACTION_DIM: usize> {
+///
+/// Policies are `Send + Sync`: `act` takes `&self`, so one loaded policy can serve several
+/// threads through `SharedPolicy`. Implementations needing mutable state must synchronize it.
+pub trait Policy<const OBS_DIM: usize, const ACTION_DIM: usize>: Send + Sync {
     /// Compute action from observation
     fn act(&self, obs: &Obs<OBS_DIM>) -> Action<ACTION_DIM>;
 
@@ -58,6 +71,19 @@ pub trait Policy<const OBS_DIM: usize, const ACTION_DIM: usize> {
 
     /// Stable name for logging, SBOM, and bundle metadata
     fn algorithm_name(&self) -> &'static str;
+
+    /// Number of learned parameters (Q-values, weights and biases)
+    fn parameter_count(&self) -> usize;
+
+    /// Algorithm-specific shape, for `Env::info`
+    fn architecture(&self) -> Architecture;
+
+    /// `(obs_dim, action_dim)` the loaded weights are shaped for, which `Env` checks
+    /// against its own dimensions
+    fn dims(&self) -> (usize, usize);
+
+    /// Bytes the policy occupies, including its heap allocations
+    fn memory_footprint(&self) -> usize;
 }
 
 #[cfg(test)]
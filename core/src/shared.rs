@@ -0,0 +1,342 @@
+//! Policies shared across threads, stepped through per-thread contexts
+//!
+//! Concurrency model: a `SharedPolicy` owns one loaded policy and may be cloned and used from
+//! any number of threads; hot-swaps and rollbacks take a short write lock. Each control loop
+//! steps through its own `StepContext`, which holds the step/episode counters and a snapshot of
+//! the live policy. A context notices a swap through an atomic generation counter and picks up
+//! the new policy at its next `reset`/`step`, so the step path takes no lock and never copies
+//! weights. `Env` remains single-owner: it is `Send + Sync`, but mutation needs `&mut`.
+
+use crate::{
+    action::Action,
+    env::{self, AlgorithmType, Env},
+    error::{Error, Result},
+    obs::Obs,
+    Policy,
+};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, PoisonError, RwLock,
+};
+
+/// Live policy and the one a rollback would restore
+struct Versions<const OBS_DIM: usize, const ACTION_DIM: usize> {
+    live: Arc<dyn Policy<OBS_DIM, ACTION_DIM>>,
+    previous: Option<Arc<dyn Policy<OBS_DIM, ACTION_DIM>>>,
+}
+
+struct Inner<const OBS_DIM: usize, const ACTION_DIM: usize> {
+    algorithm: AlgorithmType,
+    /// Shared from an environment loaded from an encrypted container; never exported
+    encrypted: bool,
+    versions: RwLock<Versions<OBS_DIM, ACTION_DIM>>,
+    /// Number of swaps and rollbacks; bumped while the write lock is held
+    generation: AtomicU64,
+}
+
+/// Read-mostly policy shared by several control loops; cloning shares, never copies weights
+pub struct SharedPolicy<const OBS_DIM: usize, const ACTION_DIM: usize> {
+    inner: Arc<Inner<OBS_DIM, ACTION_DIM>>,
+}
+
+impl<const OBS_DIM: usize, const ACTION_DIM: usize> Clone for SharedPolicy<OBS_DIM, ACTION_DIM> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<const OBS_DIM: usize, const ACTION_DIM: usize> SharedPolicy<OBS_DIM, ACTION_DIM> {
+    /// Load a shared policy from a weights blob (algorithm byte, then algorithm-specific weights)
+    pub fn from_weights(weights: &[u8]) -> Result<Self> {
+        let algorithm = AlgorithmType::from_u8(
+            *weights
+                .first()
+                .ok_or_else(|| Error::InvalidWeights("Empty weights data".to_string()))?,
+        )?;
+        let policy = Env::<OBS_DIM, ACTION_DIM>::policy_from_weights(weights)?;
+        Ok(Self::from_policy(algorithm, policy))
+    }
+
+    /// Share an already parsed policy
+    pub fn from_policy(
+        algorithm: AlgorithmType,
+        policy: Box<dyn Policy<OBS_DIM, ACTION_DIM>>,
+    ) -> Self {
+        Self::from_live(algorithm, Arc::from(policy), false)
+    }
+
+    /// Share the policy live in `env`, which is what it has verified (signed) or decrypted
+    ///
+    /// The policy is not re-parsed or copied: `env` and the shared policy hold the same
+    /// instance until either swaps its weights. A policy shared from an encrypted environment
+    /// is not exported either.
+    pub fn from_env(env: &Env<OBS_DIM, ACTION_DIM>) -> Self {
+        Self::from_live(env.state().algorithm, env.live_policy(), env.is_encrypted())
+    }
+
+    fn from_live(
+        algorithm: AlgorithmType,
+        live: Arc<dyn Policy<OBS_DIM, ACTION_DIM>>,
+        encrypted: bool,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                algorithm,
+                encrypted,
+                versions: RwLock::new(Versions {
+                    live,
+                    previous: None,
+                }),
+                generation: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// New per-thread step context running the live policy
+    pub fn context(&self) -> StepContext<OBS_DIM, ACTION_DIM> {
+        let (policy, generation) = self.snapshot();
+        StepContext {
+            shared: self.clone(),
+            policy,
+            generation,
+            step_count: 0,
+            episode_count: 0,
+        }
+    }
+
+    /// Live policy together with the generation it belongs to
+    fn snapshot(&self) -> (Arc<dyn Policy<OBS_DIM, ACTION_DIM>>, u64) {
+        let versions = self
+            .inner
+            .versions
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        (
+            Arc::clone(&versions.live),
+            self.inner.generation.load(Ordering::Acquire),
+        )
+    }
+
+    /// Hot-swap the shared policy; contexts switch over at their next `reset`/`step`
+    ///
+    /// The blob is parsed and validated as in `Env::update_weights` before the write lock is
+    /// taken, so on error the live policy is unchanged and steppers are never blocked on parsing.
+    pub fn update_weights(&self, weights: &[u8]) -> Result<()> {
+        let policy = Env::<OBS_DIM, ACTION_DIM>::validate_weights(self.inner.algorithm, weights)?;
+
+        let mut versions = self
+            .inner
+            .versions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let outgoing = core::mem::replace(&mut versions.live, Arc::from(policy));
+        versions.previous = Some(outgoing);
+        self.inner.generation.fetch_add(1, Ordering::Release);
+        Ok(())
+    }
+
+    /// Restore the policy that was live before the most recent swap
+    pub fn rollback_weights(&self) -> Result<()> {
+        let mut versions = self
+            .inner
+            .versions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let previous = versions.previous.take().ok_or(Error::NoRollbackTarget)?;
+        let outgoing = core::mem::replace(&mut versions.live, previous);
+        versions.previous = Some(outgoing);
+        self.inner.generation.fetch_add(1, Ordering::Release);
+        Ok(())
+    }
+
+    /// Serialize the live policy, algorithm byte included
+    ///
+    /// Fails with `Error::UnsupportedAlgorithm` on a policy shared from an encrypted `Env`.
+    pub fn get_weights(&self) -> Result<Vec<u8>> {
+        if self.inner.encrypted {
+            return Err(env::encrypted_export_error());
+        }
+        let mut weights = vec![self.inner.algorithm.to_u8()];
+        weights.extend(self.snapshot().0.get_weights()?);
+        Ok(weights)
+    }
+
+    /// Number of swaps and rollbacks since the policy was loaded
+    pub fn generation(&self) -> u64 {
+        self.inner.generation.load(Ordering::Acquire)
+    }
+
+    pub fn algorithm(&self) -> AlgorithmType {
+        self.inner.algorithm
+    }
+
+    pub fn algorithm_name(&self) -> &'static str {
+        self.snapshot().0.algorithm_name()
+    }
+}
+
+/// Per-thread stepping state over a `SharedPolicy`
+///
+/// Owned by one control loop at a time (`Send`, stepped through `&mut`); contexts of the same
+/// policy never contend with each other.
+pub struct StepContext<const OBS_DIM: usize, const ACTION_DIM: usize> {
+    shared: SharedPolicy<OBS_DIM, ACTION_DIM>,
+    policy: Arc<dyn Policy<OBS_DIM, ACTION_DIM>>,
+    generation: u64,
+    step_count: u64,
+    episode_count: u64,
+}
+
+impl<const OBS_DIM: usize, const ACTION_DIM: usize> StepContext<OBS_DIM, ACTION_DIM> {
+    /// Pick up a swapped policy at the step boundary
+    fn refresh(&mut self) {
+        if self.shared.inner.generation.load(Ordering::Acquire) != self.generation {
+            (self.policy, self.generation) = self.shared.snapshot();
+        }
+    }
+
+    /// Start a new episode and return the first action
+    pub fn reset(&mut self, obs: &Obs<OBS_DIM>) -> Action<ACTION_DIM> {
+        self.refresh();
+        self.step_count = 0;
+        self.episode_count += 1;
+        self.policy.act(obs)
+    }
+
+    /// Advance one step and return the action
+    pub fn step(&mut self, obs: &Obs<OBS_DIM>) -> Action<ACTION_DIM> {
+        self.refresh();
+        self.step_count += 1;
+        self.policy.act(obs)
+    }
+
+    /// Verify the runtime safety invariant (same check as `Env::check_invariant`)
+    pub fn check_invariant(&self, obs: &Obs<OBS_DIM>, action: &Action<ACTION_DIM>) -> Result<()> {
+        env::check_invariant(obs, action)
+    }
+
+    pub fn step_count(&self) -> u64 {
+        self.step_count
+    }
+
+    pub fn episode_count(&self) -> u64 {
+        self.episode_count
+    }
+
+    /// Generation of the policy this context last stepped with
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The policy this context steps
+    pub fn shared(&self) -> &SharedPolicy<OBS_DIM, ACTION_DIM> {
+        &self.shared
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weights;
+
+    fn linear_fa_weights(weight: f32, bias: f32) -> Vec<u8> {
+        weights::linear_fa_weights(4, 2, &[weight; 8], &[bias; 2], 0.01).unwrap()
+    }
+
+    #[test]
+    fn test_policy_types_are_thread_safe() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Env<4, 2>>();
+        assert_send_sync::<SharedPolicy<4, 2>>();
+        assert_send_sync::<StepContext<4, 2>>();
+        assert_send_sync::<Box<dyn Policy<4, 2>>>();
+    }
+
+    #[test]
+    fn test_contexts_share_policy_and_keep_own_state() {
+        let shared = SharedPolicy::<4, 2>::from_weights(&linear_fa_weights(0.1, 0.0)).unwrap();
+        let obs = Obs::new([1.0; 4]);
+        let mut a = shared.context();
+        let mut b = shared.context();
+
+        let first = a.reset(&obs);
+        a.step(&obs);
+        assert_eq!(b.step(&obs), first);
+        assert_eq!((a.step_count(), a.episode_count()), (1, 1));
+        assert_eq!((b.step_count(), b.episode_count()), (1, 0));
+        assert!(a.check_invariant(&obs, &first).is_ok());
+        assert_eq!(shared.get_weights().unwrap(), linear_fa_weights(0.1, 0.0));
+    }
+
+    #[test]
+    fn test_from_env_shares_the_live_policy() {
+        let mut env = Env::<4, 2>::from_weights(&linear_fa_weights(0.1, 0.0)).unwrap();
+        let shared = SharedPolicy::from_env(&env);
+        assert!(Arc::ptr_eq(&shared.snapshot().0, &env.live_policy()));
+
+        // Swapping either side leaves the other on the policy they shared
+        env.update_weights(&linear_fa_weights(0.0, 0.5)).unwrap();
+        assert_eq!(shared.get_weights().unwrap(), linear_fa_weights(0.1, 0.0));
+        shared.update_weights(&linear_fa_weights(0.2, 0.0)).unwrap();
+        assert_eq!(env.get_weights().unwrap(), linear_fa_weights(0.0, 0.5));
+    }
+
+    #[test]
+    fn test_swap_reaches_every_context_at_step_boundary() {
+        let shared = SharedPolicy::<4, 2>::from_weights(&linear_fa_weights(0.1, 0.0)).unwrap();
+        let obs = Obs::new([1.0; 4]);
+        let mut ctx = shared.context();
+        let before = ctx.step(&obs);
+
+        shared.update_weights(&linear_fa_weights(0.0, 0.5)).unwrap();
+        assert_eq!(ctx.generation(), 0);
+        assert!((ctx.step(&obs).as_slice()[0] - 0.5f32.tanh()).abs() < 1e-6);
+        assert_eq!(ctx.generation(), 1);
+
+        // Invalid or mismatched blobs leave the live policy in place
+        assert!(shared.update_weights(&[1, 0, 0]).is_err());
+        let tabular = weights::tabular_q_weights(1, 1, &[0.0], 0.1, 0.9).unwrap();
+        assert!(matches!(
+            shared.update_weights(&tabular),
+            Err(Error::WeightsMismatch(_))
+        ));
+        assert_eq!(shared.generation(), 1);
+
+        shared.rollback_weights().unwrap();
+        assert_eq!(ctx.step(&obs), before);
+        assert_eq!(shared.generation(), 2);
+    }
+
+    #[test]
+    fn test_threads_step_while_policy_swaps() {
+        let shared = SharedPolicy::<4, 2>::from_weights(&linear_fa_weights(0.1, 0.0)).unwrap();
+        let obs = Obs::new([1.0; 4]);
+        let allowed = [0.4f32.tanh(), 0.5f32.tanh()];
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let mut ctx = shared.context();
+                scope.spawn(move || {
+                    for _ in 0..1000 {
+                        let action = ctx.step(&obs);
+                        assert!(allowed
+                            .iter()
+                            .any(|a| (action.as_slice()[0] - a).abs() < 1e-6));
+                    }
+                    assert_eq!(ctx.step_count(), 1000);
+                });
+            }
+            for i in 0..50 {
+                let blob = if i % 2 == 0 {
+                    linear_fa_weights(0.0, 0.5)
+                } else {
+                    linear_fa_weights(0.1, 0.0)
+                };
+                shared.update_weights(&blob).unwrap();
+            }
+        });
+        assert_eq!(shared.generation(), 50);
+    }
+}
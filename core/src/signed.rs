@@ -0,0 +1,201 @@
+//! Ed25519-signed weights containers.
+//!
+//! A signed container wraps an ordinary weights blob (algorithm byte + policy weights):
+//!
+//! ```text
+//! magic "LRSG" (4) | version (1) | payload_len u32 LE (4) | payload | signature (64)
+//! ```
+//!
+//! The signature covers every byte before it, so the header cannot be altered independently
+//! of the payload. Verification needs no allocation or RNG and works in `no_std` builds.
+//!
+//! Firmware can pin a verification key at compile time by setting `LEANRL_WEIGHTS_PUBKEY`
+//! (64 hex characters) when building `leanrl_core`; see [`EMBEDDED_PUBLIC_KEY`].
+
+use crate::error::{Error, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+/// Container magic bytes
+pub const MAGIC: [u8; 4] = *b"LRSG";
+
+/// Current container format version
+pub const VERSION: u8 = 1;
+
+/// Bytes before the payload: magic, version and payload length
+pub const HEADER_LEN: usize = 4 + 1 + 4;
+
+/// Ed25519 signature length
+pub const SIGNATURE_LEN: usize = 64;
+
+/// Ed25519 public key length
+pub const PUBLIC_KEY_LEN: usize = 32;
+
+/// Verification key compiled into the firmware, if `LEANRL_WEIGHTS_PUBKEY` was set at build time
+///
+/// When present, the C API refuses unsigned weights in `lr_init` and `lr_update_weights`.
+pub const EMBEDDED_PUBLIC_KEY: Option<[u8; PUBLIC_KEY_LEN]> =
+    match option_env!("LEANRL_WEIGHTS_PUBKEY") {
+        Some(hex) => Some(decode_hex_key(hex)),
+        None => None,
+    };
+
+/// Decode a 64-character hex string at compile time
+const fn decode_hex_key(hex: &str) -> [u8; PUBLIC_KEY_LEN] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("LEANRL_WEIGHTS_PUBKEY must be hex"),
+        }
+    }
+
+    let bytes = hex.as_bytes();
+    assert!(
+        bytes.len() == PUBLIC_KEY_LEN * 2,
+        "LEANRL_WEIGHTS_PUBKEY must be 64 hex characters"
+    );
+
+    let mut key = [0u8; PUBLIC_KEY_LEN];
+    let mut i = 0;
+    while i < PUBLIC_KEY_LEN {
+        key[i] = (nibble(bytes[2 * i]) << 4) | nibble(bytes[2 * i + 1]);
+        i += 1;
+    }
+    key
+}
+
+/// Check whether a blob starts with the signed-container magic
+pub fn is_signed(blob: &[u8]) -> bool {
+    blob.len() >= MAGIC.len() && blob[..MAGIC.len()] == MAGIC
+}
+
+/// Verify a signed container and return the weights payload it carries
+pub fn verify<'a>(container: &'a [u8], public_key: &[u8; PUBLIC_KEY_LEN]) -> Result<&'a [u8]> {
+    if !is_signed(container) {
+        return Err(Error::SignatureInvalid(
+            "Weights are not a signed container".to_string(),
+        ));
+    }
+
+    if container.len() < HEADER_LEN + SIGNATURE_LEN {
+        return Err(Error::SignatureInvalid(
+            "Truncated signed container".to_string(),
+        ));
+    }
+
+    if container[4] != VERSION {
+        return Err(Error::SignatureInvalid(format!(
+            "Unsupported signed container version: {}",
+            container[4]
+        )));
+    }
+
+    let payload_len =
+        u32::from_le_bytes([container[5], container[6], container[7], container[8]]) as usize;
+    // Compared against what is left after the header and signature, so a u32 length cannot
+    // overflow `usize` on 32-bit targets
+    if payload_len != container.len() - HEADER_LEN - SIGNATURE_LEN {
+        return Err(Error::SignatureInvalid(
+            "Signed container length mismatch".to_string(),
+        ));
+    }
+
+    let (message, signature) = container.split_at(container.len() - SIGNATURE_LEN);
+    let mut signature_bytes = [0u8; SIGNATURE_LEN];
+    signature_bytes.copy_from_slice(signature);
+
+    let verifying_key = VerifyingKey::from_bytes(public_key)
+        .map_err(|_| Error::SignatureInvalid("Malformed public key".to_string()))?;
+    verifying_key
+        .verify_strict(message, &Signature::from_bytes(&signature_bytes))
+        .map_err(|_| Error::SignatureInvalid("Signature verification failed".to_string()))?;
+
+    Ok(&message[HEADER_LEN..])
+}
+
+/// Wrap a weights blob in a signed container
+///
+/// `secret_key` is the 32-byte Ed25519 seed. Signing normally happens off-target (e.g. in
+/// the release pipeline); it is provided here so tooling and tests share one implementation.
+pub fn sign(payload: &[u8], secret_key: &[u8; 32]) -> Result<Vec<u8>> {
+    let payload_len = u32::try_from(payload.len())
+        .map_err(|_| Error::InvalidWeights("Weights too large to sign".to_string()))?;
+
+    let mut container = Vec::with_capacity(HEADER_LEN + payload.len() + SIGNATURE_LEN);
+    container.extend_from_slice(&MAGIC);
+    container.push(VERSION);
+    container.extend_from_slice(&payload_len.to_le_bytes());
+    container.extend_from_slice(payload);
+
+    let signature = SigningKey::from_bytes(secret_key).sign(&container);
+    container.extend_from_slice(&signature.to_bytes());
+
+    Ok(container)
+}
+
+/// Derive the public key matching a 32-byte Ed25519 seed
+pub fn public_key(secret_key: &[u8; 32]) -> [u8; PUBLIC_KEY_LEN] {
+    SigningKey::from_bytes(secret_key)
+        .verifying_key()
+        .to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: [u8; 32] = [7u8; 32];
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        let payload = [0u8, 1, 2, 3, 4];
+        let container = sign(&payload, &SECRET).unwrap();
+
+        assert!(is_signed(&container));
+        assert_eq!(verify(&container, &public_key(&SECRET)).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering() {
+        let mut container = sign(&[0u8, 1, 2, 3, 4], &SECRET).unwrap();
+        container[HEADER_LEN + 2] ^= 0x01;
+
+        let result = verify(&container, &public_key(&SECRET));
+        assert!(matches!(result, Err(Error::SignatureInvalid(_))));
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_key() {
+        let container = sign(&[0u8, 1, 2, 3, 4], &SECRET).unwrap();
+
+        let result = verify(&container, &public_key(&[9u8; 32]));
+        assert!(matches!(result, Err(Error::SignatureInvalid(_))));
+    }
+
+    #[test]
+    fn test_verify_rejects_unsigned_and_truncated() {
+        let key = public_key(&SECRET);
+        assert!(verify(&[0u8, 1, 2], &key).is_err());
+
+        let mut container = sign(&[0u8, 1, 2, 3, 4], &SECRET).unwrap();
+        assert!(verify(&container[..container.len() - 1], &key).is_err());
+
+        // A length that would wrap `HEADER_LEN + payload_len + SIGNATURE_LEN` on 32-bit targets
+        container[5..9].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            verify(&container, &key),
+            Err(Error::SignatureInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_hex_key() {
+        let key =
+            decode_hex_key("00112233445566778899aabbccddeeff00112233445566778899AABBCCDDEEFF");
+        assert_eq!(key[0], 0x00);
+        assert_eq!(key[1], 0x11);
+        assert_eq!(key[15], 0xff);
+        assert_eq!(key[31], 0xff);
+    }
+}
@@ -0,0 +1,344 @@
+//! Weights blob encoding and decoding (decoding requires the `dyn-dims` feature).
+//!
+//! Encoders produce the exact byte layout `Env::from_weights` parses (algorithm byte, then
+//! little-endian algorithm-specific data). Decoding round-trips the blob through the runtime
+//! first, so only weights the runtime accepts are ever decoded.
+
+#[cfg(feature = "dyn-dims")]
+use crate::dims::new_policy;
+use crate::{algorithms::tiny_nn::ActivationFunction, env::AlgorithmType, Error, Result};
+
+/// Hidden layer widths of the fixed TinyNN architecture `[obs_dim, 64, 32, action_dim]`
+pub const TINY_NN_HIDDEN: [usize; 2] = [64, 32];
+
+/// One dense layer: row-major `(outputs, inputs)` weights, `outputs` biases, activation
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenseLayer {
+    pub weights: Vec<f32>,
+    pub bias: Vec<f32>,
+    pub activation: ActivationFunction,
+}
+
+/// Decoded algorithm parameters; matrices are row-major
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedWeights {
+    TabularQLearning {
+        num_states: usize,
+        num_actions: usize,
+        alpha: f32,
+        gamma: f32,
+        /// `(num_states, num_actions)`
+        q_table: Vec<f32>,
+    },
+    LinearFA {
+        learning_rate: f32,
+        /// `(action_dim, obs_dim)`
+        weights: Vec<f32>,
+        bias: Vec<f32>,
+    },
+    TinyNN {
+        layers: Vec<DenseLayer>,
+    },
+}
+
+/// Layer widths of TinyNN for the given dimensions
+pub fn tiny_nn_layer_sizes(obs_dim: usize, action_dim: usize) -> [usize; 4] {
+    [obs_dim, TINY_NN_HIDDEN[0], TINY_NN_HIDDEN[1], action_dim]
+}
+
+/// Parse an activation name (`relu`, `tanh`, `sigmoid`, `linear`)
+pub fn parse_activation(name: &str) -> Result<ActivationFunction> {
+    match name.to_ascii_lowercase().as_str() {
+        "relu" => Ok(ActivationFunction::ReLU),
+        "tanh" => Ok(ActivationFunction::Tanh),
+        "sigmoid" => Ok(ActivationFunction::Sigmoid),
+        "linear" => Ok(ActivationFunction::Linear),
+        _ => Err(Error::InvalidWeights(format!(
+            "Unknown activation function: {}",
+            name
+        ))),
+    }
+}
+
+/// Lowercase name accepted by `parse_activation`
+pub fn activation_name(activation: ActivationFunction) -> &'static str {
+    match activation {
+        ActivationFunction::ReLU => "relu",
+        ActivationFunction::Tanh => "tanh",
+        ActivationFunction::Sigmoid => "sigmoid",
+        ActivationFunction::Linear => "linear",
+    }
+}
+
+fn check_len(what: &str, values: &[f32], expected: usize) -> Result<()> {
+    if values.len() != expected {
+        return Err(Error::InvalidWeights(format!(
+            "{} has {} values, expected {}",
+            what,
+            values.len(),
+            expected
+        )));
+    }
+    Ok(())
+}
+
+fn extend_f32(blob: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        blob.extend(value.to_le_bytes());
+    }
+}
+
+/// Encode a TabularQLearning policy with a `(num_states, num_actions)` Q-table
+pub fn tabular_q_weights(
+    num_states: usize,
+    num_actions: usize,
+    q_table: &[f32],
+    alpha: f32,
+    gamma: f32,
+) -> Result<Vec<u8>> {
+    check_len("Q-table", q_table, num_states * num_actions)?;
+    let as_u32 = |value: usize| {
+        u32::try_from(value)
+            .map_err(|_| Error::InvalidWeights(format!("Table dimension {} too large", value)))
+    };
+
+    let mut blob = vec![AlgorithmType::TabularQLearning.to_u8()];
+    blob.extend(as_u32(num_states)?.to_le_bytes());
+    blob.extend(as_u32(num_actions)?.to_le_bytes());
+    blob.extend(alpha.to_le_bytes());
+    blob.extend(gamma.to_le_bytes());
+    extend_f32(&mut blob, q_table);
+    Ok(blob)
+}
+
+/// Encode a LinearFA policy with `(action_dim, obs_dim)` weights and `action_dim` biases
+pub fn linear_fa_weights(
+    obs_dim: usize,
+    action_dim: usize,
+    weights: &[f32],
+    bias: &[f32],
+    learning_rate: f32,
+) -> Result<Vec<u8>> {
+    check_len("LinearFA weights", weights, action_dim * obs_dim)?;
+    check_len("LinearFA bias", bias, action_dim)?;
+
+    let mut blob = vec![AlgorithmType::LinearFA.to_u8()];
+    blob.extend(learning_rate.to_le_bytes());
+    extend_f32(&mut blob, weights);
+    extend_f32(&mut blob, bias);
+    Ok(blob)
+}
+
+/// Encode a TinyNN policy; `layers` must follow `tiny_nn_layer_sizes(obs_dim, action_dim)`
+pub fn tiny_nn_weights(
+    obs_dim: usize,
+    action_dim: usize,
+    layers: &[DenseLayer],
+) -> Result<Vec<u8>> {
+    let sizes = tiny_nn_layer_sizes(obs_dim, action_dim);
+    if layers.len() != sizes.len() - 1 {
+        return Err(Error::InvalidWeights(format!(
+            "TinyNN needs {} layers, got {}",
+            sizes.len() - 1,
+            layers.len()
+        )));
+    }
+
+    let mut blob = vec![AlgorithmType::TinyNN.to_u8()];
+    blob.extend((sizes.len() as u16).to_le_bytes());
+    blob.extend(layers.iter().map(|layer| layer.activation.to_u8()));
+    for (i, (layer, io)) in layers.iter().zip(sizes.windows(2)).enumerate() {
+        check_len(
+            &format!("TinyNN layer {} weights", i),
+            &layer.weights,
+            io[1] * io[0],
+        )?;
+        check_len(&format!("TinyNN layer {} bias", i), &layer.bias, io[1])?;
+        extend_f32(&mut blob, &layer.weights);
+        extend_f32(&mut blob, &layer.bias);
+    }
+    Ok(blob)
+}
+
+/// Little-endian reader over a canonical weights blob
+#[cfg(feature = "dyn-dims")]
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+#[cfg(feature = "dyn-dims")]
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8]> {
+        if self.data.len() < len {
+            return Err(Error::InvalidWeights("Truncated weights data".to_string()));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<usize> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+
+    fn f32(&mut self) -> Result<f32> {
+        let bytes = self.take(4)?;
+        Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn f32s(&mut self, count: usize) -> Result<Vec<f32>> {
+        (0..count).map(|_| self.f32()).collect()
+    }
+}
+
+/// Decode a weights blob for `obs_dim`/`action_dim` into its algorithm parameters
+///
+/// The blob is first loaded by the runtime and re-serialized, so missing optional data (a
+/// TabularQ blob without a Q-table) decodes to what the runtime actually runs, while anything
+/// it rejects (a truncated Q-table, say) is an error.
+#[cfg(feature = "dyn-dims")]
+pub fn decode_weights(weights: &[u8], obs_dim: usize, action_dim: usize) -> Result<DecodedWeights> {
+    let canonical = new_policy(weights, obs_dim, action_dim)?.get_weights()?;
+    let mut reader = Reader { data: &canonical };
+
+    Ok(match AlgorithmType::from_u8(reader.u8()?)? {
+        AlgorithmType::TabularQLearning => {
+            let num_states = reader.u32()?;
+            let num_actions = reader.u32()?;
+            DecodedWeights::TabularQLearning {
+                num_states,
+                num_actions,
+                alpha: reader.f32()?,
+                gamma: reader.f32()?,
+                q_table: reader.f32s(num_states * num_actions)?,
+            }
+        }
+        AlgorithmType::LinearFA => DecodedWeights::LinearFA {
+            learning_rate: reader.f32()?,
+            weights: reader.f32s(action_dim * obs_dim)?,
+            bias: reader.f32s(action_dim)?,
+        },
+        AlgorithmType::TinyNN => {
+            let sizes = tiny_nn_layer_sizes(obs_dim, action_dim);
+            reader.take(2)?; // layer count, fixed by the architecture
+            let activations = (0..sizes.len() - 1)
+                .map(|_| ActivationFunction::from_u8(reader.u8()?))
+                .collect::<Result<Vec<_>>>()?;
+            let layers = sizes
+                .windows(2)
+                .zip(activations)
+                .map(|(io, activation)| {
+                    Ok(DenseLayer {
+                        weights: reader.f32s(io[1] * io[0])?,
+                        bias: reader.f32s(io[1])?,
+                        activation,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            DecodedWeights::TinyNN { layers }
+        }
+    })
+}
+
+#[cfg(all(test, feature = "dyn-dims"))]
+mod tests {
+    use super::*;
+    use crate::Env;
+
+    #[test]
+    fn test_linear_fa_round_trip() {
+        let weights: Vec<f32> = (0..8).map(|i| i as f32 * 0.1).collect();
+        let blob = linear_fa_weights(4, 2, &weights, &[0.5, -0.5], 0.01).unwrap();
+
+        // Same layout the runtime reads
+        let env = Env::<4, 2>::from_weights(&blob).unwrap();
+        assert_eq!(env.get_weights().unwrap(), blob);
+
+        assert_eq!(
+            decode_weights(&blob, 4, 2).unwrap(),
+            DecodedWeights::LinearFA {
+                learning_rate: 0.01,
+                weights,
+                bias: vec![0.5, -0.5],
+            }
+        );
+        assert!(linear_fa_weights(4, 2, &[0.0; 7], &[0.0; 2], 0.01).is_err());
+        assert!(decode_weights(&blob, 5, 2).is_err());
+    }
+
+    #[test]
+    fn test_tabular_q_round_trip_fills_missing_table() {
+        let blob = tabular_q_weights(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 0.1, 0.9).unwrap();
+        match decode_weights(&blob, 4, 3).unwrap() {
+            DecodedWeights::TabularQLearning {
+                num_states,
+                num_actions,
+                q_table,
+                ..
+            } => {
+                assert_eq!((num_states, num_actions), (2, 3));
+                assert_eq!(q_table[5], 6.0);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Header-only blob: the runtime starts from a zeroed table
+        match decode_weights(&blob[..17], 4, 3).unwrap() {
+            DecodedWeights::TabularQLearning { q_table, .. } => {
+                assert_eq!(q_table, vec![0.0; 6]);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        // A truncated table is not silently zero-filled
+        assert!(matches!(
+            decode_weights(&blob[..blob.len() - 4], 4, 3),
+            Err(Error::MalformedWeights { .. })
+        ));
+
+        // A table with more actions than the policy's action_dim does not load
+        assert!(matches!(
+            decode_weights(&blob, 4, 2),
+            Err(Error::InvalidActionSize {
+                expected: 2,
+                actual: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_tiny_nn_round_trip() {
+        let layers: Vec<DenseLayer> = tiny_nn_layer_sizes(3, 2)
+            .windows(2)
+            .zip(["relu", "tanh", "linear"])
+            .map(|(io, activation)| DenseLayer {
+                weights: vec![0.01; io[0] * io[1]],
+                bias: vec![0.1; io[1]],
+                activation: parse_activation(activation).unwrap(),
+            })
+            .collect();
+        let blob = tiny_nn_weights(3, 2, &layers).unwrap();
+        assert_eq!(
+            Env::<3, 2>::from_weights(&blob)
+                .unwrap()
+                .get_weights()
+                .unwrap(),
+            blob
+        );
+        assert_eq!(
+            decode_weights(&blob, 3, 2).unwrap(),
+            DecodedWeights::TinyNN {
+                layers: layers.clone()
+            }
+        );
+
+        assert!(tiny_nn_weights(4, 2, &layers).is_err());
+        assert!(tiny_nn_weights(3, 2, &layers[..2]).is_err());
+        assert!(parse_activation("gelu").is_err());
+        assert_eq!(activation_name(layers[0].activation), "relu");
+    }
+}
@@ -1,4 +1,274 @@
 #include <gtest/gtest.h>
 
-// Placeholder: full tests would link the cxx-generated shim and exercise leanrl.hpp APIs.
-TEST(LeanRLCppHarness, Placeholder) { EXPECT_TRUE(true); }
+#include <array>
+#include <cmath>
+#include <cstring>
+#include <string>
+#include <thread>
+#include <utility>
+#include <vector>
+
+#include "leanrl.hpp"
+
+namespace {
+
+void append_f32(std::vector<uint8_t>& blob, float value) {
+    uint8_t bytes[sizeof(float)];
+    std::memcpy(bytes, &value, sizeof(float));
+    blob.insert(blob.end(), bytes, bytes + sizeof(float));
+}
+
+// LinearFA weights for the 4x2 C API: every weight `w`, every bias `bias`
+std::vector<uint8_t> linear_fa_weights(float w, float bias) {
+    std::vector<uint8_t> blob{1};
+    append_f32(blob, 0.01f);  // learning rate
+    for (int i = 0; i < 4 * 2; ++i) append_f32(blob, w);
+    for (int i = 0; i < 2; ++i) append_f32(blob, bias);
+    return blob;
+}
+
+// LinearFA weights exported for 8 observations x 4 actions
+std::vector<uint8_t> linear_fa_8x4_weights() {
+    std::vector<uint8_t> blob{1};
+    append_f32(blob, 0.01f);
+    for (int i = 0; i < 8 * 4 + 4; ++i) append_f32(blob, 0.1f);
+    return blob;
+}
+
+// Single-state, single-action TabularQ weights
+std::vector<uint8_t> tabular_weights() {
+    std::vector<uint8_t> blob{0, 1, 0, 0, 0, 1, 0, 0, 0};
+    append_f32(blob, 0.1f);
+    append_f32(blob, 0.9f);
+    return blob;
+}
+
+} // namespace
+
+TEST(LeanRLCpp, ResetAndStep) {
+    leanrl::Env4x2 env(linear_fa_weights(0.1f, 0.0f));
+    ASSERT_TRUE(env.is_valid());
+
+    leanrl::Obs4 obs({1.0f, 1.0f, 1.0f, 1.0f});
+    leanrl::Action2 action = env.reset(obs);
+    // tanh(0.1 * 4)
+    EXPECT_NEAR(action[0], 0.3799f, 1e-3f);
+    EXPECT_TRUE(env.check_invariant(obs, action));
+    EXPECT_FALSE(env.check_invariant(obs, leanrl::Action2({5.0f, 0.0f})));
+
+    env.step(obs);
+    env.step(obs);
+    leanrl::StateCounts state = env.get_state();
+    EXPECT_EQ(state.step_count, 2u);
+    EXPECT_EQ(state.episode_count, 1u);
+}
+
+TEST(LeanRLCpp, UpdateWeightsAndRollback) {
+    leanrl::Env4x2 env(linear_fa_weights(0.1f, 0.0f));
+    leanrl::Obs4 obs({1.0f, 1.0f, 1.0f, 1.0f});
+    float before = env.reset(obs)[0];
+
+    try {
+        env.rollback_weights();
+        FAIL() << "expected leanrl::Error";
+    } catch (const leanrl::Error& e) {
+        EXPECT_EQ(e.code(), LR_ENOROLLBACK);
+    }
+    EXPECT_EQ(env.generation(), 0u);
+
+    env.update_weights(linear_fa_weights(0.0f, 0.5f));
+    EXPECT_EQ(env.generation(), 1u);
+    EXPECT_NEAR(env.step(obs)[0], 0.4621f, 1e-3f);  // tanh(0.5)
+    EXPECT_EQ(env.get_weights().size(), linear_fa_weights(0.0f, 0.5f).size());
+
+    // A blob for another algorithm is rejected and the live policy keeps running
+    try {
+        env.update_weights(tabular_weights());
+        FAIL() << "expected leanrl::Error";
+    } catch (const leanrl::Error& e) {
+        EXPECT_EQ(e.code(), LR_EMISMATCH);
+    }
+
+    env.rollback_weights();
+    EXPECT_EQ(env.generation(), 2u);
+    EXPECT_FLOAT_EQ(env.step(obs)[0], before);
+}
+
+TEST(LeanRLCpp, ErrorsAndOwnership) {
+    EXPECT_THROW(leanrl::Env4x2(std::vector<uint8_t>{9, 9, 9}), leanrl::Error);
+    try {
+        leanrl::Env4x2 env(std::vector<uint8_t>{});
+        FAIL() << "expected leanrl::Error";
+    } catch (const leanrl::Error& e) {
+        EXPECT_EQ(e.code(), LR_EBADWEIGHTS);
+    }
+
+    leanrl::Env4x2 env(tabular_weights());
+    leanrl::Env4x2 moved = std::move(env);
+    EXPECT_FALSE(env.is_valid());
+    EXPECT_TRUE(moved.is_valid());
+
+    auto owned = leanrl::create_env4x2(tabular_weights());
+    EXPECT_TRUE(owned->is_valid());
+
+    // Freed handles are rejected, not dereferenced
+    std::vector<uint8_t> weights = tabular_weights();
+    lr_env_t* raw = nullptr;
+    ASSERT_EQ(lr_init(weights.data(), weights.size(), &raw), LR_OK);
+    lr_free(raw);
+    uint64_t generation = 0;
+    EXPECT_EQ(lr_get_generation(raw, &generation), LR_EBADHANDLE);
+    EXPECT_EQ(lr_get_generation(nullptr, &generation), LR_ENULL);
+    EXPECT_STREQ(leanrl::error_message(LR_EBADHANDLE), "stale or invalid handle");
+}
+
+TEST(LeanRLCpp, PolicyInfo) {
+    leanrl::Env4x2 env(linear_fa_weights(0.1f, 0.0f));
+    lr_info_t info = env.info();
+    EXPECT_EQ(info.algorithm, 1);
+    EXPECT_STREQ(info.algorithm_name, "LinearFA");
+    EXPECT_EQ(info.obs_dim, 4u);
+    EXPECT_EQ(info.action_dim, 2u);
+    EXPECT_EQ(info.parameter_count, 4u * 2u + 2u);
+    EXPECT_EQ(info.num_layers, 0u);
+    EXPECT_TRUE(info.memory_bytes > 0);
+}
+
+TEST(LeanRLCpp, ErrorDetail) {
+    // Truncated blob: the error carries the byte offset and the library's message
+    std::vector<uint8_t> truncated = linear_fa_weights(0.1f, 0.0f);
+    truncated.resize(10);
+    try {
+        leanrl::Env4x2 env(truncated);
+        FAIL() << "expected leanrl::Error";
+    } catch (const leanrl::Error& e) {
+        EXPECT_EQ(e.code(), LR_EBADWEIGHTS);
+        EXPECT_EQ(e.detail().weights_offset, 10u);
+        EXPECT_TRUE(std::string(e.what()).find("at byte 10") != std::string::npos);
+    }
+
+    try {
+        leanrl::Env4x2 env(std::vector<uint8_t>{9, 9, 9});
+        FAIL() << "expected leanrl::Error";
+    } catch (const leanrl::Error& e) {
+        EXPECT_EQ(e.code(), LR_EUNSUPPORTED);
+    }
+
+    // Invariant violations are named on the handle
+    std::vector<uint8_t> weights = tabular_weights();
+    lr_env_t* raw = nullptr;
+    ASSERT_EQ(lr_init(weights.data(), weights.size(), &raw), LR_OK);
+    float obs[4] = {0.0f, 0.0f, 0.0f, 0.0f};
+    float action[2] = {2.0f, 0.0f};
+    EXPECT_EQ(lr_check_invariant(raw, obs, action), LR_EINVARIANT);
+    lr_error_t detail{};
+    EXPECT_EQ(lr_last_error(raw, &detail), LR_OK);
+    EXPECT_EQ(detail.code, LR_EINVARIANT);
+    EXPECT_STREQ(detail.invariant, "action_bounds");
+    size_t len = 0;
+    EXPECT_EQ(lr_last_error_message(raw, nullptr, 0, &len), LR_OK);
+    std::vector<char> message(len);
+    EXPECT_EQ(lr_last_error_message(raw, message.data(), message.size() - 1, &len),
+              LR_EBUFSMALL);
+    EXPECT_EQ(lr_last_error_message(raw, message.data(), message.size(), &len), LR_OK);
+    EXPECT_EQ(std::strlen(message.data()) + 1, len);
+
+    // Weights: size query, then a buffer one byte short is rejected untouched
+    size_t weights_len = 0;
+    EXPECT_EQ(lr_get_weights(raw, nullptr, 0, &weights_len), LR_OK);
+    std::vector<uint8_t> short_buf(weights_len - 1, 0xAA);
+    EXPECT_EQ(lr_get_weights(raw, short_buf.data(), short_buf.size(), &weights_len),
+              LR_EBUFSMALL);
+    EXPECT_EQ(short_buf[0], 0xAA);
+    lr_free(raw);
+}
+
+TEST(LeanRLCpp, ActionOperations) {
+    leanrl::Action2 a({0.5f, -2.0f});
+    leanrl::Action2 sum = a + leanrl::Action2({0.5f, 1.0f});
+    EXPECT_FLOAT_EQ(sum[0], 1.0f);
+    EXPECT_FLOAT_EQ((2.0f * a)[1], -4.0f);
+    EXPECT_FLOAT_EQ(a.clamp(-1.0f, 1.0f).min(), -1.0f);
+    EXPECT_FALSE(a.is_within_bounds(-1.0f, 1.0f));
+}
+
+TEST(LeanRLCpp, TemplatedDimsLoadOtherShapes) {
+    static_assert(leanrl::Env<8, 4>::obs_dim() == 8);
+    static_assert(leanrl::Env<8, 4>::action_dim() == 4);
+#if defined(LEANRL_DYN_DIMS)
+    leanrl::Env<8, 4> env(linear_fa_8x4_weights());
+    EXPECT_EQ(env.info().obs_dim, 8u);
+    leanrl::Obs<8> obs;
+    for (float& value : obs) value = 1.0f;
+    leanrl::Action<4> action = env.step(obs);
+    EXPECT_TRUE(env.check_invariant(obs, action));
+#else
+    // Without lr_init_dims the weights load at LR_OBS_DIM x LR_ACTION_DIM only
+    EXPECT_THROW(leanrl::Env<8, 4> env(linear_fa_8x4_weights()), leanrl::Error);
+#endif
+}
+
+TEST(LeanRLCpp, WeightsOfOtherDimsAreRejected) {
+    try {
+        leanrl::Env<4, 2> env(linear_fa_8x4_weights());
+        FAIL() << "expected leanrl::Error";
+    } catch (const leanrl::Error& e) {
+        EXPECT_EQ(e.code(), LR_EINVSIZE);
+    }
+}
+
+TEST(LeanRLCpp, SharedPolicyAcrossThreads) {
+    leanrl::SharedPolicy4x2 policy(leanrl::Env4x2(linear_fa_weights(0.1f, 0.0f)));
+    leanrl::Obs4 obs({1.0f, 1.0f, 1.0f, 1.0f});
+
+    std::vector<std::thread> loops;
+    for (int i = 0; i < 4; ++i) {
+        loops.emplace_back([ctx = policy.context(), obs]() mutable {
+            ctx.reset(obs);
+            for (int step = 0; step < 1000; ++step) {
+                float a = ctx.step(obs)[0];
+                // tanh(0.1 * 4) before the swap, tanh(0.5) after
+                EXPECT_TRUE(std::abs(a - 0.3799f) < 1e-3f || std::abs(a - 0.4621f) < 1e-3f);
+            }
+            EXPECT_EQ(ctx.get_state().step_count, 1000u);
+        });
+    }
+    for (int i = 0; i < 10; ++i) {
+        policy.update_weights(linear_fa_weights(i % 2 ? 0.1f : 0.0f, i % 2 ? 0.0f : 0.5f));
+    }
+    for (std::thread& loop : loops) {
+        loop.join();
+    }
+    EXPECT_EQ(policy.generation(), 10u);
+
+    // Contexts keep the policy alive once the handle is gone
+    leanrl::StepContext<4, 2> ctx = policy.context();
+    EXPECT_THROW(policy.update_weights(tabular_weights()), leanrl::Error);
+    policy = leanrl::SharedPolicy4x2(leanrl::Env4x2(linear_fa_weights(0.0f, 0.0f)));
+    EXPECT_NEAR(ctx.step(obs)[0], 0.3799f, 1e-3f);
+}
+
+#if LEANRL_HAS_SPAN
+TEST(LeanRLCpp, SpanStep) {
+    leanrl::Env4x2 env(linear_fa_weights(0.1f, 0.0f));
+    std::array<float, 4> obs{1.0f, 1.0f, 1.0f, 1.0f};
+    std::array<float, 2> action{};
+    env.reset(std::span<const float, 4>(obs), std::span<float, 2>(action));
+    EXPECT_NEAR(action[1], 0.3799f, 1e-3f);
+    env.step(obs, action);
+    EXPECT_EQ(env.get_state().step_count, 1u);
+}
+#endif
+
+#ifdef LEANRL_USE_EIGEN
+TEST(LeanRLCpp, EigenMapIsZeroCopy) {
+    leanrl::Obs4 obs({1.0f, 2.0f, 3.0f, 4.0f});
+    auto view = leanrl::eigen::map(obs);
+    view(0) = 10.0f;
+    EXPECT_FLOAT_EQ(obs[0], 10.0f);
+    EXPECT_EQ(view.data(), obs.data());
+
+    leanrl::Action2 action = leanrl::eigen::from_eigen(Eigen::Vector2f(0.5f, -0.5f));
+    EXPECT_FLOAT_EQ(leanrl::eigen::to_eigen(action).sum(), 0.0f);
+}
+#endif
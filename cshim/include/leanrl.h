@@ -0,0 +1,295 @@
+// LeanEdge-RL C API Header
+//
+// Generated from core/src/ffi.rs by `leanrl-bundle header`; do not edit.
+// Define LEANRL_ENCRYPTION when linking a library built with the `encryption` feature,
+// and LEANRL_DYN_DIMS when it was built with `dyn-dims`.
+
+#pragma once
+
+#include <stdint.h>
+#include <stddef.h>
+
+// Observation dimension `lr_init` loads policies at (`lr_init_dims` takes any)
+#define LR_OBS_DIM 4
+
+// Action dimension `lr_init` loads policies at (`lr_init_dims` takes any)
+#define LR_ACTION_DIM 2
+
+// Most layers (input and output included) a TinyNN policy has
+#define LR_MAX_LAYERS 5
+
+#define LR_OK 0
+
+#define LR_EBADWEIGHTS -1
+
+#define LR_EINVSIZE -2
+
+#define LR_EINVARIANT -3
+
+#define LR_EOUTOFMEM -4
+
+#define LR_EINTERNAL -5
+
+#define LR_EMISMATCH -6
+
+#define LR_ESIGNATURE -7
+
+// Encrypted weights failed authentication (wrong key or tampered container)
+#define LR_EDECRYPT -8
+
+// A required pointer argument was NULL
+#define LR_ENULL -9
+
+// Handle already freed, forged, or of another kind (`lr_ctx_t *` passed as `lr_env_t *`)
+#define LR_EBADHANDLE -10
+
+// Unknown algorithm byte in the weights
+#define LR_EUNSUPPORTED -11
+
+// Weights could not be serialized
+#define LR_ESERIALIZATION -12
+
+// Requested SIMD path not available on this CPU
+#define LR_ESIMD -13
+
+// Output buffer smaller than the data; the required size was still reported
+#define LR_EBUFSMALL -14
+
+// Rollback requested but no earlier weights were ever swapped out; state is unchanged
+#define LR_ENOROLLBACK -15
+
+// Opaque per-thread step context over a shared policy
+//
+// Each control loop owns its context; calls on one context are serialized, but contexts of
+// the same policy never contend with each other.
+typedef struct lr_ctx_t lr_ctx_t;
+
+// Opaque environment handle for C API
+//
+// Calls on one handle are serialized, so it may be used from any thread; for several control
+// loops running concurrently on one policy, share it with `lr_policy_share`.
+typedef struct lr_env_t lr_env_t;
+
+// Opaque shared policy handle for C API; every `lr_policy_*` call is thread-safe
+typedef struct lr_policy_t lr_policy_t;
+
+// Description of the loaded policy, filled by `lr_get_info`
+typedef struct {
+  // Algorithm byte of the weights: 0 TabularQLearning, 1 LinearFA, 2 TinyNN
+  uint8_t algorithm;
+  // Algorithm name (static string)
+  const char *algorithm_name;
+  size_t obs_dim;
+  size_t action_dim;
+  // Q-table shape of a TabularQLearning policy, else 0
+  size_t num_states;
+  size_t num_actions;
+  // Layer count of a TinyNN policy (input and output included), else 0
+  size_t num_layers;
+  // First `num_layers` entries are the layer sizes
+  size_t layer_sizes[LR_MAX_LAYERS];
+  // First `num_layers - 1` entries are activation ids (0 ReLU, 1 Tanh, 2 Sigmoid, 3 Linear)
+  uint8_t activations[LR_MAX_LAYERS];
+  // Learned parameters (Q-values, weights and biases)
+  size_t parameter_count;
+  // Bytes the policy occupies in memory
+  size_t memory_bytes;
+  // SHA-256 of the weights blob the policy was loaded from
+  uint8_t weights_sha256[32];
+  // Weights format version
+  uint32_t format_version;
+} lr_info_t;
+
+// Structured detail of the last failed call, filled by `lr_last_error`
+typedef struct {
+  // Status code the call returned; `LR_OK` when nothing has failed
+  int32_t code;
+  // Expected element count of an `LR_EINVSIZE` failure (required bytes for
+  // `LR_EBUFSMALL`), else 0
+  size_t expected;
+  // Actual element count of an `LR_EINVSIZE` failure (bytes given for `LR_EBUFSMALL`),
+  // else 0
+  size_t actual;
+  // Byte offset in the weights blob where parsing failed, or `SIZE_MAX` when unknown
+  size_t weights_offset;
+  // Name of the invariant an `LR_EINVARIANT` failure violated (static), else ""
+  const char *invariant;
+} lr_error_t;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// C API: Initialize environment with weights shaped for `LR_OBS_DIM` x `LR_ACTION_DIM`
+//
+// If the library was built with an embedded verification key (`LEANRL_WEIGHTS_PUBKEY`),
+// `weights` must be a signed container and is verified against that key. Returns
+// `LR_EBADWEIGHTS` for malformed weights and `LR_EUNSUPPORTED` for an unknown algorithm.
+// On error `*out` is set to NULL.
+int32_t lr_init(const uint8_t *weights, size_t len, lr_env_t **out);
+
+#if defined(LEANRL_DYN_DIMS)
+// C API: Initialize environment with weights shaped for `obs_dim` x `action_dim`
+//
+// Like `lr_init`, embedded verification key included, for any shape from 1x1 up to 16x8;
+// `lr_get_dims` reports the dims, and `lr_reset`, `lr_step` and `lr_check_invariant` read
+// `obs_dim` observations and write or read `action_dim` actions. Returns `LR_EINVSIZE` for
+// dims out of range or weights shaped for other dims. On error `*out` is set to NULL.
+int32_t lr_init_dims(const uint8_t *weights,
+                     size_t len,
+                     size_t obs_dim,
+                     size_t action_dim,
+                     lr_env_t **out);
+#endif
+
+// C API: Initialize environment from a signed weights container
+//
+// `public_key` points to a 32-byte Ed25519 public key, or is NULL to use the key embedded
+// at build time. Returns `LR_ESIGNATURE` if the signature does not verify, or if NULL is
+// passed and no key was embedded. On error `*out` is set to NULL.
+int32_t lr_init_signed(const uint8_t *weights,
+                       size_t len,
+                       const uint8_t *public_key,
+                       lr_env_t **out);
+
+#if defined(LEANRL_ENCRYPTION)
+// C API: Initialize environment from an AEAD-encrypted weights container
+//
+// `key` points to a 32-byte key that is used only for this call. Returns `LR_EDECRYPT`
+// if the container fails authentication. The handle keeps the ciphertext but never the
+// decrypted weights: `lr_get_weights` on it returns `LR_EUNSUPPORTED`, and plaintext blobs
+// later swapped in with `lr_update_weights` are not retained. On error `*out` is set to NULL.
+int32_t lr_init_encrypted(const uint8_t *weights, size_t len, const uint8_t *key, lr_env_t **out);
+#endif
+
+// C API: Reset environment with initial observation
+//
+// Reads `obs_dim` floats from `obs` and writes `action_dim` floats to `action` (`lr_get_dims`).
+int32_t lr_reset(lr_env_t *env, const float *obs, float *action);
+
+// C API: Step environment with new observation
+//
+// Reads `obs_dim` floats from `obs` and writes `action_dim` floats to `action` (`lr_get_dims`).
+int32_t lr_step(lr_env_t *env, const float *obs, float *action);
+
+// C API: Free environment handle
+//
+// NULL, already freed and foreign handles are ignored. Calls running on `env` in other
+// threads finish before the environment is dropped.
+void lr_free(lr_env_t *env);
+
+// C API: Get environment state (for debugging/testing)
+int32_t lr_get_state(const lr_env_t *env, uint64_t *step_count, uint64_t *episode_count);
+
+// C API: Get the observation and action dimensions of the loaded policy
+//
+// The dimensions are those the handle was created with: `LR_OBS_DIM` x `LR_ACTION_DIM` for
+// `lr_init`, or the ones given to `lr_init_dims`. Init and the weight swaps reject blobs
+// shaped for other dims with `LR_EINVSIZE`. `lr_reset`, `lr_step` and `lr_check_invariant`
+// read `obs_dim` observations and write or read `action_dim` actions.
+int32_t lr_get_dims(const lr_env_t *env, size_t *obs_dim, size_t *action_dim);
+
+// C API: Check safety invariant
+//
+// Returns `LR_EINVARIANT` on a violation; `lr_last_error` names the invariant.
+int32_t lr_check_invariant(const lr_env_t *env, const float *obs, const float *action);
+
+// C API: Update environment weights
+//
+// The new blob is validated into a shadow policy before being swapped in, so on any error
+// the live policy keeps running unchanged. On a handle whose weights were verified at init
+// (`lr_init_signed`, or any init with an embedded key) the blob must be a container signed
+// by that same key, else `LR_ESIGNATURE`. Returns `LR_EBADWEIGHTS` for malformed weights,
+// `LR_EUNSUPPORTED` for an unknown algorithm and `LR_EMISMATCH` when the blob is for a
+// different algorithm than the loaded one.
+int32_t lr_update_weights(lr_env_t *env, const uint8_t *weights, size_t len);
+
+// C API: Restore the weights that were live before the last successful update
+int32_t lr_rollback_weights(lr_env_t *env);
+
+// C API: Get the weights generation (number of swaps and rollbacks since init)
+int32_t lr_get_generation(const lr_env_t *env, uint64_t *generation);
+
+// C API: Get environment weights
+//
+// Two-phase: `*actual_len` always receives the blob size, so call with `weights == NULL`
+// and `max_len == 0` to query it, then with a buffer of that size. A smaller buffer is left
+// untouched and the call returns `LR_EBUFSMALL`. A handle from `lr_init_encrypted` never
+// exports its weights: the call returns `LR_EUNSUPPORTED` and writes nothing.
+int32_t lr_get_weights(const lr_env_t *env, uint8_t *weights, size_t max_len, size_t *actual_len);
+
+// C API: Describe the policy loaded in `env`: algorithm, dims, shape, size and weights hash
+int32_t lr_get_info(const lr_env_t *env, lr_info_t *out);
+
+// C API: Get the last failure on `env`, or on the calling thread when `env` is NULL
+//
+// Every failing call records its error on this thread and, when made on a live handle, on
+// that handle; a later success does not clear it. With nothing recorded, `out->code` is
+// `LR_OK`. Returns `LR_EBADHANDLE` for a stale `env`; this call never records a failure.
+int32_t lr_last_error(const lr_env_t *env, lr_error_t *out);
+
+// C API: Get the message of the last failure on `env`, or on the calling thread when `env`
+// is NULL
+//
+// Two-phase like `lr_get_weights`: `*actual_len` receives the message size including its
+// NUL; `buf == NULL` with `len == 0` queries it, and a smaller buffer gets `LR_EBUFSMALL`.
+// The message is empty when nothing has failed. This call never records a failure.
+int32_t lr_last_error_message(const lr_env_t *env, char *buf, size_t len, size_t *actual_len);
+
+// C API: Share the policy loaded in `env` with several threads
+//
+// The returned handle starts on the very policy `env` has live (verified or decrypted, never
+// re-parsed) and requires updates signed by the same key as `env`; otherwise it is
+// independent of `env`, which keeps running and can be freed. Step
+// it from each control loop through its own `lr_ctx_new` context; the policy is freed once
+// the handle and every context created from it are freed. Only `LR_OBS_DIM` x
+// `LR_ACTION_DIM` policies can be shared; others fail with `LR_EINVSIZE`. On error `*out` is
+// set to NULL.
+int32_t lr_policy_share(const lr_env_t *env, lr_policy_t **out);
+
+// C API: Free a shared policy handle (contexts created from it keep working)
+void lr_policy_free(lr_policy_t *policy);
+
+// C API: Hot-swap a shared policy; thread-safe
+//
+// Validated like `lr_update_weights`, signature included, before the swap, so on error every
+// context keeps the live policy. Contexts switch to the new policy at their next `lr_ctx_reset`/`lr_ctx_step`.
+int32_t lr_policy_update_weights(const lr_policy_t *policy,
+                                 const uint8_t *weights,
+                                 size_t len);
+
+// C API: Restore the shared policy that was live before the last swap; thread-safe
+int32_t lr_policy_rollback_weights(const lr_policy_t *policy);
+
+// C API: Get the shared policy generation (number of swaps and rollbacks); thread-safe
+int32_t lr_policy_get_generation(const lr_policy_t *policy, uint64_t *generation);
+
+// C API: `lr_last_error` for a shared policy; NULL reads the calling thread's last failure
+int32_t lr_policy_last_error(const lr_policy_t *policy, lr_error_t *out);
+
+// C API: `lr_last_error_message` for a shared policy
+int32_t lr_policy_last_error_message(const lr_policy_t *policy,
+                                     char *buf,
+                                     size_t len,
+                                     size_t *actual_len);
+
+// C API: Create a step context for one control loop over a shared policy; thread-safe
+//
+// On error `*out` is set to NULL.
+int32_t lr_ctx_new(const lr_policy_t *policy, lr_ctx_t **out);
+
+// C API: Free a step context
+void lr_ctx_free(lr_ctx_t *ctx);
+
+// C API: Reset a step context with an initial observation
+int32_t lr_ctx_reset(lr_ctx_t *ctx, const float *obs, float *action);
+
+// C API: Step a context with a new observation, picking up any policy swap first
+int32_t lr_ctx_step(lr_ctx_t *ctx, const float *obs, float *action);
+
+// C API: Get the step and episode counters of a context
+int32_t lr_ctx_get_state(const lr_ctx_t *ctx, uint64_t *step_count, uint64_t *episode_count);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
@@ -1,136 +1,470 @@
 #pragma once
 
+// C++ facade over the LeanEdge-RL C API (leanrl.h). Header-only: link leanrl_core as a
+// staticlib or cdylib. Every failing lr_* call throws leanrl::Error carrying its LR_E* code
+// and the detail `lr_last_error` recorded for it.
+//
+// Optional integrations: define LEANRL_USE_EIGEN for Eigen::Map views of observations and
+// actions, LEANRL_USE_TENSORRT for tensor copies. Define LEANRL_DYN_DIMS when linking a
+// leanrl_core built with the `dyn-dims` feature, so `Env<ObsN, ActN>` loads any supported shape.
+
+#include "leanrl.h"
+
+#include <algorithm>
 #include <array>
+#include <cstddef>
+#include <cstdint>
 #include <memory>
-#include <system_error>
+#include <stdexcept>
+#include <string>
 #include <vector>
-#include <cstdint>
 
-// Error handling macro
-#define LEANRL_ASSERT_OK(x) do { \
-    if (!(x)) { \
-        throw std::system_error(std::error_code(-1, std::system_category()), \
-                               "LeanRL operation failed"); \
-    } \
-} while(0)
+// std::span overloads of Env::reset / Env::step need C++20
+#if __cplusplus >= 202002L && __has_include(<span>)
+#include <span>
+#define LEANRL_HAS_SPAN 1
+#else
+#define LEANRL_HAS_SPAN 0
+#endif
 
-namespace leanrl {
+#ifdef LEANRL_USE_EIGEN
+#include <Eigen/Dense>
+#endif
 
-// Forward declarations
-class Obs4;
-class Action2;
-class Env4x2;
+namespace leanrl {
 
-/// Matches the `cxx` bridge `StateCounts` (step / episode counters).
+/// Step / episode counters reported by `lr_get_state`.
 struct StateCounts {
     uint64_t step_count;
     uint64_t episode_count;
 };
 
-// Observation class for 4-dimensional observations
-class Obs4 {
+/// Description of an LR_E* code
+inline const char* error_message(int32_t code) noexcept {
+    switch (code) {
+        case LR_OK: return "ok";
+        case LR_EBADWEIGHTS: return "invalid weights";
+        case LR_EINVSIZE: return "invalid observation or action size";
+        case LR_EINVARIANT: return "safety invariant violated";
+        case LR_EOUTOFMEM: return "out of memory";
+        case LR_EINTERNAL: return "internal error";
+        case LR_EMISMATCH: return "weights are for a different algorithm";
+        case LR_ESIGNATURE: return "weights signature verification failed";
+        case LR_EDECRYPT: return "weights decryption failed";
+        case LR_ENULL: return "null pointer argument";
+        case LR_EBADHANDLE: return "stale or invalid handle";
+        case LR_EUNSUPPORTED: return "unsupported algorithm";
+        case LR_ESERIALIZATION: return "serialization failed";
+        case LR_ESIMD: return "SIMD path not available";
+        case LR_EBUFSMALL: return "output buffer too small";
+        case LR_ENOROLLBACK: return "no previous weights to roll back to";
+        default: return "unknown error";
+    }
+}
+
+/// Failure of a C API call
+///
+/// `what()` is "<operation>: <code description>", followed by the library's message for the
+/// failure when there is one, e.g. "lr_init: invalid weights (Invalid weights data at byte 5:
+/// ...)".
+class Error : public std::runtime_error {
 public:
-    Obs4(std::array<float, 4> data);
-    Obs4(const Obs4& other);
-    Obs4& operator=(const Obs4& other);
-    ~Obs4();
-    
-    std::array<float, 4> get_data() const;
-    void set_data(std::array<float, 4> data);
-    
-    // Array-like access
-    float operator[](size_t index) const;
-    float& operator[](size_t index);
-    
-    // Iterator support
-    const float* begin() const;
-    const float* end() const;
-    float* begin();
-    float* end();
-    
-    // Size
-    static constexpr size_t size() { return 4; }
-    
+    Error(int32_t code, const char* operation)
+        : Error(code, operation, std::string(), lr_error_t{code, 0, 0, SIZE_MAX, ""}) {}
+
+    Error(int32_t code, const char* operation, const std::string& message,
+          const lr_error_t& detail)
+        : std::runtime_error(std::string(operation) + ": " + error_message(code) +
+                             (message.empty() ? std::string() : " (" + message + ")")),
+          code_(code),
+          detail_(detail) {}
+
+    /// The LR_E* code returned by the C API
+    int32_t code() const noexcept { return code_; }
+
+    /// Structured detail: sizes, weights byte offset (SIZE_MAX if unknown), invariant name
+    const lr_error_t& detail() const noexcept { return detail_; }
+
 private:
-    class Impl;
-    std::unique_ptr<Impl> pImpl;
+    int32_t code_;
+    lr_error_t detail_;
+};
+
+namespace detail {
+
+/// `Error` for a call that just returned `code`, with the detail it recorded on this thread
+inline Error last_error(int32_t code, const char* operation) {
+    lr_error_t detail{};
+    if (lr_last_error(nullptr, &detail) != LR_OK || detail.code != code) {
+        return Error(code, operation);
+    }
+    std::string message;
+    size_t len = 0;
+    if (lr_last_error_message(nullptr, nullptr, 0, &len) == LR_OK && len > 1) {
+        message.resize(len);
+        if (lr_last_error_message(nullptr, &message[0], len, &len) == LR_OK) {
+            message.resize(len - 1);  // drop the NUL
+        } else {
+            message.clear();
+        }
+    }
+    return Error(code, operation, message, detail);
+}
+
+inline void check(int32_t code, const char* operation) {
+    if (code != LR_OK) {
+        throw last_error(code, operation);
+    }
+}
+
+struct EnvDeleter {
+    void operator()(lr_env_t* env) const noexcept { lr_free(env); }
+};
+
+struct PolicyDeleter {
+    void operator()(lr_policy_t* policy) const noexcept { lr_policy_free(policy); }
+};
+
+struct CtxDeleter {
+    void operator()(lr_ctx_t* ctx) const noexcept { lr_ctx_free(ctx); }
 };
 
-// Action class for 2-dimensional actions
-class Action2 {
+/// Fixed-size float vector shared by `Obs` and `Action`
+template <size_t N>
+class FloatArray {
 public:
-    Action2(std::array<float, 2> data);
-    Action2(const Action2& other);
-    Action2& operator=(const Action2& other);
-    ~Action2();
-    
-    std::array<float, 2> get_data() const;
-    void set_data(std::array<float, 2> data);
-    
+    FloatArray() : data_{} {}
+    FloatArray(std::array<float, N> data) : data_(data) {}
+
+    std::array<float, N> get_data() const { return data_; }
+    void set_data(std::array<float, N> data) { data_ = data; }
+
     // Array-like access
-    float operator[](size_t index) const;
-    float& operator[](size_t index);
-    
+    float operator[](size_t index) const { return data_[index]; }
+    float& operator[](size_t index) { return data_[index]; }
+    const float* data() const { return data_.data(); }
+    float* data() { return data_.data(); }
+
     // Iterator support
-    const float* begin() const;
-    const float* end() const;
-    float* begin();
-    float* end();
-    
+    const float* begin() const { return data_.data(); }
+    const float* end() const { return data_.data() + N; }
+    float* begin() { return data_.data(); }
+    float* end() { return data_.data() + N; }
+
     // Size
-    static constexpr size_t size() { return 2; }
-    
+    static constexpr size_t size() { return N; }
+
+private:
+    std::array<float, N> data_;
+};
+
+} // namespace detail
+
+/// Observation with `N` components
+template <size_t N>
+class Obs : public detail::FloatArray<N> {
+public:
+    using detail::FloatArray<N>::FloatArray;
+};
+
+/// Action with `N` components
+template <size_t N>
+class Action : public detail::FloatArray<N> {
+public:
+    using detail::FloatArray<N>::FloatArray;
+
     // Vector operations
-    Action2 operator+(const Action2& other) const;
-    Action2 operator-(const Action2& other) const;
-    Action2 operator*(const Action2& other) const;
-    Action2 operator*(float scale) const;
-    
+    Action operator+(const Action& other) const {
+        return map(other, [](float a, float b) { return a + b; });
+    }
+    Action operator-(const Action& other) const {
+        return map(other, [](float a, float b) { return a - b; });
+    }
+    Action operator*(const Action& other) const {
+        return map(other, [](float a, float b) { return a * b; });
+    }
+    Action operator*(float scale) const {
+        return map(*this, [scale](float a, float) { return a * scale; });
+    }
+
     // Clamping
-    Action2 clamp(float min, float max) const;
-    
+    Action clamp(float min, float max) const {
+        return map(*this, [min, max](float a, float) { return std::clamp(a, min, max); });
+    }
+
     // Statistics
-    float max() const;
-    float min() const;
-    bool is_within_bounds(float min, float max) const;
-    
+    float max() const { return *std::max_element(this->begin(), this->end()); }
+    float min() const { return *std::min_element(this->begin(), this->end()); }
+    bool is_within_bounds(float min, float max) const {
+        return std::all_of(this->begin(), this->end(),
+                           [min, max](float a) { return a >= min && a <= max; });
+    }
+
 private:
-    class Impl;
-    std::unique_ptr<Impl> pImpl;
+    template <typename F>
+    Action map(const Action& other, F f) const {
+        Action result;
+        for (size_t i = 0; i < N; ++i) {
+            result[i] = f((*this)[i], other[i]);
+        }
+        return result;
+    }
 };
 
-// Environment class for 4x2 RL environment
-class Env4x2 {
+template <size_t ObsN, size_t ActN>
+class SharedPolicy;
+
+/// RL environment with `ObsN` observations and `ActN` actions: owns one lr_env_t handle
+/// (move-only; calls on it are serialized, so share its policy with `SharedPolicy` to run
+/// several control loops concurrently)
+///
+/// With `LEANRL_DYN_DIMS`, the weights constructor loads any shape up to 16 x 8 through
+/// `lr_init_dims`; otherwise it uses `lr_init`, which loads `LR_OBS_DIM` x `LR_ACTION_DIM` only,
+/// as do `from_signed` and `from_encrypted`. Construction throws `Error` with `LR_EINVSIZE` when
+/// the weights are shaped for other dimensions or the loaded policy's dimensions
+/// (`lr_get_dims`) differ from `ObsN` x `ActN`.
+template <size_t ObsN, size_t ActN>
+class Env {
 public:
-    Env4x2(const std::vector<uint8_t>& weights);
-    Env4x2(const Env4x2& other);
-    Env4x2& operator=(const Env4x2& other);
-    ~Env4x2();
-    
+    /// `lr_init_dims` (`lr_init` without `LEANRL_DYN_DIMS`): loads the weights, verified
+    /// against the embedded key, if any
+    explicit Env(const std::vector<uint8_t>& weights) {
+        lr_env_t* env = nullptr;
+#if defined(LEANRL_DYN_DIMS)
+        detail::check(lr_init_dims(weights.data(), weights.size(), ObsN, ActN, &env),
+                      "lr_init_dims");
+#else
+        detail::check(lr_init(weights.data(), weights.size(), &env), "lr_init");
+#endif
+        adopt(env);
+    }
+
+    /// `lr_init_signed`: loads a signed weights container, verified against `public_key`
+    static Env from_signed(const std::vector<uint8_t>& weights,
+                           const std::array<uint8_t, 32>& public_key) {
+        lr_env_t* env = nullptr;
+        detail::check(lr_init_signed(weights.data(), weights.size(), public_key.data(), &env),
+                      "lr_init_signed");
+        return Env(env);
+    }
+
+#if defined(LEANRL_ENCRYPTION)
+    /// `lr_init_encrypted`: loads an AEAD-encrypted weights container
+    static Env from_encrypted(const std::vector<uint8_t>& weights,
+                              const std::array<uint8_t, 32>& key) {
+        lr_env_t* env = nullptr;
+        detail::check(lr_init_encrypted(weights.data(), weights.size(), key.data(), &env),
+                      "lr_init_encrypted");
+        return Env(env);
+    }
+#endif
+
+    Env(Env&&) noexcept = default;
+    Env& operator=(Env&&) noexcept = default;
+    Env(const Env&) = delete;
+    Env& operator=(const Env&) = delete;
+
+    static constexpr size_t obs_dim() { return ObsN; }
+    static constexpr size_t action_dim() { return ActN; }
+
     // Core RL interface
-    std::unique_ptr<Action2> reset(const Obs4& obs);
-    std::unique_ptr<Action2> step(const Obs4& obs);
-    
+    Action<ActN> reset(const Obs<ObsN>& obs) {
+        Action<ActN> action;
+        detail::check(lr_reset(handle_.get(), obs.data(), action.data()), "lr_reset");
+        return action;
+    }
+    Action<ActN> step(const Obs<ObsN>& obs) {
+        Action<ActN> action;
+        detail::check(lr_step(handle_.get(), obs.data(), action.data()), "lr_step");
+        return action;
+    }
+
+#if LEANRL_HAS_SPAN
+    // Allocation-free calls on caller-owned buffers
+    void reset(std::span<const float, ObsN> obs, std::span<float, ActN> action) {
+        detail::check(lr_reset(handle_.get(), obs.data(), action.data()), "lr_reset");
+    }
+    void step(std::span<const float, ObsN> obs, std::span<float, ActN> action) {
+        detail::check(lr_step(handle_.get(), obs.data(), action.data()), "lr_step");
+    }
+#endif
+
     // State management
-    StateCounts get_state() const;
-    
-    // Safety and verification
-    bool check_invariant(const Obs4& obs, const Action2& action) const;
-    
-    // Weight management
-    bool update_weights(const std::vector<uint8_t>& weights);
-    std::vector<uint8_t> get_weights() const;
-    
-    // Utility methods
-    bool is_valid() const;
-    
+    StateCounts get_state() const {
+        StateCounts state{};
+        detail::check(lr_get_state(handle_.get(), &state.step_count, &state.episode_count),
+                      "lr_get_state");
+        return state;
+    }
+
+    // Safety and verification: false when the invariant is violated
+    bool check_invariant(const Obs<ObsN>& obs, const Action<ActN>& action) const {
+        int32_t code = lr_check_invariant(handle_.get(), obs.data(), action.data());
+        if (code == LR_EINVARIANT) {
+            return false;
+        }
+        detail::check(code, "lr_check_invariant");
+        return true;
+    }
+
+    // Weight management: a failed update leaves the live policy unchanged
+    void update_weights(const std::vector<uint8_t>& weights) {
+        detail::check(lr_update_weights(handle_.get(), weights.data(), weights.size()),
+                      "lr_update_weights");
+    }
+    void rollback_weights() {
+        detail::check(lr_rollback_weights(handle_.get()), "lr_rollback_weights");
+    }
+    uint64_t generation() const {
+        uint64_t generation = 0;
+        detail::check(lr_get_generation(handle_.get(), &generation), "lr_get_generation");
+        return generation;
+    }
+    // Algorithm, dims, shape, parameter count, memory footprint and weights SHA-256
+    lr_info_t info() const {
+        lr_info_t info{};
+        detail::check(lr_get_info(handle_.get(), &info), "lr_get_info");
+        return info;
+    }
+    std::vector<uint8_t> get_weights() const {
+        // Query the size, then copy into a buffer of exactly that size
+        size_t actual_len = 0;
+        detail::check(lr_get_weights(handle_.get(), nullptr, 0, &actual_len), "lr_get_weights");
+        std::vector<uint8_t> weights(actual_len);
+        detail::check(lr_get_weights(handle_.get(), weights.data(), weights.size(), &actual_len),
+                      "lr_get_weights");
+        return weights;
+    }
+
+    // Utility methods: false once moved from
+    bool is_valid() const { return handle_ != nullptr; }
+
+private:
+    explicit Env(lr_env_t* env) { adopt(env); }
+
+    /// Take ownership of `env`, rejecting a policy of other dimensions
+    void adopt(lr_env_t* env) {
+        handle_.reset(env);
+        size_t obs_dim = 0;
+        size_t action_dim = 0;
+        detail::check(lr_get_dims(env, &obs_dim, &action_dim), "lr_get_dims");
+        if (obs_dim != ObsN || action_dim != ActN) {
+            throw Error(LR_EINVSIZE, "Env: policy dimensions differ from the template's");
+        }
+    }
+
+    friend class SharedPolicy<ObsN, ActN>;
+
+    std::unique_ptr<lr_env_t, detail::EnvDeleter> handle_;
+};
+
+/// Per-thread stepping state over a `SharedPolicy`: owns one lr_ctx_t handle (move-only,
+/// one per control loop)
+template <size_t ObsN, size_t ActN>
+class StepContext {
+public:
+    StepContext(StepContext&&) noexcept = default;
+    StepContext& operator=(StepContext&&) noexcept = default;
+    StepContext(const StepContext&) = delete;
+    StepContext& operator=(const StepContext&) = delete;
+
+    // Core RL interface: a swapped policy is picked up at the next call
+    Action<ActN> reset(const Obs<ObsN>& obs) {
+        Action<ActN> action;
+        detail::check(lr_ctx_reset(handle_.get(), obs.data(), action.data()), "lr_ctx_reset");
+        return action;
+    }
+    Action<ActN> step(const Obs<ObsN>& obs) {
+        Action<ActN> action;
+        detail::check(lr_ctx_step(handle_.get(), obs.data(), action.data()), "lr_ctx_step");
+        return action;
+    }
+
+#if LEANRL_HAS_SPAN
+    // Allocation-free calls on caller-owned buffers
+    void reset(std::span<const float, ObsN> obs, std::span<float, ActN> action) {
+        detail::check(lr_ctx_reset(handle_.get(), obs.data(), action.data()), "lr_ctx_reset");
+    }
+    void step(std::span<const float, ObsN> obs, std::span<float, ActN> action) {
+        detail::check(lr_ctx_step(handle_.get(), obs.data(), action.data()), "lr_ctx_step");
+    }
+#endif
+
+    StateCounts get_state() const {
+        StateCounts state{};
+        detail::check(lr_ctx_get_state(handle_.get(), &state.step_count, &state.episode_count),
+                      "lr_ctx_get_state");
+        return state;
+    }
+
+    // Utility methods: false once moved from
+    bool is_valid() const { return handle_ != nullptr; }
+
+private:
+    friend class SharedPolicy<ObsN, ActN>;
+
+    explicit StepContext(lr_ctx_t* ctx) : handle_(ctx) {}
+
+    std::unique_ptr<lr_ctx_t, detail::CtxDeleter> handle_;
+};
+
+/// Policy shared by several control loops in one process: owns one lr_policy_t handle
+/// (move-only). Every method is thread-safe; each loop steps its own `StepContext`, which
+/// keeps the policy alive after this handle is gone.
+template <size_t ObsN, size_t ActN>
+class SharedPolicy {
+public:
+    /// `lr_policy_share`: shares the policy live in `env` (dimensions already checked)
+    explicit SharedPolicy(const Env<ObsN, ActN>& env) {
+        lr_policy_t* policy = nullptr;
+        detail::check(lr_policy_share(env.handle_.get(), &policy), "lr_policy_share");
+        handle_.reset(policy);
+    }
+
+    SharedPolicy(SharedPolicy&&) noexcept = default;
+    SharedPolicy& operator=(SharedPolicy&&) noexcept = default;
+    SharedPolicy(const SharedPolicy&) = delete;
+    SharedPolicy& operator=(const SharedPolicy&) = delete;
+
+    /// `lr_ctx_new`: a step context for one control loop
+    StepContext<ObsN, ActN> context() const {
+        lr_ctx_t* ctx = nullptr;
+        detail::check(lr_ctx_new(handle_.get(), &ctx), "lr_ctx_new");
+        return StepContext<ObsN, ActN>(ctx);
+    }
+
+    // Weight management: a failed update leaves the live policy unchanged
+    void update_weights(const std::vector<uint8_t>& weights) {
+        detail::check(lr_policy_update_weights(handle_.get(), weights.data(), weights.size()),
+                      "lr_policy_update_weights");
+    }
+    void rollback_weights() {
+        detail::check(lr_policy_rollback_weights(handle_.get()), "lr_policy_rollback_weights");
+    }
+    uint64_t generation() const {
+        uint64_t generation = 0;
+        detail::check(lr_policy_get_generation(handle_.get(), &generation),
+                      "lr_policy_get_generation");
+        return generation;
+    }
+
+    // Utility methods: false once moved from
+    bool is_valid() const { return handle_ != nullptr; }
+
 private:
-    class Impl;
-    std::unique_ptr<Impl> pImpl;
+    std::unique_ptr<lr_policy_t, detail::PolicyDeleter> handle_;
 };
 
+// The shape of the C API
+using Obs4 = Obs<4>;
+using Action2 = Action<2>;
+using Env4x2 = Env<4, 2>;
+using SharedPolicy4x2 = SharedPolicy<4, 2>;
+
 // Factory functions
-std::unique_ptr<Env4x2> create_env4x2(const std::vector<uint8_t>& weights);
+inline std::unique_ptr<Env4x2> create_env4x2(const std::vector<uint8_t>& weights) {
+    return std::make_unique<Env4x2>(weights);
+}
 
 // Utility functions
 template<typename T, size_t N>
@@ -145,39 +479,76 @@ std::vector<T> to_vector(const std::array<T, N>& arr) {
     return std::vector<T>(arr.begin(), arr.end());
 }
 
-// Eigen integration (optional)
+// Eigen integration (optional, LEANRL_USE_EIGEN): zero-copy views and copies
 #ifdef LEANRL_USE_EIGEN
-#include <Eigen/Dense>
-
 namespace eigen {
-    // Convert Obs4 to Eigen::Vector4f
-    Eigen::Vector4f to_eigen(const Obs4& obs);
-    
-    // Convert Action2 to Eigen::Vector2f
-    Eigen::Vector2f to_eigen(const Action2& action);
-    
-    // Convert Eigen::Vector4f to Obs4
-    Obs4 from_eigen(const Eigen::Vector4f& vec);
-    
-    // Convert Eigen::Vector2f to Action2
-    Action2 from_eigen(const Eigen::Vector2f& vec);
+    template <size_t N>
+    using Vector = Eigen::Matrix<float, static_cast<int>(N), 1>;
+
+    // Zero-copy views of the observation / action storage
+    template <size_t N>
+    Eigen::Map<Vector<N>> map(detail::FloatArray<N>& values) {
+        return Eigen::Map<Vector<N>>(values.data());
+    }
+    template <size_t N>
+    Eigen::Map<const Vector<N>> map(const detail::FloatArray<N>& values) {
+        return Eigen::Map<const Vector<N>>(values.data());
+    }
+
+    // Copies to and from Eigen vectors
+    template <size_t N>
+    Vector<N> to_eigen(const detail::FloatArray<N>& values) {
+        return map(values);
+    }
+    template <size_t N>
+    Obs<N> obs_from_eigen(const Vector<N>& vec) {
+        Obs<N> obs;
+        map(obs) = vec;
+        return obs;
+    }
+    template <size_t N>
+    Action<N> action_from_eigen(const Vector<N>& vec) {
+        Action<N> action;
+        map(action) = vec;
+        return action;
+    }
+
+    // The 4x2 shape of the C API
+    inline Obs4 from_eigen(const Eigen::Vector4f& vec) { return obs_from_eigen<4>(vec); }
+    inline Action2 from_eigen(const Eigen::Vector2f& vec) { return action_from_eigen<2>(vec); }
 }
 #endif
 
-// TensorRT integration (optional)
+// TensorRT integration (optional, LEANRL_USE_TENSORRT): tensors are dense float32 buffers
 #ifdef LEANRL_USE_TENSORRT
-#include <NvInfer.h>
-
 namespace tensorrt {
-    // Convert Obs4 to TensorRT tensor
-    void to_tensorrt(const Obs4& obs, void* tensor_data, size_t tensor_size);
-    
-    // Convert Action2 from TensorRT tensor
-    Action2 from_tensorrt(const void* tensor_data, size_t tensor_size);
+    // Copy an observation into a TensorRT tensor
+    template <size_t N>
+    void to_tensorrt(const Obs<N>& obs, void* tensor_data, size_t tensor_size) {
+        if (tensor_size < N * sizeof(float)) {
+            throw Error(LR_EINVSIZE, "tensorrt::to_tensorrt");
+        }
+        std::copy(obs.begin(), obs.end(), static_cast<float*>(tensor_data));
+    }
+
+    // Copy an action out of a TensorRT tensor
+    template <size_t N = 2>
+    Action<N> from_tensorrt(const void* tensor_data, size_t tensor_size) {
+        if (tensor_size < N * sizeof(float)) {
+            throw Error(LR_EINVSIZE, "tensorrt::from_tensorrt");
+        }
+        Action<N> action;
+        const float* data = static_cast<const float*>(tensor_data);
+        std::copy(data, data + N, action.begin());
+        return action;
+    }
 }
 #endif
 
 } // namespace leanrl
 
 // Global operators
-leanrl::Action2 operator*(float scale, const leanrl::Action2& action); 
\ No newline at end of file
+template <size_t N>
+leanrl::Action<N> operator*(float scale, const leanrl::Action<N>& action) {
+    return action * scale;
+}
@@ -1,5 +1,8 @@
 //! C/C++ integration helpers: re-exports the core runtime. Headers live under `include/`.
-//! Prefer linking `leanrl_core` as `cdylib`/`staticlib` and calling the `lr_*` C API in `ffi.rs`,
-//! or wrapping those symbols from C++.
+//! Prefer linking `leanrl_core` as `cdylib`/`staticlib` and calling the `lr_*` C API in `ffi.rs`
+//! (`include/leanrl.h`), or from C++ through the header-only façade in `include/leanrl.hpp`.
 
 pub use leanrl_core::*;
+
+/// Directory holding `leanrl.h` and `leanrl.hpp`, for build scripts compiling against them
+pub const INCLUDE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/include");
@@ -1,13 +1,23 @@
-use crate::{sbom, signing, tpm};
+use crate::guard::{GuardSubject, PolicyGuard, GUARD_NAME};
+use crate::manifest::{FileDigest, Manifest, MANIFEST_NAME, MANIFEST_SIG_NAME};
+use crate::{header, policy, reproducible, sbom, signing, tpm};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{self, File};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use zip::CompressionMethod;
 use zip::{write::FileOptions, ZipArchive, ZipWriter};
 
+/// Workspace packages whose staticlib/cdylib outputs ship in the bundle
+const BUNDLE_PACKAGES: &[&str] = &["leanrl_core"];
+
+/// Feature-set token that turns off the package's default features
+const NO_DEFAULT_FEATURES: &str = "no-default-features";
+
 /// Bundle metadata
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct BundleMetadata {
@@ -18,50 +28,194 @@ pub struct BundleMetadata {
     pub sbom_hash: String,
     pub signature_hash: Option<String>,
     pub tpm_attestation: Option<String>,
+    pub builds: Vec<ArtifactBuild>,
+}
+
+/// Targets, feature sets and profile the bundle's libraries are built for
+#[derive(Debug, Clone, clap::Args)]
+pub struct BuildOptions {
+    /// Target triple to build for (repeatable; default: host)
+    #[arg(long = "build-target")]
+    pub targets: Vec<String>,
+
+    /// Comma-separated features for one build (repeatable; `no-default-features` disables the
+    /// defaults; default: the package's default features)
+    #[arg(long = "feature-set")]
+    pub feature_sets: Vec<String>,
+
+    /// Cargo profile to build with
+    #[arg(long, default_value = "release")]
+    pub profile: String,
+}
+
+/// How one shipped library was built
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ArtifactBuild {
+    /// Path inside the bundle's `artifacts/` directory
+    pub path: String,
+    pub sha256: String,
+    pub package: String,
+    pub target: String,
+    pub profile: String,
+    pub features: Vec<String>,
+    pub default_features: bool,
+    pub rustc: String,
+}
+
+/// Features for one build, parsed from a `--feature-set` value
+#[derive(Debug, PartialEq)]
+struct FeatureSet {
+    features: Vec<String>,
+    default_features: bool,
+}
+
+impl FeatureSet {
+    fn parse(spec: &str) -> Self {
+        let mut features = Vec::new();
+        let mut default_features = true;
+        for feature in spec.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            if feature == NO_DEFAULT_FEATURES {
+                default_features = false;
+            } else {
+                features.push(feature.to_string());
+            }
+        }
+        Self {
+            features,
+            default_features,
+        }
+    }
+
+    /// Directory name for this feature set's outputs
+    fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.default_features {
+            parts.push("no-default");
+        } else if self.features.is_empty() {
+            parts.push("default");
+        }
+        parts.extend(self.features.iter().map(String::as_str));
+        parts.join("+")
+    }
+}
+
+/// Toolchain that builds the bundle
+pub(crate) struct Rustc {
+    pub(crate) version: String,
+    pub(crate) host: String,
 }
 
 /// Generate a complete compliance bundle
+///
+/// With a `source_date` (reproducible mode) every timestamp in the bundle is pinned to it, so
+/// two runs on the same commit produce byte-identical ZIPs. A policy guard is checked against
+/// the policies and metadata before anything is packaged, and shipped as `policy-guard.toml`
+/// so `verify` enforces it too. Returns the bundle path.
+#[allow(clippy::too_many_arguments)]
 pub async fn generate_bundle(
     output_dir: &Path,
     proof_hash: Option<&str>,
-    _policy_guard: Option<&Path>,
-    sign: bool,
-    tpm_attest: bool,
-) -> Result<()> {
+    policy_guard: Option<&Path>,
+    policies: &[PathBuf],
+    policy_options: &policy::PolicyOptions,
+    build_options: &BuildOptions,
+    sbom_format: sbom::SbomFormat,
+    sign: Option<&signing::SignOptions>,
+    tpm_attest: Option<&tpm::TpmOptions>,
+    source_date: Option<DateTime<Utc>>,
+) -> Result<PathBuf> {
     println!("Generating LeanEdge-RL compliance bundle...");
 
     // Get git SHA
     let git_sha = get_git_sha()?;
 
-    // Generate SBOM
+    // Build artifacts
+    let (mut artifacts, builds) = build_artifacts(output_dir, build_options).await?;
+
+    // Validate and add policy weights
+    let (policy_files, policy_records) =
+        policy::collect_policies(output_dir, policies, policy_options)?;
+    // Enforce the policy guard before packaging anything
+    if let Some(guard_path) = policy_guard {
+        let guard = PolicyGuard::load(guard_path)?;
+        let weights = policy_files
+            .iter()
+            .map(fs::read)
+            .collect::<io::Result<Vec<_>>>()?;
+        guard.check(&GuardSubject {
+            policies: policy_records
+                .iter()
+                .zip(&weights)
+                .map(|(record, weights)| (record, weights.as_slice()))
+                .collect(),
+            proof_hash,
+            signed_manifest: sign.is_some(),
+        })?;
+        println!("✓ Policy guard {} satisfied", guard_path.display());
+    }
+
+    artifacts.extend(policy_files);
+    let mut shipped = artifacts
+        .iter()
+        .map(|path| Ok((bundle_relative_path(output_dir, path)?, path.clone())))
+        .collect::<Result<Vec<_>>>()?;
+    shipped.sort();
+
+    // Generate SBOM covering the artifacts, resolved from the same build matrix
     let sbom_path = output_dir.join("sbom.json");
-    sbom::generate_sbom(&sbom_path)?;
+    sbom::generate_bundle_sbom(&sbom_path, sbom_format, &shipped, &builds, source_date)?;
     let sbom_hash = calculate_file_hash(&sbom_path)?;
 
-    // Build artifacts
-    let artifacts = build_artifacts(output_dir).await?;
+    // Record how each library was built
+    let build_info_path = output_dir.join("build_info.json");
+    fs::write(&build_info_path, serde_json::to_string_pretty(&builds)?)?;
 
     // Generate bundle filename
     let bundle_name = format!("leanrl_bundle_{}.zip", git_sha);
     let bundle_path = output_dir.join(bundle_name);
 
-    // Create ZIP bundle
-    create_zip_bundle(&bundle_path, &artifacts, &sbom_path).await?;
+    // Create ZIP bundle with its (optionally signed) manifest
+    let created_at = source_date.unwrap_or_else(Utc::now);
+    let mut manifest = Manifest {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at,
+        git_sha: git_sha.clone(),
+        proof_hash: proof_hash.map(|s| s.to_string()),
+        sbom_hash: sbom_hash.clone(),
+        files: BTreeMap::new(),
+        policies: policy_records,
+    };
+    let mut metadata_files = vec![
+        ("sbom.json", sbom_path.as_path()),
+        ("build_info.json", build_info_path.as_path()),
+    ];
+    if let Some(guard_path) = policy_guard {
+        metadata_files.push((GUARD_NAME, guard_path));
+    }
+    create_zip_bundle(
+        &bundle_path,
+        &shipped,
+        &metadata_files,
+        reproducible::zip_timestamp(created_at),
+        &mut manifest,
+        sign,
+    )
+    .await?;
 
     // Calculate bundle hash
     let bundle_hash = calculate_file_hash(&bundle_path)?;
 
     // Sign bundle if requested
-    let signature_hash = if sign {
-        signing::sign_bundle(&bundle_path).await?;
+    let signature_hash = if let Some(sign_options) = sign {
+        signing::sign_bundle(&bundle_path, sign_options).await?;
         Some(calculate_file_hash(&bundle_path.with_extension("zip.sig"))?)
     } else {
         None
     };
 
     // Generate TPM attestation if requested
-    let tpm_attestation = if tpm_attest {
-        Some(tpm::generate_attestation(&bundle_path).await?)
+    let tpm_attestation = if let Some(tpm_options) = tpm_attest {
+        Some(tpm::generate_attestation(&bundle_path, tpm_options).await?)
     } else {
         None
     };
@@ -69,12 +223,13 @@ pub async fn generate_bundle(
     // Create metadata
     let metadata = BundleMetadata {
         version: env!("CARGO_PKG_VERSION").to_string(),
-        created_at: Utc::now(),
+        created_at,
         git_sha,
         proof_hash: proof_hash.map(|s| s.to_string()),
         sbom_hash,
         signature_hash,
         tpm_attestation,
+        builds,
     };
 
     // Write metadata
@@ -86,11 +241,159 @@ pub async fn generate_bundle(
     println!("Bundle hash: {}", bundle_hash);
     println!("Size: {} bytes", fs::metadata(&bundle_path)?.len());
 
-    Ok(())
+    Ok(bundle_path)
+}
+
+/// Rebuild a bundle in reproducible mode and compare it with an existing one
+///
+/// The build matrix comes from the bundle's `build_info.json`, the proof hash and policies
+/// (with their provenance) from its manifest, the policy guard from `policy-guard.toml` and the
+/// SBOM format from `sbom.json`. The manifest signature cannot be re-created
+/// without the key, so a signed bundle reproduces when every other entry matches.
+/// Fails listing the differing entries otherwise.
+pub async fn reproduce_bundle(bundle_path: &Path) -> Result<()> {
+    println!("Reproducing bundle: {}", bundle_path.display());
+
+    let build_info = read_bundle_entry(bundle_path, "build_info.json")?
+        .context("Bundle has no build_info.json; it predates reproducible bundles")?;
+    let builds: Vec<ArtifactBuild> =
+        serde_json::from_slice(&build_info).context("Invalid build_info.json")?;
+    let build_options = build_options_from_records(&builds)?;
+    let sbom_format = sbom::detect_format(
+        &read_bundle_entry(bundle_path, "sbom.json")?.context("Bundle has no sbom.json")?,
+    )?;
+    let original_manifest: Manifest = serde_json::from_slice(
+        &read_bundle_entry(bundle_path, MANIFEST_NAME)?.context("Bundle has no MANIFEST.json")?,
+    )
+    .context("Invalid MANIFEST.json")?;
+
+    let source_date = reproducible::reproducible_timestamp()?;
+    println!("Using SOURCE_DATE_EPOCH={}", source_date.timestamp());
+
+    // Policies are inputs rather than build outputs: take them from the original bundle,
+    // loaded at their recorded dims and with their recorded signing keys
+    let policy_options = policy::PolicyOptions::from_records(&original_manifest.policies)?;
+    let inputs_dir = tempfile::tempdir()?;
+    let mut policies = Vec::new();
+    for record in &original_manifest.policies {
+        let name = Path::new(&record.path)
+            .file_name()
+            .context("Invalid policy path in manifest")?;
+        let path = inputs_dir.path().join(name);
+        let weights = read_bundle_entry(bundle_path, &record.path)?
+            .with_context(|| format!("{} is missing from the bundle", record.path))?;
+        fs::write(&path, weights)?;
+        if let Some(provenance) = &record.provenance {
+            fs::write(
+                policy::provenance_path(&path),
+                serde_json::to_string(provenance)?,
+            )?;
+        }
+        policies.push(path);
+    }
+    let guard_path = match read_bundle_entry(bundle_path, GUARD_NAME)? {
+        Some(guard) => {
+            let path = inputs_dir.path().join(GUARD_NAME);
+            fs::write(&path, guard)?;
+            Some(path)
+        }
+        None => None,
+    };
+
+    let temp_dir = tempfile::tempdir()?;
+    let rebuilt = generate_bundle(
+        temp_dir.path(),
+        original_manifest.proof_hash.as_deref(),
+        guard_path.as_deref(),
+        &policies,
+        &policy_options,
+        &build_options,
+        sbom_format,
+        None,
+        None,
+        Some(source_date),
+    )
+    .await?;
+
+    let expected = calculate_file_hash(bundle_path)?;
+    let actual = calculate_file_hash(&rebuilt)?;
+    if expected == actual {
+        println!("✓ Bundle reproduced: {}", actual);
+        return Ok(());
+    }
+
+    let original_entries = hash_bundle_entries(bundle_path)?;
+    let rebuilt_entries = hash_bundle_entries(&rebuilt)?;
+    let names: BTreeSet<&String> = original_entries
+        .keys()
+        .chain(rebuilt_entries.keys())
+        .filter(|name| *name != MANIFEST_SIG_NAME)
+        .collect();
+    let mut differs = false;
+    for name in names {
+        match (original_entries.get(name), rebuilt_entries.get(name)) {
+            (Some(a), Some(b)) if a == b => continue,
+            (Some(a), Some(b)) => println!("✗ {}: {} != {}", name, a.sha256, b.sha256),
+            (Some(_), None) => println!("✗ {}: missing from the rebuild", name),
+            (None, _) => println!("✗ {}: not in the original bundle", name),
+        }
+        differs = true;
+    }
+    if !differs && original_entries.contains_key(MANIFEST_SIG_NAME) {
+        println!("✓ Bundle contents reproduced (manifest signature not re-created)");
+        return Ok(());
+    }
+    anyhow::bail!(
+        "Bundle is not reproducible: original {}, rebuilt {} (identical entries mean timestamps, \
+         permissions or ordering differ)",
+        expected,
+        actual
+    )
+}
+
+/// Targets, feature sets and profile recorded in a bundle's build records
+fn build_options_from_records(builds: &[ArtifactBuild]) -> Result<BuildOptions> {
+    let mut targets: Vec<String> = Vec::new();
+    let mut feature_sets: Vec<String> = Vec::new();
+    let mut profiles = BTreeSet::new();
+
+    for build in builds {
+        if !targets.contains(&build.target) {
+            targets.push(build.target.clone());
+        }
+        let mut spec = build.features.clone();
+        if !build.default_features {
+            spec.insert(0, NO_DEFAULT_FEATURES.to_string());
+        }
+        let spec = spec.join(",");
+        if !feature_sets.contains(&spec) {
+            feature_sets.push(spec);
+        }
+        profiles.insert(build.profile.clone());
+    }
+
+    if profiles.len() > 1 {
+        anyhow::bail!("Bundle mixes build profiles: {:?}", profiles);
+    }
+    Ok(BuildOptions {
+        targets,
+        feature_sets,
+        profile: profiles
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| "release".to_string()),
+    })
 }
 
 /// Verify bundle integrity
-pub async fn verify_bundle(bundle_path: &Path) -> Result<()> {
+///
+/// The bundled `policy-guard.toml` is enforced if present; `policy_guard` replaces it.
+pub async fn verify_bundle(
+    bundle_path: &Path,
+    policy_guard: Option<&Path>,
+    trust: &signing::VerifyOptions,
+    tpm_trust: &tpm::TpmVerifyOptions,
+) -> Result<()> {
     println!("Verifying bundle integrity: {}", bundle_path.display());
 
     // Check if bundle exists
@@ -98,8 +401,14 @@ pub async fn verify_bundle(bundle_path: &Path) -> Result<()> {
         anyhow::bail!("Bundle file not found: {}", bundle_path.display());
     }
 
-    // Verify ZIP integrity
-    verify_zip_integrity(bundle_path)?;
+    // Verify the manifest and every entry it lists
+    verify_manifest(bundle_path, trust)?;
+    println!("✓ Manifest verification passed");
+
+    // Enforce the policy guard
+    if verify_policy_guard(bundle_path, policy_guard)? {
+        println!("✓ Policy guard verification passed");
+    }
 
     // Extract and verify SBOM
     let temp_dir = tempfile::tempdir()?;
@@ -107,41 +416,109 @@ pub async fn verify_bundle(bundle_path: &Path) -> Result<()> {
 
     let sbom_path = temp_dir.path().join("sbom.json");
     if sbom_path.exists() {
-        sbom::verify_sbom(&sbom_path)?;
+        sbom::verify_sbom(&sbom_path, &hash_bundle_artifacts(bundle_path)?)?;
         println!("✓ SBOM verification passed");
     }
 
-    // Verify signature if present
+    verify_detached(bundle_path, trust, tpm_trust).await?;
+
+    println!("Bundle verification completed successfully");
+    Ok(())
+}
+
+/// Verify the `.zip.sig` signature and `.zip.attest` attestation stored next to the bundle
+async fn verify_detached(
+    bundle_path: &Path,
+    trust: &signing::VerifyOptions,
+    tpm_trust: &tpm::TpmVerifyOptions,
+) -> Result<()> {
+    // Verify signature if present; required once a trust anchor is given
     let sig_path = bundle_path.with_extension("zip.sig");
     if sig_path.exists() {
-        signing::verify_signature(bundle_path, &sig_path).await?;
+        signing::verify_signature(bundle_path, &sig_path, trust).await?;
         println!("✓ Signature verification passed");
+    } else if trust.public_key.is_some() || trust.ca_cert.is_some() {
+        anyhow::bail!(
+            "Bundle is not signed ({} is missing) but a trust anchor was given",
+            sig_path.display()
+        );
     }
 
-    // Verify TPM attestation if present
+    // Verify TPM attestation if present; required once an AK or nonce is given
     let attest_path = bundle_path.with_extension("zip.attest");
     if attest_path.exists() {
-        tpm::verify_attestation(&attest_path).await?;
+        tpm::verify_attestation(bundle_path, &attest_path, tpm_trust).await?;
         println!("✓ TPM attestation verification passed");
+    } else if tpm_trust.tpm_ak_pub.is_some() || tpm_trust.tpm_expected_nonce.is_some() {
+        anyhow::bail!(
+            "Bundle is not attested ({} is missing) but TPM trust options were given",
+            attest_path.display()
+        );
     }
-
-    println!("Bundle verification completed successfully");
     Ok(())
 }
 
 /// Build all artifacts for the bundle
-async fn build_artifacts(output_dir: &Path) -> Result<Vec<PathBuf>> {
+async fn build_artifacts(
+    output_dir: &Path,
+    options: &BuildOptions,
+) -> Result<(Vec<PathBuf>, Vec<ArtifactBuild>)> {
     let mut artifacts = Vec::new();
+    let mut builds = Vec::new();
 
-    // Build core library
-    println!("Building core library...");
-    let core_lib = build_core_library(output_dir).await?;
-    artifacts.push(core_lib);
+    let rustc = rustc_version()?;
+    let targets = if options.targets.is_empty() {
+        vec![rustc.host.clone()]
+    } else {
+        options.targets.clone()
+    };
+    let feature_sets: Vec<FeatureSet> = if options.feature_sets.is_empty() {
+        vec![FeatureSet::parse("")]
+    } else {
+        options
+            .feature_sets
+            .iter()
+            .map(|spec| FeatureSet::parse(spec))
+            .collect()
+    };
 
-    // Build C++ shim
-    println!("Building C++ shim...");
-    let cshim_lib = build_cshim_library(output_dir).await?;
-    artifacts.push(cshim_lib);
+    // Build libraries for every target and feature set
+    for target in &targets {
+        for feature_set in &feature_sets {
+            for package in BUNDLE_PACKAGES {
+                println!(
+                    "Building {} for {} ({})...",
+                    package,
+                    target,
+                    feature_set.label()
+                );
+                let outputs = cargo_build(package, target, &options.profile, feature_set)?;
+
+                let lib_dir = output_dir
+                    .join("lib")
+                    .join(target)
+                    .join(feature_set.label());
+                fs::create_dir_all(&lib_dir)?;
+                for output in outputs {
+                    let lib_path = lib_dir.join(output.file_name().context("Invalid output")?);
+                    fs::copy(&output, &lib_path).with_context(|| {
+                        format!("Failed to copy build output {}", output.display())
+                    })?;
+                    builds.push(ArtifactBuild {
+                        path: bundle_relative_path(output_dir, &lib_path)?,
+                        sha256: calculate_file_hash(&lib_path)?,
+                        package: package.to_string(),
+                        target: target.clone(),
+                        profile: options.profile.clone(),
+                        features: feature_set.features.clone(),
+                        default_features: feature_set.default_features,
+                        rustc: rustc.version.clone(),
+                    });
+                    artifacts.push(lib_path);
+                }
+            }
+        }
+    }
 
     // Copy headers
     println!("Copying headers...");
@@ -153,33 +530,129 @@ async fn build_artifacts(output_dir: &Path) -> Result<Vec<PathBuf>> {
     let docs = copy_documentation(output_dir)?;
     artifacts.extend(docs);
 
-    Ok(artifacts)
+    Ok((artifacts, builds))
 }
 
-/// Build core library
-async fn build_core_library(output_dir: &Path) -> Result<PathBuf> {
-    // This would typically run `cargo build --release` for the core crate
-    // For now, we'll create a placeholder
-    let lib_path = output_dir.join("lib").join("libleanrl_core.a");
-    fs::create_dir_all(lib_path.parent().unwrap())?;
+/// Build one package's libraries with cargo, returning the staticlib/cdylib outputs
+fn cargo_build(
+    package: &str,
+    target: &str,
+    profile: &str,
+    feature_set: &FeatureSet,
+) -> Result<Vec<PathBuf>> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let mut command = Command::new(cargo);
+    command.args([
+        "build",
+        "--package",
+        package,
+        "--lib",
+        "--profile",
+        profile,
+        "--target",
+        target,
+        "--message-format=json-render-diagnostics",
+    ]);
+    if !feature_set.features.is_empty() {
+        command.args(["--features", &feature_set.features.join(",")]);
+    }
+    if !feature_set.default_features {
+        command.arg("--no-default-features");
+    }
 
-    // Create a placeholder library file
-    let mut file = File::create(&lib_path)?;
-    file.write_all(b"# Placeholder for libleanrl_core.a")?;
+    let output = command
+        .stderr(Stdio::inherit())
+        .output()
+        .context("Failed to run cargo build")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "cargo build of {} for {} ({}) failed: {}",
+            package,
+            target,
+            feature_set.label(),
+            output.status
+        );
+    }
+
+    let outputs = parse_library_outputs(&String::from_utf8(output.stdout)?, package)?;
+    if outputs.is_empty() {
+        anyhow::bail!(
+            "cargo build of {} for {} produced no staticlib or cdylib",
+            package,
+            target
+        );
+    }
+    Ok(outputs)
+}
 
-    Ok(lib_path)
+/// Staticlib/cdylib files of `package` in cargo's JSON build messages
+fn parse_library_outputs(messages: &str, package: &str) -> Result<Vec<PathBuf>> {
+    const LIBRARY_EXTENSIONS: &[&str] = &["a", "lib", "so", "dylib", "dll"];
+
+    let mut outputs = Vec::new();
+    for line in messages.lines().filter(|l| l.starts_with('{')) {
+        let message: serde_json::Value =
+            serde_json::from_str(line).context("Malformed cargo build message")?;
+        if message["reason"] != "compiler-artifact"
+            || message["target"]["name"] != package.replace('-', "_")
+        {
+            continue;
+        }
+        let crate_types = message["target"]["crate_types"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        if !crate_types
+            .iter()
+            .any(|t| t == "staticlib" || t == "cdylib")
+        {
+            continue;
+        }
+        for file in message["filenames"].as_array().into_iter().flatten() {
+            let path = PathBuf::from(file.as_str().context("Malformed cargo filename")?);
+            let is_library = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| LIBRARY_EXTENSIONS.contains(&e));
+            if is_library {
+                outputs.push(path);
+            }
+        }
+    }
+    Ok(outputs)
 }
 
-/// Build C++ shim library
-async fn build_cshim_library(output_dir: &Path) -> Result<PathBuf> {
-    let lib_path = output_dir.join("lib").join("libleanrl_cshim.a");
-    fs::create_dir_all(lib_path.parent().unwrap())?;
+/// Version and host triple of the rustc cargo builds with
+pub(crate) fn rustc_version() -> Result<Rustc> {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let output = Command::new(rustc)
+        .arg("-vV")
+        .output()
+        .context("Failed to run rustc")?;
+    if !output.status.success() {
+        anyhow::bail!("rustc -vV failed: {}", output.status);
+    }
 
-    // Create a placeholder library file
-    let mut file = File::create(&lib_path)?;
-    file.write_all(b"# Placeholder for libleanrl_cshim.a")?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let version = stdout.lines().next().unwrap_or_default().to_string();
+    let host = stdout
+        .lines()
+        .find_map(|l| l.strip_prefix("host: "))
+        .context("rustc -vV did not report a host triple")?
+        .to_string();
+    Ok(Rustc { version, host })
+}
 
-    Ok(lib_path)
+/// Path of a file under `output_dir` as it appears under `artifacts/` in the bundle
+fn bundle_relative_path(output_dir: &Path, path: &Path) -> Result<String> {
+    let relative = path
+        .strip_prefix(output_dir)
+        .with_context(|| format!("{} is outside the output directory", path.display()))?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    Ok(parts.join("/"))
 }
 
 /// Copy header files
@@ -194,9 +667,9 @@ fn copy_headers(output_dir: &Path) -> Result<Vec<PathBuf>> {
     fs::copy("cshim/include/leanrl.hpp", &cpp_header)?;
     headers.push(cpp_header);
 
-    // Copy C header
+    // Copy C header, generated from the FFI definitions
     let c_header = headers_dir.join("leanrl.h");
-    create_c_header(&c_header)?;
+    fs::copy(header::HEADER_PATH, &c_header)?;
     headers.push(c_header);
 
     Ok(headers)
@@ -214,127 +687,158 @@ fn copy_documentation(output_dir: &Path) -> Result<Vec<PathBuf>> {
     fs::copy("README.md", &readme)?;
     docs.push(readme);
 
-    // Create API documentation placeholder
-    let api_doc = docs_dir.join("api.md");
-    let mut file = File::create(&api_doc)?;
-    file.write_all(b"# LeanEdge-RL API Documentation\n\n## C API\n\n### lr_init\n### lr_reset\n### lr_step\n### lr_free\n\n## C++ API\n\n### leanrl::Env4x2\n### leanrl::Obs4\n### leanrl::Action2")?;
-    docs.push(api_doc);
-
     Ok(docs)
 }
 
-/// Create C header file
-fn create_c_header(header_path: &Path) -> Result<()> {
-    let mut file = File::create(header_path)?;
-
-    writeln!(file, "// LeanEdge-RL C API Header")?;
-    writeln!(file, "#pragma once")?;
-    file.write_all(b"\n")?;
-    writeln!(file, "#include <stdint.h>")?;
-    writeln!(file, "#include <stddef.h>")?;
-    file.write_all(b"\n")?;
-    writeln!(file, "// Error codes")?;
-    writeln!(file, "#define LR_OK             0")?;
-    writeln!(file, "#define LR_EBADWEIGHTS   -1")?;
-    writeln!(file, "#define LR_EINVSIZE      -2")?;
-    writeln!(file, "#define LR_EINVARIANT    -3")?;
-    writeln!(file, "#define LR_EOUTOFMEM     -4")?;
-    file.write_all(b"\n")?;
-    writeln!(file, "// Opaque environment handle")?;
-    writeln!(file, "typedef struct lr_env lr_env_t;")?;
-    file.write_all(b"\n")?;
-    writeln!(file, "// C API functions")?;
-    writeln!(
-        file,
-        "int lr_init(const uint8_t* weights, size_t len, lr_env_t** out);"
-    )?;
-    writeln!(
-        file,
-        "int lr_reset(lr_env_t* env, const float* obs, float* action);"
-    )?;
-    writeln!(
-        file,
-        "int lr_step(lr_env_t* env, const float* obs, float* action);"
-    )?;
-    writeln!(file, "void lr_free(lr_env_t* env);")?;
-    writeln!(
-        file,
-        "int lr_check_invariant(lr_env_t* env, const float* obs, const float* action);"
-    )?;
-    writeln!(
-        file,
-        "int lr_update_weights(lr_env_t* env, const uint8_t* weights, size_t len);"
-    )?;
-    writeln!(
-        file,
-        "int lr_get_weights(lr_env_t* env, uint8_t* weights, size_t max_len, size_t* actual_len);"
-    )?;
-
-    Ok(())
-}
-
 /// Create ZIP bundle
+///
+/// Artifacts go under `artifacts/` and `metadata` files (SBOM, build records, policy guard) at
+/// the root, under the given names. Fills in `manifest.files` with every entry written, then
+/// adds `MANIFEST.json` (and `MANIFEST.json.sig` when signing) as the last entries.
 async fn create_zip_bundle(
     bundle_path: &Path,
-    artifacts: &[PathBuf],
-    sbom_path: &Path,
+    artifacts: &[(String, PathBuf)],
+    metadata: &[(&str, &Path)],
+    mtime: zip::DateTime,
+    manifest: &mut Manifest,
+    sign: Option<&signing::SignOptions>,
 ) -> Result<()> {
     let file = File::create(bundle_path)?;
     let mut zip = ZipWriter::new(file);
-
-    // Add artifacts
-    for artifact in artifacts {
-        add_file_to_zip(&mut zip, artifact, "artifacts/").await?;
+    // Fixed timestamp and permissions so identical inputs give an identical ZIP
+    let options = FileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(mtime)
+        .unix_permissions(0o644);
+
+    // Add artifacts (in path order)
+    for (name, path) in artifacts {
+        let zip_path = format!("artifacts/{}", name);
+        add_file_to_zip(&mut zip, manifest, path, &zip_path, options).await?;
     }
 
-    // Add SBOM
-    add_file_to_zip(&mut zip, sbom_path, "").await?;
+    // Add SBOM, build records and policy guard
+    for (name, path) in metadata {
+        add_file_to_zip(&mut zip, manifest, path, name, options).await?;
+    }
 
     // Add README
-    let readme_content = b"# LeanEdge-RL Compliance Bundle\n\nThis bundle contains:\n- Core library (libleanrl_core.a)\n- C++ shim library (libleanrl_cshim.a)\n- Headers (leanrl.h, leanrl.hpp)\n- Documentation\n- SBOM (sbom.json)\n- Proof metadata\n\nFor integration instructions, see docs/api.md";
-    zip.start_file(
-        "README.md",
-        FileOptions::default().compression_method(CompressionMethod::Deflated),
-    )?;
-    zip.write_all(readme_content)?;
+    let readme_content = b"# LeanEdge-RL Compliance Bundle\n\nThis bundle contains:\n- Core libraries (artifacts/lib/<target>/<feature-set>/libleanrl_core.a and the shared library)\n- Headers (artifacts/include/leanrl.h, artifacts/include/leanrl.hpp)\n- Documentation\n- SBOM (sbom.json)\n- Build records (build_info.json: rustc, target, profile and features per library)\n- Manifest (MANIFEST.json: SHA-256 and size of every entry, optionally signed)\n- Policy guard (policy-guard.toml, when generated with one)\n- Proof metadata\n\nFor the API reference, see the documented declarations in artifacts/include/leanrl.h; for integration instructions, see artifacts/docs/README.md";
+    add_bytes_to_zip(&mut zip, manifest, readme_content, "README.md", options)?;
+
+    // Add manifest, signed as a unit
+    let manifest_json = serde_json::to_vec_pretty(manifest)?;
+    zip.start_file(MANIFEST_NAME, options)?;
+    zip.write_all(&manifest_json)?;
+    if let Some(sign_options) = sign {
+        let signature = signing::sign_payload(&manifest_json, sign_options)?;
+        zip.start_file(MANIFEST_SIG_NAME, options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&signature)?)?;
+    }
 
     zip.finish()?;
     Ok(())
 }
 
 /// Add file to ZIP
-async fn add_file_to_zip(zip: &mut ZipWriter<File>, file_path: &Path, prefix: &str) -> Result<()> {
-    let file_name = file_path.file_name().unwrap().to_str().unwrap();
-    let zip_path = format!("{}{}", prefix, file_name);
+async fn add_file_to_zip(
+    zip: &mut ZipWriter<File>,
+    manifest: &mut Manifest,
+    file_path: &Path,
+    zip_path: &str,
+    options: FileOptions,
+) -> Result<()> {
+    let content =
+        fs::read(file_path).with_context(|| format!("Failed to read {}", file_path.display()))?;
+    add_bytes_to_zip(zip, manifest, &content, zip_path, options)
+}
 
-    zip.start_file(
-        zip_path,
-        FileOptions::default().compression_method(CompressionMethod::Deflated),
-    )?;
+/// Add an entry to the ZIP and record its digest in the manifest
+fn add_bytes_to_zip(
+    zip: &mut ZipWriter<File>,
+    manifest: &mut Manifest,
+    content: &[u8],
+    zip_path: &str,
+    options: FileOptions,
+) -> Result<()> {
+    zip.start_file(zip_path, options)?;
+    zip.write_all(content)?;
+    manifest
+        .files
+        .insert(zip_path.to_string(), FileDigest::of(content));
+    Ok(())
+}
 
-    let mut file = File::open(file_path)?;
-    io::copy(&mut file, zip)?;
+/// Verify the in-bundle manifest: its signature, then the digest of every entry
+///
+/// A manifest signature is required whenever a trust anchor is given.
+fn verify_manifest(bundle_path: &Path, trust: &signing::VerifyOptions) -> Result<()> {
+    let content =
+        read_bundle_entry(bundle_path, MANIFEST_NAME)?.context("Bundle has no MANIFEST.json")?;
+    let manifest: Manifest = serde_json::from_slice(&content).context("Invalid MANIFEST.json")?;
+
+    match read_bundle_entry(bundle_path, MANIFEST_SIG_NAME)? {
+        Some(signature) => {
+            let signature: signing::SignatureBundle =
+                serde_json::from_slice(&signature).context("Invalid manifest signature format")?;
+            signing::verify_payload(&content, &signature, trust)
+                .context("Manifest signature verification failed")?;
+            println!("✓ Manifest signature verification passed");
+        }
+        None if trust.public_key.is_some() || trust.ca_cert.is_some() => {
+            anyhow::bail!("Manifest is not signed but a trust anchor was given")
+        }
+        None => {}
+    }
+
+    manifest.check(&hash_bundle_entries(bundle_path)?)?;
 
+    // Policies must still load with the recorded shape
+    for record in &manifest.policies {
+        let weights = read_bundle_entry(bundle_path, &record.path)?
+            .with_context(|| format!("{} is missing from the bundle", record.path))?;
+        policy::check_record(record, &weights)?;
+    }
     Ok(())
 }
 
-/// Verify ZIP integrity
-fn verify_zip_integrity(bundle_path: &Path) -> Result<()> {
-    let file = File::open(bundle_path)?;
-    let mut archive = ZipArchive::new(file)?;
-
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        let mut buffer = Vec::new();
-        io::copy(&mut file, &mut buffer)?;
+/// Check the bundle against a policy guard file, else the bundled one
+///
+/// Returns whether a guard was checked. Call after `verify_manifest`, which ties the bundled
+/// guard, policies and metadata to the manifest.
+fn verify_policy_guard(bundle_path: &Path, policy_guard: Option<&Path>) -> Result<bool> {
+    let guard = match policy_guard {
+        Some(path) => PolicyGuard::load(path)?,
+        None => match read_bundle_entry(bundle_path, GUARD_NAME)? {
+            Some(content) => PolicyGuard::parse(&String::from_utf8(content)?)
+                .context("Invalid policy guard in the bundle")?,
+            None => return Ok(false),
+        },
+    };
 
-        // Verify file integrity by checking it can be read
-        if buffer.is_empty() && !file.name().ends_with('/') {
-            anyhow::bail!("Empty file in bundle: {}", file.name());
-        }
+    let manifest: Manifest = serde_json::from_slice(
+        &read_bundle_entry(bundle_path, MANIFEST_NAME)?.context("Bundle has no MANIFEST.json")?,
+    )
+    .context("Invalid MANIFEST.json")?;
+    let mut weights = Vec::new();
+    for record in &manifest.policies {
+        weights.push(
+            read_bundle_entry(bundle_path, &record.path)?
+                .with_context(|| format!("{} is missing from the bundle", record.path))?,
+        );
     }
 
-    Ok(())
+    guard.check(&GuardSubject {
+        policies: manifest
+            .policies
+            .iter()
+            .zip(&weights)
+            .map(|(record, weights)| (record, weights.as_slice()))
+            .collect(),
+        proof_hash: manifest.proof_hash.as_deref(),
+        signed_manifest: read_bundle_entry(bundle_path, MANIFEST_SIG_NAME)?.is_some(),
+    })?;
+    Ok(true)
 }
 
 /// Extract SBOM from bundle
@@ -355,6 +859,53 @@ async fn extract_sbom_from_bundle(bundle_path: &Path, output_dir: &Path) -> Resu
     Ok(())
 }
 
+/// Contents of one bundle entry, if present
+fn read_bundle_entry(bundle_path: &Path, name: &str) -> Result<Option<Vec<u8>>> {
+    let file = File::open(bundle_path)?;
+    let mut archive = ZipArchive::new(file)?;
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut content = Vec::new();
+    io::copy(&mut entry, &mut content)?;
+    Ok(Some(content))
+}
+
+/// SHA-256 and size of each entry in the bundle, keyed by its path
+fn hash_bundle_entries(bundle_path: &Path) -> Result<BTreeMap<String, FileDigest>> {
+    let file = File::open(bundle_path)?;
+    let mut archive = ZipArchive::new(file)?;
+    let mut digests = BTreeMap::new();
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let name = file.name().to_string();
+        let mut hasher = Sha256::new();
+        let size = io::copy(&mut file, &mut hasher)?;
+        let digest = FileDigest {
+            sha256: hex::encode(hasher.finalize()),
+            size,
+        };
+        if digests.insert(name.clone(), digest).is_some() {
+            anyhow::bail!("Duplicate bundle entry: {}", name);
+        }
+    }
+
+    Ok(digests)
+}
+
+/// SHA-256 of each file under `artifacts/` in the bundle, keyed by its path below `artifacts/`
+fn hash_bundle_artifacts(bundle_path: &Path) -> Result<BTreeMap<String, String>> {
+    Ok(hash_bundle_entries(bundle_path)?
+        .into_iter()
+        .filter_map(|(name, digest)| {
+            Some((name.strip_prefix("artifacts/")?.to_string(), digest.sha256))
+        })
+        .collect())
+}
+
 /// Get git SHA
 fn get_git_sha() -> Result<String> {
     let output = std::process::Command::new("git")
@@ -376,3 +927,326 @@ fn calculate_file_hash(file_path: &Path) -> Result<String> {
     let hash = hasher.finalize();
     Ok(hex::encode(hash))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_set_parsing() {
+        let default = FeatureSet::parse("");
+        assert!(default.default_features && default.features.is_empty());
+        assert_eq!(default.label(), "default");
+
+        let neon = FeatureSet::parse("simd_neon, alloc");
+        assert!(neon.default_features);
+        assert_eq!(neon.features, ["simd_neon", "alloc"]);
+        assert_eq!(neon.label(), "simd_neon+alloc");
+
+        let bare = FeatureSet::parse("no-default-features,alloc");
+        assert!(!bare.default_features);
+        assert_eq!(bare.label(), "no-default+alloc");
+    }
+
+    #[test]
+    fn test_parse_library_outputs() {
+        let messages = [
+            r#"{"reason":"compiler-artifact","target":{"name":"thiserror","crate_types":["lib"]},"filenames":["/t/libthiserror.rlib"]}"#,
+            r#"{"reason":"compiler-artifact","target":{"name":"leanrl_core","crate_types":["staticlib","cdylib","rlib"]},"filenames":["/t/libleanrl_core.a","/t/libleanrl_core.so","/t/libleanrl_core.rlib"]}"#,
+            r#"{"reason":"build-finished","success":true}"#,
+        ]
+        .join("\n");
+
+        let outputs = parse_library_outputs(&messages, "leanrl_core").unwrap();
+        assert_eq!(
+            outputs,
+            [
+                PathBuf::from("/t/libleanrl_core.a"),
+                PathBuf::from("/t/libleanrl_core.so")
+            ]
+        );
+        assert!(parse_library_outputs(&messages, "leanrl_cshim")
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Bundle inputs in `dir`: artifacts, SBOM and build records
+    fn bundle_inputs(dir: &Path) -> (Vec<(String, PathBuf)>, PathBuf, PathBuf) {
+        let lib = dir.join("libleanrl_core.a");
+        let header = dir.join("leanrl.h");
+        let sbom = dir.join("sbom.json");
+        let build_info = dir.join("build_info.json");
+        for (path, content) in [
+            (&lib, "lib"),
+            (&header, "h"),
+            (&sbom, "{}"),
+            (&build_info, "[]"),
+        ] {
+            fs::write(path, content).unwrap();
+        }
+        let artifacts = vec![
+            ("include/leanrl.h".to_string(), header),
+            ("lib/host/default/libleanrl_core.a".to_string(), lib),
+        ];
+        (artifacts, sbom, build_info)
+    }
+
+    fn empty_manifest(sbom: &Path, created_at: DateTime<Utc>) -> Manifest {
+        Manifest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at,
+            git_sha: "abc1234".to_string(),
+            proof_hash: None,
+            sbom_hash: calculate_file_hash(sbom).unwrap(),
+            files: BTreeMap::new(),
+            policies: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_zip_bundle_is_deterministic() {
+        let dir = tempfile::tempdir().unwrap();
+        let (artifacts, sbom, build_info) = bundle_inputs(dir.path());
+        let created_at = Utc::now();
+        let mtime = reproducible::zip_timestamp(created_at);
+
+        let mut bundles = Vec::new();
+        for name in ["first.zip", "second.zip"] {
+            let path = dir.path().join(name);
+            let mut manifest = empty_manifest(&sbom, created_at);
+            create_zip_bundle(
+                &path,
+                &artifacts,
+                &[("sbom.json", &sbom), ("build_info.json", &build_info)],
+                mtime,
+                &mut manifest,
+                None,
+            )
+            .await
+            .unwrap();
+            bundles.push(fs::read(&path).unwrap());
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(bundles[0], bundles[1]);
+
+        let first = dir.path().join("first.zip");
+        let entries = hash_bundle_entries(&first).unwrap();
+        assert!(entries.contains_key("artifacts/include/leanrl.h"));
+        assert!(entries.contains_key(MANIFEST_NAME));
+        assert_eq!(
+            read_bundle_entry(&first, "build_info.json").unwrap(),
+            Some(b"[]".to_vec())
+        );
+        assert_eq!(read_bundle_entry(&first, "missing").unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_signed_manifest_detects_tampered_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let (artifacts, sbom, build_info) = bundle_inputs(dir.path());
+        let (key_path, pub_path) = signing::generate_keypair(&dir.path().join("keys")).unwrap();
+        let sign = signing::SignOptions {
+            signing_key: Some(key_path),
+            ..Default::default()
+        };
+        let trust = signing::VerifyOptions {
+            public_key: Some(pub_path),
+            ..Default::default()
+        };
+
+        let bundle = dir.path().join("bundle.zip");
+        let mut manifest = empty_manifest(&sbom, Utc::now());
+        create_zip_bundle(
+            &bundle,
+            &artifacts,
+            &[("sbom.json", &sbom), ("build_info.json", &build_info)],
+            reproducible::zip_timestamp(Utc::now()),
+            &mut manifest,
+            Some(&sign),
+        )
+        .await
+        .unwrap();
+        verify_manifest(&bundle, &trust).unwrap();
+
+        // Same manifest and signature, one entry swapped
+        let tampered = dir.path().join("tampered.zip");
+        let mut archive = ZipArchive::new(File::open(&bundle).unwrap()).unwrap();
+        let mut zip = ZipWriter::new(File::create(&tampered).unwrap());
+        for i in 0..archive.len() {
+            let entry = archive.by_index(i).unwrap();
+            if entry.name() == "artifacts/include/leanrl.h" {
+                zip.start_file(entry.name(), FileOptions::default())
+                    .unwrap();
+                zip.write_all(b"#define LR_OK 1").unwrap();
+            } else {
+                zip.raw_copy_file(entry).unwrap();
+            }
+        }
+        zip.finish().unwrap();
+
+        let error = format!("{:#}", verify_manifest(&tampered, &trust).unwrap_err());
+        assert!(error.contains("artifacts/include/leanrl.h: tampered"));
+        assert!(!error.contains("libleanrl_core.a"));
+
+        // A manifest signature is required once a trust anchor is given
+        let mut unsigned_manifest = empty_manifest(&sbom, Utc::now());
+        let unsigned = dir.path().join("unsigned.zip");
+        create_zip_bundle(
+            &unsigned,
+            &artifacts,
+            &[("sbom.json", &sbom), ("build_info.json", &build_info)],
+            reproducible::zip_timestamp(Utc::now()),
+            &mut unsigned_manifest,
+            None,
+        )
+        .await
+        .unwrap();
+        verify_manifest(&unsigned, &signing::VerifyOptions::default()).unwrap();
+        assert!(verify_manifest(&unsigned, &trust).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_requires_signature_with_trust_anchor() {
+        let dir = tempfile::tempdir().unwrap();
+        let (artifacts, sbom, build_info) = bundle_inputs(dir.path());
+        let (key_path, pub_path) = signing::generate_keypair(&dir.path().join("keys")).unwrap();
+        let sign = signing::SignOptions {
+            signing_key: Some(key_path),
+            ..Default::default()
+        };
+        let trust = signing::VerifyOptions {
+            public_key: Some(pub_path),
+            ..Default::default()
+        };
+
+        let bundle = dir.path().join("bundle.zip");
+        let mut manifest = empty_manifest(&sbom, Utc::now());
+        create_zip_bundle(
+            &bundle,
+            &artifacts,
+            &[("sbom.json", &sbom), ("build_info.json", &build_info)],
+            reproducible::zip_timestamp(Utc::now()),
+            &mut manifest,
+            Some(&sign),
+        )
+        .await
+        .unwrap();
+        let tpm_trust = tpm::TpmVerifyOptions::default();
+        verify_manifest(&bundle, &trust).unwrap();
+
+        // No detached signature next to the bundle
+        let error = verify_detached(&bundle, &trust, &tpm_trust)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("bundle.zip.sig is missing"));
+        verify_detached(&bundle, &signing::VerifyOptions::default(), &tpm_trust)
+            .await
+            .unwrap();
+
+        signing::sign_bundle(&bundle, &sign).await.unwrap();
+        verify_detached(&bundle, &trust, &tpm_trust).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_requires_attestation_with_tpm_trust() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("bundle.zip");
+        fs::write(&bundle, "zip").unwrap();
+        let trust = signing::VerifyOptions::default();
+
+        for tpm_trust in [
+            tpm::TpmVerifyOptions {
+                tpm_ak_pub: Some(dir.path().join("tpm-ak.pub")),
+                ..Default::default()
+            },
+            tpm::TpmVerifyOptions {
+                tpm_expected_nonce: Some("00".repeat(32)),
+                ..Default::default()
+            },
+        ] {
+            let error = verify_detached(&bundle, &trust, &tpm_trust)
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains("bundle.zip.attest is missing"));
+        }
+        verify_detached(&bundle, &trust, &tpm::TpmVerifyOptions::default())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_enforces_bundled_policy_guard() {
+        let dir = tempfile::tempdir().unwrap();
+        let (artifacts, sbom, build_info) = bundle_inputs(dir.path());
+        let guard = dir.path().join(GUARD_NAME);
+        fs::write(&guard, "required_shields = [\"signed_manifest\"]").unwrap();
+
+        let bundle = dir.path().join("bundle.zip");
+        let mut manifest = empty_manifest(&sbom, Utc::now());
+        create_zip_bundle(
+            &bundle,
+            &artifacts,
+            &[
+                ("sbom.json", &sbom),
+                ("build_info.json", &build_info),
+                (GUARD_NAME, &guard),
+            ],
+            reproducible::zip_timestamp(Utc::now()),
+            &mut manifest,
+            None,
+        )
+        .await
+        .unwrap();
+        verify_manifest(&bundle, &signing::VerifyOptions::default()).unwrap();
+
+        let error = verify_policy_guard(&bundle, None).unwrap_err();
+        assert!(error.to_string().contains("signed_manifest"));
+
+        // An explicit guard replaces the bundled one
+        let lenient = dir.path().join("lenient.toml");
+        fs::write(&lenient, "max_parameters = 10").unwrap();
+        assert!(verify_policy_guard(&bundle, Some(&lenient)).unwrap());
+    }
+
+    #[test]
+    fn test_build_options_from_records() {
+        let record = |target: &str, features: &[&str], default_features| ArtifactBuild {
+            path: String::new(),
+            sha256: String::new(),
+            package: "leanrl_core".to_string(),
+            target: target.to_string(),
+            profile: "release".to_string(),
+            features: features.iter().map(|f| f.to_string()).collect(),
+            default_features,
+            rustc: String::new(),
+        };
+        let builds = [
+            record("x86_64-unknown-linux-gnu", &[], true),
+            record("x86_64-unknown-linux-gnu", &[], true),
+            record("x86_64-unknown-linux-gnu", &["alloc"], false),
+            record("aarch64-unknown-linux-gnu", &["simd_neon"], true),
+        ];
+
+        let options = build_options_from_records(&builds).unwrap();
+        assert_eq!(
+            options.targets,
+            ["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gnu"]
+        );
+        assert_eq!(
+            options.feature_sets,
+            ["", "no-default-features,alloc", "simd_neon"]
+        );
+        assert_eq!(options.profile, "release");
+    }
+
+    #[test]
+    fn test_bundle_relative_path() {
+        let out = Path::new("/out");
+        assert_eq!(
+            bundle_relative_path(out, &out.join("lib/x86_64-unknown-linux-gnu/default/a.a"))
+                .unwrap(),
+            "lib/x86_64-unknown-linux-gnu/default/a.a"
+        );
+        assert!(bundle_relative_path(out, Path::new("/elsewhere/a.a")).is_err());
+    }
+}
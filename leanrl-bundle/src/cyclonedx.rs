@@ -0,0 +1,364 @@
+use crate::sbom::{is_hex, ComponentKind, Sbom, SbomFile};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// bom-ref of the bundle itself (`metadata.component`)
+const BUNDLE_REF: &str = "leanrl-bundle";
+
+/// CycloneDX 1.5 JSON schema, vendored so verification works offline
+pub const SCHEMA: &str = include_str!("../schemas/bom-1.5.schema.json");
+
+/// CycloneDX 1.5 BOM
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycloneDxDocument {
+    pub bom_format: String,
+    pub spec_version: String,
+    pub serial_number: String,
+    pub version: u32,
+    pub metadata: Metadata,
+    #[serde(default)]
+    pub components: Vec<Component>,
+    #[serde(default)]
+    pub dependencies: Vec<Dependency>,
+}
+
+/// BOM metadata
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Metadata {
+    pub timestamp: String,
+    pub tools: Tools,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component: Option<Component>,
+}
+
+/// Tools that produced the BOM
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Tools {
+    pub components: Vec<Component>,
+}
+
+/// Component (library, application or file)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Component {
+    #[serde(rename = "type")]
+    pub component_type: String,
+    #[serde(rename = "bom-ref", default, skip_serializing_if = "Option::is_none")]
+    pub bom_ref: Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supplier: Option<OrganizationalEntity>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hashes: Vec<Hash>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<LicenseChoice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purl: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_references: Vec<ExternalReference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<Property>,
+}
+
+/// Supplier
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationalEntity {
+    pub name: String,
+}
+
+/// Component hash
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Hash {
+    pub alg: String,
+    pub content: String,
+}
+
+/// License: either an SPDX expression or a license object
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LicenseChoice {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<serde_json::Value>,
+}
+
+/// External reference (e.g. distribution URL)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExternalReference {
+    #[serde(rename = "type")]
+    pub reference_type: String,
+    pub url: String,
+}
+
+/// Name/value property
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Property {
+    pub name: String,
+    pub value: String,
+}
+
+/// Dependency graph entry
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dependency {
+    #[serde(rename = "ref")]
+    pub dependency_ref: String,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+/// Render the model as a CycloneDX 1.5 BOM
+///
+/// CycloneDX 1.5 has no build-dependency or generated-from relationship, so both become
+/// `dependsOn` entries; the described components hang off the bundle's `metadata.component`.
+pub fn from_sbom(sbom: &Sbom) -> CycloneDxDocument {
+    let components = sbom
+        .components
+        .iter()
+        .map(|(component_ref, c)| Component {
+            component_type: match c.kind {
+                ComponentKind::Crate | ComponentKind::Library => "library",
+                ComponentKind::Application => "application",
+                ComponentKind::File => "file",
+            }
+            .to_string(),
+            bom_ref: Some(component_ref.clone()),
+            name: c.name.clone(),
+            version: Some(c.version.clone()),
+            description: (!c.description.is_empty()).then(|| c.description.clone()),
+            supplier: c.supplier.as_deref().map(|supplier| OrganizationalEntity {
+                name: supplier
+                    .split_once(": ")
+                    .map_or(supplier, |(_, name)| name)
+                    .to_string(),
+            }),
+            hashes: c
+                .sha256
+                .iter()
+                .map(|value| Hash {
+                    alg: "SHA-256".to_string(),
+                    content: value.clone(),
+                })
+                .collect(),
+            licenses: c
+                .license
+                .iter()
+                .map(|expression| LicenseChoice {
+                    expression: Some(expression.clone()),
+                    license: None,
+                })
+                .collect(),
+            copyright: c.copyright.clone(),
+            purl: c.purl.clone(),
+            external_references: c
+                .download_location
+                .iter()
+                .map(|url| ExternalReference {
+                    reference_type: "distribution".to_string(),
+                    url: url.clone(),
+                })
+                .collect(),
+            properties: if c.features.is_empty() {
+                Vec::new()
+            } else {
+                vec![Property {
+                    name: "leanrl:cargo:features".to_string(),
+                    value: c.features.iter().cloned().collect::<Vec<_>>().join(","),
+                }]
+            },
+        })
+        .collect();
+
+    let mut depends_on: BTreeMap<&str, BTreeSet<&str>> = sbom
+        .components
+        .keys()
+        .map(|component_ref| (component_ref.as_str(), BTreeSet::new()))
+        .collect();
+    for (from, to, _) in &sbom.relations {
+        depends_on.entry(from).or_default().insert(to);
+    }
+
+    let mut dependencies = vec![Dependency {
+        dependency_ref: BUNDLE_REF.to_string(),
+        depends_on: sbom.describes.iter().cloned().collect(),
+    }];
+    dependencies.extend(depends_on.into_iter().map(|(from, to)| Dependency {
+        dependency_ref: from.to_string(),
+        depends_on: to.into_iter().map(String::from).collect(),
+    }));
+
+    CycloneDxDocument {
+        bom_format: "CycloneDX".to_string(),
+        spec_version: "1.5".to_string(),
+        serial_number: format!("urn:uuid:{}", sbom.serial),
+        version: 1,
+        metadata: Metadata {
+            timestamp: sbom.created.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            tools: Tools {
+                components: vec![tool_component("leanrl-bundle")],
+            },
+            component: Some(Component {
+                bom_ref: Some(BUNDLE_REF.to_string()),
+                description: Some("LeanEdge-RL compliance bundle".to_string()),
+                ..tool_component("LeanEdge-RL")
+            }),
+        },
+        components,
+        dependencies,
+    }
+}
+
+fn tool_component(name: &str) -> Component {
+    Component {
+        component_type: "application".to_string(),
+        bom_ref: None,
+        name: name.to_string(),
+        version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        description: None,
+        supplier: Some(OrganizationalEntity {
+            name: "LeanEdge-RL Team".to_string(),
+        }),
+        hashes: Vec::new(),
+        licenses: Vec::new(),
+        copyright: None,
+        purl: None,
+        external_references: Vec::new(),
+        properties: Vec::new(),
+    }
+}
+
+/// Validate a CycloneDX 1.5 BOM beyond its JSON schema: version, serial number, hash lengths,
+/// unique bom-refs and dependency targets
+///
+/// Returns the bundle outputs (components without a package URL) it lists.
+pub fn validate(doc: &CycloneDxDocument) -> Result<Vec<SbomFile>> {
+    const TYPES: &[&str] = &[
+        "application",
+        "framework",
+        "library",
+        "container",
+        "platform",
+        "operating-system",
+        "device",
+        "device-driver",
+        "firmware",
+        "file",
+        "machine-learning-model",
+        "data",
+    ];
+
+    if doc.bom_format != "CycloneDX" {
+        anyhow::bail!("bomFormat must be CycloneDX");
+    }
+    if doc.spec_version != "1.5" {
+        anyhow::bail!("Unsupported CycloneDX specVersion: {}", doc.spec_version);
+    }
+    if !is_urn_uuid(&doc.serial_number) {
+        anyhow::bail!("serialNumber must be a urn:uuid: {}", doc.serial_number);
+    }
+    if doc.version < 1 {
+        anyhow::bail!("version must be at least 1");
+    }
+    chrono::DateTime::parse_from_rfc3339(&doc.metadata.timestamp)
+        .map_err(|_| anyhow::anyhow!("metadata.timestamp must be RFC 3339"))?;
+
+    let mut refs = BTreeSet::new();
+    let mut files = Vec::new();
+    let all = doc
+        .metadata
+        .component
+        .iter()
+        .chain(&doc.metadata.tools.components)
+        .chain(&doc.components);
+    for component in all {
+        let label = component.bom_ref.as_deref().unwrap_or(&component.name);
+        if !TYPES.contains(&component.component_type.as_str()) {
+            anyhow::bail!(
+                "{}: invalid component type {}",
+                label,
+                component.component_type
+            );
+        }
+        if component.name.is_empty() {
+            anyhow::bail!("Component name is required");
+        }
+        if let Some(bom_ref) = &component.bom_ref {
+            if !refs.insert(bom_ref.as_str()) {
+                anyhow::bail!("Duplicate bom-ref: {}", bom_ref);
+            }
+        }
+        for hash in &component.hashes {
+            let len = match hash.alg.as_str() {
+                "MD5" => 32,
+                "SHA-1" => 40,
+                "SHA-256" | "SHA3-256" | "BLAKE2b-256" | "BLAKE3" => 64,
+                "SHA-384" | "SHA3-384" | "BLAKE2b-384" => 96,
+                "SHA-512" | "SHA3-512" | "BLAKE2b-512" => 128,
+                other => anyhow::bail!("{}: unsupported hash algorithm {}", label, other),
+            };
+            if !is_hex(&hash.content, len) {
+                anyhow::bail!("{}: malformed {} hash", label, hash.alg);
+            }
+        }
+        for license in &component.licenses {
+            if license.expression.is_some() == license.license.is_some() {
+                anyhow::bail!("{}: license must be an expression or a license", label);
+            }
+        }
+        if let Some(purl) = &component.purl {
+            if !purl.starts_with("pkg:") {
+                anyhow::bail!("{}: invalid purl {}", label, purl);
+            }
+        }
+        if component
+            .external_references
+            .iter()
+            .any(|r| r.reference_type.is_empty() || r.url.is_empty())
+        {
+            anyhow::bail!("{}: external references need a type and url", label);
+        }
+    }
+
+    for component in &doc.components {
+        if component.purl.is_none() {
+            files.push(SbomFile {
+                name: component.name.clone(),
+                sha256: component
+                    .hashes
+                    .iter()
+                    .find(|h| h.alg == "SHA-256")
+                    .map(|h| h.content.clone()),
+            });
+        }
+    }
+
+    for dependency in &doc.dependencies {
+        for reference in std::iter::once(&dependency.dependency_ref).chain(&dependency.depends_on) {
+            if !refs.contains(reference.as_str()) {
+                anyhow::bail!("Dependency references unknown bom-ref {}", reference);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// `urn:uuid:` followed by a canonical lowercase UUID
+fn is_urn_uuid(value: &str) -> bool {
+    let Some(uuid) = value.strip_prefix("urn:uuid:") else {
+        return false;
+    };
+    let groups: Vec<&str> = uuid.split('-').collect();
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, len)| {
+            is_hex(group, len) && !group.chars().any(|c| c.is_ascii_uppercase())
+        })
+}
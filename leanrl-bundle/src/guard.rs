@@ -0,0 +1,475 @@
+use crate::policy::{self, PolicyRecord};
+use anyhow::{Context, Result};
+use leanrl_core::dims::DynEnv;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Name of the policy guard inside the bundle
+pub const GUARD_NAME: &str = "policy-guard.toml";
+
+/// Pseudo-random observations probed in addition to the corners and centre of the box
+const RANDOM_PROBES: usize = 256;
+
+/// Policy guard: constraints every bundled policy and the bundle metadata must satisfy
+///
+/// ```toml
+/// allowed_algorithms = ["LinearFA", "TinyNN"]
+/// max_obs_dim = 4
+/// max_action_dim = 2
+/// max_parameters = 4096
+/// required_proof_hash = "…"
+/// required_shields = ["check_invariant", "finite_actions", "signed_manifest"]
+///
+/// [action_bounds]
+/// min = -1.0
+/// max = 1.0
+///
+/// [observation_bounds]   # box probed for action bounds (default -1..1)
+/// min = -1.0
+/// max = 1.0
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyGuard {
+    #[serde(default)]
+    pub allowed_algorithms: Option<Vec<String>>,
+    #[serde(default)]
+    pub max_obs_dim: Option<usize>,
+    #[serde(default)]
+    pub max_action_dim: Option<usize>,
+    #[serde(default)]
+    pub max_parameters: Option<usize>,
+    #[serde(default)]
+    pub action_bounds: Option<Bounds>,
+    #[serde(default)]
+    pub observation_bounds: Option<Bounds>,
+    #[serde(default)]
+    pub required_shields: Vec<Shield>,
+    #[serde(default)]
+    pub required_proof_hash: Option<String>,
+}
+
+/// Closed interval applied to every component
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bounds {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Runtime safety shield a bundle can be required to satisfy
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Shield {
+    /// Probed actions pass `Env::check_invariant` (`lr_check_invariant`)
+    CheckInvariant,
+    /// Probed actions contain no NaN or infinity
+    FiniteActions,
+    /// The bundle manifest is signed
+    SignedManifest,
+}
+
+/// What a guard is checked against
+pub struct GuardSubject<'a> {
+    /// Policies with their weights
+    pub policies: Vec<(&'a PolicyRecord, &'a [u8])>,
+    pub proof_hash: Option<&'a str>,
+    pub signed_manifest: bool,
+}
+
+impl PolicyGuard {
+    /// Read and validate a guard file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read policy guard {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid policy guard {}", path.display()))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let guard: Self = toml::from_str(content)?;
+        for bounds in guard.action_bounds.iter().chain(&guard.observation_bounds) {
+            if !(bounds.min.is_finite() && bounds.max.is_finite() && bounds.min <= bounds.max) {
+                anyhow::bail!("Bounds must be finite with min <= max");
+            }
+        }
+        Ok(guard)
+    }
+
+    /// Check the bundle against the guard, reporting every breach
+    pub fn check(&self, subject: &GuardSubject) -> Result<()> {
+        let mut breaches = Vec::new();
+
+        if let Some(required) = &self.required_proof_hash {
+            match subject.proof_hash {
+                Some(actual) if actual.eq_ignore_ascii_case(required) => {}
+                Some(actual) => breaches.push(format!(
+                    "proof hash {} does not match the required {}",
+                    actual, required
+                )),
+                None => breaches.push(format!("proof hash {} is required", required)),
+            }
+        }
+        if self.required_shields.contains(&Shield::SignedManifest) && !subject.signed_manifest {
+            breaches.push("shield signed_manifest: the manifest is not signed".to_string());
+        }
+
+        for (record, weights) in &subject.policies {
+            for breach in self.check_policy(record, weights)? {
+                breaches.push(format!("{}: {}", record.path, breach));
+            }
+        }
+
+        if !breaches.is_empty() {
+            anyhow::bail!("Policy guard breached:\n  {}", breaches.join("\n  "));
+        }
+        Ok(())
+    }
+
+    fn check_policy(&self, record: &PolicyRecord, weights: &[u8]) -> Result<Vec<String>> {
+        let mut breaches = Vec::new();
+
+        match &record.shape {
+            Some(shape) => {
+                if let Some(allowed) = &self.allowed_algorithms {
+                    if !allowed.contains(&shape.algorithm) {
+                        breaches.push(format!("algorithm {} is not allowed", shape.algorithm));
+                    }
+                }
+                if let Some(max) = self.max_obs_dim {
+                    if shape.obs_dim > max {
+                        breaches.push(format!("obs dim {} exceeds {}", shape.obs_dim, max));
+                    }
+                }
+                if let Some(max) = self.max_action_dim {
+                    if shape.action_dim > max {
+                        breaches.push(format!("action dim {} exceeds {}", shape.action_dim, max));
+                    }
+                }
+                if let Some(max) = self.max_parameters {
+                    if shape.parameter_count > max {
+                        breaches.push(format!(
+                            "{} parameters exceed {}",
+                            shape.parameter_count, max
+                        ));
+                    }
+                }
+            }
+            // Nothing is known about encrypted weights, so no constraint on them can pass
+            None => {
+                if self.allowed_algorithms.is_some()
+                    || self.max_obs_dim.is_some()
+                    || self.max_action_dim.is_some()
+                    || self.max_parameters.is_some()
+                {
+                    breaches.push(
+                        "weights are encrypted; algorithm, dims and parameters cannot be checked"
+                            .to_string(),
+                    );
+                }
+            }
+        }
+
+        let needs_probe = self.action_bounds.is_some()
+            || self.required_shields.contains(&Shield::CheckInvariant)
+            || self.required_shields.contains(&Shield::FiniteActions);
+        if needs_probe {
+            // Weights that do not load cannot be probed; that is a breach, not a crash
+            let mut env = match policy::load_policy(record, weights) {
+                Ok(env) => env,
+                Err(e) => {
+                    breaches.push(format!("weights do not load: {:#}", e));
+                    return Ok(breaches);
+                }
+            };
+            let (obs_dim, action_dim) = env.dims();
+            let observations = probe_observations(
+                self.observation_bounds.unwrap_or(Bounds {
+                    min: -1.0,
+                    max: 1.0,
+                }),
+                obs_dim,
+            );
+            let mut action = vec![0.0; action_dim];
+            for obs in &observations {
+                env.step(obs, &mut action)?;
+                if let Some(breach) = self.probe_breach(env.as_ref(), obs, &action) {
+                    breaches.push(format!("{} at observation {:?}", breach, obs));
+                    break;
+                }
+            }
+        }
+
+        Ok(breaches)
+    }
+
+    /// Action bounds and action shields for one probed observation
+    fn probe_breach(&self, env: &dyn DynEnv, obs: &[f32], values: &[f32]) -> Option<String> {
+        if self.required_shields.contains(&Shield::FiniteActions)
+            && values.iter().any(|a| !a.is_finite())
+        {
+            return Some(format!("shield finite_actions: action {:?}", values));
+        }
+        if self.required_shields.contains(&Shield::CheckInvariant) {
+            if let Err(e) = env.check_invariant(obs, values) {
+                return Some(format!("shield check_invariant: {}", e));
+            }
+        }
+        if let Some(bounds) = self.action_bounds {
+            if values
+                .iter()
+                .any(|a| !(bounds.min..=bounds.max).contains(a))
+            {
+                return Some(format!(
+                    "action {:?} outside [{}, {}]",
+                    values, bounds.min, bounds.max
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Observations probed for action bounds: every corner of the box, its centre, and a fixed
+/// pseudo-random sample inside it
+///
+/// Probing samples the policy; it does not prove the bound for every observation.
+fn probe_observations(bounds: Bounds, obs_dim: usize) -> Vec<Vec<f32>> {
+    let span = bounds.max - bounds.min;
+    let mut observations = Vec::new();
+
+    for corner in 0..1usize << obs_dim {
+        let mut values = vec![0.0; obs_dim];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = if corner & (1 << i) == 0 {
+                bounds.min
+            } else {
+                bounds.max
+            };
+        }
+        observations.push(values);
+    }
+    observations.push(vec![bounds.min + span / 2.0; obs_dim]);
+
+    // xorshift32 with a fixed seed, so generate and verify probe the same points
+    let mut state = 0x9e37_79b9u32;
+    for _ in 0..RANDOM_PROBES {
+        let mut values = vec![0.0; obs_dim];
+        for value in values.iter_mut() {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            *value = bounds.min + span * (state as f32 / u32::MAX as f32);
+        }
+        observations.push(values);
+    }
+
+    observations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{inspect_weights, PolicyOptions, ACTION_DIM, OBS_DIM};
+    use leanrl_core::{encrypted, signed, weights};
+
+    /// LinearFA weights whose action is `w * sum(obs) + bias` in every component
+    fn linear_fa_weights(w: f32, bias: f32) -> Vec<u8> {
+        weights::linear_fa_weights(
+            OBS_DIM,
+            ACTION_DIM,
+            &[w; OBS_DIM * ACTION_DIM],
+            &[bias; ACTION_DIM],
+            0.01,
+        )
+        .unwrap()
+    }
+
+    fn check(guard: &PolicyGuard, weights: &[u8], proof_hash: Option<&str>) -> Result<()> {
+        let record = inspect_weights(
+            "artifacts/policies/p.bin",
+            weights,
+            &PolicyOptions::default(),
+        )
+        .unwrap();
+        guard.check(&GuardSubject {
+            policies: vec![(&record, weights)],
+            proof_hash,
+            signed_manifest: false,
+        })
+    }
+
+    #[test]
+    fn test_guard_parsing() {
+        let guard = PolicyGuard::parse(
+            r#"
+            allowed_algorithms = ["LinearFA"]
+            max_parameters = 100
+            required_shields = ["check_invariant", "finite_actions"]
+
+            [action_bounds]
+            min = -1.0
+            max = 1.0
+            "#,
+        )
+        .unwrap();
+        assert_eq!(guard.max_parameters, Some(100));
+        assert_eq!(guard.required_shields.len(), 2);
+
+        assert!(PolicyGuard::parse("required_shields = [\"magic\"]").is_err());
+        assert!(PolicyGuard::parse("max_dims = 3").is_err());
+        assert!(PolicyGuard::parse("[action_bounds]\nmin = 1.0\nmax = -1.0").is_err());
+    }
+
+    #[test]
+    fn test_guard_enforces_policy_constraints() {
+        let safe = linear_fa_weights(0.1, 0.0);
+        let guard = PolicyGuard::parse(
+            r#"
+            allowed_algorithms = ["LinearFA"]
+            max_action_dim = 2
+            required_shields = ["check_invariant"]
+            [action_bounds]
+            min = -0.5
+            max = 0.5
+            "#,
+        )
+        .unwrap();
+        check(&guard, &safe, None).unwrap();
+
+        // tanh(0.2 * 4) ≈ 0.66 at the corners: outside the guard's bounds
+        let error = check(&guard, &linear_fa_weights(0.2, 0.0), None).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("artifacts/policies/p.bin: action"));
+
+        let tiny_nn_only = PolicyGuard::parse(r#"allowed_algorithms = ["TinyNN"]"#).unwrap();
+        assert!(check(&tiny_nn_only, &safe, None).is_err());
+
+        let small = PolicyGuard::parse("max_parameters = 4").unwrap();
+        assert!(check(&small, &safe, None).is_err());
+    }
+
+    #[test]
+    fn test_guard_enforces_invariant_shield_and_metadata() {
+        // NaN weights load fine but produce NaN actions
+        let unsafe_weights = linear_fa_weights(0.0, f32::NAN);
+        let shielded = PolicyGuard::parse(r#"required_shields = ["check_invariant"]"#).unwrap();
+        let error = check(&shielded, &unsafe_weights, None).unwrap_err();
+        assert!(error.to_string().contains("shield check_invariant"));
+        let finite = PolicyGuard::parse(r#"required_shields = ["finite_actions"]"#).unwrap();
+        let error = check(&finite, &unsafe_weights, None).unwrap_err();
+        assert!(error.to_string().contains("shield finite_actions"));
+
+        let proof = PolicyGuard::parse(
+            "required_proof_hash = \"abcd\"\nrequired_shields = [\"signed_manifest\"]",
+        )
+        .unwrap();
+        let error = check(&proof, &unsafe_weights, Some("ABCD")).unwrap_err();
+        assert!(!error.to_string().contains("proof hash"));
+        assert!(error.to_string().contains("signed_manifest"));
+        let error = check(&proof, &unsafe_weights, None).unwrap_err();
+        assert!(error.to_string().contains("proof hash abcd is required"));
+    }
+
+    #[test]
+    fn test_guard_checks_policy_dims_and_envelopes() {
+        let guard = PolicyGuard::parse(
+            "max_obs_dim = 4\nmax_action_dim = 2\nrequired_shields = [\"finite_actions\"]",
+        )
+        .unwrap();
+
+        let wide = weights::linear_fa_weights(8, 3, &[0.5; 8 * 3], &[0.5; 3], 0.01).unwrap();
+        let options = PolicyOptions {
+            obs_dim: 8,
+            action_dim: 3,
+            ..Default::default()
+        };
+        let record = inspect_weights("artifacts/policies/wide.bin", &wide, &options).unwrap();
+        let error = guard
+            .check(&GuardSubject {
+                policies: vec![(&record, &wide)],
+                proof_hash: None,
+                signed_manifest: false,
+            })
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("obs dim 8 exceeds 4"));
+        assert!(error.contains("action dim 3 exceeds 2"));
+
+        // Signed weights are probed through their recorded key
+        let secret = [3u8; 32];
+        let container = signed::sign(&linear_fa_weights(0.0, f32::NAN), &secret).unwrap();
+        let options = PolicyOptions {
+            public_keys: vec![signed::public_key(&secret)],
+            ..Default::default()
+        };
+        let record = inspect_weights("artifacts/policies/s.bin", &container, &options).unwrap();
+        let error = guard
+            .check(&GuardSubject {
+                policies: vec![(&record, &container)],
+                proof_hash: None,
+                signed_manifest: false,
+            })
+            .unwrap_err();
+        assert!(error.to_string().contains("shield finite_actions"));
+
+        // Encrypted weights cannot satisfy policy-level constraints
+        let container = encrypted::encrypt(
+            &linear_fa_weights(0.1, 0.0),
+            &[1u8; encrypted::KEY_LEN],
+            encrypted::Cipher::ChaCha20Poly1305,
+            &[2u8; encrypted::NONCE_LEN],
+        )
+        .unwrap();
+        let record = inspect_weights(
+            "artifacts/policies/e.bin",
+            &container,
+            &PolicyOptions::default(),
+        )
+        .unwrap();
+        let error = guard
+            .check(&GuardSubject {
+                policies: vec![(&record, &container)],
+                proof_hash: None,
+                signed_manifest: false,
+            })
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("weights are encrypted"));
+        assert!(error.contains("weights do not load"));
+        PolicyGuard::default()
+            .check(&GuardSubject {
+                policies: vec![(&record, &container)],
+                proof_hash: None,
+                signed_manifest: false,
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_guard_reports_weights_that_do_not_load() {
+        let record = inspect_weights(
+            "artifacts/policies/p.bin",
+            &linear_fa_weights(0.1, 0.0),
+            &PolicyOptions::default(),
+        )
+        .unwrap();
+
+        // Zero-state TabularQ header swapped in behind the manifest record
+        let mut zero_states = vec![0u8];
+        zero_states.extend_from_slice(&[0u8; 4]);
+        zero_states.extend_from_slice(&2u32.to_le_bytes());
+        zero_states.extend_from_slice(&[0u8; 8]);
+        let guard = PolicyGuard::parse(r#"required_shields = ["finite_actions"]"#).unwrap();
+        let error = guard
+            .check(&GuardSubject {
+                policies: vec![(&record, &zero_states)],
+                proof_hash: None,
+                signed_manifest: false,
+            })
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("artifacts/policies/p.bin: weights do not load"));
+    }
+}
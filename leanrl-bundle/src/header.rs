@@ -0,0 +1,224 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::process::Command;
+
+/// Checked-in C header, relative to the workspace root
+pub const HEADER_PATH: &str = "cshim/include/leanrl.h";
+
+/// Sources of the C API, relative to the workspace root: the `lr_*` functions and the `LR_*`
+/// error codes
+const FFI_SOURCES: &[&str] = &["core/src/ffi.rs", "core/src/error.rs"];
+
+/// Generate `leanrl.h` from the `#[no_mangle]` functions and error codes in `leanrl_core`
+///
+/// Items behind `#[cfg(feature = "encryption")]` are wrapped in `#if defined(LEANRL_ENCRYPTION)`,
+/// and those behind `#[cfg(feature = "dyn-dims")]` in `#if defined(LEANRL_DYN_DIMS)`.
+pub fn generate_c_header(workspace_root: &Path) -> Result<String> {
+    let mut config = cbindgen::Config {
+        language: cbindgen::Language::C,
+        header: Some(
+            "// LeanEdge-RL C API Header\n//\n// Generated from core/src/ffi.rs by \
+             `leanrl-bundle header`; do not edit.\n// Define LEANRL_ENCRYPTION when linking a \
+             library built with the `encryption` feature,\n// and LEANRL_DYN_DIMS when it was \
+             built with `dyn-dims`."
+                .to_string(),
+        ),
+        pragma_once: true,
+        cpp_compat: true,
+        style: cbindgen::Style::Type,
+        no_includes: true,
+        sys_includes: vec!["stdint.h".to_string(), "stddef.h".to_string()],
+        documentation_style: cbindgen::DocumentationStyle::C99,
+        usize_is_size_t: true,
+        ..Default::default()
+    };
+    for name in ["lr_env", "lr_policy", "lr_ctx", "lr_error", "lr_info"] {
+        config
+            .export
+            .rename
+            .insert(name.to_string(), format!("{}_t", name));
+    }
+    config.defines.insert(
+        "feature = encryption".to_string(),
+        "LEANRL_ENCRYPTION".to_string(),
+    );
+    config.defines.insert(
+        "feature = dyn-dims".to_string(),
+        "LEANRL_DYN_DIMS".to_string(),
+    );
+
+    let mut builder = cbindgen::Builder::new().with_config(config);
+    for source in FFI_SOURCES {
+        builder = builder.with_src(workspace_root.join(source));
+    }
+    let bindings = builder
+        .generate()
+        .context("Failed to generate the C header")?;
+    let mut header = Vec::new();
+    bindings.write(&mut header);
+    Ok(String::from_utf8(header)?)
+}
+
+/// `lr_*` functions declared in a C header
+pub fn declared_functions(header: &str) -> BTreeSet<String> {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    header
+        .split(|c: char| !is_ident(c) && c != '(')
+        .flat_map(|token| token.split_inclusive('('))
+        .filter_map(|token| token.strip_suffix('('))
+        .filter(|name| name.starts_with("lr_"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// `lr_*` functions a compiled `leanrl_core` cdylib (`.so`) or staticlib (`.a`) defines, read
+/// with `nm`
+pub fn exported_functions(library: &Path) -> Result<BTreeSet<String>> {
+    let table = if library.extension().is_some_and(|ext| ext == "a") {
+        "--extern-only"
+    } else {
+        "--dynamic"
+    };
+    let output = Command::new("nm")
+        .args(["--defined-only", table])
+        .arg(library)
+        .output()
+        .context("Failed to run nm")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "nm failed on {}: {}",
+            library.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(2))
+        .filter(|name| name.starts_with("lr_"))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Fail unless `library` exports exactly the `lr_*` functions `header` declares
+///
+/// Build the library with every C API feature (`encryption`, `dyn-dims`): the header declares
+/// the feature-gated functions too.
+pub fn check_exported_functions(header: &str, library: &Path) -> Result<()> {
+    let declared = declared_functions(header);
+    let exported = exported_functions(library)?;
+    if declared != exported {
+        let list = |names: Vec<&String>| {
+            names
+                .into_iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        anyhow::bail!(
+            "{} does not export what the header declares; declared only: [{}], exported only: [{}]",
+            library.display(),
+            list(declared.difference(&exported).collect()),
+            list(exported.difference(&declared).collect())
+        );
+    }
+    Ok(())
+}
+
+/// Write the generated header, or with `check` fail if the checked-in one differs or, for
+/// each of `libraries`, its exported `lr_*` functions differ from the declared ones
+pub fn write_c_header(
+    workspace_root: &Path,
+    output: &Path,
+    check: bool,
+    libraries: &[&Path],
+) -> Result<()> {
+    let header = generate_c_header(workspace_root)?;
+    if check {
+        let current = std::fs::read_to_string(output)
+            .with_context(|| format!("Failed to read {}", output.display()))?;
+        if current != header {
+            anyhow::bail!(
+                "{} is out of date with core/src/ffi.rs; run `leanrl-bundle header`",
+                output.display()
+            );
+        }
+        println!("✓ {} is up to date", output.display());
+        for library in libraries {
+            check_exported_functions(&header, library)?;
+            println!("✓ {} exports the declared functions", library.display());
+        }
+    } else {
+        std::fs::write(output, header)?;
+        println!("C header generated: {}", output.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace_root() -> &'static Path {
+        Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/.."))
+    }
+
+    #[test]
+    fn test_checked_in_header_matches_ffi() {
+        let root = workspace_root();
+        let header = generate_c_header(root).unwrap();
+        let checked_in = std::fs::read_to_string(root.join(HEADER_PATH)).unwrap();
+        assert!(
+            header == checked_in,
+            "{} is stale; run `cargo run -p leanrl-bundle -- header`",
+            HEADER_PATH
+        );
+
+        // The declarations the hand-written header got wrong
+        assert!(header.contains("LR_EINTERNAL -5"));
+        assert!(header.contains("int32_t lr_get_state(const lr_env_t *env"));
+        assert!(header.contains("int32_t lr_check_invariant(const lr_env_t *env"));
+        assert!(!header.contains("KEY_LEN"));
+        assert!(header.contains("#if defined(LEANRL_ENCRYPTION)"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_built_libraries_export_the_declared_functions() {
+        let root = workspace_root();
+        // A target directory of its own: the one running this test is locked by cargo
+        let target_dir = root.join("target/header-symbols");
+        let status = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".into()))
+            .args([
+                "build",
+                "--quiet",
+                "-p",
+                "leanrl_core",
+                "--lib",
+                "--features",
+                "encryption,dyn-dims",
+                "--target-dir",
+            ])
+            .arg(&target_dir)
+            .current_dir(root)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let header = std::fs::read_to_string(root.join(HEADER_PATH)).unwrap();
+        let declared = declared_functions(&header);
+        assert!(declared.contains("lr_init_encrypted") && declared.contains("lr_init_dims"));
+        for library in ["libleanrl_core.so", "libleanrl_core.a"] {
+            let library = target_dir.join("debug").join(library);
+            check_exported_functions(&header, &library).unwrap();
+        }
+
+        // A function the library does not define is reported
+        let extra = format!("{}\nint32_t lr_not_exported(void);\n", header);
+        let error = check_exported_functions(&extra, &target_dir.join("debug/libleanrl_core.so"))
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("declared only: [lr_not_exported], exported only: []"));
+    }
+}
@@ -1,11 +1,18 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 mod bundle;
+mod cyclonedx;
+mod guard;
+mod header;
+mod manifest;
+mod policy;
+mod reproducible;
 mod sbom;
 mod signing;
 mod tpm;
+mod weights;
 
 #[derive(Parser)]
 #[command(name = "leanrl-bundle")]
@@ -18,11 +25,17 @@ struct Cli {
     #[arg(short, long, default_value = ".")]
     output_dir: PathBuf,
 
-    #[arg(long)]
+    #[arg(long, global = true)]
     sign: bool,
 
-    #[arg(long)]
+    #[arg(long, global = true)]
     tpm_attest: bool,
+
+    #[command(flatten)]
+    signing: signing::SignOptions,
+
+    #[command(flatten)]
+    tpm: tpm::TpmOptions,
 }
 
 #[derive(Subcommand)]
@@ -32,27 +45,110 @@ enum Commands {
         #[arg(short, long)]
         proof_hash: Option<String>,
 
-        #[arg(short, long)]
+        /// Policy guard (TOML) the policies and bundle metadata must satisfy; shipped in the
+        /// bundle and enforced again by `verify`
+        #[arg(long)]
         policy_guard: Option<PathBuf>,
+
+        /// Policy weights to ship (repeatable); a `<file>.provenance.json` sidecar records
+        /// dataset hash, trainer version and training run
+        #[arg(long = "policy")]
+        policies: Vec<PathBuf>,
+
+        #[command(flatten)]
+        policy: policy::PolicyOptions,
+
+        /// Pin timestamps to SOURCE_DATE_EPOCH (default: HEAD commit time) for a
+        /// byte-identical bundle; implied when SOURCE_DATE_EPOCH is set
+        #[arg(long)]
+        reproducible: bool,
+
+        #[command(flatten)]
+        build: bundle::BuildOptions,
+
+        /// SBOM format to write; its dependency graph follows `--build-target`/`--feature-set`
+        #[arg(long, value_enum, default_value = "spdx")]
+        sbom_format: sbom::SbomFormat,
     },
 
     /// Generate SBOM only
     Sbom {
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        #[command(flatten)]
+        options: sbom::SbomOptions,
+    },
+
+    /// Rebuild a bundle reproducibly and compare digests with an existing one
+    Reproduce {
+        #[arg(short, long)]
+        bundle: PathBuf,
     },
 
     /// Verify bundle integrity
     Verify {
         #[arg(short, long)]
         bundle: PathBuf,
+
+        /// Policy guard to enforce in place of the one shipped in the bundle
+        #[arg(long)]
+        policy_guard: Option<PathBuf>,
+
+        #[command(flatten)]
+        trust: signing::VerifyOptions,
+
+        #[command(flatten)]
+        tpm_trust: tpm::TpmVerifyOptions,
+    },
+
+    /// Generate the C header from the FFI definitions in core/src/ffi.rs
+    Header {
+        #[arg(short, long, default_value = header::HEADER_PATH)]
+        output: PathBuf,
+
+        /// Fail if the header at `output` is out of date instead of writing it
+        #[arg(long)]
+        check: bool,
+
+        /// With `--check`, also fail unless this compiled `leanrl_core` cdylib or staticlib
+        /// exports exactly the declared `lr_*` functions (repeatable)
+        #[arg(long, requires = "check")]
+        library: Vec<PathBuf>,
     },
 
-    /// Sign bundle with Sigstore
+    /// Sign bundle with a cosign-compatible signature
     Sign {
         #[arg(short, long)]
         bundle: PathBuf,
     },
+
+    /// Generate an ECDSA P-256 signing keypair
+    Keygen {
+        /// Also generate a local Fulcio stand-in CA for keyless signing
+        #[arg(long)]
+        ca: bool,
+
+        /// Also generate an attestation key for the software TPM
+        #[arg(long)]
+        tpm_ak: bool,
+    },
+
+    /// Encrypt a weights blob with an AEAD cipher
+    Encrypt {
+        #[arg(short, long)]
+        input: PathBuf,
+
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// File containing the 256-bit key as 64 hex characters
+        #[arg(short, long)]
+        key_file: PathBuf,
+
+        #[arg(short, long, value_enum, default_value = "chacha20-poly1305")]
+        cipher: weights::CipherArg,
+    },
 }
 
 #[tokio::main]
@@ -63,30 +159,101 @@ async fn main() -> Result<()> {
         Commands::Generate {
             proof_hash,
             policy_guard,
+            policies,
+            policy,
+            reproducible,
+            build,
+            sbom_format,
         } => {
+            let source_date = if reproducible {
+                Some(reproducible::reproducible_timestamp()?)
+            } else {
+                reproducible::source_date_epoch()?
+            };
             bundle::generate_bundle(
                 &cli.output_dir,
                 proof_hash.as_deref(),
                 policy_guard.as_deref(),
-                cli.sign,
-                cli.tpm_attest,
+                &policies,
+                &policy,
+                &build,
+                sbom_format,
+                cli.sign.then_some(&cli.signing),
+                cli.tpm_attest.then_some(&cli.tpm),
+                source_date,
             )
             .await?;
         }
 
-        Commands::Sbom { output } => {
+        Commands::Reproduce { bundle } => {
+            bundle::reproduce_bundle(&bundle).await?;
+        }
+
+        Commands::Sbom { output, options } => {
             let output_path = output.unwrap_or_else(|| PathBuf::from("sbom.json"));
-            sbom::generate_sbom(&output_path)?;
+            sbom::generate_sbom(&output_path, &options, reproducible::source_date_epoch()?)?;
+        }
+
+        Commands::Verify {
+            bundle,
+            policy_guard,
+            trust,
+            tpm_trust,
+        } => {
+            bundle::verify_bundle(&bundle, policy_guard.as_deref(), &trust, &tpm_trust).await?;
         }
 
-        Commands::Verify { bundle } => {
-            bundle::verify_bundle(&bundle).await?;
+        Commands::Header {
+            output,
+            check,
+            library,
+        } => {
+            let libraries: Vec<&Path> = library.iter().map(PathBuf::as_path).collect();
+            header::write_c_header(Path::new("."), &output, check, &libraries)?;
         }
 
         Commands::Sign { bundle } => {
-            signing::sign_bundle(&bundle).await?;
+            signing::sign_bundle(&bundle, &cli.signing).await?;
+        }
+
+        Commands::Keygen { ca, tpm_ak } => {
+            signing::generate_keypair(&cli.output_dir)?;
+            if ca {
+                let fulcio = signing::LocalFulcio::generate("leanrl-local-fulcio")?;
+                fulcio.write(
+                    &cli.output_dir.join("fulcio-ca.key"),
+                    &cli.output_dir.join("fulcio-ca.pem"),
+                )?;
+                println!(
+                    "Fulcio stand-in CA written: {}",
+                    cli.output_dir.join("fulcio-ca.pem").display()
+                );
+            }
+            if tpm_ak {
+                tpm::generate_ak(&cli.output_dir)?;
+            }
+        }
+
+        Commands::Encrypt {
+            input,
+            output,
+            key_file,
+            cipher,
+        } => {
+            weights::encrypt_weights(&input, &output, &key_file, cipher)?;
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,142 @@
+use crate::policy::PolicyRecord;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Name of the manifest entry inside the bundle
+pub const MANIFEST_NAME: &str = "MANIFEST.json";
+
+/// Name of the manifest signature entry inside the bundle
+pub const MANIFEST_SIG_NAME: &str = "MANIFEST.json.sig";
+
+/// In-bundle manifest: bundle metadata plus the digest of every other entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: String,
+    pub created_at: DateTime<Utc>,
+    pub git_sha: String,
+    pub proof_hash: Option<String>,
+    pub sbom_hash: String,
+    /// Entries keyed by their path in the ZIP
+    pub files: BTreeMap<String, FileDigest>,
+    /// Policy weights in the bundle, with their shape and training provenance
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policies: Vec<PolicyRecord>,
+}
+
+/// SHA-256 and size of one bundle entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDigest {
+    pub sha256: String,
+    pub size: u64,
+}
+
+impl FileDigest {
+    pub fn of(content: &[u8]) -> Self {
+        Self {
+            sha256: hex::encode(Sha256::digest(content)),
+            size: content.len() as u64,
+        }
+    }
+}
+
+impl Manifest {
+    /// Check the bundle entries (other than the manifest and its signature) against the manifest
+    ///
+    /// Every tampered, missing or unlisted entry is reported by name.
+    pub fn check(&self, entries: &BTreeMap<String, FileDigest>) -> Result<()> {
+        let mut problems = Vec::new();
+
+        for (path, expected) in &self.files {
+            match entries.get(path) {
+                None => problems.push(format!("{}: missing from the bundle", path)),
+                Some(actual) if actual.sha256 != expected.sha256 => problems.push(format!(
+                    "{}: tampered (sha256 {}, manifest lists {})",
+                    path, actual.sha256, expected.sha256
+                )),
+                Some(actual) if actual.size != expected.size => problems.push(format!(
+                    "{}: tampered ({} bytes, manifest lists {})",
+                    path, actual.size, expected.size
+                )),
+                Some(_) => {}
+            }
+        }
+        for path in entries.keys() {
+            let is_manifest = path == MANIFEST_NAME || path == MANIFEST_SIG_NAME;
+            if !is_manifest && !self.files.contains_key(path) {
+                problems.push(format!("{}: not listed in the manifest", path));
+            }
+        }
+
+        match self.files.get("sbom.json") {
+            Some(sbom) if sbom.sha256 == self.sbom_hash => {}
+            Some(sbom) => problems.push(format!(
+                "sbom.json: manifest sbom_hash {} does not match its entry {}",
+                self.sbom_hash, sbom.sha256
+            )),
+            None => problems.push("sbom.json: not listed in the manifest".to_string()),
+        }
+
+        for policy in &self.policies {
+            match self.files.get(&policy.path) {
+                Some(file) if file.sha256 == policy.sha256 => {}
+                _ => problems.push(format!(
+                    "{}: policy record does not match its manifest entry",
+                    policy.path
+                )),
+            }
+        }
+
+        if !problems.is_empty() {
+            anyhow::bail!("Manifest verification failed:\n  {}", problems.join("\n  "));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(files: &BTreeMap<String, FileDigest>) -> Manifest {
+        Manifest {
+            version: "0.1.0".to_string(),
+            created_at: Utc::now(),
+            git_sha: "abc1234".to_string(),
+            proof_hash: None,
+            sbom_hash: files["sbom.json"].sha256.clone(),
+            files: files.clone(),
+            policies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_reports_each_problem_by_name() {
+        let mut entries = BTreeMap::new();
+        entries.insert("sbom.json".to_string(), FileDigest::of(b"{}"));
+        entries.insert(
+            "artifacts/include/leanrl.h".to_string(),
+            FileDigest::of(b"h"),
+        );
+        entries.insert("artifacts/docs/api.md".to_string(), FileDigest::of(b"doc"));
+        let manifest = manifest(&entries);
+
+        let mut bundle = entries.clone();
+        bundle.insert(MANIFEST_NAME.to_string(), FileDigest::of(b"manifest"));
+        manifest.check(&bundle).unwrap();
+
+        bundle.insert(
+            "artifacts/include/leanrl.h".to_string(),
+            FileDigest::of(b"evil"),
+        );
+        bundle.remove("artifacts/docs/api.md");
+        bundle.insert("extra.bin".to_string(), FileDigest::of(b"x"));
+        let error = manifest.check(&bundle).unwrap_err().to_string();
+        assert!(error.contains("artifacts/include/leanrl.h: tampered"));
+        assert!(error.contains("artifacts/docs/api.md: missing"));
+        assert!(error.contains("extra.bin: not listed"));
+        assert!(!error.contains("sbom.json"));
+    }
+}
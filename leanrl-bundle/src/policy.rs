@@ -0,0 +1,481 @@
+use crate::sbom::is_hex;
+use anyhow::{Context, Result};
+use leanrl_core::dims::{new_env, DynEnv};
+use leanrl_core::encrypted::{self, Cipher};
+use leanrl_core::signed::{self, PUBLIC_KEY_LEN};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default observation dimension: the shipped C API's (`lr_init` loads an `Env<4, 2>`)
+pub const OBS_DIM: usize = 4;
+
+/// Default action dimension: the shipped C API's
+pub const ACTION_DIM: usize = 2;
+
+/// Extension of the provenance sidecar next to a weights file (`weights.bin.provenance.json`)
+const PROVENANCE_EXTENSION: &str = "provenance.json";
+
+/// Dimensions policies are loaded at and keys their signed containers verify with
+#[derive(Debug, Clone, clap::Args)]
+pub struct PolicyOptions {
+    /// Observation dimension of the policies
+    #[arg(long = "policy-obs-dim", default_value_t = OBS_DIM)]
+    pub obs_dim: usize,
+
+    /// Action dimension of the policies
+    #[arg(long = "policy-action-dim", default_value_t = ACTION_DIM)]
+    pub action_dim: usize,
+
+    /// Ed25519 public key (64 hex characters) signed policy containers are verified with
+    /// (repeatable); a signed policy no key verifies is rejected
+    #[arg(long = "policy-key", value_parser = parse_public_key)]
+    pub public_keys: Vec<[u8; PUBLIC_KEY_LEN]>,
+}
+
+impl Default for PolicyOptions {
+    fn default() -> Self {
+        Self {
+            obs_dim: OBS_DIM,
+            action_dim: ACTION_DIM,
+            public_keys: Vec::new(),
+        }
+    }
+}
+
+impl PolicyOptions {
+    /// Options that load the recorded policies again: their dims and signing keys
+    ///
+    /// Fails if the records disagree on dims; encrypted records carry neither.
+    pub fn from_records(records: &[PolicyRecord]) -> Result<Self> {
+        let mut options = Self::default();
+        let mut dims = None;
+        for record in records {
+            if let Some(shape) = &record.shape {
+                let shape_dims = (shape.obs_dim, shape.action_dim);
+                if dims.replace(shape_dims).is_some_and(|d| d != shape_dims) {
+                    anyhow::bail!("Policies in the manifest have different dimensions");
+                }
+            }
+            if let Some(Envelope::Signed { public_key }) = &record.envelope {
+                let key = parse_public_key(public_key)
+                    .with_context(|| format!("{}: invalid public key", record.path))?;
+                if !options.public_keys.contains(&key) {
+                    options.public_keys.push(key);
+                }
+            }
+        }
+        if let Some((obs_dim, action_dim)) = dims {
+            options.obs_dim = obs_dim;
+            options.action_dim = action_dim;
+        }
+        Ok(options)
+    }
+}
+
+/// Parse a hex-encoded Ed25519 public key
+fn parse_public_key(hex_key: &str) -> Result<[u8; PUBLIC_KEY_LEN]> {
+    let mut key = [0u8; PUBLIC_KEY_LEN];
+    hex::decode_to_slice(hex_key.trim(), &mut key)
+        .context("Public key must be 64 hex characters")?;
+    Ok(key)
+}
+
+/// Training provenance of a policy, read from its sidecar file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Provenance {
+    /// SHA-256 of the training dataset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataset_sha256: Option<String>,
+    /// Trainer name and version, e.g. `leanrl-train 0.3.1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trainer_version: Option<String>,
+    /// Training run identifier (experiment tracker ID, CI job URL, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub training_run: Option<String>,
+    /// Commit of the training code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_commit: Option<String>,
+}
+
+/// Container wrapping the weights blob of a policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Envelope {
+    /// `LRSG` container, verified with `public_key` (hex); the payload is inspected
+    Signed { public_key: String },
+    /// `LREN` container; without its key the payload cannot be inspected
+    Encrypted { cipher: String },
+}
+
+/// What loading a policy reveals about it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyShape {
+    pub algorithm: String,
+    pub obs_dim: usize,
+    pub action_dim: usize,
+    pub parameter_count: usize,
+}
+
+/// Policy weights shipped in the bundle, as recorded in the manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyRecord {
+    /// Path of the weights in the ZIP
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+    /// Signed or encrypted container; absent for a bare weights blob
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub envelope: Option<Envelope>,
+    /// Absent only for encrypted weights
+    #[serde(flatten)]
+    pub shape: Option<PolicyShape>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+impl PolicyRecord {
+    /// One-line summary for error messages, e.g. `LinearFA 4x2 (10 parameters, 45 bytes)`
+    fn describe(&self) -> String {
+        match &self.shape {
+            Some(shape) => format!(
+                "{} {}x{} ({} parameters, {} bytes)",
+                shape.algorithm, shape.obs_dim, shape.action_dim, shape.parameter_count, self.size
+            ),
+            None => format!("encrypted weights ({} bytes)", self.size),
+        }
+    }
+}
+
+/// Validate policy weights and copy them into `output_dir/policies`
+///
+/// Each blob must load at the dimensions of `options`; see [`inspect_weights`]. A
+/// `<file>.provenance.json` sidecar, if present, is attached to the record.
+pub fn collect_policies(
+    output_dir: &Path,
+    policies: &[PathBuf],
+    options: &PolicyOptions,
+) -> Result<(Vec<PathBuf>, Vec<PolicyRecord>)> {
+    let policies_dir = output_dir.join("policies");
+    let mut names = BTreeSet::new();
+    let mut files = Vec::new();
+    let mut records = Vec::new();
+
+    for path in policies {
+        println!("Validating policy {}...", path.display());
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .with_context(|| format!("Invalid policy path: {}", path.display()))?;
+        if !names.insert(name.to_string()) {
+            anyhow::bail!("Two policies are named {}", name);
+        }
+
+        let weights =
+            fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut record =
+            inspect_weights(&format!("artifacts/policies/{}", name), &weights, options)
+                .with_context(|| format!("Invalid policy {}", path.display()))?;
+        record.provenance = read_provenance(&provenance_path(path))?;
+
+        fs::create_dir_all(&policies_dir)?;
+        let bundled = policies_dir.join(name);
+        fs::write(&bundled, &weights)?;
+        files.push(bundled);
+        records.push(record);
+    }
+
+    Ok((files, records))
+}
+
+/// Load weights at the dimensions of `options` and describe them
+///
+/// A signed container must verify with one of the option keys, which is recorded, and its
+/// payload is inspected. An encrypted container cannot be opened here: only its envelope
+/// is recorded, and a policy guard cannot check it.
+pub fn inspect_weights(
+    path: &str,
+    weights: &[u8],
+    options: &PolicyOptions,
+) -> Result<PolicyRecord> {
+    let mut record = PolicyRecord {
+        path: path.to_string(),
+        sha256: hex::encode(Sha256::digest(weights)),
+        size: weights.len() as u64,
+        envelope: None,
+        shape: None,
+        provenance: None,
+    };
+
+    if encrypted::is_encrypted(weights) {
+        if weights.len() < encrypted::HEADER_LEN + encrypted::TAG_LEN {
+            anyhow::bail!("Truncated encrypted container");
+        }
+        let cipher = match Cipher::from_u8(weights[encrypted::MAGIC.len() + 1])? {
+            Cipher::ChaCha20Poly1305 => "chacha20-poly1305",
+            Cipher::Aes256Gcm => "aes-256-gcm",
+        };
+        record.envelope = Some(Envelope::Encrypted {
+            cipher: cipher.to_string(),
+        });
+        return Ok(record);
+    }
+
+    let payload = if signed::is_signed(weights) {
+        let (key, payload) = options
+            .public_keys
+            .iter()
+            .find_map(|key| Some((key, signed::verify(weights, key).ok()?)))
+            .context("Signed container does not verify with any --policy-key")?;
+        record.envelope = Some(Envelope::Signed {
+            public_key: hex::encode(key),
+        });
+        payload
+    } else {
+        weights
+    };
+
+    let env = new_env(payload, options.obs_dim, options.action_dim)?;
+    record.shape = Some(PolicyShape {
+        algorithm: env.algorithm_name().to_string(),
+        obs_dim: options.obs_dim,
+        action_dim: options.action_dim,
+        parameter_count: env.parameter_count(),
+    });
+    Ok(record)
+}
+
+/// Load recorded weights for probing, unwrapping a signed container with its recorded key
+pub fn load_policy(record: &PolicyRecord, weights: &[u8]) -> Result<Box<dyn DynEnv>> {
+    let shape = record
+        .shape
+        .as_ref()
+        .context("weights are encrypted and cannot be loaded without their key")?;
+    let payload = match &record.envelope {
+        Some(Envelope::Signed { public_key }) => {
+            signed::verify(weights, &parse_public_key(public_key)?)?
+        }
+        _ => weights,
+    };
+    Ok(new_env(payload, shape.obs_dim, shape.action_dim)?)
+}
+
+/// Check bundled weights against their manifest record
+pub fn check_record(record: &PolicyRecord, weights: &[u8]) -> Result<()> {
+    let options = PolicyOptions::from_records(std::slice::from_ref(record))?;
+    let actual = inspect_weights(&record.path, weights, &options)
+        .with_context(|| format!("{}: weights no longer load", record.path))?;
+    let expected = PolicyRecord {
+        provenance: None,
+        ..record.clone()
+    };
+    if actual != expected {
+        anyhow::bail!(
+            "{}: manifest lists {}, bundle has {}",
+            record.path,
+            record.describe(),
+            actual.describe()
+        );
+    }
+    Ok(())
+}
+
+/// Sidecar provenance file for a weights file
+pub fn provenance_path(weights_path: &Path) -> PathBuf {
+    let mut name = weights_path.as_os_str().to_owned();
+    name.push(".");
+    name.push(PROVENANCE_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Read and validate a provenance sidecar, if it exists
+fn read_provenance(path: &Path) -> Result<Option<Provenance>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let provenance: Provenance = serde_json::from_str(&fs::read_to_string(path)?)
+        .with_context(|| format!("Invalid provenance file {}", path.display()))?;
+
+    if let Some(digest) = &provenance.dataset_sha256 {
+        if !is_hex(digest, 64) {
+            anyhow::bail!(
+                "{}: dataset_sha256 must be 64 hex characters",
+                path.display()
+            );
+        }
+    }
+    Ok(Some(provenance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leanrl_core::weights;
+
+    /// LinearFA weights at the given dimensions
+    fn linear_fa_weights_for(obs_dim: usize, action_dim: usize) -> Vec<u8> {
+        weights::linear_fa_weights(
+            obs_dim,
+            action_dim,
+            &vec![0.5; obs_dim * action_dim],
+            &vec![0.5; action_dim],
+            0.01,
+        )
+        .unwrap()
+    }
+
+    /// LinearFA weights at the C API dimensions
+    fn linear_fa_weights() -> Vec<u8> {
+        linear_fa_weights_for(OBS_DIM, ACTION_DIM)
+    }
+
+    #[test]
+    fn test_collect_policies_with_provenance() {
+        let dir = tempfile::tempdir().unwrap();
+        let weights_path = dir.path().join("controller.bin");
+        fs::write(&weights_path, linear_fa_weights()).unwrap();
+        fs::write(
+            provenance_path(&weights_path),
+            format!(
+                r#"{{"dataset_sha256": "{}", "trainer_version": "leanrl-train 0.3.1"}}"#,
+                "ab".repeat(32)
+            ),
+        )
+        .unwrap();
+
+        let out = dir.path().join("out");
+        let options = PolicyOptions::default();
+        let (files, records) =
+            collect_policies(&out, std::slice::from_ref(&weights_path), &options).unwrap();
+        assert_eq!(files, [out.join("policies/controller.bin")]);
+
+        let record = &records[0];
+        assert_eq!(record.path, "artifacts/policies/controller.bin");
+        let shape = record.shape.as_ref().unwrap();
+        assert_eq!(shape.algorithm, "LinearFA");
+        assert_eq!(shape.parameter_count, 10);
+        assert_eq!(
+            record
+                .provenance
+                .as_ref()
+                .unwrap()
+                .trainer_version
+                .as_deref(),
+            Some("leanrl-train 0.3.1")
+        );
+        check_record(record, &linear_fa_weights()).unwrap();
+
+        // Duplicate names and unloadable blobs are rejected
+        assert!(collect_policies(&out, &[weights_path.clone(), weights_path], &options).is_err());
+        let garbage = dir.path().join("garbage.bin");
+        fs::write(&garbage, [9u8, 1, 2, 3]).unwrap();
+        assert!(collect_policies(&out, &[garbage], &options).is_err());
+    }
+
+    #[test]
+    fn test_inspect_weights_at_cli_dims() {
+        let options = PolicyOptions {
+            obs_dim: 8,
+            action_dim: 3,
+            ..Default::default()
+        };
+        let weights = linear_fa_weights_for(8, 3);
+        let record = inspect_weights("p.bin", &weights, &options).unwrap();
+        let shape = record.shape.as_ref().unwrap();
+        assert_eq!((shape.obs_dim, shape.action_dim), (8, 3));
+        assert_eq!(shape.parameter_count, 27);
+        check_record(&record, &weights).unwrap();
+        assert_eq!(load_policy(&record, &weights).unwrap().dims(), (8, 3));
+
+        // The same blob does not load at the default 4x2
+        assert!(inspect_weights("p.bin", &weights, &PolicyOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_inspect_signed_weights() {
+        let secret = [7u8; 32];
+        let container = signed::sign(&linear_fa_weights(), &secret).unwrap();
+        let mut options = PolicyOptions {
+            public_keys: vec![signed::public_key(&[8u8; 32])],
+            ..Default::default()
+        };
+
+        // No key verifies the container
+        assert!(inspect_weights("p.bin", &container, &PolicyOptions::default()).is_err());
+        assert!(inspect_weights("p.bin", &container, &options).is_err());
+
+        options.public_keys.push(signed::public_key(&secret));
+        let record = inspect_weights("p.bin", &container, &options).unwrap();
+        assert_eq!(
+            record.envelope,
+            Some(Envelope::Signed {
+                public_key: hex::encode(signed::public_key(&secret))
+            })
+        );
+        assert_eq!(record.shape.as_ref().unwrap().algorithm, "LinearFA");
+        check_record(&record, &container).unwrap();
+        load_policy(&record, &container).unwrap();
+
+        // Manifest round trip, and the bare payload no longer matches the record
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains(r#""envelope":{"format":"signed""#));
+        assert_eq!(serde_json::from_str::<PolicyRecord>(&json).unwrap(), record);
+        assert!(check_record(&record, &linear_fa_weights()).is_err());
+    }
+
+    #[test]
+    fn test_inspect_encrypted_weights_records_envelope() {
+        let container = encrypted::encrypt(
+            &linear_fa_weights(),
+            &[1u8; encrypted::KEY_LEN],
+            Cipher::Aes256Gcm,
+            &[2u8; encrypted::NONCE_LEN],
+        )
+        .unwrap();
+        let record = inspect_weights("p.bin", &container, &PolicyOptions::default()).unwrap();
+        assert_eq!(
+            record.envelope,
+            Some(Envelope::Encrypted {
+                cipher: "aes-256-gcm".to_string()
+            })
+        );
+        assert_eq!(record.shape, None);
+        check_record(&record, &container).unwrap();
+        assert!(load_policy(&record, &container).is_err());
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(!json.contains("algorithm"));
+        assert_eq!(serde_json::from_str::<PolicyRecord>(&json).unwrap(), record);
+
+        // Records of different dims cannot be reloaded with one set of options
+        let wide = PolicyOptions {
+            obs_dim: 8,
+            action_dim: 3,
+            ..Default::default()
+        };
+        let records = [
+            inspect_weights("a.bin", &linear_fa_weights(), &PolicyOptions::default()).unwrap(),
+            inspect_weights("b.bin", &linear_fa_weights_for(8, 3), &wide).unwrap(),
+            record,
+        ];
+        assert!(PolicyOptions::from_records(&records[1..]).is_ok());
+        assert!(PolicyOptions::from_records(&records).is_err());
+    }
+
+    #[test]
+    fn test_provenance_rejects_unknown_fields_and_bad_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("p.provenance.json");
+
+        fs::write(&path, r#"{"trainer": "x"}"#).unwrap();
+        assert!(read_provenance(&path).is_err());
+
+        fs::write(&path, r#"{"dataset_sha256": "xyz"}"#).unwrap();
+        assert!(read_provenance(&path).is_err());
+
+        assert_eq!(read_provenance(&dir.path().join("none")).unwrap(), None);
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
+
+/// Build timestamp from `SOURCE_DATE_EPOCH`, if set
+///
+/// See <https://reproducible-builds.org/specs/source-date-epoch/>.
+pub fn source_date_epoch() -> Result<Option<DateTime<Utc>>> {
+    match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(value) => parse_epoch(&value).map(Some),
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(e) => Err(e).context("SOURCE_DATE_EPOCH is not valid UTF-8"),
+    }
+}
+
+/// Timestamp for a reproducible build: `SOURCE_DATE_EPOCH`, else the HEAD commit time
+pub fn reproducible_timestamp() -> Result<DateTime<Utc>> {
+    if let Some(epoch) = source_date_epoch()? {
+        return Ok(epoch);
+    }
+
+    let output = std::process::Command::new("git")
+        .args(["log", "-1", "--format=%ct"])
+        .output()
+        .context("Failed to get commit timestamp")?;
+    if !output.status.success() {
+        anyhow::bail!("git log failed; set SOURCE_DATE_EPOCH instead");
+    }
+    parse_epoch(String::from_utf8(output.stdout)?.trim())
+}
+
+/// Parse seconds since the Unix epoch
+fn parse_epoch(value: &str) -> Result<DateTime<Utc>> {
+    let seconds: i64 = value
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid SOURCE_DATE_EPOCH: {}", value))?;
+    Utc.timestamp_opt(seconds, 0)
+        .single()
+        .with_context(|| format!("SOURCE_DATE_EPOCH out of range: {}", value))
+}
+
+/// ZIP (MS-DOS) modification time for a timestamp, clamped to 1980..=2107
+pub fn zip_timestamp(time: DateTime<Utc>) -> zip::DateTime {
+    let time = time.clamp(
+        Utc.with_ymd_and_hms(1980, 1, 1, 0, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2107, 12, 31, 23, 59, 58).unwrap(),
+    );
+    zip::DateTime::from_date_and_time(
+        time.year() as u16,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    )
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epoch_parsing_and_zip_clamping() {
+        let time = parse_epoch("1700000000").unwrap();
+        assert_eq!(time.to_rfc3339(), "2023-11-14T22:13:20+00:00");
+        assert!(parse_epoch("yesterday").is_err());
+
+        let zip_time = zip_timestamp(time);
+        assert_eq!(
+            (zip_time.year(), zip_time.month(), zip_time.day()),
+            (2023, 11, 14)
+        );
+        assert_eq!(zip_timestamp(parse_epoch("0").unwrap()).year(), 1980);
+    }
+}
@@ -1,10 +1,26 @@
-use anyhow::Result;
-use chrono::Utc;
+use crate::bundle::ArtifactBuild;
+use crate::cyclonedx;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha1::Sha1;
 use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Source of crates.io packages in `cargo metadata` and `Cargo.lock`
+const CRATES_IO_SOURCE: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+/// SPDX 2.3 JSON schema, vendored so verification works offline
+const SPDX_SCHEMA: &str = include_str!("../schemas/spdx-2.3.schema.json");
+
+/// Namespace of content-derived serial numbers (the RFC 4122 URL namespace)
+const SERIAL_NAMESPACE: [u8; 16] = [
+    0x6b, 0xa7, 0xb8, 0x11, 0x9d, 0xad, 0x11, 0xd1, 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8,
+];
 
 /// SPDX SBOM document
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,6 +34,8 @@ pub struct SpdxDocument {
     pub name: String,
     #[serde(rename = "dataLicense")]
     pub data_license: String,
+    #[serde(rename = "documentNamespace")]
+    pub document_namespace: String,
     pub packages: Vec<Package>,
     pub relationships: Vec<Relationship>,
 }
@@ -42,7 +60,11 @@ pub struct Package {
     pub version_info: String,
     #[serde(rename = "packageFileName")]
     pub package_file_name: String,
-    #[serde(rename = "checksums")]
+    #[serde(rename = "downloadLocation")]
+    pub download_location: String,
+    #[serde(rename = "filesAnalyzed")]
+    pub files_analyzed: bool,
+    #[serde(rename = "checksums", default, skip_serializing_if = "Vec::is_empty")]
     pub checksums: Vec<Checksum>,
     #[serde(rename = "licenseConcluded")]
     pub license_concluded: String,
@@ -53,6 +75,20 @@ pub struct Package {
     #[serde(rename = "supplier")]
     pub supplier: String,
     pub description: String,
+    #[serde(
+        rename = "primaryPackagePurpose",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub primary_package_purpose: Option<String>,
+    #[serde(
+        rename = "externalRefs",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub external_refs: Vec<ExternalRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
 }
 
 /// Checksum information
@@ -63,6 +99,17 @@ pub struct Checksum {
     pub checksum_value: String,
 }
 
+/// External reference, used for package URLs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExternalRef {
+    #[serde(rename = "referenceCategory")]
+    pub reference_category: String,
+    #[serde(rename = "referenceType")]
+    pub reference_type: String,
+    #[serde(rename = "referenceLocator")]
+    pub reference_locator: String,
+}
+
 /// Relationship between packages
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Relationship {
@@ -74,150 +121,1379 @@ pub struct Relationship {
     pub relationship_type: String,
 }
 
+/// SBOM output format
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum SbomFormat {
+    /// SPDX 2.3 JSON
+    #[default]
+    Spdx,
+    /// CycloneDX 1.5 JSON
+    Cyclonedx,
+}
+
+/// Output format plus the feature and platform selection used to resolve the dependency graph
+/// of the standalone `sbom` command; bundles take both from their build matrix
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct SbomOptions {
+    /// SBOM format to write
+    #[arg(long, value_enum, default_value = "spdx")]
+    pub format: SbomFormat,
+
+    /// Features to activate (comma separated, `crate/feature` allowed)
+    #[arg(long, value_delimiter = ',')]
+    pub features: Vec<String>,
+
+    /// Activate all features of every workspace member
+    #[arg(long)]
+    pub all_features: bool,
+
+    /// Do not activate default features
+    #[arg(long)]
+    pub no_default_features: bool,
+
+    /// Resolve platform-specific dependencies for this target triple (default: host)
+    #[arg(long)]
+    pub target: Option<String>,
+}
+
+impl SbomOptions {
+    fn cargo_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        args
+    }
+}
+
+/// Format-neutral bill of materials, rendered as SPDX or CycloneDX
+#[derive(Debug)]
+pub struct Sbom {
+    pub created: DateTime<Utc>,
+    /// Document UUID (CycloneDX serial number, SPDX namespace suffix)
+    pub serial: String,
+    /// Components keyed by reference (`Package-…`, `Artifact-…`, `File-…`)
+    pub components: BTreeMap<String, Component>,
+    /// References of the components the document is about
+    pub describes: BTreeSet<String>,
+    /// (from, to, relation) between component references
+    pub relations: BTreeSet<(String, String, Relation)>,
+}
+
+impl Sbom {
+    /// Empty document with a random serial number
+    fn new() -> Result<Self> {
+        Ok(Self {
+            created: Utc::now(),
+            serial: random_uuid()?,
+            components: BTreeMap::new(),
+            describes: BTreeSet::new(),
+            relations: BTreeSet::new(),
+        })
+    }
+}
+
+/// Kind of component in the model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComponentKind {
+    /// Cargo package (workspace member or dependency)
+    Crate,
+    /// Library built from a workspace member
+    Library,
+    /// Executable built from a workspace member
+    Application,
+    /// Other file shipped in the bundle
+    File,
+}
+
+/// Relation between two components
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Relation {
+    DependsOn,
+    BuildDependsOn,
+    GeneratedFrom,
+}
+
+/// Component of the model
+#[derive(Debug, Clone)]
+pub struct Component {
+    pub kind: ComponentKind,
+    pub name: String,
+    pub version: String,
+    pub file_name: String,
+    pub download_location: Option<String>,
+    pub sha256: Option<String>,
+    /// SPDX license expression
+    pub license: Option<String>,
+    pub supplier: Option<String>,
+    pub copyright: Option<String>,
+    pub description: String,
+    pub purl: Option<String>,
+    pub features: BTreeSet<String>,
+}
+
+/// Bundle output listed in an SBOM
+#[derive(Debug)]
+pub struct SbomFile {
+    pub name: String,
+    pub sha256: Option<String>,
+}
+
 /// Generate SBOM
-pub fn generate_sbom(output_path: &Path) -> Result<()> {
+///
+/// Describes every workspace member and its built artifacts, with the dependency graph
+/// resolved for the features and target `options` select. A `created` time (from
+/// `SOURCE_DATE_EPOCH`) pins the timestamp and derives the serial number from the content,
+/// so identical inputs give an identical document.
+pub fn generate_sbom(
+    output_path: &Path,
+    options: &SbomOptions,
+    created: Option<DateTime<Utc>>,
+) -> Result<()> {
+    println!("Generating SBOM...");
+
+    let metadata = cargo_metadata(options)?;
+    let checksums = read_lock_checksums(&metadata.workspace_root.join("Cargo.lock"))?;
+
+    let mut graphs = Vec::new();
+    for member in metadata.members() {
+        graphs.push((member, resolve_artifact_graph(member, options)?));
+    }
+
+    let mut sbom = build_sbom(&metadata, &graphs, &checksums, options)?;
+    write_sbom(output_path, &mut sbom, options.format, created)
+}
+
+/// Generate the SBOM of a bundle
+///
+/// Each library in `builds` is described with the graph of the target and feature set it was
+/// built with, so the document always matches `build_info.json`. `bundle_files` are the files
+/// shipped in the bundle (path inside the bundle, path on disk); their SHA-256 is recorded so
+/// `verify` can cross-check the bundle contents. `created` works as for `generate_sbom`.
+pub fn generate_bundle_sbom(
+    output_path: &Path,
+    format: SbomFormat,
+    bundle_files: &[(String, PathBuf)],
+    builds: &[ArtifactBuild],
+    created: Option<DateTime<Utc>>,
+) -> Result<()> {
     println!("Generating SBOM...");
 
-    let sbom = create_spdx_document()?;
+    let mut sbom = Sbom::new()?;
+    attach_builds(&mut sbom, builds)?;
+    attach_bundle_files(&mut sbom, bundle_files, builds)?;
+    write_sbom(output_path, &mut sbom, format, created)
+}
+
+fn write_sbom(
+    output_path: &Path,
+    sbom: &mut Sbom,
+    format: SbomFormat,
+    created: Option<DateTime<Utc>>,
+) -> Result<()> {
+    if let Some(created) = created {
+        sbom.created = created;
+        sbom.serial = content_uuid(sbom);
+    }
 
+    let json = match format {
+        SbomFormat::Spdx => serde_json::to_string_pretty(&to_spdx(sbom))?,
+        SbomFormat::Cyclonedx => serde_json::to_string_pretty(&cyclonedx::from_sbom(sbom))?,
+    };
     let mut file = File::create(output_path)?;
-    let json = serde_json::to_string_pretty(&sbom)?;
     file.write_all(json.as_bytes())?;
 
     println!("SBOM generated: {}", output_path.display());
     Ok(())
 }
 
+/// Format of an existing SBOM document, detected from its content
+pub fn detect_format(content: &[u8]) -> Result<SbomFormat> {
+    let value: serde_json::Value =
+        serde_json::from_slice(content).context("SBOM is not valid JSON")?;
+    Ok(match value.get("bomFormat") {
+        Some(_) => SbomFormat::Cyclonedx,
+        None => SbomFormat::Spdx,
+    })
+}
+
 /// Verify SBOM
-pub fn verify_sbom(sbom_path: &Path) -> Result<()> {
+///
+/// Validates the document (SPDX 2.3 or CycloneDX 1.5, detected from its content) against its
+/// JSON schema and the references the schema cannot express, and checks that `bundle_files`
+/// (path inside the bundle -> SHA-256) match the outputs it lists, in both directions.
+pub fn verify_sbom(sbom_path: &Path, bundle_files: &BTreeMap<String, String>) -> Result<()> {
     let content = std::fs::read_to_string(sbom_path)?;
-    let _sbom: SpdxDocument = serde_json::from_str(&content)?;
+    let value: serde_json::Value =
+        serde_json::from_str(&content).context("SBOM is not valid JSON")?;
+
+    let listed = if value.get("bomFormat").is_some() {
+        check_schema(cyclonedx::SCHEMA, &value).context("Invalid CycloneDX document")?;
+        let doc: cyclonedx::CycloneDxDocument =
+            serde_json::from_value(value).context("Invalid CycloneDX document")?;
+        cyclonedx::validate(&doc)?
+    } else {
+        check_schema(SPDX_SCHEMA, &value).context("Invalid SPDX document")?;
+        let doc: SpdxDocument = serde_json::from_value(value).context("Invalid SPDX document")?;
+        validate_spdx(&doc)?
+    };
+
+    let listed: BTreeMap<&str, Option<&str>> = listed
+        .iter()
+        .map(|f| (f.name.as_str(), f.sha256.as_deref()))
+        .collect();
+    for (name, sha256) in bundle_files {
+        match listed.get(name.as_str()) {
+            Some(Some(expected)) if expected.eq_ignore_ascii_case(sha256) => {}
+            Some(Some(expected)) => anyhow::bail!(
+                "Checksum mismatch for {}: SBOM lists {}, bundle has {}",
+                name,
+                expected,
+                sha256
+            ),
+            _ => anyhow::bail!(
+                "Bundle file {} is not listed with a checksum in the SBOM",
+                name
+            ),
+        }
+    }
+    for (name, sha256) in &listed {
+        if sha256.is_some() && !bundle_files.contains_key(*name) {
+            anyhow::bail!("SBOM lists {} but it is missing from the bundle", name);
+        }
+    }
 
     println!("SBOM verification passed");
     Ok(())
 }
 
-/// Create SPDX document
-fn create_spdx_document() -> Result<SpdxDocument> {
-    let now = Utc::now();
-    let timestamp = now.format("%Y-%m-%dT%H:%M:%SZ").to_string();
-
-    let mut packages = Vec::new();
-    let mut relationships = Vec::new();
-
-    // Add main package
-    let main_package = Package {
-        spdx_id: "SPDXRef-leanrl-core".to_string(),
-        name: "LeanEdge-RL Core".to_string(),
-        version_info: env!("CARGO_PKG_VERSION").to_string(),
-        package_file_name: "libleanrl_core.a".to_string(),
-        checksums: vec![Checksum {
-            algorithm: "SHA256".to_string(),
-            checksum_value: calculate_file_hash("core/src/lib.rs")?,
-        }],
-        license_concluded: "MIT OR Apache-2.0".to_string(),
-        license_declared: "MIT OR Apache-2.0".to_string(),
-        copyright_text: "Copyright (c) 2025 LeanEdge-RL Team".to_string(),
-        supplier: "LeanEdge-RL Team".to_string(),
-        description: "Core RL runtime library for safety-critical edge systems".to_string(),
-    };
-    packages.push(main_package);
-
-    // Add C++ shim package
-    let cshim_package = Package {
-        spdx_id: "SPDXRef-leanrl-cshim".to_string(),
-        name: "LeanEdge-RL C++ Shim".to_string(),
-        version_info: env!("CARGO_PKG_VERSION").to_string(),
-        package_file_name: "libleanrl_cshim.a".to_string(),
-        checksums: vec![Checksum {
-            algorithm: "SHA256".to_string(),
-            checksum_value: calculate_file_hash("cshim/src/lib.rs")?,
-        }],
-        license_concluded: "MIT OR Apache-2.0".to_string(),
-        license_declared: "MIT OR Apache-2.0".to_string(),
-        copyright_text: "Copyright (c) 2025 LeanEdge-RL Team".to_string(),
-        supplier: "LeanEdge-RL Team".to_string(),
-        description: "C++ shim and header generation for LeanEdge-RL".to_string(),
-    };
-    packages.push(cshim_package);
-
-    // Add dependencies
-    let dependencies = vec![
-        ("thiserror", "1.0", "MIT OR Apache-2.0"),
-        ("serde", "1.0", "MIT OR Apache-2.0"),
-        ("libc", "0.2", "MIT OR Apache-2.0"),
-        ("packed_simd_2", "0.3", "MIT OR Apache-2.0"),
-        ("cxx", "1.0", "MIT OR Apache-2.0"),
-        ("clap", "4.4", "MIT OR Apache-2.0"),
-        ("zip", "0.6", "MIT"),
-        ("walkdir", "2.4", "Unlicense/MIT"),
-        ("toml", "0.8", "MIT"),
-        ("serde_json", "1.0", "MIT OR Apache-2.0"),
-        ("sha2", "0.10", "MIT OR Apache-2.0"),
-        ("hex", "0.4", "MIT OR Apache-2.0"),
-        ("chrono", "0.4", "MIT OR Apache-2.0"),
-        ("anyhow", "1.0", "MIT OR Apache-2.0"),
-    ];
+/// Subset of `cargo metadata --format-version 1`
+#[derive(Debug, Deserialize)]
+struct Metadata {
+    packages: Vec<MetadataPackage>,
+    workspace_members: Vec<String>,
+    workspace_root: PathBuf,
+}
 
-    for (i, (name, version, license)) in dependencies.iter().enumerate() {
-        let package = Package {
-            spdx_id: format!("SPDXRef-dependency-{}", i),
-            name: name.to_string(),
-            version_info: version.to_string(),
-            package_file_name: format!("{}-{}.crate", name, version),
-            checksums: vec![Checksum {
-                algorithm: "SHA256".to_string(),
-                checksum_value: format!("placeholder_hash_{}", i),
-            }],
-            license_concluded: license.to_string(),
-            license_declared: license.to_string(),
-            copyright_text: "Copyright (c) respective authors".to_string(),
-            supplier: "Crates.io".to_string(),
-            description: format!("Dependency: {}", name),
-        };
-        packages.push(package);
+#[derive(Debug, Clone, Deserialize)]
+struct MetadataPackage {
+    id: String,
+    name: String,
+    version: String,
+    license: Option<String>,
+    description: Option<String>,
+    source: Option<String>,
+    manifest_path: PathBuf,
+    #[serde(default)]
+    authors: Vec<String>,
+    targets: Vec<MetadataTarget>,
+}
 
-        // Add relationship
-        relationships.push(Relationship {
-            spdx_element_id: "SPDXRef-leanrl-core".to_string(),
-            related_spdx_element: format!("SPDXRef-dependency-{}", i),
-            relationship_type: "DEPENDS_ON".to_string(),
-        });
+#[derive(Debug, Clone, Deserialize)]
+struct MetadataTarget {
+    name: String,
+    kind: Vec<String>,
+}
+
+impl Metadata {
+    fn members(&self) -> Vec<&MetadataPackage> {
+        self.packages
+            .iter()
+            .filter(|p| self.workspace_members.contains(&p.id))
+            .collect()
+    }
+
+    fn find(&self, name: &str, version: &str) -> Option<&MetadataPackage> {
+        self.packages
+            .iter()
+            .find(|p| p.name == name && p.version == version)
+    }
+}
+
+/// Subset of `Cargo.lock`
+#[derive(Debug, Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LockPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    checksum: Option<String>,
+}
+
+/// Registry checksums keyed by (name, version, source)
+type LockChecksums = HashMap<(String, String, String), String>;
+
+/// Resolved dependency graph of one workspace member, as built
+#[derive(Debug, Default)]
+struct ArtifactGraph {
+    /// (name, version) -> activated features
+    packages: BTreeMap<(String, String), BTreeSet<String>>,
+    /// (parent, child) edges, each side a (name, version)
+    normal: BTreeSet<((String, String), (String, String))>,
+    build: BTreeSet<((String, String), (String, String))>,
+}
+
+fn run_cargo(args: &[String]) -> Result<Vec<u8>> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let output = Command::new(&cargo)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {}", cargo))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "cargo {} failed: {}",
+            args.first().map(String::as_str).unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+fn cargo_metadata(options: &SbomOptions) -> Result<Metadata> {
+    let mut args = vec!["metadata".to_string(), "--format-version=1".to_string()];
+    args.extend(options.cargo_args());
+    if let Some(target) = &options.target {
+        args.push(format!("--filter-platform={}", target));
     }
 
-    // Add relationship between core and cshim
-    relationships.push(Relationship {
-        spdx_element_id: "SPDXRef-leanrl-cshim".to_string(),
-        related_spdx_element: "SPDXRef-leanrl-core".to_string(),
-        relationship_type: "DEPENDS_ON".to_string(),
-    });
-
-    let creation_info = CreationInfo {
-        creators: vec![
-            "Tool: leanrl-bundle".to_string(),
-            "Organization: LeanEdge-RL Team".to_string(),
-        ],
-        created: timestamp,
-        license_list_version: "3.19".to_string(),
+    serde_json::from_slice(&run_cargo(&args)?).context("Failed to parse cargo metadata")
+}
+
+fn read_lock_checksums(lock_path: &Path) -> Result<LockChecksums> {
+    let content = std::fs::read_to_string(lock_path)
+        .with_context(|| format!("Failed to read {}", lock_path.display()))?;
+    let lockfile: Lockfile = toml::from_str(&content).context("Failed to parse Cargo.lock")?;
+
+    Ok(lockfile
+        .package
+        .into_iter()
+        .filter_map(|p| Some(((p.name, p.version, p.source?), p.checksum?)))
+        .collect())
+}
+
+/// Resolve the graph of one member with per-package feature resolution (`cargo tree -p`)
+///
+/// `cargo metadata` unifies features across the whole workspace; `cargo tree` resolves what a
+/// build of this member alone activates. Build edges are those absent from the normal-only tree.
+fn resolve_artifact_graph(
+    member: &MetadataPackage,
+    options: &SbomOptions,
+) -> Result<ArtifactGraph> {
+    let mut graph = ArtifactGraph::default();
+
+    for (edges, build) in [("normal", false), ("normal,build", true)] {
+        let mut args = vec![
+            "tree".to_string(),
+            "--package".to_string(),
+            format!("{}@{}", member.name, member.version),
+            "--edges".to_string(),
+            edges.to_string(),
+            "--prefix=depth".to_string(),
+            "--format={p}|{f}".to_string(),
+        ];
+        args.extend(options.cargo_args());
+        if let Some(target) = &options.target {
+            args.push(format!("--target={}", target));
+        }
+
+        let output = String::from_utf8(run_cargo(&args)?)?;
+        parse_tree(&output, &mut graph, build)?;
+    }
+
+    let normal = graph.normal.clone();
+    graph.build.retain(|edge| !normal.contains(edge));
+    Ok(graph)
+}
+
+/// Parse `cargo tree --prefix=depth --format={p}|{f}` output into `graph`
+fn parse_tree(output: &str, graph: &mut ArtifactGraph, build: bool) -> Result<()> {
+    let mut stack: Vec<(String, String)> = Vec::new();
+
+    for line in output.lines() {
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 {
+            continue;
+        }
+        let depth: usize = line[..digits].parse()?;
+        let (package, features) = line[digits..]
+            .split_once('|')
+            .with_context(|| format!("Unexpected cargo tree line: {}", line))?;
+
+        let mut parts = package.split_whitespace();
+        let name = parts.next().context("Missing package name")?;
+        let version = parts
+            .next()
+            .and_then(|v| v.strip_prefix('v'))
+            .with_context(|| format!("Missing package version: {}", line))?;
+        let node = (name.to_string(), version.to_string());
+
+        graph.packages.entry(node.clone()).or_default().extend(
+            features
+                .trim_end_matches("(*)")
+                .split(',')
+                .map(str::trim)
+                .filter(|f| !f.is_empty())
+                .map(String::from),
+        );
+
+        stack.truncate(depth);
+        if let Some(parent) = stack.last() {
+            let edge = (parent.clone(), node.clone());
+            if build {
+                graph.build.insert(edge);
+            } else {
+                graph.normal.insert(edge);
+            }
+        }
+        stack.push(node);
+    }
+
+    Ok(())
+}
+
+/// Build the format-neutral model from the resolved per-member graphs
+fn build_sbom(
+    metadata: &Metadata,
+    graphs: &[(&MetadataPackage, ArtifactGraph)],
+    checksums: &LockChecksums,
+    options: &SbomOptions,
+) -> Result<Sbom> {
+    let mut sbom = Sbom::new()?;
+
+    for (member, graph) in graphs {
+        let member_ref = crate_ref(&member.name, &member.version);
+        sbom.describes.insert(member_ref.clone());
+
+        // Built artifacts of this member, with the features this member's own build activates
+        let member_features = graph
+            .packages
+            .get(&(member.name.clone(), member.version.clone()))
+            .cloned()
+            .unwrap_or_default();
+        for target in &member.targets {
+            for kind in &target.kind {
+                let Some((file_name, component_kind)) =
+                    artifact_file_name(&target.name, kind, options.target.as_deref())
+                else {
+                    continue;
+                };
+                let artifact_ref = format!("Artifact-{}", sanitize_spdx_id(&file_name));
+                sbom.components
+                    .entry(artifact_ref.clone())
+                    .or_insert_with(|| Component {
+                        kind: component_kind,
+                        name: file_name.clone(),
+                        version: member.version.clone(),
+                        file_name: file_name.clone(),
+                        download_location: None,
+                        sha256: None,
+                        license: spdx_license(member.license.as_deref()),
+                        supplier: supplier(member),
+                        copyright: None,
+                        description: format!("{} built from {}", kind, member.name),
+                        purl: None,
+                        features: member_features.clone(),
+                    });
+                sbom.relations
+                    .insert((artifact_ref, member_ref.clone(), Relation::GeneratedFrom));
+            }
+        }
+
+        add_graph(&mut sbom, metadata, graph, checksums)?;
+    }
+
+    Ok(sbom)
+}
+
+/// Add the packages reached by one build, with their activated features, and its edges
+fn add_graph(
+    sbom: &mut Sbom,
+    metadata: &Metadata,
+    graph: &ArtifactGraph,
+    checksums: &LockChecksums,
+) -> Result<()> {
+    for ((name, version), features) in &graph.packages {
+        let component_ref = crate_ref(name, version);
+        if !sbom.components.contains_key(&component_ref) {
+            let meta = metadata
+                .find(name, version)
+                .with_context(|| format!("{} {} missing from cargo metadata", name, version))?;
+            sbom.components
+                .insert(component_ref.clone(), crate_component(meta, checksums)?);
+        }
+        if let Some(component) = sbom.components.get_mut(&component_ref) {
+            component.features.extend(features.iter().cloned());
+        }
+    }
+
+    for (parent, child) in &graph.normal {
+        sbom.relations.insert((
+            crate_ref(&parent.0, &parent.1),
+            crate_ref(&child.0, &child.1),
+            Relation::DependsOn,
+        ));
+    }
+    for (parent, child) in &graph.build {
+        sbom.relations.insert((
+            crate_ref(&parent.0, &parent.1),
+            crate_ref(&child.0, &child.1),
+            Relation::BuildDependsOn,
+        ));
+    }
+    Ok(())
+}
+
+/// Resolve the graph each shipped library was built with and attach the library to it
+///
+/// Builds of one package for the same target and feature set share a resolution. Each library
+/// is generated from its package and depends on every package its build links, with the
+/// features that build activates.
+fn attach_builds(sbom: &mut Sbom, builds: &[ArtifactBuild]) -> Result<()> {
+    type BuildKey = (String, String, Vec<String>, bool);
+    let mut resolved: BTreeMap<BuildKey, (MetadataPackage, ArtifactGraph)> = BTreeMap::new();
+
+    for build in builds {
+        let key = (
+            build.package.clone(),
+            build.target.clone(),
+            build.features.clone(),
+            build.default_features,
+        );
+        if !resolved.contains_key(&key) {
+            let options = SbomOptions {
+                // Qualified, since `cargo metadata` runs for the whole workspace
+                features: build
+                    .features
+                    .iter()
+                    .map(|f| match f.contains('/') {
+                        true => f.clone(),
+                        false => format!("{}/{}", build.package, f),
+                    })
+                    .collect(),
+                no_default_features: !build.default_features,
+                target: Some(build.target.clone()),
+                ..Default::default()
+            };
+            let metadata = cargo_metadata(&options)?;
+            let member = metadata
+                .members()
+                .into_iter()
+                .find(|m| m.name == build.package)
+                .cloned()
+                .with_context(|| format!("{} is not a workspace member", build.package))?;
+            let graph = resolve_artifact_graph(&member, &options)?;
+            let checksums = read_lock_checksums(&metadata.workspace_root.join("Cargo.lock"))?;
+            add_graph(sbom, &metadata, &graph, &checksums)?;
+            resolved.insert(key.clone(), (member, graph));
+        }
+        let (member, graph) = &resolved[&key];
+
+        let artifact_ref = format!("Artifact-{}", sanitize_spdx_id(&build.path));
+        sbom.components.insert(
+            artifact_ref.clone(),
+            Component {
+                kind: ComponentKind::Library,
+                name: build.path.clone(),
+                version: member.version.clone(),
+                file_name: build.path.clone(),
+                download_location: None,
+                sha256: Some(build.sha256.clone()),
+                license: spdx_license(member.license.as_deref()),
+                supplier: supplier(member),
+                copyright: None,
+                description: format!("Library built from {} for {}", member.name, build.target),
+                purl: None,
+                features: graph
+                    .packages
+                    .get(&(member.name.clone(), member.version.clone()))
+                    .cloned()
+                    .unwrap_or_default(),
+            },
+        );
+        sbom.describes.insert(artifact_ref.clone());
+        sbom.relations.insert((
+            artifact_ref.clone(),
+            crate_ref(&member.name, &member.version),
+            Relation::GeneratedFrom,
+        ));
+        for (_, (name, version)) in &graph.normal {
+            sbom.relations.insert((
+                artifact_ref.clone(),
+                crate_ref(name, version),
+                Relation::DependsOn,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Record the files shipped in the bundle, with their SHA-256
+///
+/// `files` pairs each file's path inside the bundle with its location on disk. Libraries in
+/// `builds` are already attached by `attach_builds`; anything else (headers, documentation,
+/// policies) becomes a file component described by the document.
+fn attach_bundle_files(
+    sbom: &mut Sbom,
+    files: &[(String, PathBuf)],
+    builds: &[ArtifactBuild],
+) -> Result<()> {
+    for (name, path) in files {
+        if builds.iter().any(|build| build.path == *name) {
+            continue;
+        }
+        let sha256 = hex::encode(Sha256::digest(std::fs::read(path)?));
+
+        let file_ref = format!("File-{}", sanitize_spdx_id(name));
+        sbom.components.insert(
+            file_ref.clone(),
+            Component {
+                kind: ComponentKind::File,
+                name: name.clone(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                file_name: name.clone(),
+                download_location: None,
+                sha256: Some(sha256),
+                license: None,
+                supplier: Some("Organization: LeanEdge-RL Team".to_string()),
+                copyright: None,
+                description: "Bundle file".to_string(),
+                purl: None,
+                features: BTreeSet::new(),
+            },
+        );
+        sbom.describes.insert(file_ref);
+    }
+    Ok(())
+}
+
+/// Component for a crate, with its registry checksum or (for path crates) a source digest
+fn crate_component(meta: &MetadataPackage, checksums: &LockChecksums) -> Result<Component> {
+    let is_local = meta.source.is_none();
+
+    let (sha256, download_location) = match &meta.source {
+        None => {
+            let dir = meta
+                .manifest_path
+                .parent()
+                .context("Manifest path has no parent")?;
+            (Some(hash_package_sources(dir)?), None)
+        }
+        Some(source) => {
+            let checksum = checksums
+                .get(&(meta.name.clone(), meta.version.clone(), source.clone()))
+                .cloned();
+            let location = if source == CRATES_IO_SOURCE {
+                Some(format!(
+                    "https://crates.io/api/v1/crates/{}/{}/download",
+                    meta.name, meta.version
+                ))
+            } else if source.starts_with("git+") {
+                Some(source.clone())
+            } else {
+                None
+            };
+            (checksum, location)
+        }
     };
 
-    Ok(SpdxDocument {
+    Ok(Component {
+        kind: ComponentKind::Crate,
+        name: meta.name.clone(),
+        version: meta.version.clone(),
+        file_name: format!("{}-{}.crate", meta.name, meta.version),
+        download_location,
+        sha256,
+        license: spdx_license(meta.license.as_deref()),
+        supplier: if is_local { supplier(meta) } else { None },
+        copyright: is_local.then(|| format!("Copyright (c) {}", meta.authors.join(", "))),
+        description: meta.description.clone().unwrap_or_default(),
+        purl: Some(format!("pkg:cargo/{}@{}", meta.name, meta.version)),
+        features: BTreeSet::new(),
+    })
+}
+
+/// Render the model as an SPDX 2.3 document
+fn to_spdx(sbom: &Sbom) -> SpdxDocument {
+    let spdx_ref = |component_ref: &str| format!("SPDXRef-{}", component_ref);
+    let or_noassertion =
+        |value: &Option<String>| value.clone().unwrap_or_else(|| "NOASSERTION".to_string());
+
+    let packages = sbom
+        .components
+        .iter()
+        .map(|(component_ref, c)| Package {
+            spdx_id: spdx_ref(component_ref),
+            name: c.name.clone(),
+            version_info: c.version.clone(),
+            package_file_name: c.file_name.clone(),
+            download_location: or_noassertion(&c.download_location),
+            files_analyzed: false,
+            checksums: c
+                .sha256
+                .iter()
+                .map(|value| Checksum {
+                    algorithm: "SHA256".to_string(),
+                    checksum_value: value.clone(),
+                })
+                .collect(),
+            license_concluded: or_noassertion(&c.license),
+            license_declared: or_noassertion(&c.license),
+            copyright_text: or_noassertion(&c.copyright),
+            supplier: or_noassertion(&c.supplier),
+            description: c.description.clone(),
+            primary_package_purpose: Some(
+                match c.kind {
+                    ComponentKind::Crate | ComponentKind::Library => "LIBRARY",
+                    ComponentKind::Application => "APPLICATION",
+                    ComponentKind::File => "FILE",
+                }
+                .to_string(),
+            ),
+            external_refs: c
+                .purl
+                .iter()
+                .map(|purl| ExternalRef {
+                    reference_category: "PACKAGE-MANAGER".to_string(),
+                    reference_type: "purl".to_string(),
+                    reference_locator: purl.clone(),
+                })
+                .collect(),
+            comment: (!c.features.is_empty()).then(|| {
+                format!(
+                    "Features: {}",
+                    c.features.iter().cloned().collect::<Vec<_>>().join(", ")
+                )
+            }),
+        })
+        .collect();
+
+    let mut relationships: Vec<Relationship> = sbom
+        .describes
+        .iter()
+        .map(|described| Relationship {
+            spdx_element_id: "SPDXRef-DOCUMENT".to_string(),
+            related_spdx_element: spdx_ref(described),
+            relationship_type: "DESCRIBES".to_string(),
+        })
+        .collect();
+    relationships.extend(
+        sbom.relations
+            .iter()
+            .map(|(from, to, relation)| match relation {
+                Relation::DependsOn => Relationship {
+                    spdx_element_id: spdx_ref(from),
+                    related_spdx_element: spdx_ref(to),
+                    relationship_type: "DEPENDS_ON".to_string(),
+                },
+                Relation::BuildDependsOn => Relationship {
+                    spdx_element_id: spdx_ref(to),
+                    related_spdx_element: spdx_ref(from),
+                    relationship_type: "BUILD_DEPENDENCY_OF".to_string(),
+                },
+                Relation::GeneratedFrom => Relationship {
+                    spdx_element_id: spdx_ref(from),
+                    related_spdx_element: spdx_ref(to),
+                    relationship_type: "GENERATED_FROM".to_string(),
+                },
+            }),
+    );
+
+    SpdxDocument {
         spdx_id: "SPDXRef-DOCUMENT".to_string(),
         spdx_version: "SPDX-2.3".to_string(),
-        creation_info,
+        creation_info: CreationInfo {
+            creators: vec![
+                format!("Tool: leanrl-bundle-{}", env!("CARGO_PKG_VERSION")),
+                "Organization: LeanEdge-RL Team".to_string(),
+            ],
+            created: sbom.created.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            license_list_version: "3.19".to_string(),
+        },
         name: "LeanEdge-RL Software Bill of Materials".to_string(),
         data_license: "CC0-1.0".to_string(),
+        document_namespace: format!(
+            "https://github.com/leanrl/leanedge-rl/spdx/leanrl-{}-{}",
+            env!("CARGO_PKG_VERSION"),
+            sbom.serial
+        ),
         packages,
         relationships,
+    }
+}
+
+/// Validate `document` against a vendored JSON schema, listing every violation
+pub(crate) fn check_schema(schema: &str, document: &serde_json::Value) -> Result<()> {
+    let schema: serde_json::Value =
+        serde_json::from_str(schema).context("Vendored schema is not valid JSON")?;
+    let validator = jsonschema::validator_for(&schema)
+        .map_err(|err| anyhow::anyhow!("Vendored schema does not compile: {}", err))?;
+
+    let errors: Vec<String> = validator
+        .iter_errors(document)
+        .map(|err| format!("{}: {}", err.instance_path, err))
+        .collect();
+    if !errors.is_empty() {
+        anyhow::bail!("Schema violations:\n  {}", errors.join("\n  "));
+    }
+    Ok(())
+}
+
+/// Validate an SPDX 2.3 document against the spec, beyond its JSON schema: identifiers,
+/// formats, checksum lengths and relationship targets
+///
+/// Returns the bundle outputs (packages without a package URL) it lists.
+fn validate_spdx(doc: &SpdxDocument) -> Result<Vec<SbomFile>> {
+    const PURPOSES: &[&str] = &[
+        "APPLICATION",
+        "FRAMEWORK",
+        "LIBRARY",
+        "CONTAINER",
+        "OPERATING-SYSTEM",
+        "DEVICE",
+        "FIRMWARE",
+        "SOURCE",
+        "ARCHIVE",
+        "FILE",
+        "INSTALL",
+        "OTHER",
+    ];
+    const RELATIONSHIPS: &[&str] = &[
+        "DESCRIBES",
+        "DESCRIBED_BY",
+        "CONTAINS",
+        "CONTAINED_BY",
+        "DEPENDS_ON",
+        "DEPENDENCY_OF",
+        "BUILD_DEPENDENCY_OF",
+        "DEV_DEPENDENCY_OF",
+        "RUNTIME_DEPENDENCY_OF",
+        "GENERATED_FROM",
+        "GENERATES",
+        "OTHER",
+    ];
+
+    if doc.spdx_version != "SPDX-2.3" {
+        anyhow::bail!("Unsupported SPDX version: {}", doc.spdx_version);
+    }
+    if doc.spdx_id != "SPDXRef-DOCUMENT" {
+        anyhow::bail!("Document SPDXID must be SPDXRef-DOCUMENT");
+    }
+    if doc.data_license != "CC0-1.0" {
+        anyhow::bail!("dataLicense must be CC0-1.0");
+    }
+    if !doc.document_namespace.contains("://") || doc.document_namespace.contains('#') {
+        anyhow::bail!(
+            "documentNamespace must be an absolute URI without fragment: {}",
+            doc.document_namespace
+        );
+    }
+    chrono::NaiveDateTime::parse_from_str(&doc.creation_info.created, "%Y-%m-%dT%H:%M:%SZ")
+        .context("creationInfo.created must be YYYY-MM-DDThh:mm:ssZ")?;
+    if doc.creation_info.creators.is_empty()
+        || !doc
+            .creation_info
+            .creators
+            .iter()
+            .all(|c| is_actor(c, false))
+    {
+        anyhow::bail!("creationInfo.creators must be Tool:/Organization:/Person: entries");
+    }
+
+    let mut ids = BTreeSet::new();
+    let mut files = Vec::new();
+    for package in &doc.packages {
+        let id = &package.spdx_id;
+        if !is_spdx_id(id) {
+            anyhow::bail!("Invalid SPDXID: {}", id);
+        }
+        if !ids.insert(id.as_str()) {
+            anyhow::bail!("Duplicate SPDXID: {}", id);
+        }
+        if package.name.is_empty() || package.download_location.is_empty() {
+            anyhow::bail!("{}: name and downloadLocation are required", id);
+        }
+        if !is_actor(&package.supplier, true) {
+            anyhow::bail!("{}: invalid supplier {}", id, package.supplier);
+        }
+        if let Some(purpose) = &package.primary_package_purpose {
+            if !PURPOSES.contains(&purpose.as_str()) {
+                anyhow::bail!("{}: invalid primaryPackagePurpose {}", id, purpose);
+            }
+        }
+        for checksum in &package.checksums {
+            let len = match checksum.algorithm.as_str() {
+                "SHA1" => 40,
+                "SHA256" | "SHA3-256" | "BLAKE2b-256" => 64,
+                "SHA384" | "SHA3-384" | "BLAKE2b-384" => 96,
+                "SHA512" | "SHA3-512" | "BLAKE2b-512" => 128,
+                other => anyhow::bail!("{}: unsupported checksum algorithm {}", id, other),
+            };
+            if !is_hex(&checksum.checksum_value, len) {
+                anyhow::bail!("{}: malformed {} checksum", id, checksum.algorithm);
+            }
+        }
+
+        if package.external_refs.is_empty() {
+            files.push(SbomFile {
+                name: package.package_file_name.clone(),
+                sha256: package
+                    .checksums
+                    .iter()
+                    .find(|c| c.algorithm == "SHA256")
+                    .map(|c| c.checksum_value.clone()),
+            });
+        }
+    }
+
+    for relationship in &doc.relationships {
+        if !RELATIONSHIPS.contains(&relationship.relationship_type.as_str()) {
+            anyhow::bail!(
+                "Invalid relationshipType: {}",
+                relationship.relationship_type
+            );
+        }
+        for element in [
+            &relationship.spdx_element_id,
+            &relationship.related_spdx_element,
+        ] {
+            if element != "SPDXRef-DOCUMENT" && !ids.contains(element.as_str()) {
+                anyhow::bail!("Relationship references unknown element {}", element);
+            }
+        }
+    }
+    if !doc
+        .relationships
+        .iter()
+        .any(|r| r.relationship_type == "DESCRIBES")
+    {
+        anyhow::bail!("Document does not DESCRIBE any package");
+    }
+
+    Ok(files)
+}
+
+/// File produced for a target kind, and its component kind; `None` for kinds that are not shipped
+fn artifact_file_name(
+    name: &str,
+    kind: &str,
+    target: Option<&str>,
+) -> Option<(String, ComponentKind)> {
+    let target = target.unwrap_or(std::env::consts::OS);
+    let lib_name = name.replace('-', "_");
+    let library = ComponentKind::Library;
+
+    match kind {
+        "staticlib" if target.contains("windows") => Some((format!("{}.lib", lib_name), library)),
+        "staticlib" => Some((format!("lib{}.a", lib_name), library)),
+        "cdylib" if target.contains("windows") => Some((format!("{}.dll", lib_name), library)),
+        "cdylib" if target.contains("apple") || target.contains("macos") => {
+            Some((format!("lib{}.dylib", lib_name), library))
+        }
+        "cdylib" => Some((format!("lib{}.so", lib_name), library)),
+        "bin" if target.contains("windows") => {
+            Some((format!("{}.exe", name), ComponentKind::Application))
+        }
+        "bin" => Some((name.to_string(), ComponentKind::Application)),
+        _ => None,
+    }
+}
+
+/// Normalize a Cargo license field to an SPDX expression
+fn spdx_license(license: Option<&str>) -> Option<String> {
+    match license {
+        // Legacy `MIT/Apache-2.0` syntax means OR
+        Some(license) if license.contains('/') => Some(
+            license
+                .split('/')
+                .map(str::trim)
+                .collect::<Vec<_>>()
+                .join(" OR "),
+        ),
+        Some(license) => Some(license.to_string()),
+        None => None,
+    }
+}
+
+fn supplier(meta: &MetadataPackage) -> Option<String> {
+    (!meta.authors.is_empty()).then(|| format!("Organization: {}", meta.authors.join(", ")))
+}
+
+/// Model reference of a crate; `SPDXRef-` is prepended for SPDX
+fn crate_ref(name: &str, version: &str) -> String {
+    format!(
+        "Package-{}",
+        sanitize_spdx_id(&format!("{}-{}", name, version))
+    )
+}
+
+/// Random (version 4) UUID used as document serial number
+fn random_uuid() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).context("Failed to generate document serial")?;
+    Ok(format_uuid(bytes, 0x40))
+}
+
+/// Serial number derived from the document content (version 5 UUID over its SHA-256, since
+/// CycloneDX only accepts versions 1-5), so that reproducible builds produce identical documents
+fn content_uuid(sbom: &Sbom) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(sbom.created.to_rfc3339().as_bytes());
+    for (component_ref, component) in &sbom.components {
+        hasher.update(component_ref.as_bytes());
+        hasher.update(component.version.as_bytes());
+        hasher.update(component.sha256.as_deref().unwrap_or_default().as_bytes());
+    }
+    for (from, to, relation) in &sbom.relations {
+        hasher.update(format!("{} {:?} {}", from, relation, to).as_bytes());
+    }
+
+    let mut name_hasher = Sha1::new();
+    name_hasher.update(SERIAL_NAMESPACE);
+    name_hasher.update(hex::encode(hasher.finalize()).as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&name_hasher.finalize()[..16]);
+    format_uuid(bytes, 0x50)
+}
+
+/// Hyphenated UUID with the given version nibble and the RFC 4122 variant
+fn format_uuid(mut bytes: [u8; 16], version: u8) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | version;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// `SPDXRef-` followed by letters, digits, `.` or `-`
+fn is_spdx_id(id: &str) -> bool {
+    id.strip_prefix("SPDXRef-").is_some_and(|rest| {
+        !rest.is_empty()
+            && rest
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
     })
 }
 
-/// Calculate file hash
-fn calculate_file_hash(file_path: &str) -> Result<String> {
-    // In a real implementation, this would read the actual file
-    // For now, we'll create a placeholder hash
+/// SPDX actor (`Tool:`, `Organization:`, `Person:`), optionally allowing NOASSERTION
+fn is_actor(value: &str, allow_noassertion: bool) -> bool {
+    (allow_noassertion && value == "NOASSERTION")
+        || ["Tool: ", "Organization: ", "Person: "]
+            .iter()
+            .any(|prefix| value.strip_prefix(prefix).is_some_and(|v| !v.is_empty()))
+}
+
+pub fn is_hex(value: &str, len: usize) -> bool {
+    value.len() == len && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// SPDX identifiers may only contain letters, digits, `.` and `-`
+fn sanitize_spdx_id(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Digest of a path crate's sources: sorted relative paths and file contents, excluding `target/`
+fn hash_package_sources(dir: &Path) -> Result<String> {
+    let mut files: Vec<PathBuf> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != "target")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.path().strip_prefix(dir).unwrap_or(e.path()).to_path_buf())
+        .collect();
+    files.sort();
+
     let mut hasher = Sha256::new();
-    hasher.update(file_path.as_bytes());
-    let hash = hasher.finalize();
-    Ok(hex::encode(hash))
+    for file in files {
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update([0u8]);
+        hasher.update(Sha256::digest(std::fs::read(dir.join(&file))?));
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TREE: &str = "\
+0demo v0.1.0 (/work/demo)|default,std
+1serde v1.0.0|default,derive
+2serde_derive v1.0.0 (proc-macro)|default
+1libc v0.2.0|
+1serde v1.0.0|default,derive (*)
+";
+
+    #[test]
+    fn test_parse_tree_edges_and_features() {
+        let mut graph = ArtifactGraph::default();
+        parse_tree(TREE, &mut graph, false).unwrap();
+
+        let demo = ("demo".to_string(), "0.1.0".to_string());
+        let serde = ("serde".to_string(), "1.0.0".to_string());
+        let derive = ("serde_derive".to_string(), "1.0.0".to_string());
+
+        assert_eq!(graph.packages.len(), 4);
+        assert!(graph.packages[&demo].contains("std"));
+        assert_eq!(graph.packages[&serde].len(), 2);
+        assert!(graph.normal.contains(&(demo.clone(), serde.clone())));
+        assert!(graph.normal.contains(&(serde, derive)));
+        assert_eq!(graph.normal.len(), 3);
+    }
+
+    #[test]
+    fn test_spdx_license_and_ids() {
+        assert_eq!(
+            spdx_license(Some("MIT/Apache-2.0")).unwrap(),
+            "MIT OR Apache-2.0"
+        );
+        assert_eq!(
+            spdx_license(Some("MIT OR Apache-2.0")).unwrap(),
+            "MIT OR Apache-2.0"
+        );
+        assert_eq!(spdx_license(None), None);
+        assert_eq!(
+            crate_ref("zstd-sys", "2.0.16+zstd.1.5.7"),
+            "Package-zstd-sys-2.0.16-zstd.1.5.7"
+        );
+        assert!(is_spdx_id("SPDXRef-Package-zstd-sys-2.0.16-zstd.1.5.7"));
+        assert!(!is_spdx_id("SPDXRef-Package_x"));
+    }
+
+    #[test]
+    fn test_validate_spdx_rejects_dangling_relationship() {
+        let sbom = sample_sbom();
+        let mut doc = to_spdx(&sbom);
+        assert_eq!(validate_spdx(&doc).unwrap().len(), 1);
+
+        doc.relationships.push(Relationship {
+            spdx_element_id: "SPDXRef-Package-demo-0.1.0".to_string(),
+            related_spdx_element: "SPDXRef-Package-missing-1.0.0".to_string(),
+            relationship_type: "DEPENDS_ON".to_string(),
+        });
+        assert!(validate_spdx(&doc).is_err());
+    }
+
+    #[test]
+    fn test_cyclonedx_roundtrip_and_validation() {
+        let sbom = sample_sbom();
+        let doc = cyclonedx::from_sbom(&sbom);
+        let json = serde_json::to_string(&doc).unwrap();
+        let parsed: cyclonedx::CycloneDxDocument = serde_json::from_str(&json).unwrap();
+
+        let files = cyclonedx::validate(&parsed).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "libdemo.a");
+        assert_eq!(files[0].sha256.as_deref(), Some("cd".repeat(32).as_str()));
+
+        let mut broken = parsed;
+        broken.spec_version = "1.2".to_string();
+        assert!(cyclonedx::validate(&broken).is_err());
+    }
+
+    #[test]
+    fn test_documents_match_vendored_schemas() {
+        let mut sbom = sample_sbom();
+        // Reproducible serial numbers too
+        sbom.serial = content_uuid(&sbom);
+
+        let mut cyclonedx = serde_json::to_value(cyclonedx::from_sbom(&sbom)).unwrap();
+        check_schema(cyclonedx::SCHEMA, &cyclonedx).unwrap();
+        cyclonedx["components"][0]["type"] = "widget".into();
+        cyclonedx["components"][0]["hashes"][0]["content"] = "xyz".into();
+        let err = check_schema(cyclonedx::SCHEMA, &cyclonedx).unwrap_err();
+        assert!(err.to_string().contains("/components/0/type"), "{err}");
+        assert!(
+            err.to_string().contains("/components/0/hashes/0/content"),
+            "{err}"
+        );
+
+        let mut spdx = serde_json::to_value(to_spdx(&sbom)).unwrap();
+        check_schema(SPDX_SCHEMA, &spdx).unwrap();
+        spdx["relationships"][0]["relationshipType"] = "LINKS_TO".into();
+        spdx["packages"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("downloadLocation");
+        let err = check_schema(SPDX_SCHEMA, &spdx).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("/relationships/0/relationshipType"),
+            "{err}"
+        );
+        assert!(err.to_string().contains("downloadLocation"), "{err}");
+    }
+
+    #[test]
+    fn test_workspace_sbom_from_cargo_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let default_lib = dir.path().join("default.a");
+        let bare_lib = dir.path().join("bare.a");
+        let header = dir.path().join("leanrl.h");
+        std::fs::write(&default_lib, b"archive").unwrap();
+        std::fs::write(&bare_lib, b"bare archive").unwrap();
+        std::fs::write(&header, b"header").unwrap();
+
+        let default_path = "lib/host/default/libleanrl_core.a".to_string();
+        let bare_path = "lib/host/no-default/libleanrl_core.a".to_string();
+        let shipped = [
+            (default_path.clone(), default_lib),
+            (bare_path.clone(), bare_lib),
+            ("include/leanrl.h".to_string(), header),
+        ];
+        let host = crate::bundle::rustc_version().unwrap().host;
+        let builds: Vec<ArtifactBuild> = shipped[..2]
+            .iter()
+            .map(|(name, path)| ArtifactBuild {
+                path: name.clone(),
+                sha256: hex::encode(Sha256::digest(std::fs::read(path).unwrap())),
+                package: "leanrl_core".to_string(),
+                target: host.clone(),
+                profile: "release".to_string(),
+                features: Vec::new(),
+                default_features: *name == default_path,
+                rustc: String::new(),
+            })
+            .collect();
+
+        let sbom_path = dir.path().join("sbom.json");
+        generate_bundle_sbom(&sbom_path, SbomFormat::Spdx, &shipped, &builds, None).unwrap();
+
+        let sbom: SpdxDocument =
+            serde_json::from_str(&std::fs::read_to_string(&sbom_path).unwrap()).unwrap();
+        let names: Vec<&str> = sbom.packages.iter().map(|p| p.name.as_str()).collect();
+        assert!(names.contains(&"leanrl_core"));
+        assert!(names.contains(&default_path.as_str()));
+        assert!(!names.contains(&"libleanrl_core.a"));
+        assert!(!names.contains(&"packed_simd_2"));
+        assert!(!names.contains(&"cxx"));
+        // Dev-dependencies are not shipped
+        assert!(!names.contains(&"criterion"));
+
+        let thiserror = sbom
+            .packages
+            .iter()
+            .find(|p| p.name == "thiserror")
+            .unwrap();
+        assert_eq!(thiserror.checksums[0].checksum_value.len(), 64);
+
+        assert!(sbom.relationships.iter().any(|r| {
+            r.spdx_element_id
+                .starts_with("SPDXRef-Package-leanrl-core-")
+                && r.related_spdx_element
+                    .starts_with("SPDXRef-Package-thiserror-")
+                && r.relationship_type == "DEPENDS_ON"
+        }));
+
+        // Each library carries the graph of its own build: `std` (and with it `log/std`) is
+        // only activated with the default features
+        let depends_on = |artifact: &str, package: &str| {
+            let id = format!("SPDXRef-Artifact-{}", sanitize_spdx_id(artifact));
+            sbom.relationships.iter().any(|r| {
+                r.spdx_element_id == id
+                    && r.related_spdx_element
+                        .starts_with(&format!("SPDXRef-Package-{}-", package))
+                    && r.relationship_type == "DEPENDS_ON"
+            })
+        };
+        assert!(depends_on(&default_path, "thiserror"));
+        assert!(depends_on(&bare_path, "thiserror"));
+        assert!(depends_on(&default_path, "log"));
+        assert!(!depends_on(&bare_path, "log"));
+
+        // Cross-check against the bundle contents
+        let mut bundle_files = BTreeMap::new();
+        for (name, path) in &shipped {
+            bundle_files.insert(
+                name.clone(),
+                hex::encode(Sha256::digest(std::fs::read(path).unwrap())),
+            );
+        }
+        verify_sbom(&sbom_path, &bundle_files).unwrap();
+
+        bundle_files.insert(default_path.clone(), "00".repeat(32));
+        assert!(verify_sbom(&sbom_path, &bundle_files).is_err());
+
+        bundle_files.remove(&default_path);
+        assert!(verify_sbom(&sbom_path, &bundle_files).is_err());
+    }
+
+    /// One crate, one artifact built from it
+    fn sample_sbom() -> Sbom {
+        let mut components = BTreeMap::new();
+        components.insert(
+            "Package-demo-0.1.0".to_string(),
+            Component {
+                kind: ComponentKind::Crate,
+                name: "demo".to_string(),
+                version: "0.1.0".to_string(),
+                file_name: "demo-0.1.0.crate".to_string(),
+                download_location: None,
+                sha256: Some("ab".repeat(32)),
+                license: Some("MIT".to_string()),
+                supplier: Some("Organization: Demo".to_string()),
+                copyright: None,
+                description: "Demo crate".to_string(),
+                purl: Some("pkg:cargo/demo@0.1.0".to_string()),
+                features: ["std".to_string()].into(),
+            },
+        );
+        components.insert(
+            "Artifact-libdemo.a".to_string(),
+            Component {
+                kind: ComponentKind::Library,
+                name: "libdemo.a".to_string(),
+                version: "0.1.0".to_string(),
+                file_name: "libdemo.a".to_string(),
+                download_location: None,
+                sha256: Some("cd".repeat(32)),
+                license: Some("MIT".to_string()),
+                supplier: None,
+                copyright: None,
+                description: "staticlib built from demo".to_string(),
+                purl: None,
+                features: BTreeSet::new(),
+            },
+        );
+
+        Sbom {
+            created: Utc::now(),
+            serial: random_uuid().unwrap(),
+            components,
+            describes: ["Package-demo-0.1.0".to_string()].into(),
+            relations: [(
+                "Artifact-libdemo.a".to_string(),
+                "Package-demo-0.1.0".to_string(),
+                Relation::GeneratedFrom,
+            )]
+            .into(),
+        }
+    }
 }
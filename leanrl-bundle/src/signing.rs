@@ -1,42 +1,724 @@
-use anyhow::Result;
-use std::path::Path;
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat, Utc};
+use p256::ecdsa::{signature::Signer, signature::Verifier, DerSignature, Signature};
+use p256::ecdsa::{SigningKey, VerifyingKey};
+use p256::pkcs8::{
+    DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding,
+};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use x509_cert::builder::{Builder, CertificateBuilder, Profile};
+use x509_cert::der::asn1::Ia5String;
+use x509_cert::der::{DecodePem, Encode, EncodePem};
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::ext::pkix::SubjectAltName;
+use x509_cert::name::Name;
+use x509_cert::serial_number::SerialNumber;
+use x509_cert::spki::SubjectPublicKeyInfoOwned;
+use x509_cert::time::Validity;
+use x509_cert::Certificate;
 
-/// Sign bundle with Sigstore
-pub async fn sign_bundle(bundle_path: &Path) -> Result<()> {
-    println!("Signing bundle with Sigstore: {}", bundle_path.display());
+/// Lifetime of certificates issued by the local Fulcio stand-in (matches Fulcio's 10 minutes)
+const LEAF_CERT_LIFETIME: Duration = Duration::from_secs(10 * 60);
 
-    // In a real implementation, this would integrate with Sigstore
-    // For now, we'll create a placeholder signature file
+/// Lifetime of a locally generated Fulcio stand-in root
+const ROOT_CERT_LIFETIME: Duration = Duration::from_secs(365 * 24 * 60 * 60);
 
-    let sig_path = bundle_path.with_extension("zip.sig");
-    let sig_content = format!(
-        "SIGSTORE_PLACEHOLDER\nBundle: {}\nTimestamp: {}\nSignature: placeholder_signature",
-        bundle_path.display(),
-        chrono::Utc::now()
-    );
+/// Signature bundle written next to the signed file (`<bundle>.zip.sig`, or
+/// `MANIFEST.json.sig` inside the bundle)
+///
+/// `base64Signature` and `cert` follow the layout of `cosign sign-blob --bundle`: an ASN.1 DER
+/// ECDSA P-256/SHA-256 signature over the file, and optionally the base64-encoded PEM signing
+/// certificate. `payloadDigest`, `signedAt` and `signedAtSignature` are leanrl extensions that
+/// cosign ignores; `signedAtSignature` is made with the same key over the payload digest and
+/// `signedAt` (see [`timestamp_statement`]), so the signing time cannot be edited after the fact.
+/// A bundle written by cosign itself carries none of them (its `rekorBundle` is ignored) and is
+/// verified on the signature and certificate alone.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignatureBundle {
+    #[serde(rename = "base64Signature")]
+    pub base64_signature: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert: Option<String>,
+    #[serde(
+        rename = "payloadDigest",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub payload_digest: Option<PayloadDigest>,
+    #[serde(rename = "signedAt", default, skip_serializing_if = "Option::is_none")]
+    pub signed_at: Option<DateTime<Utc>>,
+    #[serde(
+        rename = "signedAtSignature",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub signed_at_signature: Option<String>,
+}
+
+/// Digest of the signed payload
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayloadDigest {
+    pub algorithm: String,
+    pub value: String,
+}
+
+/// How to sign: an offline key, or an ephemeral key certified by a (local) Fulcio CA
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct SignOptions {
+    /// PKCS#8 PEM ECDSA P-256 private key (see `keygen`)
+    #[arg(long, env = "LEANRL_SIGNING_KEY", global = true)]
+    pub signing_key: Option<PathBuf>,
+
+    /// Private key of the Fulcio stand-in CA for keyless signing
+    #[arg(long, global = true, requires = "fulcio_ca_cert")]
+    pub fulcio_ca_key: Option<PathBuf>,
+
+    /// Certificate of the Fulcio stand-in CA for keyless signing
+    #[arg(long, global = true, requires = "fulcio_ca_key")]
+    pub fulcio_ca_cert: Option<PathBuf>,
+
+    /// Identity (email) bound into the keyless signing certificate
+    #[arg(long, global = true)]
+    pub identity: Option<String>,
+}
+
+/// Trust anchors for verifying a signature bundle
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct VerifyOptions {
+    /// SPKI PEM public key matching an offline signing key
+    #[arg(long)]
+    pub public_key: Option<PathBuf>,
+
+    /// Root certificate that must have issued the bundle's signing certificate
+    #[arg(long)]
+    pub ca_cert: Option<PathBuf>,
+
+    /// Identity (email SAN) the signing certificate must carry; required with --ca-cert
+    #[arg(long, requires = "ca_cert")]
+    pub certificate_identity: Option<String>,
+
+    /// Issuer name (e.g. `CN=leanrl-fulcio`) the signing certificate must carry; required with
+    /// --ca-cert
+    #[arg(long, requires = "ca_cert")]
+    pub certificate_issuer: Option<String>,
+}
+
+/// Local stand-in for Fulcio: a CA that certifies ephemeral signing keys for an identity
+pub struct LocalFulcio {
+    ca_key: SigningKey,
+    ca_cert: Certificate,
+}
+
+impl LocalFulcio {
+    /// Create a new self-signed CA
+    pub fn generate(common_name: &str) -> Result<Self> {
+        let ca_key = SigningKey::random(&mut OsRng);
+        let subject = Name::from_str(&format!("CN={}", common_name))?;
+        let spki = SubjectPublicKeyInfoOwned::from_key(*ca_key.verifying_key())?;
+
+        let builder = CertificateBuilder::new(
+            Profile::Root,
+            random_serial()?,
+            Validity::from_now(ROOT_CERT_LIFETIME)?,
+            subject,
+            spki,
+            &ca_key,
+        )?;
+        let ca_cert = builder.build::<DerSignature>()?;
+
+        Ok(Self { ca_key, ca_cert })
+    }
+
+    /// Load a CA from PEM files
+    pub fn load(key_path: &Path, cert_path: &Path) -> Result<Self> {
+        let ca_key = read_signing_key(key_path)?;
+        let ca_cert = read_certificate(cert_path)?;
+        Ok(Self { ca_key, ca_cert })
+    }
+
+    /// Write the CA key and certificate as PEM files
+    pub fn write(&self, key_path: &Path, cert_path: &Path) -> Result<()> {
+        write_signing_key(&self.ca_key, key_path)?;
+        std::fs::write(cert_path, self.ca_cert.to_pem(LineEnding::LF)?)?;
+        Ok(())
+    }
+
+    /// Issue a short-lived leaf certificate binding `public_key` to `identity`
+    pub fn issue(&self, identity: &str, public_key: &VerifyingKey) -> Result<Certificate> {
+        let issuer = self.ca_cert.tbs_certificate.subject.clone();
+        let spki = SubjectPublicKeyInfoOwned::from_key(*public_key)?;
+
+        let mut builder = CertificateBuilder::new(
+            Profile::Leaf {
+                issuer,
+                enable_key_agreement: false,
+                enable_key_encipherment: false,
+            },
+            random_serial()?,
+            Validity::from_now(LEAF_CERT_LIFETIME)?,
+            Name::default(),
+            spki,
+            &self.ca_key,
+        )?;
+        builder.add_extension(&SubjectAltName(vec![GeneralName::Rfc822Name(
+            Ia5String::new(identity)?,
+        )]))?;
+
+        Ok(builder.build::<DerSignature>()?)
+    }
+}
+
+/// Generate an offline ECDSA P-256 keypair (`leanrl-signing.key` / `leanrl-signing.pub`)
+///
+/// The private key is unencrypted PKCS#8 PEM, which cosign cannot read (it expects its own
+/// encrypted format), hence not `cosign.key`; the SPKI public key works with cosign as is.
+pub fn generate_keypair(output_dir: &Path) -> Result<(PathBuf, PathBuf)> {
+    std::fs::create_dir_all(output_dir)?;
+
+    let signing_key = SigningKey::random(&mut OsRng);
+    let key_path = output_dir.join("leanrl-signing.key");
+    let pub_path = output_dir.join("leanrl-signing.pub");
+
+    write_signing_key(&signing_key, &key_path)?;
+    std::fs::write(
+        &pub_path,
+        signing_key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)?,
+    )?;
+
+    println!("Private key written: {}", key_path.display());
+    println!("Public key written: {}", pub_path.display());
+    Ok((key_path, pub_path))
+}
+
+/// Sign bundle, writing a cosign-style signature bundle to `<bundle>.zip.sig`
+pub async fn sign_bundle(bundle_path: &Path, options: &SignOptions) -> Result<()> {
+    println!("Signing bundle: {}", bundle_path.display());
 
-    std::fs::write(&sig_path, sig_content)?;
+    let payload = std::fs::read(bundle_path)
+        .with_context(|| format!("Failed to read {}", bundle_path.display()))?;
+    let bundle = sign_payload(&payload, options)?;
+
+    let sig_path = bundle_path.with_extension("zip.sig");
+    std::fs::write(&sig_path, serde_json::to_string_pretty(&bundle)?)?;
 
     println!("Bundle signed: {}", sig_path.display());
     Ok(())
 }
 
+/// Sign a payload held in memory (e.g. the in-bundle manifest)
+pub fn sign_payload(payload: &[u8], options: &SignOptions) -> Result<SignatureBundle> {
+    let (signing_key, cert) = match (&options.signing_key, &options.fulcio_ca_key) {
+        (Some(key_path), _) => (read_signing_key(key_path)?, None),
+        (None, Some(ca_key_path)) => {
+            let ca_cert_path = options
+                .fulcio_ca_cert
+                .as_ref()
+                .context("--fulcio-ca-cert is required for keyless signing")?;
+            let identity = options
+                .identity
+                .as_deref()
+                .context("--identity is required for keyless signing")?;
+
+            // Keyless flow: certify an ephemeral key, sign, then discard the key
+            let fulcio = LocalFulcio::load(ca_key_path, ca_cert_path)?;
+            let ephemeral = SigningKey::random(&mut OsRng);
+            let cert = fulcio.issue(identity, ephemeral.verifying_key())?;
+            (ephemeral, Some(cert))
+        }
+        (None, None) => anyhow::bail!(
+            "No signing key configured: pass --signing-key (or LEANRL_SIGNING_KEY) or --fulcio-ca-key/--fulcio-ca-cert"
+        ),
+    };
+
+    let digest = hex::encode(Sha256::digest(payload));
+    let signed_at = Utc::now();
+    let signature: Signature = signing_key.sign(payload);
+    let signed_at_signature: Signature =
+        signing_key.sign(timestamp_statement(&digest, signed_at).as_bytes());
+    Ok(SignatureBundle {
+        base64_signature: BASE64.encode(signature.to_der().as_bytes()),
+        cert: cert
+            .map(|c| c.to_pem(LineEnding::LF).map(|pem| BASE64.encode(pem)))
+            .transpose()?,
+        payload_digest: Some(PayloadDigest {
+            algorithm: "sha256".to_string(),
+            value: digest,
+        }),
+        signed_at: Some(signed_at),
+        signed_at_signature: Some(BASE64.encode(signed_at_signature.to_der().as_bytes())),
+    })
+}
+
+/// Message signed into `signedAtSignature`: binds the signing time to the payload digest
+fn timestamp_statement(digest: &str, signed_at: DateTime<Utc>) -> String {
+    format!(
+        "leanrl-signed-at/v1 sha256:{} {}",
+        digest,
+        signed_at.to_rfc3339_opts(SecondsFormat::Nanos, true)
+    )
+}
+
 /// Verify signature
-pub async fn verify_signature(_bundle_path: &Path, sig_path: &Path) -> Result<()> {
+pub async fn verify_signature(
+    bundle_path: &Path,
+    sig_path: &Path,
+    options: &VerifyOptions,
+) -> Result<()> {
     println!("Verifying signature: {}", sig_path.display());
 
-    // In a real implementation, this would verify the actual signature
-    // For now, we'll just check that the signature file exists and contains expected content
-
     if !sig_path.exists() {
         anyhow::bail!("Signature file not found: {}", sig_path.display());
     }
 
-    let sig_content = std::fs::read_to_string(sig_path)?;
-    if !sig_content.contains("SIGSTORE_PLACEHOLDER") {
-        anyhow::bail!("Invalid signature format");
+    let bundle: SignatureBundle = serde_json::from_str(&std::fs::read_to_string(sig_path)?)
+        .context("Invalid signature bundle format")?;
+    let payload = std::fs::read(bundle_path)?;
+    verify_payload(&payload, &bundle, options)
+}
+
+/// Verify a signature bundle over a payload held in memory
+pub fn verify_payload(
+    payload: &[u8],
+    bundle: &SignatureBundle,
+    options: &VerifyOptions,
+) -> Result<()> {
+    // Payload digest
+    let digest = hex::encode(Sha256::digest(payload));
+    if let Some(payload_digest) = &bundle.payload_digest {
+        if payload_digest.algorithm != "sha256" {
+            anyhow::bail!(
+                "Unsupported payload digest algorithm: {}",
+                payload_digest.algorithm
+            );
+        }
+        if digest != payload_digest.value {
+            anyhow::bail!(
+                "Payload digest mismatch: signature covers {}, file is {}",
+                payload_digest.value,
+                digest
+            );
+        }
     }
 
-    println!("Signature verification passed");
+    // Resolve the verifying key from the trust anchor
+    let verifying_key = match (&options.public_key, &options.ca_cert) {
+        (Some(pub_path), _) => {
+            let verifying_key = read_verifying_key(pub_path)?;
+            verify_signed_at(&verifying_key, &digest, bundle)?;
+            verifying_key
+        }
+        (None, Some(ca_path)) => {
+            let cert_pem = bundle
+                .cert
+                .as_deref()
+                .context("Signature bundle has no certificate to verify against --ca-cert")?;
+            let identity = options
+                .certificate_identity
+                .as_deref()
+                .context("--certificate-identity is required with --ca-cert")?;
+            let issuer = options
+                .certificate_issuer
+                .as_deref()
+                .context("--certificate-issuer is required with --ca-cert")?;
+            let cert = Certificate::from_pem(BASE64.decode(cert_pem)?)?;
+            let verifying_key =
+                verify_certificate(&cert, &read_certificate(ca_path)?, identity, issuer)?;
+            match verify_signed_at(&verifying_key, &digest, bundle)? {
+                Some(signed_at) => check_validity(&cert, signed_at)?,
+                None => println!(
+                    "  ! No signedAt in the signature bundle; certificate validity not checked"
+                ),
+            }
+            verifying_key
+        }
+        (None, None) => anyhow::bail!(
+            "Signature present but no trust anchor given: pass --public-key or --ca-cert"
+        ),
+    };
+
+    // Signature
+    let signature = Signature::from_der(&BASE64.decode(&bundle.base64_signature)?)
+        .context("Malformed signature")?;
+    verifying_key
+        .verify(payload, &signature)
+        .map_err(|_| anyhow::anyhow!("Signature verification failed"))?;
+
     Ok(())
 }
+
+/// Check that `signedAtSignature` covers the payload `digest` and `signedAt`, and return the
+/// signing time; `None` for a bundle without one (e.g. written by cosign)
+fn verify_signed_at(
+    verifying_key: &VerifyingKey,
+    digest: &str,
+    bundle: &SignatureBundle,
+) -> Result<Option<DateTime<Utc>>> {
+    let (signed_at, signed_at_signature) = match (bundle.signed_at, &bundle.signed_at_signature) {
+        (Some(signed_at), Some(signature)) => (signed_at, signature),
+        (None, None) => return Ok(None),
+        _ => anyhow::bail!("signedAt is not covered by the signature"),
+    };
+    let signature = Signature::from_der(&BASE64.decode(signed_at_signature)?)
+        .context("Malformed signedAt signature")?;
+    let statement = timestamp_statement(digest, signed_at);
+    verifying_key
+        .verify(statement.as_bytes(), &signature)
+        .map_err(|_| anyhow::anyhow!("signedAt is not covered by the signature"))?;
+    Ok(Some(signed_at))
+}
+
+/// Check a leaf certificate against the trusted root, identity and issuer, and return its
+/// public key
+fn verify_certificate(
+    cert: &Certificate,
+    root: &Certificate,
+    identity: &str,
+    issuer: &str,
+) -> Result<VerifyingKey> {
+    if cert.tbs_certificate.issuer != root.tbs_certificate.subject {
+        anyhow::bail!("Signing certificate was not issued by the trusted CA");
+    }
+    let cert_issuer = cert.tbs_certificate.issuer.to_string();
+    if cert_issuer != issuer {
+        anyhow::bail!(
+            "Certificate issuer mismatch: expected {}, found {}",
+            issuer,
+            cert_issuer
+        );
+    }
+
+    let root_key =
+        VerifyingKey::from_public_key_der(&root.tbs_certificate.subject_public_key_info.to_der()?)?;
+    let cert_signature = Signature::from_der(cert.signature.raw_bytes())
+        .context("Malformed certificate signature")?;
+    root_key
+        .verify(&cert.tbs_certificate.to_der()?, &cert_signature)
+        .map_err(|_| anyhow::anyhow!("Certificate signature verification failed"))?;
+
+    let identities = certificate_identities(cert)?;
+    if !identities.iter().any(|id| id == identity) {
+        anyhow::bail!(
+            "Certificate identity mismatch: expected {}, found {:?}",
+            identity,
+            identities
+        );
+    }
+
+    Ok(VerifyingKey::from_public_key_der(
+        &cert.tbs_certificate.subject_public_key_info.to_der()?,
+    )?)
+}
+
+/// Check that the (signature-bound) signing time falls within the certificate's validity
+fn check_validity(cert: &Certificate, signed_at: DateTime<Utc>) -> Result<()> {
+    let validity = &cert.tbs_certificate.validity;
+    let not_before: DateTime<Utc> = validity.not_before.to_system_time().into();
+    let not_after: DateTime<Utc> = validity.not_after.to_system_time().into();
+    if signed_at < not_before || signed_at > not_after {
+        anyhow::bail!(
+            "Signing time {} is outside certificate validity ({} to {})",
+            signed_at,
+            not_before,
+            not_after
+        );
+    }
+    Ok(())
+}
+
+/// Email identities from a certificate's SubjectAltName extension
+fn certificate_identities(cert: &Certificate) -> Result<Vec<String>> {
+    use x509_cert::der::Decode;
+    use x509_cert::ext::pkix::name::GeneralNames;
+
+    let mut identities = Vec::new();
+    for ext in cert.tbs_certificate.extensions.iter().flatten() {
+        if ext.extn_id == <SubjectAltName as x509_cert::der::oid::AssociatedOid>::OID {
+            for name in GeneralNames::from_der(ext.extn_value.as_bytes())? {
+                if let GeneralName::Rfc822Name(email) = name {
+                    identities.push(email.to_string());
+                }
+            }
+        }
+    }
+    Ok(identities)
+}
+
+/// Random positive 64-bit certificate serial number
+fn random_serial() -> Result<SerialNumber> {
+    let mut bytes = [0u8; 8];
+    rand_core::RngCore::fill_bytes(&mut OsRng, &mut bytes);
+    bytes[0] &= 0x7f;
+    bytes[0] |= 0x01;
+    Ok(SerialNumber::new(&bytes)?)
+}
+
+fn read_signing_key(path: &Path) -> Result<SigningKey> {
+    let pem = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read signing key {}", path.display()))?;
+    SigningKey::from_pkcs8_pem(&pem).context("Signing key must be a PKCS#8 PEM P-256 key")
+}
+
+fn write_signing_key(key: &SigningKey, path: &Path) -> Result<()> {
+    let pem = key.to_pkcs8_pem(LineEnding::LF)?;
+    write_private_key(path, pem.as_bytes())
+}
+
+/// Create a private key file readable by its owner only (0600 on unix), refusing to replace
+/// an existing key
+pub fn write_private_key(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path).map_err(|err| {
+        if err.kind() == std::io::ErrorKind::AlreadyExists {
+            anyhow::anyhow!(
+                "{} already exists; refusing to overwrite a private key",
+                path.display()
+            )
+        } else {
+            anyhow::Error::new(err).context(format!("Failed to create {}", path.display()))
+        }
+    })?;
+    file.write_all(contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+fn read_verifying_key(path: &Path) -> Result<VerifyingKey> {
+    let pem = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read public key {}", path.display()))?;
+    VerifyingKey::from_public_key_pem(&pem).context("Public key must be an SPKI PEM P-256 key")
+}
+
+fn read_certificate(path: &Path) -> Result<Certificate> {
+    let pem = std::fs::read(path)
+        .with_context(|| format!("Failed to read certificate {}", path.display()))?;
+    Certificate::from_pem(pem).context("Certificate must be PEM encoded")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_payload(dir: &Path) -> PathBuf {
+        let bundle_path = dir.join("leanrl_bundle_test.zip");
+        std::fs::write(&bundle_path, b"bundle contents").unwrap();
+        bundle_path
+    }
+
+    #[tokio::test]
+    async fn test_offline_key_sign_and_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = write_payload(dir.path());
+        let (key_path, pub_path) = generate_keypair(dir.path()).unwrap();
+
+        let sign_options = SignOptions {
+            signing_key: Some(key_path),
+            ..Default::default()
+        };
+        sign_bundle(&bundle_path, &sign_options).await.unwrap();
+
+        let sig_path = bundle_path.with_extension("zip.sig");
+        let verify_options = VerifyOptions {
+            public_key: Some(pub_path),
+            ..Default::default()
+        };
+        verify_signature(&bundle_path, &sig_path, &verify_options)
+            .await
+            .unwrap();
+
+        // Moving signedAt must be detected
+        let original = std::fs::read_to_string(&sig_path).unwrap();
+        let mut bundle: SignatureBundle = serde_json::from_str(&original).unwrap();
+        bundle.signed_at = bundle.signed_at.map(|t| t - chrono::Duration::days(1));
+        std::fs::write(&sig_path, serde_json::to_string(&bundle).unwrap()).unwrap();
+        let err = verify_signature(&bundle_path, &sig_path, &verify_options)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("signedAt"), "{err}");
+        std::fs::write(&sig_path, original).unwrap();
+
+        // Tampering with the payload must be detected
+        std::fs::write(&bundle_path, b"bundle contents!").unwrap();
+        assert!(verify_signature(&bundle_path, &sig_path, &verify_options)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_cosign_bundle_without_leanrl_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = write_payload(dir.path());
+        let fulcio = LocalFulcio::generate("leanrl-test-fulcio").unwrap();
+        let ca_key = dir.path().join("fulcio-ca.key");
+        let ca_cert = dir.path().join("fulcio-ca.pem");
+        fulcio.write(&ca_key, &ca_cert).unwrap();
+
+        let sign_options = SignOptions {
+            fulcio_ca_key: Some(ca_key),
+            fulcio_ca_cert: Some(ca_cert.clone()),
+            identity: Some("release@leanrl.dev".to_string()),
+            ..Default::default()
+        };
+        let signed = sign_payload(b"bundle contents", &sign_options).unwrap();
+
+        // Layout of `cosign sign-blob --bundle`
+        let sig_path = bundle_path.with_extension("zip.sig");
+        let cosign = serde_json::json!({
+            "base64Signature": signed.base64_signature,
+            "cert": signed.cert,
+            "rekorBundle": { "SignedEntryTimestamp": "", "Payload": {} },
+        });
+        std::fs::write(&sig_path, cosign.to_string()).unwrap();
+
+        let verify_options = VerifyOptions {
+            ca_cert: Some(ca_cert),
+            certificate_identity: Some("release@leanrl.dev".to_string()),
+            certificate_issuer: Some("CN=leanrl-test-fulcio".to_string()),
+            ..Default::default()
+        };
+        verify_signature(&bundle_path, &sig_path, &verify_options)
+            .await
+            .unwrap();
+
+        std::fs::write(&bundle_path, b"bundle contents!").unwrap();
+        assert!(verify_signature(&bundle_path, &sig_path, &verify_options)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_keygen_writes_owner_only_key_and_refuses_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let (key_path, _) = generate_keypair(dir.path()).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&key_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let key = std::fs::read(&key_path).unwrap();
+        let err = generate_keypair(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("refusing to overwrite"), "{err}");
+        assert_eq!(std::fs::read(&key_path).unwrap(), key);
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_wrong_key_and_missing_anchor() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = write_payload(dir.path());
+        let (key_path, _) = generate_keypair(&dir.path().join("a")).unwrap();
+        let (_, other_pub) = generate_keypair(&dir.path().join("b")).unwrap();
+
+        let sign_options = SignOptions {
+            signing_key: Some(key_path),
+            ..Default::default()
+        };
+        sign_bundle(&bundle_path, &sign_options).await.unwrap();
+        let sig_path = bundle_path.with_extension("zip.sig");
+
+        let wrong_key = VerifyOptions {
+            public_key: Some(other_pub),
+            ..Default::default()
+        };
+        assert!(verify_signature(&bundle_path, &sig_path, &wrong_key)
+            .await
+            .is_err());
+        assert!(
+            verify_signature(&bundle_path, &sig_path, &VerifyOptions::default())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keyless_sign_and_verify_with_local_fulcio() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = write_payload(dir.path());
+
+        let fulcio = LocalFulcio::generate("leanrl-test-fulcio").unwrap();
+        let ca_key = dir.path().join("fulcio-ca.key");
+        let ca_cert = dir.path().join("fulcio-ca.pem");
+        fulcio.write(&ca_key, &ca_cert).unwrap();
+
+        let sign_options = SignOptions {
+            fulcio_ca_key: Some(ca_key),
+            fulcio_ca_cert: Some(ca_cert.clone()),
+            identity: Some("release@leanrl.dev".to_string()),
+            ..Default::default()
+        };
+        sign_bundle(&bundle_path, &sign_options).await.unwrap();
+        let sig_path = bundle_path.with_extension("zip.sig");
+
+        let verify_options = VerifyOptions {
+            ca_cert: Some(ca_cert),
+            certificate_identity: Some("release@leanrl.dev".to_string()),
+            certificate_issuer: Some("CN=leanrl-test-fulcio".to_string()),
+            ..Default::default()
+        };
+        verify_signature(&bundle_path, &sig_path, &verify_options)
+            .await
+            .unwrap();
+
+        let wrong_identity = VerifyOptions {
+            certificate_identity: Some("someone@else.dev".to_string()),
+            ..verify_options.clone()
+        };
+        assert!(verify_signature(&bundle_path, &sig_path, &wrong_identity)
+            .await
+            .is_err());
+        let wrong_issuer = VerifyOptions {
+            certificate_issuer: Some("CN=other".to_string()),
+            ..verify_options.clone()
+        };
+        assert!(verify_signature(&bundle_path, &sig_path, &wrong_issuer)
+            .await
+            .is_err());
+
+        // A CA alone would accept any identity it ever certified
+        for (identity, issuer) in [
+            (None, Some("CN=leanrl-test-fulcio")),
+            (Some("release@leanrl.dev"), None),
+        ] {
+            let unpinned = VerifyOptions {
+                certificate_identity: identity.map(str::to_string),
+                certificate_issuer: issuer.map(str::to_string),
+                ..verify_options.clone()
+            };
+            let err = verify_signature(&bundle_path, &sig_path, &unpinned)
+                .await
+                .unwrap_err();
+            assert!(
+                err.to_string().contains("is required with --ca-cert"),
+                "{err}"
+            );
+        }
+
+        // A certificate from a different CA must not be trusted
+        let other_ca = dir.path().join("other-ca.pem");
+        LocalFulcio::generate("other")
+            .unwrap()
+            .write(&dir.path().join("other-ca.key"), &other_ca)
+            .unwrap();
+        let untrusted = VerifyOptions {
+            ca_cert: Some(other_ca),
+            ..verify_options.clone()
+        };
+        assert!(verify_signature(&bundle_path, &sig_path, &untrusted)
+            .await
+            .is_err());
+    }
+}
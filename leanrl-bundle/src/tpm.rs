@@ -1,7 +1,38 @@
-use anyhow::Result;
+use crate::signing::write_private_key;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use p256::ecdsa::{signature::Signer, signature::Verifier, Signature, SigningKey, VerifyingKey};
+use p256::pkcs8::{
+    DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding,
+};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Resettable application PCR that receives the bundle digest
+pub const BUNDLE_PCR: u32 = 23;
+
+/// TPM_GENERATED_VALUE, the magic at the start of every TPMS_ATTEST
+const TPM_GENERATED_VALUE: u32 = 0xff54_4347;
+
+/// TPM_ST_ATTEST_QUOTE structure tag
+const TPM_ST_ATTEST_QUOTE: u16 = 0x8018;
+
+/// TPM_ALG_SHA256
+const TPM_ALG_SHA256: u16 = 0x000b;
+
+/// Number of PCRs in a bank (TPM 2.0 PC client profile)
+const PCR_COUNT: usize = 24;
+
+/// TPM_ALG_ECC, TPM_ALG_NULL, TPM_ALG_ECDSA and TPM_ECC_NIST_P256
+const TPM_ALG_ECC: u16 = 0x0023;
+const TPM_ALG_NULL: u16 = 0x0010;
+const TPM_ALG_ECDSA: u16 = 0x0018;
+const TPM_ECC_NIST_P256: u16 = 0x0003;
+
+/// Attributes of a `tpm2_createak` key: fixedTPM, fixedParent, sensitiveDataOrigin,
+/// userWithAuth, restricted and sign
+const AK_OBJECT_ATTRIBUTES: u32 = 0x0005_0072;
 
 /// TPM attestation data
 #[derive(Debug, Serialize, Deserialize)]
@@ -9,8 +40,14 @@ pub struct TpmAttestation {
     pub version: String,
     pub timestamp: DateTime<Utc>,
     pub bundle_hash: String,
+    /// Marshalled TPMS_ATTEST returned by TPM2_Quote (hex)
     pub tpm_quote: String,
     pub pcr_values: Vec<PcrValue>,
+    /// Digests extended into the bundle PCR since reset, oldest first (hex)
+    pub event_log: Vec<String>,
+    /// Qualifying data passed to TPM2_Quote (hex)
+    pub nonce: String,
+    /// ASN.1 DER ECDSA P-256 signature of the attestation key over `tpm_quote` (hex)
     pub signature: String,
 }
 
@@ -22,70 +59,586 @@ pub struct PcrValue {
     pub algorithm: String,
 }
 
+/// Where quotes come from: the built-in software TPM, or a TPM driven through tpm2-tools
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct TpmOptions {
+    /// Attestation key (PKCS#8 PEM) for the built-in software TPM (see `keygen --tpm-ak`)
+    #[arg(long, global = true)]
+    pub tpm_ak_key: Option<PathBuf>,
+
+    /// TCTI of a TPM or swtpm used through tpm2-tools (e.g. `swtpm:port=2321`)
+    #[arg(long, global = true, requires = "tpm_ak_context")]
+    pub tpm_tcti: Option<String>,
+
+    /// Loaded attestation key context for `--tpm-tcti` (from `tpm2_createak -c`)
+    #[arg(long, global = true)]
+    pub tpm_ak_context: Option<PathBuf>,
+
+    /// Verifier-supplied nonce (hex); a random nonce is used when omitted
+    #[arg(long, global = true)]
+    pub tpm_nonce: Option<String>,
+}
+
+/// Trust anchor and freshness challenge for verifying an attestation
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct TpmVerifyOptions {
+    /// Attestation key public key (SPKI PEM) that must have signed the quote
+    #[arg(long)]
+    pub tpm_ak_pub: Option<PathBuf>,
+
+    /// Nonce (hex) the quote must carry, proving it was produced for this challenge
+    #[arg(long)]
+    pub tpm_expected_nonce: Option<String>,
+
+    /// Accept a quote without checking its nonce against --tpm-expected-nonce (a replayed
+    /// quote passes)
+    #[arg(long, conflicts_with = "tpm_expected_nonce")]
+    pub allow_stale_quote: bool,
+}
+
 /// Generate TPM attestation
-pub async fn generate_attestation(bundle_path: &Path) -> Result<String> {
+///
+/// Resets the bundle PCR, extends it with the bundle digest and writes a signed quote over it
+/// to `<bundle>.zip.attest`. Returns the SHA-256 of the attestation file.
+pub async fn generate_attestation(bundle_path: &Path, options: &TpmOptions) -> Result<String> {
     println!("Generating TPM attestation for: {}", bundle_path.display());
 
-    // In a real implementation, this would:
-    // 1. Calculate bundle hash
-    // 2. Get TPM quote
-    // 3. Read PCR values
-    // 4. Sign the attestation
+    let mut backend: Box<dyn TpmBackend> = match (&options.tpm_tcti, &options.tpm_ak_key) {
+        (Some(tcti), _) => {
+            let ak_context = options
+                .tpm_ak_context
+                .clone()
+                .context("--tpm-ak-context is required with --tpm-tcti")?;
+            Box::new(Tpm2Tools::new(tcti, ak_context)?)
+        }
+        (None, Some(ak_path)) => Box::new(SoftwareTpm::new(read_ak(ak_path)?)),
+        (None, None) => anyhow::bail!(
+            "No TPM configured: pass --tpm-ak-key (software TPM) or --tpm-tcti/--tpm-ak-context"
+        ),
+    };
+
+    let nonce = match &options.tpm_nonce {
+        Some(nonce) => hex::decode(nonce).context("--tpm-nonce must be hex")?,
+        None => {
+            let mut nonce = vec![0u8; 32];
+            getrandom::getrandom(&mut nonce).context("Failed to generate nonce")?;
+            nonce
+        }
+    };
 
     let bundle_hash = calculate_bundle_hash(bundle_path)?;
+    let mut digest = [0u8; 32];
+    hex::decode_to_slice(&bundle_hash, &mut digest)?;
+
+    backend.pcr_reset(BUNDLE_PCR)?;
+    backend.pcr_extend(BUNDLE_PCR, &digest)?;
+    let pcr_value = backend.pcr_read(BUNDLE_PCR)?;
+    let (quote, signature) = backend.quote(&[BUNDLE_PCR], &nonce)?;
 
     let attestation = TpmAttestation {
-        version: "1.0".to_string(),
+        version: "2.0".to_string(),
         timestamp: Utc::now(),
-        bundle_hash,
-        tpm_quote: "placeholder_tpm_quote".to_string(),
-        pcr_values: vec![
-            PcrValue {
-                pcr_index: 0,
-                value: "placeholder_pcr_0".to_string(),
-                algorithm: "SHA256".to_string(),
-            },
-            PcrValue {
-                pcr_index: 1,
-                value: "placeholder_pcr_1".to_string(),
-                algorithm: "SHA256".to_string(),
-            },
-        ],
-        signature: "placeholder_signature".to_string(),
+        bundle_hash: bundle_hash.clone(),
+        tpm_quote: hex::encode(quote),
+        pcr_values: vec![PcrValue {
+            pcr_index: BUNDLE_PCR,
+            value: hex::encode(pcr_value),
+            algorithm: "SHA256".to_string(),
+        }],
+        event_log: vec![bundle_hash],
+        nonce: hex::encode(nonce),
+        signature: hex::encode(signature),
     };
 
     // Write attestation to file
     let attest_path = bundle_path.with_extension("zip.attest");
     let attest_json = serde_json::to_string_pretty(&attestation)?;
-    std::fs::write(&attest_path, attest_json)?;
+    std::fs::write(&attest_path, &attest_json)?;
 
     println!("TPM attestation generated: {}", attest_path.display());
-    Ok(attestation.bundle_hash)
+    Ok(hex::encode(Sha256::digest(attest_json.as_bytes())))
 }
 
 /// Verify TPM attestation
-pub async fn verify_attestation(attest_path: &Path) -> Result<()> {
+///
+/// Checks the quote signature against the AK, that the quoted PCR digest matches the reported
+/// PCR values, that the event log replays to those values and ends with this bundle's digest,
+/// and that the quote carries the recorded and the expected nonce. Without an expected nonce
+/// the quote is rejected unless `allow_stale_quote` is set.
+pub async fn verify_attestation(
+    bundle_path: &Path,
+    attest_path: &Path,
+    options: &TpmVerifyOptions,
+) -> Result<()> {
     println!("Verifying TPM attestation: {}", attest_path.display());
 
-    // In a real implementation, this would:
-    // 1. Verify TPM quote
-    // 2. Verify PCR values
-    // 3. Verify signature
-
     if !attest_path.exists() {
         anyhow::bail!("Attestation file not found: {}", attest_path.display());
     }
 
     let content = std::fs::read_to_string(attest_path)?;
-    let _attestation: TpmAttestation = serde_json::from_str(&content)?;
+    let attestation: TpmAttestation =
+        serde_json::from_str(&content).context("Invalid attestation format")?;
+
+    // Bundle digest must be the last measurement
+    let bundle_hash = calculate_bundle_hash(bundle_path)?;
+    if attestation.bundle_hash != bundle_hash || attestation.event_log.last() != Some(&bundle_hash)
+    {
+        anyhow::bail!(
+            "Attestation does not cover this bundle (digest {})",
+            bundle_hash
+        );
+    }
+
+    // Quote signature
+    let ak_path = options
+        .tpm_ak_pub
+        .as_ref()
+        .context("Attestation present but no AK given: pass --tpm-ak-pub")?;
+    let ak = read_ak_pub(ak_path)?;
+    let quote_bytes = hex::decode(&attestation.tpm_quote).context("Malformed quote")?;
+    let signature = Signature::from_der(&hex::decode(&attestation.signature)?)
+        .context("Malformed quote signature")?;
+    ak.verify(&quote_bytes, &signature)
+        .map_err(|_| anyhow::anyhow!("Quote signature verification failed"))?;
+    let quote = Quote::parse(&quote_bytes)?;
+    if quote.qualified_signer != ak_name(&ak)? {
+        anyhow::bail!("Quote names another signing key than the AK given by --tpm-ak-pub");
+    }
+
+    // Nonce freshness
+    let nonce = hex::decode(&attestation.nonce).context("Malformed nonce")?;
+    if quote.extra_data != nonce {
+        anyhow::bail!("Quote nonce does not match the attestation nonce");
+    }
+    match &options.tpm_expected_nonce {
+        Some(expected) => {
+            if hex::decode(expected).context("--tpm-expected-nonce must be hex")? != nonce {
+                anyhow::bail!("Quote nonce does not match the expected nonce (stale or replayed)");
+            }
+        }
+        None if options.allow_stale_quote => {
+            println!("Warning: --allow-stale-quote given, quote freshness not checked")
+        }
+        None => anyhow::bail!(
+            "No --tpm-expected-nonce given: pass the challenge nonce, or --allow-stale-quote to skip the freshness check"
+        ),
+    }
+
+    // Reported PCR values must match the signed PCR digest
+    let mut indices = Vec::new();
+    let mut hasher = Sha256::new();
+    for pcr in &attestation.pcr_values {
+        if pcr.algorithm != "SHA256" {
+            anyhow::bail!("Unsupported PCR bank: {}", pcr.algorithm);
+        }
+        indices.push(pcr.pcr_index);
+        hasher.update(hex::decode(&pcr.value)?);
+    }
+    if quote.pcr_selection != vec![(TPM_ALG_SHA256, indices)] {
+        anyhow::bail!("Quoted PCR selection does not match the reported PCRs");
+    }
+    if hasher.finalize().as_slice() != quote.pcr_digest.as_slice() {
+        anyhow::bail!("Quoted PCR digest does not match the reported PCR values");
+    }
+
+    // Event log must replay to the bundle PCR
+    let mut replayed = [0u8; 32];
+    for event in &attestation.event_log {
+        let mut digest = [0u8; 32];
+        hex::decode_to_slice(event, &mut digest).context("Malformed event log entry")?;
+        replayed = extend(&replayed, &digest);
+    }
+    let reported = attestation
+        .pcr_values
+        .iter()
+        .find(|pcr| pcr.pcr_index == BUNDLE_PCR)
+        .context("Attestation does not report the bundle PCR")?;
+    if hex::encode(replayed) != reported.value {
+        anyhow::bail!("Event log does not replay to PCR {}", BUNDLE_PCR);
+    }
 
     println!("TPM attestation verification passed");
     Ok(())
 }
 
+/// Generate an attestation key for the software TPM (`tpm-ak.key` / `tpm-ak.pub`)
+pub fn generate_ak(output_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(output_dir)?;
+
+    let ak = SigningKey::random(&mut rand_core::OsRng);
+    let key_path = output_dir.join("tpm-ak.key");
+    let pub_path = output_dir.join("tpm-ak.pub");
+    write_private_key(&key_path, ak.to_pkcs8_pem(LineEnding::LF)?.as_bytes())?;
+    std::fs::write(
+        &pub_path,
+        ak.verifying_key().to_public_key_pem(LineEnding::LF)?,
+    )?;
+
+    println!("TPM attestation key written: {}", key_path.display());
+    println!("TPM attestation public key written: {}", pub_path.display());
+    Ok(())
+}
+
+/// TPM operations needed to attest a bundle
+trait TpmBackend {
+    fn pcr_reset(&mut self, index: u32) -> Result<()>;
+
+    fn pcr_extend(&mut self, index: u32, digest: &[u8; 32]) -> Result<()>;
+
+    fn pcr_read(&mut self, index: u32) -> Result<[u8; 32]>;
+
+    /// TPM2_Quote over SHA-256 PCRs; returns the marshalled TPMS_ATTEST and DER signature
+    fn quote(&mut self, pcrs: &[u32], nonce: &[u8]) -> Result<(Vec<u8>, Vec<u8>)>;
+}
+
+/// In-process TPM with a SHA-256 PCR bank and an ECDSA P-256 attestation key
+pub struct SoftwareTpm {
+    pcrs: [[u8; 32]; PCR_COUNT],
+    ak: SigningKey,
+    started: std::time::Instant,
+}
+
+impl SoftwareTpm {
+    pub fn new(ak: SigningKey) -> Self {
+        Self {
+            pcrs: [[0u8; 32]; PCR_COUNT],
+            ak,
+            started: std::time::Instant::now(),
+        }
+    }
+
+    fn pcr(&self, index: u32) -> Result<&[u8; 32]> {
+        self.pcrs
+            .get(index as usize)
+            .with_context(|| format!("PCR index out of range: {}", index))
+    }
+}
+
+impl TpmBackend for SoftwareTpm {
+    fn pcr_reset(&mut self, index: u32) -> Result<()> {
+        // Only the debug and application PCRs are resettable from locality 0
+        if index != 16 && index != 23 {
+            anyhow::bail!("PCR {} is not resettable", index);
+        }
+        self.pcrs[index as usize] = [0u8; 32];
+        Ok(())
+    }
+
+    fn pcr_extend(&mut self, index: u32, digest: &[u8; 32]) -> Result<()> {
+        let extended = extend(self.pcr(index)?, digest);
+        self.pcrs[index as usize] = extended;
+        Ok(())
+    }
+
+    fn pcr_read(&mut self, index: u32) -> Result<[u8; 32]> {
+        Ok(*self.pcr(index)?)
+    }
+
+    fn quote(&mut self, pcrs: &[u32], nonce: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        let mut hasher = Sha256::new();
+        for &index in pcrs {
+            hasher.update(self.pcr(index)?);
+        }
+
+        let quote = Quote {
+            qualified_signer: ak_name(self.ak.verifying_key())?,
+            extra_data: nonce.to_vec(),
+            clock: self.started.elapsed().as_millis() as u64,
+            reset_count: 0,
+            restart_count: 0,
+            safe: true,
+            firmware_version: 0,
+            pcr_selection: vec![(TPM_ALG_SHA256, pcrs.to_vec())],
+            pcr_digest: hasher.finalize().to_vec(),
+        };
+        let attest = quote.marshal()?;
+        let signature: Signature = self.ak.sign(&attest);
+
+        Ok((attest, signature.to_der().as_bytes().to_vec()))
+    }
+}
+
+/// TPM (hardware or swtpm) driven through the tpm2-tools command line
+///
+/// The AK must be an ECDSA P-256 key created with
+/// `tpm2_createak -C ek.ctx -c ak.ctx -G ecc -g sha256 -s ecdsa -u ak.pub -f pem`.
+struct Tpm2Tools {
+    tcti: String,
+    ak_context: PathBuf,
+    workdir: tempfile::TempDir,
+}
+
+impl Tpm2Tools {
+    fn new(tcti: &str, ak_context: PathBuf) -> Result<Self> {
+        Ok(Self {
+            tcti: tcti.to_string(),
+            ak_context,
+            workdir: tempfile::tempdir()?,
+        })
+    }
+
+    fn run(&self, tool: &str, args: &[&str]) -> Result<()> {
+        let output = std::process::Command::new(tool)
+            .args(args)
+            .env("TPM2TOOLS_TCTI", &self.tcti)
+            .output()
+            .with_context(|| format!("Failed to run {} (is tpm2-tools installed?)", tool))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "{} failed: {}",
+                tool,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl TpmBackend for Tpm2Tools {
+    fn pcr_reset(&mut self, index: u32) -> Result<()> {
+        self.run("tpm2_pcrreset", &[&index.to_string()])
+    }
+
+    fn pcr_extend(&mut self, index: u32, digest: &[u8; 32]) -> Result<()> {
+        let arg = format!("{}:sha256={}", index, hex::encode(digest));
+        self.run("tpm2_pcrextend", &[&arg])
+    }
+
+    fn pcr_read(&mut self, index: u32) -> Result<[u8; 32]> {
+        let out = self.workdir.path().join("pcr.bin");
+        let selection = format!("sha256:{}", index);
+        self.run("tpm2_pcrread", &[&selection, "-o", &out.to_string_lossy()])?;
+
+        std::fs::read(&out)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Unexpected PCR value length from tpm2_pcrread"))
+    }
+
+    fn quote(&mut self, pcrs: &[u32], nonce: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        let message = self.workdir.path().join("quote.msg");
+        let signature = self.workdir.path().join("quote.sig");
+        let selection = format!(
+            "sha256:{}",
+            pcrs.iter()
+                .map(|index| index.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+
+        // `-f plain` emits the ECDSA signature as ASN.1 DER
+        self.run(
+            "tpm2_quote",
+            &[
+                "-c",
+                &self.ak_context.to_string_lossy(),
+                "-l",
+                &selection,
+                "-q",
+                &hex::encode(nonce),
+                "-g",
+                "sha256",
+                "-m",
+                &message.to_string_lossy(),
+                "-s",
+                &signature.to_string_lossy(),
+                "-f",
+                "plain",
+            ],
+        )?;
+
+        Ok((std::fs::read(&message)?, std::fs::read(&signature)?))
+    }
+}
+
+/// TPMS_ATTEST carrying a TPMS_QUOTE_INFO
+#[derive(Debug, PartialEq)]
+struct Quote {
+    qualified_signer: Vec<u8>,
+    extra_data: Vec<u8>,
+    clock: u64,
+    reset_count: u32,
+    restart_count: u32,
+    safe: bool,
+    firmware_version: u64,
+    /// (hash algorithm, selected PCR indices) per bank
+    pcr_selection: Vec<(u16, Vec<u32>)>,
+    pcr_digest: Vec<u8>,
+}
+
+impl Quote {
+    /// Marshal in TPM wire format (big-endian, TPM2B size prefixes)
+    fn marshal(&self) -> Result<Vec<u8>> {
+        fn tpm2b(out: &mut Vec<u8>, data: &[u8]) -> Result<()> {
+            out.extend_from_slice(&u16::try_from(data.len())?.to_be_bytes());
+            out.extend_from_slice(data);
+            Ok(())
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(&TPM_GENERATED_VALUE.to_be_bytes());
+        out.extend_from_slice(&TPM_ST_ATTEST_QUOTE.to_be_bytes());
+        tpm2b(&mut out, &self.qualified_signer)?;
+        tpm2b(&mut out, &self.extra_data)?;
+        out.extend_from_slice(&self.clock.to_be_bytes());
+        out.extend_from_slice(&self.reset_count.to_be_bytes());
+        out.extend_from_slice(&self.restart_count.to_be_bytes());
+        out.push(self.safe as u8);
+        out.extend_from_slice(&self.firmware_version.to_be_bytes());
+
+        out.extend_from_slice(&(self.pcr_selection.len() as u32).to_be_bytes());
+        for (hash, indices) in &self.pcr_selection {
+            let mut bitmap = [0u8; PCR_COUNT / 8];
+            for &index in indices {
+                let index = index as usize;
+                if index >= PCR_COUNT {
+                    anyhow::bail!("PCR index out of range: {}", index);
+                }
+                bitmap[index / 8] |= 1 << (index % 8);
+            }
+            out.extend_from_slice(&hash.to_be_bytes());
+            out.push(bitmap.len() as u8);
+            out.extend_from_slice(&bitmap);
+        }
+        tpm2b(&mut out, &self.pcr_digest)?;
+
+        Ok(out)
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader(bytes);
+
+        if reader.u32()? != TPM_GENERATED_VALUE {
+            anyhow::bail!("Quote was not generated by a TPM (bad magic)");
+        }
+        if reader.u16()? != TPM_ST_ATTEST_QUOTE {
+            anyhow::bail!("Attestation structure is not a quote");
+        }
+
+        let qualified_signer = reader.tpm2b()?;
+        let extra_data = reader.tpm2b()?;
+        let clock = reader.u64()?;
+        let reset_count = reader.u32()?;
+        let restart_count = reader.u32()?;
+        let safe = reader.take(1)?[0] != 0;
+        let firmware_version = reader.u64()?;
+
+        let mut pcr_selection = Vec::new();
+        for _ in 0..reader.u32()? {
+            let hash = reader.u16()?;
+            let size = reader.take(1)?[0] as usize;
+            let bitmap = reader.take(size)?;
+            let indices = (0..size * 8)
+                .filter(|&i| bitmap[i / 8] & (1 << (i % 8)) != 0)
+                .map(|i| i as u32)
+                .collect();
+            pcr_selection.push((hash, indices));
+        }
+        let pcr_digest = reader.tpm2b()?;
+
+        if !reader.0.is_empty() {
+            anyhow::bail!("Trailing bytes after quote");
+        }
+
+        Ok(Self {
+            qualified_signer,
+            extra_data,
+            clock,
+            reset_count,
+            restart_count,
+            safe,
+            firmware_version,
+            pcr_selection,
+            pcr_digest,
+        })
+    }
+}
+
+/// Big-endian cursor over TPM wire data
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.0.len() < len {
+            anyhow::bail!("Truncated quote");
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn tpm2b(&mut self) -> Result<Vec<u8>> {
+        let size = self.u16()? as usize;
+        Ok(self.take(size)?.to_vec())
+    }
+}
+
+/// TPM name of an attestation key: nameAlg followed by the SHA-256 of its TPMT_PUBLIC
+///
+/// The public area is the one `tpm2_createak -G ecc -g sha256 -s ecdsa` creates (no auth
+/// policy, no symmetric or KDF scheme), which the software TPM mirrors, so the name follows
+/// from the public key alone.
+fn ak_name(ak: &VerifyingKey) -> Result<Vec<u8>> {
+    let point = ak.to_encoded_point(false);
+    let (x, y) = point
+        .x()
+        .zip(point.y())
+        .context("AK public key is the identity point")?;
+
+    let mut public = Vec::new();
+    public.extend_from_slice(&TPM_ALG_ECC.to_be_bytes());
+    public.extend_from_slice(&TPM_ALG_SHA256.to_be_bytes());
+    public.extend_from_slice(&AK_OBJECT_ATTRIBUTES.to_be_bytes());
+    public.extend_from_slice(&0u16.to_be_bytes()); // authPolicy
+    public.extend_from_slice(&TPM_ALG_NULL.to_be_bytes()); // symmetric
+    public.extend_from_slice(&TPM_ALG_ECDSA.to_be_bytes());
+    public.extend_from_slice(&TPM_ALG_SHA256.to_be_bytes());
+    public.extend_from_slice(&TPM_ECC_NIST_P256.to_be_bytes());
+    public.extend_from_slice(&TPM_ALG_NULL.to_be_bytes()); // kdf
+    for coordinate in [x, y] {
+        public.extend_from_slice(&u16::try_from(coordinate.len())?.to_be_bytes());
+        public.extend_from_slice(coordinate);
+    }
+
+    let mut name = TPM_ALG_SHA256.to_be_bytes().to_vec();
+    name.extend_from_slice(&Sha256::digest(&public));
+    Ok(name)
+}
+
+/// PCR extend: SHA-256(old || digest)
+fn extend(pcr: &[u8; 32], digest: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(pcr);
+    hasher.update(digest);
+    hasher.finalize().into()
+}
+
+fn read_ak(path: &Path) -> Result<SigningKey> {
+    let pem = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read attestation key {}", path.display()))?;
+    SigningKey::from_pkcs8_pem(&pem).context("Attestation key must be a PKCS#8 PEM P-256 key")
+}
+
+fn read_ak_pub(path: &Path) -> Result<VerifyingKey> {
+    let pem = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read AK public key {}", path.display()))?;
+    VerifyingKey::from_public_key_pem(&pem).context("AK public key must be an SPKI PEM P-256 key")
+}
+
 /// Calculate bundle hash
 fn calculate_bundle_hash(bundle_path: &Path) -> Result<String> {
-    use sha2::{Digest, Sha256};
     use std::fs::File;
     use std::io::Read;
 
@@ -104,3 +657,198 @@ fn calculate_bundle_hash(bundle_path: &Path) -> Result<String> {
     let hash = hasher.finalize();
     Ok(hex::encode(hash))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(dir: &Path) -> (PathBuf, TpmOptions, TpmVerifyOptions) {
+        let bundle_path = dir.join("leanrl_bundle_test.zip");
+        std::fs::write(&bundle_path, b"bundle contents").unwrap();
+        generate_ak(dir).unwrap();
+
+        let options = TpmOptions {
+            tpm_ak_key: Some(dir.join("tpm-ak.key")),
+            tpm_nonce: Some("00112233".to_string()),
+            ..Default::default()
+        };
+        let verify_options = TpmVerifyOptions {
+            tpm_ak_pub: Some(dir.join("tpm-ak.pub")),
+            tpm_expected_nonce: Some("00112233".to_string()),
+            ..Default::default()
+        };
+        (bundle_path, options, verify_options)
+    }
+
+    fn attest_path(bundle_path: &Path) -> PathBuf {
+        bundle_path.with_extension("zip.attest")
+    }
+
+    #[test]
+    fn test_quote_marshal_roundtrip() {
+        let quote = Quote {
+            qualified_signer: vec![0, 0x0b, 1, 2, 3],
+            extra_data: vec![9; 32],
+            clock: 42,
+            reset_count: 1,
+            restart_count: 2,
+            safe: true,
+            firmware_version: 7,
+            pcr_selection: vec![(TPM_ALG_SHA256, vec![0, 7, 23])],
+            pcr_digest: vec![5; 32],
+        };
+        let bytes = quote.marshal().unwrap();
+        assert_eq!(Quote::parse(&bytes).unwrap(), quote);
+        assert!(Quote::parse(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_attestation_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let (bundle_path, options, verify_options) = setup(dir.path());
+
+        generate_attestation(&bundle_path, &options).await.unwrap();
+        verify_attestation(&bundle_path, &attest_path(&bundle_path), &verify_options)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_stale_nonce_and_other_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let (bundle_path, options, verify_options) = setup(dir.path());
+        generate_attestation(&bundle_path, &options).await.unwrap();
+
+        let stale = TpmVerifyOptions {
+            tpm_expected_nonce: Some("deadbeef".to_string()),
+            ..verify_options.clone()
+        };
+        assert!(
+            verify_attestation(&bundle_path, &attest_path(&bundle_path), &stale)
+                .await
+                .is_err()
+        );
+
+        std::fs::write(&bundle_path, b"other bundle").unwrap();
+        assert!(
+            verify_attestation(&bundle_path, &attest_path(&bundle_path), &verify_options)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_replayed_quote_without_expected_nonce() {
+        let dir = tempfile::tempdir().unwrap();
+        let (bundle_path, options, verify_options) = setup(dir.path());
+        generate_attestation(&bundle_path, &options).await.unwrap();
+
+        // A quote captured earlier verifies against anything unless a challenge is pinned
+        let replayed = dir.path().join("replayed.zip.attest");
+        std::fs::copy(attest_path(&bundle_path), &replayed).unwrap();
+        let unpinned = TpmVerifyOptions {
+            tpm_expected_nonce: None,
+            ..verify_options.clone()
+        };
+        let err = verify_attestation(&bundle_path, &replayed, &unpinned)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("--tpm-expected-nonce"), "{err}");
+
+        let stale_ok = TpmVerifyOptions {
+            allow_stale_quote: true,
+            ..unpinned
+        };
+        verify_attestation(&bundle_path, &replayed, &stale_ok)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_forged_pcr_and_wrong_ak() {
+        let dir = tempfile::tempdir().unwrap();
+        let (bundle_path, options, verify_options) = setup(dir.path());
+        generate_attestation(&bundle_path, &options).await.unwrap();
+
+        // Reported PCR value no longer matches the signed digest
+        let path = attest_path(&bundle_path);
+        let mut attestation: TpmAttestation =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        attestation.pcr_values[0].value = hex::encode([0u8; 32]);
+        let forged = dir.path().join("forged.zip.attest");
+        std::fs::write(&forged, serde_json::to_string(&attestation).unwrap()).unwrap();
+        assert!(verify_attestation(&bundle_path, &forged, &verify_options)
+            .await
+            .is_err());
+
+        // Signed by the AK, but naming another key as the signer
+        let mut quote = Quote::parse(&hex::decode(&attestation.tpm_quote).unwrap()).unwrap();
+        quote.qualified_signer[2] ^= 1;
+        let quote = quote.marshal().unwrap();
+        let ak = read_ak(&dir.path().join("tpm-ak.key")).unwrap();
+        let signature: Signature = ak.sign(&quote);
+        attestation.pcr_values[0].value = hex::encode(extend(
+            &[0u8; 32],
+            &hex::decode(&attestation.bundle_hash)
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        ));
+        attestation.tpm_quote = hex::encode(quote);
+        attestation.signature = hex::encode(signature.to_der().as_bytes());
+        std::fs::write(&forged, serde_json::to_string(&attestation).unwrap()).unwrap();
+        let err = verify_attestation(&bundle_path, &forged, &verify_options)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("another signing key"), "{err}");
+
+        let other = dir.path().join("other");
+        generate_ak(&other).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(other.join("tpm-ak.key"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert!(generate_ak(&other).is_err());
+        let wrong_ak = TpmVerifyOptions {
+            tpm_ak_pub: Some(other.join("tpm-ak.pub")),
+            ..verify_options
+        };
+        assert!(verify_attestation(&bundle_path, &path, &wrong_ak)
+            .await
+            .is_err());
+    }
+
+    /// Requires swtpm and tpm2-tools: `LEANRL_TPM_TCTI=swtpm:port=2321 LEANRL_TPM_AK_DIR=<dir
+    /// with ak.ctx and ak.pub> cargo test -p leanrl-bundle -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn test_attestation_against_swtpm() {
+        let tcti = std::env::var("LEANRL_TPM_TCTI").unwrap();
+        let ak_dir = PathBuf::from(std::env::var("LEANRL_TPM_AK_DIR").unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let bundle_path = dir.path().join("leanrl_bundle_test.zip");
+        std::fs::write(&bundle_path, b"bundle contents").unwrap();
+
+        let options = TpmOptions {
+            tpm_tcti: Some(tcti),
+            tpm_ak_context: Some(ak_dir.join("ak.ctx")),
+            tpm_nonce: Some("00112233".to_string()),
+            ..Default::default()
+        };
+        generate_attestation(&bundle_path, &options).await.unwrap();
+
+        let verify_options = TpmVerifyOptions {
+            tpm_ak_pub: Some(ak_dir.join("ak.pub")),
+            tpm_expected_nonce: Some("00112233".to_string()),
+            ..Default::default()
+        };
+        verify_attestation(&bundle_path, &attest_path(&bundle_path), &verify_options)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use leanrl_core::encrypted::{self, Cipher};
+use std::path::Path;
+
+/// AEAD cipher selectable on the command line
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum CipherArg {
+    #[value(name = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+    #[value(name = "aes-256-gcm")]
+    Aes256Gcm,
+}
+
+impl From<CipherArg> for Cipher {
+    fn from(arg: CipherArg) -> Self {
+        match arg {
+            CipherArg::ChaCha20Poly1305 => Cipher::ChaCha20Poly1305,
+            CipherArg::Aes256Gcm => Cipher::Aes256Gcm,
+        }
+    }
+}
+
+/// Encrypt a weights blob for storage at rest
+pub fn encrypt_weights(
+    input: &Path,
+    output: &Path,
+    key_file: &Path,
+    cipher: CipherArg,
+) -> Result<()> {
+    println!("Encrypting weights: {}", input.display());
+
+    let key = read_key(key_file)?;
+    let plaintext = zeroize::Zeroizing::new(
+        std::fs::read(input).with_context(|| format!("Failed to read {}", input.display()))?,
+    );
+
+    let mut nonce = [0u8; encrypted::NONCE_LEN];
+    getrandom::getrandom(&mut nonce).context("Failed to generate nonce")?;
+
+    let container = encrypted::encrypt(&plaintext, &key, cipher.into(), &nonce)
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    std::fs::write(output, container)?;
+
+    println!("Encrypted weights written: {}", output.display());
+    Ok(())
+}
+
+/// Read a hex-encoded 256-bit key from a file
+fn read_key(key_file: &Path) -> Result<zeroize::Zeroizing<[u8; encrypted::KEY_LEN]>> {
+    let text = zeroize::Zeroizing::new(
+        std::fs::read_to_string(key_file)
+            .with_context(|| format!("Failed to read key file {}", key_file.display()))?,
+    );
+
+    let mut key = zeroize::Zeroizing::new([0u8; encrypted::KEY_LEN]);
+    hex::decode_to_slice(text.trim(), key.as_mut())
+        .context("Key file must contain 64 hex characters")?;
+
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_weights_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("weights.bin");
+        let output = dir.path().join("weights.enc");
+        let key_file = dir.path().join("weights.key");
+
+        std::fs::write(&input, [0u8, 1, 2, 3]).unwrap();
+        std::fs::write(&key_file, format!("{}\n", "ab".repeat(32))).unwrap();
+
+        encrypt_weights(&input, &output, &key_file, CipherArg::Aes256Gcm).unwrap();
+
+        let container = std::fs::read(&output).unwrap();
+        let plaintext = encrypted::decrypt(&container, &[0xab; encrypted::KEY_LEN]).unwrap();
+        assert_eq!(plaintext.as_slice(), [0u8, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_read_key_rejects_short_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_file = dir.path().join("weights.key");
+        std::fs::write(&key_file, "abcd").unwrap();
+
+        assert!(read_key(&key_file).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use crate::inspect::read_weights;
+use anyhow::{bail, Context, Result};
+use leanrl_core::dims::new_env;
+use leanrl_core::info::WEIGHTS_FORMAT_VERSION;
+use std::fs;
+use std::path::Path;
+
+/// Canonicalize a weights blob, written in format version `to_version`
+///
+/// The blob is loaded through the runtime at `obs_dim`/`action_dim` and re-serialized, so
+/// anything the runtime rejects is an error and the output is exactly what the runtime would
+/// export: optional data the runtime fills in (a TabularQ blob without a Q-table) is written
+/// out. This is not a migration: version 1 is the only layout so far, the blob carries no
+/// version field, and `to_version` must be the current version.
+pub fn convert_weights(
+    weights: &[u8],
+    obs_dim: usize,
+    action_dim: usize,
+    to_version: u32,
+) -> Result<Vec<u8>> {
+    let env = new_env(weights, obs_dim, action_dim)?;
+    match to_version {
+        WEIGHTS_FORMAT_VERSION => Ok(env.get_weights()?),
+        _ => bail!(
+            "Unsupported format version {} (this build writes version {})",
+            to_version,
+            WEIGHTS_FORMAT_VERSION
+        ),
+    }
+}
+
+/// `convert_weights` from `input` to `output`
+pub fn convert_file(
+    input: &Path,
+    output: &Path,
+    obs_dim: usize,
+    action_dim: usize,
+    to_version: u32,
+) -> Result<()> {
+    let weights = convert_weights(&read_weights(input)?, obs_dim, action_dim, to_version)
+        .with_context(|| format!("Cannot convert {}", input.display()))?;
+    fs::write(output, &weights).with_context(|| format!("Failed to write {}", output.display()))?;
+
+    println!(
+        "Canonicalized {} -> {} (format version {}, {} bytes)",
+        input.display(),
+        output.display(),
+        to_version,
+        weights.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leanrl_core::weights::tabular_q_weights;
+
+    #[test]
+    fn test_convert_writes_canonical_blob() {
+        let full = tabular_q_weights(2, 2, &[0.0; 4], 0.1, 0.9).unwrap();
+        let header_only = &full[..17];
+
+        let converted = convert_weights(header_only, 4, 2, WEIGHTS_FORMAT_VERSION).unwrap();
+        assert_eq!(converted, full);
+        assert_eq!(
+            convert_weights(&converted, 4, 2, WEIGHTS_FORMAT_VERSION).unwrap(),
+            converted
+        );
+        assert!(convert_weights(&full[..full.len() - 4], 4, 2, WEIGHTS_FORMAT_VERSION).is_err());
+        assert!(convert_weights(&full, 4, 2, WEIGHTS_FORMAT_VERSION + 1).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use crate::inspect::read_weights;
+use crate::tensors::{hyperparameters, tensors, Tensor};
+use anyhow::{bail, Context, Result};
+use leanrl_core::weights::{decode_weights, DecodedWeights};
+use std::fmt;
+use std::path::Path;
+
+/// Difference of one tensor or hyperparameter between two blobs
+#[derive(Debug, Clone, PartialEq)]
+pub struct TensorDiff {
+    pub name: String,
+    pub shape: String,
+    /// L2 norm of `b - a`
+    pub l2: f32,
+    /// Largest `|b - a|`
+    pub max_abs: f32,
+    /// Values that differ at all (NaN compares unequal to everything)
+    pub changed: usize,
+}
+
+/// Per-tensor differences between two blobs of the same architecture
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diff {
+    pub tensors: Vec<TensorDiff>,
+}
+
+impl Diff {
+    pub fn is_identical(&self) -> bool {
+        self.tensors.iter().all(|tensor| tensor.changed == 0)
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<16} {:>8} {:>11} {:>11} {:>8}",
+            "tensor", "shape", "l2", "max-abs", "changed"
+        )?;
+        for tensor in &self.tensors {
+            write!(
+                f,
+                "\n{:<16} {:>8} {:>11.4e} {:>11.4e} {:>8}",
+                tensor.name, tensor.shape, tensor.l2, tensor.max_abs, tensor.changed
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn diff_values(name: String, shape: String, a: &[f32], b: &[f32]) -> TensorDiff {
+    let deltas: Vec<f64> = a
+        .iter()
+        .zip(b)
+        .map(|(&x, &y)| y as f64 - x as f64)
+        .collect();
+    TensorDiff {
+        name,
+        shape,
+        l2: deltas.iter().map(|d| d * d).sum::<f64>().sqrt() as f32,
+        max_abs: deltas.iter().fold(0.0, |acc: f64, d| acc.max(d.abs())) as f32,
+        changed: a.iter().zip(b).filter(|(x, y)| x != y).count(),
+    }
+}
+
+/// Compare two blobs decoded at `obs_dim`/`action_dim`
+///
+/// Both must hold the same algorithm with the same tensor shapes.
+pub fn diff_weights(a: &[u8], b: &[u8], obs_dim: usize, action_dim: usize) -> Result<Diff> {
+    let a = decode_weights(a, obs_dim, action_dim).context("First blob")?;
+    let b = decode_weights(b, obs_dim, action_dim).context("Second blob")?;
+
+    let (tensors_a, tensors_b) = (tensors(&a), tensors(&b));
+    let shapes = |tensors: &[Tensor]| {
+        tensors
+            .iter()
+            .map(|tensor| (tensor.name.clone(), tensor.shape.clone()))
+            .collect::<Vec<_>>()
+    };
+    if std::mem::discriminant(&a) != std::mem::discriminant(&b)
+        || shapes(&tensors_a) != shapes(&tensors_b)
+    {
+        bail!("Blobs have different algorithms or tensor shapes");
+    }
+    if let (DecodedWeights::TinyNN { layers: x }, DecodedWeights::TinyNN { layers: y }) = (&a, &b) {
+        if x.iter()
+            .map(|layer| layer.activation)
+            .ne(y.iter().map(|layer| layer.activation))
+        {
+            bail!("Blobs use different activations");
+        }
+    }
+
+    let scalars = hyperparameters(&a)
+        .into_iter()
+        .zip(hyperparameters(&b))
+        .map(|((name, x), (_, y))| diff_values(name.to_string(), "1".to_string(), &[x], &[y]));
+    let arrays = tensors_a
+        .iter()
+        .zip(&tensors_b)
+        .map(|(x, y)| diff_values(x.name.clone(), x.shape_label(), &x.values, &y.values));
+
+    Ok(Diff {
+        tensors: scalars.chain(arrays).collect(),
+    })
+}
+
+/// `diff_weights` for two files
+pub fn diff_files(a: &Path, b: &Path, obs_dim: usize, action_dim: usize) -> Result<Diff> {
+    diff_weights(&read_weights(a)?, &read_weights(b)?, obs_dim, action_dim)
+        .with_context(|| format!("Cannot compare {} and {}", a.display(), b.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leanrl_core::weights::{linear_fa_weights, tabular_q_weights};
+
+    #[test]
+    fn test_diff_reports_l2_and_max_abs_per_tensor() {
+        let a = linear_fa_weights(2, 1, &[0.0, 1.0], &[0.5], 0.01).unwrap();
+        let b = linear_fa_weights(2, 1, &[3.0, -3.0], &[0.5], 0.01).unwrap();
+
+        let diff = diff_weights(&a, &b, 2, 1).unwrap();
+        assert!(!diff.is_identical());
+        assert_eq!(diff.tensors.len(), 3);
+        assert_eq!(diff.tensors[0].name, "learning_rate");
+        assert_eq!(diff.tensors[0].changed, 0);
+        assert_eq!(
+            diff.tensors[1],
+            TensorDiff {
+                name: "weights".to_string(),
+                shape: "1x2".to_string(),
+                l2: 5.0,
+                max_abs: 4.0,
+                changed: 2,
+            }
+        );
+        assert_eq!(diff.tensors[2].changed, 0);
+
+        assert!(diff_weights(&a, &a, 2, 1).unwrap().is_identical());
+    }
+
+    #[test]
+    fn test_diff_rejects_different_architectures() {
+        let linear = linear_fa_weights(4, 2, &[0.0; 8], &[0.0; 2], 0.01).unwrap();
+        let small = tabular_q_weights(2, 2, &[0.0; 4], 0.1, 0.9).unwrap();
+        let large = tabular_q_weights(3, 2, &[0.0; 6], 0.1, 0.9).unwrap();
+
+        assert!(diff_weights(&linear, &small, 4, 2).is_err());
+        assert!(diff_weights(&small, &large, 4, 2).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::tensors::{hyperparameters, tensors, Stats};
+use anyhow::{Context, Result};
+use leanrl_core::PolicyInfo;
+use leanrl_core::{dims::new_env, weights::decode_weights};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Observation dimension of the shipped C API (`lr_init` loads an `Env<4, 2>`)
+pub const OBS_DIM: usize = 4;
+
+/// Action dimension of the shipped C API
+pub const ACTION_DIM: usize = 2;
+
+/// Statistics of one tensor, as printed by `inspect`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TensorSummary {
+    pub name: String,
+    pub shape: String,
+    pub stats: Stats,
+}
+
+/// What `inspect` reports for a weights blob
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inspection {
+    pub info: PolicyInfo,
+    pub hyperparameters: Vec<(&'static str, f32)>,
+    pub tensors: Vec<TensorSummary>,
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.info)?;
+        for (name, value) in &self.hyperparameters {
+            writeln!(f, "{:<17}{}", format!("{}:", name), value)?;
+        }
+        writeln!(f)?;
+        write!(
+            f,
+            "{:<16} {:>8} {:>11} {:>11} {:>11} {:>11} {:>11} {:>10}",
+            "tensor", "shape", "min", "max", "mean", "std", "l2", "non-finite"
+        )?;
+        for tensor in &self.tensors {
+            let stats = &tensor.stats;
+            write!(
+                f,
+                "\n{:<16} {:>8} {:>11.4e} {:>11.4e} {:>11.4e} {:>11.4e} {:>11.4e} {:>10}",
+                tensor.name,
+                tensor.shape,
+                stats.min,
+                stats.max,
+                stats.mean,
+                stats.std,
+                stats.l2,
+                stats.non_finite
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Load weights at `obs_dim`/`action_dim` and describe the policy and its tensors
+pub fn inspect_weights(weights: &[u8], obs_dim: usize, action_dim: usize) -> Result<Inspection> {
+    let info = new_env(weights, obs_dim, action_dim)?.info();
+    let decoded = decode_weights(weights, obs_dim, action_dim)?;
+
+    Ok(Inspection {
+        info,
+        hyperparameters: hyperparameters(&decoded),
+        tensors: tensors(&decoded)
+            .into_iter()
+            .map(|tensor| TensorSummary {
+                shape: tensor.shape_label(),
+                stats: Stats::of(&tensor.values),
+                name: tensor.name,
+            })
+            .collect(),
+    })
+}
+
+/// Read a weights file
+pub fn read_weights(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// `inspect_weights` for a file
+pub fn inspect_file(path: &Path, obs_dim: usize, action_dim: usize) -> Result<Inspection> {
+    inspect_weights(&read_weights(path)?, obs_dim, action_dim)
+        .with_context(|| format!("Invalid weights {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leanrl_core::weights::tabular_q_weights;
+    use leanrl_core::Architecture;
+
+    #[test]
+    fn test_inspect_tabular_weights() {
+        let weights = tabular_q_weights(3, 2, &[1.0, -2.0, 0.0, 0.5, 0.5, 0.0], 0.1, 0.9).unwrap();
+
+        let inspection = inspect_weights(&weights, OBS_DIM, ACTION_DIM).unwrap();
+        assert_eq!(
+            inspection.info.architecture,
+            Architecture::Tabular {
+                num_states: 3,
+                num_actions: 2
+            }
+        );
+        assert_eq!(inspection.info.parameter_count, 6);
+        assert_eq!(inspection.hyperparameters, [("alpha", 0.1), ("gamma", 0.9)]);
+        assert_eq!(inspection.tensors[0].shape, "3x2");
+        assert_eq!(inspection.tensors[0].stats.min, -2.0);
+
+        let text = inspection.to_string();
+        assert!(text.starts_with("algorithm:       TabularQLearning\n"));
+        assert!(text.contains("q-table:         3 states x 2 actions"));
+        assert!(text.contains("gamma:           0.9\n"));
+        assert!(text.contains("\nq_table               3x2"));
+    }
+
+    #[test]
+    fn test_inspect_rejects_truncated_weights() {
+        let err = inspect_weights(&[1, 0, 0], OBS_DIM, ACTION_DIM).unwrap_err();
+        assert!(err.to_string().contains("at byte 3"), "{}", err);
+        assert!(inspect_weights(&[1, 0, 0], 0, ACTION_DIM).is_err());
+    }
+}
@@ -0,0 +1,618 @@
+//! Per-step latency distributions of `Env::step`, for regression tracking against a baseline
+//!
+//! Every algorithm runs at every requested size with synthetic weights, once with warm caches
+//! (back-to-back steps) and once cache-cold (a buffer larger than the last-level cache is
+//! written between steps). Each sample is one `Instant`-timed step, so timer overhead (tens of
+//! ns) is included. Run release builds on an otherwise idle, frequency-pinned machine.
+
+use anyhow::{bail, Context, Result};
+use leanrl_core::algorithms::tiny_nn::ActivationFunction;
+use leanrl_core::dims::{new_env, DynEnv};
+use leanrl_core::env::AlgorithmType;
+use leanrl_core::weights::{
+    linear_fa_weights, tabular_q_weights, tiny_nn_layer_sizes, tiny_nn_weights, DenseLayer,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hint::black_box;
+use std::path::Path;
+use std::time::Instant;
+
+/// Version of the JSON report layout
+pub const REPORT_VERSION: u32 = 1;
+
+/// Discrete states of the synthetic TabularQ policies
+const TABULAR_STATES: usize = 64;
+
+/// Steps run before sampling, so first-call effects stay out of the warm distribution
+const WARMUP_STEPS: usize = 100;
+
+/// Algorithm selectable on the command line
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum AlgorithmArg {
+    #[value(name = "tabular-q")]
+    TabularQ,
+    #[value(name = "linear-fa")]
+    LinearFa,
+    #[value(name = "tiny-nn")]
+    TinyNn,
+}
+
+impl From<AlgorithmArg> for AlgorithmType {
+    fn from(arg: AlgorithmArg) -> Self {
+        match arg {
+            AlgorithmArg::TabularQ => AlgorithmType::TabularQLearning,
+            AlgorithmArg::LinearFa => AlgorithmType::LinearFA,
+            AlgorithmArg::TinyNn => AlgorithmType::TinyNN,
+        }
+    }
+}
+
+/// Cache state a sample is taken in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Warm,
+    Cold,
+}
+
+/// How the harness runs
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub algorithms: Vec<AlgorithmType>,
+    /// `(obs_dim, action_dim)` pairs
+    pub sizes: Vec<(usize, usize)>,
+    /// Samples per warm case
+    pub iterations: usize,
+    /// Samples per cold case (each one evicts the caches first)
+    pub cold_iterations: usize,
+    /// Bytes written between cold samples; should exceed the last-level cache
+    pub evict_bytes: usize,
+}
+
+/// Latency samples bucketed by powers of two
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bucket {
+    /// Samples in this bucket took less than `below_ns` (and at least half of it)
+    pub below_ns: u64,
+    pub count: u64,
+}
+
+/// Latency distribution of one algorithm, size and mode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseReport {
+    pub algorithm: String,
+    pub obs_dim: usize,
+    pub action_dim: usize,
+    pub mode: Mode,
+    pub parameter_count: usize,
+    pub samples: usize,
+    pub p50_ns: u64,
+    pub p99_ns: u64,
+    pub p999_ns: u64,
+    pub max_ns: u64,
+    pub mean_ns: u64,
+    /// Non-empty buckets in ascending order
+    pub histogram: Vec<Bucket>,
+}
+
+impl CaseReport {
+    /// `TinyNN 4x2 cold`
+    pub fn label(&self) -> String {
+        format!(
+            "{} {}x{} {}",
+            self.algorithm,
+            self.obs_dim,
+            self.action_dim,
+            match self.mode {
+                Mode::Warm => "warm",
+                Mode::Cold => "cold",
+            }
+        )
+    }
+
+    /// Summarize `samples` (ns), which are sorted in place
+    fn from_samples(env: &dyn DynEnv, mode: Mode, samples: &mut [u64]) -> Self {
+        samples.sort_unstable();
+        let (obs_dim, action_dim) = env.dims();
+        Self {
+            algorithm: env.algorithm_name().to_string(),
+            obs_dim,
+            action_dim,
+            mode,
+            parameter_count: env.parameter_count(),
+            samples: samples.len(),
+            p50_ns: percentile(samples, 0.50),
+            p99_ns: percentile(samples, 0.99),
+            p999_ns: percentile(samples, 0.999),
+            max_ns: samples.last().copied().unwrap_or(0),
+            mean_ns: samples.iter().sum::<u64>() / samples.len().max(1) as u64,
+            histogram: histogram(samples),
+        }
+    }
+}
+
+/// Machine-readable harness output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub version: u32,
+    /// `<arch>-<os>` the report was measured on
+    pub target: String,
+    /// False for debug builds, whose numbers are not comparable
+    pub optimized: bool,
+    pub cases: Vec<CaseReport>,
+}
+
+impl Report {
+    /// The latency table, and a jitter histogram per case if `histograms` is set
+    pub fn render(&self, histograms: bool) -> String {
+        let us = |ns: u64| ns as f64 / 1e3;
+        let mut out = format!(
+            "{:<28} {:>10} {:>10} {:>10} {:>10}",
+            "case (us)", "p50", "p99", "p99.9", "max"
+        );
+        for case in &self.cases {
+            out.push_str(&format!(
+                "\n{:<28} {:>10.3} {:>10.3} {:>10.3} {:>10.3}",
+                case.label(),
+                us(case.p50_ns),
+                us(case.p99_ns),
+                us(case.p999_ns),
+                us(case.max_ns)
+            ));
+        }
+        if histograms {
+            for case in &self.cases {
+                out.push_str(&format!("\n\n{}", case.label()));
+                let widest = case.histogram.iter().map(|b| b.count).max().unwrap_or(1);
+                for bucket in &case.histogram {
+                    let bar = (bucket.count * 50).div_ceil(widest) as usize;
+                    out.push_str(&format!(
+                        "\n  < {:>10.3} us {:>8} {}",
+                        us(bucket.below_ns),
+                        bucket.count,
+                        "#".repeat(bar)
+                    ));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+pub fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Power-of-two buckets of sorted samples
+pub fn histogram(sorted: &[u64]) -> Vec<Bucket> {
+    let mut buckets: Vec<Bucket> = Vec::new();
+    for &sample in sorted {
+        let below_ns = (sample + 1).next_power_of_two();
+        match buckets.last_mut() {
+            Some(bucket) if bucket.below_ns == below_ns => bucket.count += 1,
+            _ => buckets.push(Bucket { below_ns, count: 1 }),
+        }
+    }
+    buckets
+}
+
+/// Synthetic weights of `algorithm` at the given dimensions
+pub fn synthetic_weights(
+    algorithm: AlgorithmType,
+    obs_dim: usize,
+    action_dim: usize,
+) -> Result<Vec<u8>> {
+    // Small, varied values so no branch or activation is constant
+    let values = |count: usize| -> Vec<f32> {
+        (0..count)
+            .map(|i| ((i * 37 % 101) as f32 / 101.0 - 0.5) * 0.2)
+            .collect()
+    };
+
+    Ok(match algorithm {
+        AlgorithmType::TabularQLearning => tabular_q_weights(
+            TABULAR_STATES,
+            action_dim,
+            &values(TABULAR_STATES * action_dim),
+            0.1,
+            0.9,
+        )?,
+        AlgorithmType::LinearFA => linear_fa_weights(
+            obs_dim,
+            action_dim,
+            &values(obs_dim * action_dim),
+            &values(action_dim),
+            0.01,
+        )?,
+        AlgorithmType::TinyNN => {
+            let sizes = tiny_nn_layer_sizes(obs_dim, action_dim);
+            let layers: Vec<DenseLayer> = sizes
+                .windows(2)
+                .enumerate()
+                .map(|(i, io)| DenseLayer {
+                    weights: values(io[0] * io[1]),
+                    bias: values(io[1]),
+                    activation: if i + 2 < sizes.len() {
+                        ActivationFunction::ReLU
+                    } else {
+                        ActivationFunction::Tanh
+                    },
+                })
+                .collect();
+            tiny_nn_weights(obs_dim, action_dim, &layers)?
+        }
+    })
+}
+
+/// Deterministic observations in [-1, 1] that sweep the TabularQ states
+fn observations(obs_dim: usize, count: usize) -> Vec<Vec<f32>> {
+    (0..count)
+        .map(|i| {
+            (0..obs_dim)
+                .map(|j| ((i * 7 + j * 13) % 200) as f32 / 100.0 - 1.0)
+                .collect()
+        })
+        .collect()
+}
+
+/// Writes a buffer larger than the caches so the next step starts cold
+struct Evictor {
+    buffer: Vec<u8>,
+    round: u8,
+}
+
+impl Evictor {
+    fn evict(&mut self) {
+        self.round = self.round.wrapping_add(1);
+        // One write per cache line
+        for byte in self.buffer.iter_mut().step_by(64) {
+            *byte = self.round;
+        }
+        black_box(&self.buffer);
+    }
+}
+
+/// Time `samples` steps of `env`, evicting the caches before each one if `evictor` is set
+fn sample(
+    env: &mut dyn DynEnv,
+    observations: &[Vec<f32>],
+    samples: usize,
+    mut evictor: Option<&mut Evictor>,
+) -> Result<Vec<u64>> {
+    let mut action = vec![0.0; env.dims().1];
+    env.reset(&observations[0], &mut action)?;
+    for obs in observations.iter().cycle().take(WARMUP_STEPS) {
+        env.step(obs, &mut action)?;
+    }
+
+    let mut out = Vec::with_capacity(samples);
+    for obs in observations.iter().cycle().take(samples) {
+        if let Some(evictor) = evictor.as_deref_mut() {
+            evictor.evict();
+        }
+        let start = Instant::now();
+        env.step(black_box(obs), &mut action)?;
+        let elapsed = start.elapsed();
+        black_box(&action);
+        out.push(elapsed.as_nanos() as u64);
+    }
+    Ok(out)
+}
+
+/// Measure every algorithm and size in warm and cold mode
+pub fn measure(options: &Options) -> Result<Report> {
+    let mut evictor = Evictor {
+        buffer: vec![0; options.evict_bytes],
+        round: 0,
+    };
+    let mut cases = Vec::new();
+
+    for &(obs_dim, action_dim) in &options.sizes {
+        let observations = observations(obs_dim, 1024);
+        for &algorithm in &options.algorithms {
+            let weights = synthetic_weights(algorithm, obs_dim, action_dim)?;
+            let mut env = new_env(&weights, obs_dim, action_dim)
+                .with_context(|| format!("{:?} at {}x{}", algorithm, obs_dim, action_dim))?;
+
+            let mut warm = sample(env.as_mut(), &observations, options.iterations, None)?;
+            cases.push(CaseReport::from_samples(
+                env.as_ref(),
+                Mode::Warm,
+                &mut warm,
+            ));
+
+            let mut cold = sample(
+                env.as_mut(),
+                &observations,
+                options.cold_iterations,
+                Some(&mut evictor),
+            )?;
+            cases.push(CaseReport::from_samples(
+                env.as_ref(),
+                Mode::Cold,
+                &mut cold,
+            ));
+        }
+    }
+
+    Ok(Report {
+        version: REPORT_VERSION,
+        target: format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+        optimized: !cfg!(debug_assertions),
+        cases,
+    })
+}
+
+/// A percentile that got slower than the baseline allows
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub case: String,
+    pub metric: &'static str,
+    pub baseline_ns: u64,
+    pub current_ns: u64,
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {} ns (baseline {} ns, {:+.1}%)",
+            self.case,
+            self.metric,
+            self.current_ns,
+            self.baseline_ns,
+            (self.current_ns as f64 / self.baseline_ns.max(1) as f64 - 1.0) * 100.0
+        )
+    }
+}
+
+/// P50 and P99 of every case in both reports that exceed the baseline by more than
+/// `tolerance` (relative) and `slack_ns` (absolute, so nanosecond noise on fast cases does
+/// not count)
+///
+/// P99.9 and max are reported but not compared: they measure the machine as much as the code.
+/// Reports from another target or build profile are not comparable and are an error.
+pub fn compare(
+    current: &Report,
+    baseline: &Report,
+    tolerance: f64,
+    slack_ns: u64,
+) -> Result<Vec<Regression>> {
+    let profile = |report: &Report| {
+        let build = if report.optimized { "release" } else { "debug" };
+        format!("{} {}", report.target, build)
+    };
+    if baseline.target != current.target || baseline.optimized != current.optimized {
+        bail!(
+            "Baseline was measured on {}, this run on {}; their latencies are not comparable",
+            profile(baseline),
+            profile(current)
+        );
+    }
+
+    let mut regressions = Vec::new();
+    for case in &current.cases {
+        let Some(base) = baseline
+            .cases
+            .iter()
+            .find(|base| base.label() == case.label())
+        else {
+            continue;
+        };
+        for (metric, baseline_ns, current_ns) in [
+            ("p50", base.p50_ns, case.p50_ns),
+            ("p99", base.p99_ns, case.p99_ns),
+        ] {
+            let allowed = (baseline_ns as f64 * (1.0 + tolerance)) as u64;
+            if current_ns > allowed && current_ns - baseline_ns > slack_ns {
+                regressions.push(Regression {
+                    case: case.label(),
+                    metric,
+                    baseline_ns,
+                    current_ns,
+                });
+            }
+        }
+    }
+    Ok(regressions)
+}
+
+/// Parse `4x2` into `(4, 2)`
+pub fn parse_size(size: &str) -> Result<(usize, usize), String> {
+    let (obs, action) = size
+        .split_once('x')
+        .ok_or_else(|| format!("expected OBSxACTION, got {:?}", size))?;
+    let parse = |value: &str| value.trim().parse::<usize>().map_err(|e| e.to_string());
+    Ok((parse(obs)?, parse(action)?))
+}
+
+/// Run the harness, print the table, write the report and check it against a baseline
+pub fn run(
+    options: &Options,
+    histograms: bool,
+    output: Option<&Path>,
+    baseline: Option<&Path>,
+    tolerance: f64,
+    slack_ns: u64,
+) -> Result<()> {
+    if cfg!(debug_assertions) {
+        eprintln!("warning: debug build; measure with --release");
+    }
+    let report = measure(options)?;
+    println!("{}", report.render(histograms));
+
+    if let Some(output) = output {
+        let json = serde_json::to_string_pretty(&report)?;
+        std::fs::write(output, json + "\n")
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+    }
+
+    if let Some(path) = baseline {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let baseline: Report = serde_json::from_str(&text)
+            .with_context(|| format!("Invalid latency report {}", path.display()))?;
+        if baseline.version != REPORT_VERSION {
+            bail!(
+                "Baseline {} has report version {}, expected {}",
+                path.display(),
+                baseline.version,
+                REPORT_VERSION
+            );
+        }
+
+        let regressions = compare(&report, &baseline, tolerance, slack_ns)
+            .with_context(|| format!("Cannot compare against {}", path.display()))?;
+        if !regressions.is_empty() {
+            for regression in &regressions {
+                eprintln!("  {}", regression);
+            }
+            bail!(
+                "{} latency regressions against {}",
+                regressions.len(),
+                path.display()
+            );
+        }
+        println!("No regressions against {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_and_histogram() {
+        let samples: Vec<u64> = (1..=1000).collect();
+        assert_eq!(percentile(&samples, 0.50), 500);
+        assert_eq!(percentile(&samples, 0.99), 990);
+        assert_eq!(percentile(&samples, 0.999), 999);
+        assert_eq!(percentile(&samples, 1.0), 1000);
+        assert_eq!(percentile(&[], 0.5), 0);
+
+        assert_eq!(
+            histogram(&[1, 2, 3, 3, 100]),
+            [
+                Bucket {
+                    below_ns: 2,
+                    count: 1
+                },
+                Bucket {
+                    below_ns: 4,
+                    count: 3
+                },
+                Bucket {
+                    below_ns: 128,
+                    count: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_measure_covers_every_algorithm_size_and_mode() {
+        let options = Options {
+            algorithms: vec![
+                AlgorithmType::TabularQLearning,
+                AlgorithmType::LinearFA,
+                AlgorithmType::TinyNN,
+            ],
+            sizes: vec![(4, 2), (8, 4)],
+            iterations: 20,
+            cold_iterations: 5,
+            evict_bytes: 4096,
+        };
+        let report = measure(&options).unwrap();
+
+        assert_eq!(report.cases.len(), 12);
+        assert_eq!(report.cases[0].label(), "TabularQLearning 4x2 warm");
+        assert_eq!(report.cases[5].label(), "TinyNN 4x2 cold");
+        assert_eq!(report.cases[11].label(), "TinyNN 8x4 cold");
+        for case in &report.cases {
+            assert!(case.p50_ns <= case.p99_ns && case.p99_ns <= case.max_ns);
+            let counted: u64 = case.histogram.iter().map(|bucket| bucket.count).sum();
+            assert_eq!(counted as usize, case.samples);
+        }
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(serde_json::from_str::<Report>(&json).unwrap(), report);
+        assert!(report.render(true).contains("TinyNN 8x4 cold"));
+    }
+
+    #[test]
+    fn test_compare_flags_only_real_slowdowns() {
+        let case = |p50_ns, p99_ns| CaseReport {
+            algorithm: "LinearFA".to_string(),
+            obs_dim: 4,
+            action_dim: 2,
+            mode: Mode::Warm,
+            parameter_count: 10,
+            samples: 100,
+            p50_ns,
+            p99_ns,
+            p999_ns: p99_ns,
+            max_ns: p99_ns,
+            mean_ns: p50_ns,
+            histogram: Vec::new(),
+        };
+        let report = |cases| Report {
+            version: REPORT_VERSION,
+            target: "x86_64-linux".to_string(),
+            optimized: true,
+            cases,
+        };
+        let baseline = report(vec![case(100, 1_000)]);
+
+        // Within tolerance, or within the absolute slack
+        assert!(compare(&report(vec![case(120, 1_200)]), &baseline, 0.25, 0)
+            .unwrap()
+            .is_empty());
+        assert!(
+            compare(&report(vec![case(300, 1_000)]), &baseline, 0.25, 500)
+                .unwrap()
+                .is_empty()
+        );
+
+        let regressions = compare(&report(vec![case(100, 5_000)]), &baseline, 0.25, 500).unwrap();
+        assert_eq!(
+            regressions,
+            [Regression {
+                case: "LinearFA 4x2 warm".to_string(),
+                metric: "p99",
+                baseline_ns: 1_000,
+                current_ns: 5_000,
+            }]
+        );
+        assert!(regressions[0]
+            .to_string()
+            .ends_with("(baseline 1000 ns, +400.0%)"));
+
+        // Cases missing from the baseline are not compared
+        let mut other = case(100, 5_000);
+        other.mode = Mode::Cold;
+        assert!(compare(&report(vec![other]), &baseline, 0.25, 500)
+            .unwrap()
+            .is_empty());
+
+        // Another target or build profile is not compared at all
+        let mut debug = report(vec![case(100, 1_000)]);
+        debug.optimized = false;
+        let error = compare(&debug, &baseline, 0.25, 500).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("on x86_64-linux release, this run on x86_64-linux debug"));
+        let mut arm = report(vec![case(100, 1_000)]);
+        arm.target = "aarch64-linux".to_string();
+        assert!(compare(&arm, &baseline, 0.25, 500).is_err());
+
+        assert_eq!(parse_size("16x8"), Ok((16, 8)));
+        assert!(parse_size("16").is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand};
+use leanrl_core::{env::AlgorithmType, info::WEIGHTS_FORMAT_VERSION};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+mod convert;
+mod diff;
+mod inspect;
+mod latency;
+mod run;
+mod tensors;
+mod validate;
+
+#[derive(Parser)]
+#[command(name = "leanrl")]
+#[command(about = "Inspect, validate, diff, convert, run and profile LeanEdge-RL weights")]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+/// Dimensions the weights are loaded at
+#[derive(Debug, Clone, Copy, clap::Args)]
+struct Dims {
+    /// Observation dimension (default: the C API's)
+    #[arg(long, default_value_t = inspect::OBS_DIM)]
+    obs_dim: usize,
+
+    /// Action dimension (default: the C API's)
+    #[arg(long, default_value_t = inspect::ACTION_DIM)]
+    action_dim: usize,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Describe a weights blob: algorithm, dims, shape, SHA-256 and statistics per tensor
+    Inspect {
+        /// Weights file (algorithm byte, then the algorithm's payload)
+        weights: PathBuf,
+
+        #[command(flatten)]
+        dims: Dims,
+    },
+
+    /// Load weights through the runtime and check every value is finite and bounded
+    Validate {
+        weights: PathBuf,
+
+        #[command(flatten)]
+        dims: Dims,
+
+        /// Largest allowed |weight|
+        #[arg(long, default_value_t = validate::DEFAULT_MAX_ABS)]
+        max_abs: f32,
+    },
+
+    /// Per-tensor L2 and max-abs differences between two blobs of the same architecture
+    Diff {
+        a: PathBuf,
+        b: PathBuf,
+
+        #[command(flatten)]
+        dims: Dims,
+
+        /// Exit with an error if any value differs
+        #[arg(long)]
+        exit_code: bool,
+    },
+
+    /// Canonicalize weights: load them through the runtime and write them back in canonical
+    /// form (format version 1 is the only one so far, so this does not change versions)
+    Convert {
+        input: PathBuf,
+        output: PathBuf,
+
+        #[command(flatten)]
+        dims: Dims,
+
+        /// Format version to write; only the current one is supported
+        #[arg(long, default_value_t = WEIGHTS_FORMAT_VERSION)]
+        to_version: u32,
+    },
+
+    /// Drive reset/step from observation lines, printing actions, invariant verdicts and
+    /// per-step latency
+    Run {
+        weights: PathBuf,
+
+        #[command(flatten)]
+        dims: Dims,
+
+        /// Observation line format (results use the same)
+        #[arg(long, value_enum, default_value = "csv")]
+        format: run::Format,
+
+        /// Read observations from a serial device instead of stdin
+        #[arg(long, conflicts_with = "udp")]
+        serial: Option<PathBuf>,
+
+        /// Receive observations as UDP datagrams on this address and reply to the sender
+        #[arg(long)]
+        udp: Option<SocketAddr>,
+    },
+
+    /// Measure Env::step latency (P50/P99/P99.9/max) per algorithm and size, cache-warm and
+    /// cache-cold, and compare against a baseline report
+    Latency {
+        /// Algorithm to measure (repeatable; default: all)
+        #[arg(long = "algorithm", value_enum)]
+        algorithms: Vec<latency::AlgorithmArg>,
+
+        /// Dimensions as OBSxACTION (repeatable; default: 4x2, 8x4 and 16x8)
+        #[arg(long = "size", value_parser = latency::parse_size)]
+        sizes: Vec<(usize, usize)>,
+
+        /// Samples per cache-warm case
+        #[arg(long, default_value_t = 10_000)]
+        iterations: usize,
+
+        /// Samples per cache-cold case
+        #[arg(long, default_value_t = 1_000)]
+        cold_iterations: usize,
+
+        /// Bytes written between cache-cold samples; should exceed the last-level cache
+        #[arg(long, default_value_t = 8 << 20)]
+        evict_bytes: usize,
+
+        /// Print a jitter histogram per case
+        #[arg(long)]
+        histogram: bool,
+
+        /// Write the JSON report here
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Fail if P50 or P99 regressed against this JSON report
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+
+        /// Allowed relative slowdown against the baseline
+        #[arg(long, default_value_t = 0.25)]
+        tolerance: f64,
+
+        /// Slowdowns up to this many ns never count as regressions
+        #[arg(long, default_value_t = 1_000)]
+        slack_ns: u64,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Commands::Inspect { weights, dims } => {
+            println!(
+                "{}",
+                inspect::inspect_file(&weights, dims.obs_dim, dims.action_dim)?
+            );
+        }
+        Commands::Validate {
+            weights,
+            dims,
+            max_abs,
+        } => {
+            validate::validate_file(&weights, dims.obs_dim, dims.action_dim, max_abs)?;
+        }
+        Commands::Diff {
+            a,
+            b,
+            dims,
+            exit_code,
+        } => {
+            let diff = diff::diff_files(&a, &b, dims.obs_dim, dims.action_dim)?;
+            println!("{}", diff);
+            if exit_code && !diff.is_identical() {
+                bail!("{} and {} differ", a.display(), b.display());
+            }
+        }
+        Commands::Convert {
+            input,
+            output,
+            dims,
+            to_version,
+        } => {
+            convert::convert_file(&input, &output, dims.obs_dim, dims.action_dim, to_version)?;
+        }
+        Commands::Run {
+            weights,
+            dims,
+            format,
+            serial,
+            udp,
+        } => {
+            let source = match (&serial, udp) {
+                (Some(device), _) => run::Source::Serial(device),
+                (None, Some(addr)) => run::Source::Udp(addr),
+                (None, None) => run::Source::Stdin,
+            };
+            run::run(&weights, dims.obs_dim, dims.action_dim, format, source)?;
+        }
+        Commands::Latency {
+            algorithms,
+            sizes,
+            iterations,
+            cold_iterations,
+            evict_bytes,
+            histogram,
+            output,
+            baseline,
+            tolerance,
+            slack_ns,
+        } => {
+            let algorithms = if algorithms.is_empty() {
+                vec![
+                    AlgorithmType::TabularQLearning,
+                    AlgorithmType::LinearFA,
+                    AlgorithmType::TinyNN,
+                ]
+            } else {
+                algorithms.into_iter().map(Into::into).collect()
+            };
+            let sizes = if sizes.is_empty() {
+                vec![(4, 2), (8, 4), (16, 8)]
+            } else {
+                sizes
+            };
+            let options = latency::Options {
+                algorithms,
+                sizes,
+                iterations,
+                cold_iterations,
+                evict_bytes,
+            };
+            latency::run(
+                &options,
+                histogram,
+                output.as_deref(),
+                baseline.as_deref(),
+                tolerance,
+                slack_ns,
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,366 @@
+//! Interactive step runner: observations in, actions, invariant verdicts and latency out
+//!
+//! Each input line is one observation, as comma-separated floats (`csv`) or a JSON array or
+//! `{"obs": [...]}` object (`json`). The first observation of an episode goes to
+//! `Env::reset`, the rest to `Env::step`; a `reset` line (`"reset"` in JSON) starts a new
+//! episode. Blank lines and `#` comments are skipped. A bad line is reported and the run
+//! goes on.
+
+use crate::inspect::read_weights;
+use anyhow::{Context, Result};
+use leanrl_core::dims::{new_env, DynEnv};
+use std::io::{BufRead, Write};
+use std::net::{SocketAddr, UdpSocket};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Largest UDP datagram accepted
+const MAX_DATAGRAM: usize = 65_507;
+
+/// Line format of observations, and of the results written back
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    /// `0.1,0.2,...` in; `episode,step,latency_us,invariant,action...` out
+    Csv,
+    /// `[0.1, 0.2, ...]` or `{"obs": [...]}` in; one JSON object per step out
+    Json,
+}
+
+/// One parsed input line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    Observation(Vec<f32>),
+    Reset,
+}
+
+/// Parse an input line; `None` for blank lines and comments
+pub fn parse_line(line: &str, format: Format) -> Result<Option<Input>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    match format {
+        Format::Csv if line.eq_ignore_ascii_case("reset") => Ok(Some(Input::Reset)),
+        Format::Csv => line
+            .split(',')
+            .map(|field| {
+                field
+                    .trim()
+                    .parse::<f32>()
+                    .with_context(|| format!("Invalid observation value {:?}", field.trim()))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(|obs| Some(Input::Observation(obs))),
+        Format::Json => {
+            let value: serde_json::Value = serde_json::from_str(line).context("Invalid JSON")?;
+            if value == "reset" {
+                return Ok(Some(Input::Reset));
+            }
+            let obs = value.get("obs").unwrap_or(&value);
+            serde_json::from_value(obs.clone())
+                .context("Expected an array of numbers or {\"obs\": [...]}")
+                .map(|obs| Some(Input::Observation(obs)))
+        }
+    }
+}
+
+/// Outcome of one `reset`/`step`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepRecord {
+    pub episode: u64,
+    /// 0 for the `reset` that starts an episode
+    pub step: u64,
+    pub action: Vec<f32>,
+    /// Name of the violated invariant, `None` if the step satisfied all of them
+    pub violation: Option<&'static str>,
+    /// Time spent in `Env::reset`/`Env::step`
+    pub latency: Duration,
+}
+
+impl StepRecord {
+    /// Render in `format`, without a trailing newline
+    pub fn render(&self, format: Format) -> String {
+        let latency_us = self.latency.as_secs_f64() * 1e6;
+        let verdict = self.violation.unwrap_or("ok");
+        match format {
+            Format::Csv => {
+                let mut line = format!(
+                    "{},{},{:.3},{}",
+                    self.episode, self.step, latency_us, verdict
+                );
+                for value in &self.action {
+                    line.push_str(&format!(",{}", value));
+                }
+                line
+            }
+            Format::Json => serde_json::json!({
+                "episode": self.episode,
+                "step": self.step,
+                "action": self.action,
+                "invariant": verdict,
+                "latency_us": latency_us,
+            })
+            .to_string(),
+        }
+    }
+}
+
+/// Render an input error in `format`
+fn render_error(err: &anyhow::Error, format: Format) -> String {
+    match format {
+        Format::Csv => format!("error: {:#}", err),
+        Format::Json => serde_json::json!({ "error": format!("{:#}", err) }).to_string(),
+    }
+}
+
+/// Drives an `Env` one observation at a time
+pub struct Runner {
+    env: Box<dyn DynEnv>,
+    action: Vec<f32>,
+    /// Next observation starts an episode
+    needs_reset: bool,
+    format: Format,
+    /// Steps run, violations seen and latency totals, for the summary
+    steps: u64,
+    violations: u64,
+    max_latency: Duration,
+    total_latency: Duration,
+}
+
+impl Runner {
+    pub fn new(env: Box<dyn DynEnv>, format: Format) -> Self {
+        let (_, action_dim) = env.dims();
+        Self {
+            env,
+            action: vec![0.0; action_dim],
+            needs_reset: true,
+            format,
+            steps: 0,
+            violations: 0,
+            max_latency: Duration::ZERO,
+            total_latency: Duration::ZERO,
+        }
+    }
+
+    /// Feed one observation, or start a new episode on `Input::Reset`
+    pub fn feed(&mut self, input: Input) -> Result<Option<StepRecord>> {
+        let obs = match input {
+            Input::Reset => {
+                self.needs_reset = true;
+                return Ok(None);
+            }
+            Input::Observation(obs) => obs,
+        };
+
+        let start = Instant::now();
+        if self.needs_reset {
+            self.env.reset(&obs, &mut self.action)?;
+        } else {
+            self.env.step(&obs, &mut self.action)?;
+        }
+        let latency = start.elapsed();
+        self.needs_reset = false;
+
+        let violation = self
+            .env
+            .check_invariant(&obs, &self.action)
+            .err()
+            .map(|err| {
+                err.invariant()
+                    .map_or("error", |invariant| invariant.name())
+            });
+        self.steps += 1;
+        self.violations += u64::from(violation.is_some());
+        self.max_latency = self.max_latency.max(latency);
+        self.total_latency += latency;
+
+        let state = self.env.state();
+        Ok(Some(StepRecord {
+            episode: state.episode_count,
+            step: state.step_count,
+            action: self.action.clone(),
+            violation,
+            latency,
+        }))
+    }
+
+    /// Parse and feed one line, returning the rendered result (or error) if there is one
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        let record = parse_line(line, self.format)
+            .and_then(|input| input.map_or(Ok(None), |input| self.feed(input)));
+        match record {
+            Ok(record) => record.map(|record| record.render(self.format)),
+            Err(err) => Some(render_error(&err, self.format)),
+        }
+    }
+
+    /// Feed every line of `input`, writing results to `output`
+    pub fn run_lines(&mut self, input: impl BufRead, mut output: impl Write) -> Result<()> {
+        for line in input.lines() {
+            if let Some(result) = self.handle_line(&line?) {
+                writeln!(output, "{}", result)?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Feed every line of a datagram, returning the results to send back
+    pub fn handle_datagram(&mut self, datagram: &[u8]) -> String {
+        String::from_utf8_lossy(datagram)
+            .lines()
+            .filter_map(|line| self.handle_line(line))
+            .map(|result| result + "\n")
+            .collect()
+    }
+
+    /// One-line summary of the run
+    pub fn summary(&self) -> String {
+        let mean = self.total_latency.as_secs_f64() * 1e6 / self.steps.max(1) as f64;
+        format!(
+            "{} steps, {} invariant violations, latency mean {:.3} us, max {:.3} us",
+            self.steps,
+            self.violations,
+            mean,
+            self.max_latency.as_secs_f64() * 1e6
+        )
+    }
+}
+
+/// Where observations come from
+#[derive(Debug, Clone)]
+pub enum Source<'a> {
+    Stdin,
+    /// A serial device (configure baud rate and line discipline with `stty` first)
+    Serial(&'a Path),
+    /// UDP datagrams of one or more lines; results go back to the sender
+    Udp(SocketAddr),
+}
+
+/// Load `weights` at `obs_dim`/`action_dim` and drive it from `source` until it closes
+pub fn run(
+    weights: &Path,
+    obs_dim: usize,
+    action_dim: usize,
+    format: Format,
+    source: Source<'_>,
+) -> Result<()> {
+    let env = new_env(&read_weights(weights)?, obs_dim, action_dim)
+        .with_context(|| format!("Invalid weights {}", weights.display()))?;
+    let mut runner = Runner::new(env, format);
+    let stdout = std::io::stdout();
+
+    match source {
+        Source::Stdin => runner.run_lines(std::io::stdin().lock(), stdout.lock())?,
+        Source::Serial(device) => {
+            let port = std::fs::File::open(device)
+                .with_context(|| format!("Failed to open {}", device.display()))?;
+            runner.run_lines(std::io::BufReader::new(port), stdout.lock())?;
+        }
+        Source::Udp(addr) => {
+            let socket =
+                UdpSocket::bind(addr).with_context(|| format!("Failed to bind {}", addr))?;
+            eprintln!("Listening on {}", socket.local_addr()?);
+            let mut buf = vec![0u8; MAX_DATAGRAM];
+            loop {
+                let (len, peer) = socket.recv_from(&mut buf)?;
+                let results = runner.handle_datagram(&buf[..len]);
+                if !results.is_empty() {
+                    print!("{}", results);
+                    stdout.lock().flush()?;
+                    socket.send_to(results.as_bytes(), peer)?;
+                }
+            }
+        }
+    }
+
+    eprintln!("{}", runner.summary());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leanrl_core::weights::linear_fa_weights;
+
+    fn runner(format: Format) -> Runner {
+        let weights = linear_fa_weights(2, 1, &[0.5, 0.0], &[0.0], 0.01).unwrap();
+        Runner::new(new_env(&weights, 2, 1).unwrap(), format)
+    }
+
+    #[test]
+    fn test_parse_csv_and_json_lines() {
+        assert_eq!(
+            parse_line(" 0.5, -1 ", Format::Csv).unwrap(),
+            Some(Input::Observation(vec![0.5, -1.0]))
+        );
+        assert_eq!(
+            parse_line("RESET", Format::Csv).unwrap(),
+            Some(Input::Reset)
+        );
+        assert_eq!(parse_line("# header", Format::Csv).unwrap(), None);
+        assert!(parse_line("0.5,x", Format::Csv).is_err());
+
+        assert_eq!(
+            parse_line("[0.5, -1]", Format::Json).unwrap(),
+            Some(Input::Observation(vec![0.5, -1.0]))
+        );
+        assert_eq!(
+            parse_line(r#"{"obs": [1, 2], "t": 7}"#, Format::Json).unwrap(),
+            Some(Input::Observation(vec![1.0, 2.0]))
+        );
+        assert_eq!(
+            parse_line(r#""reset""#, Format::Json).unwrap(),
+            Some(Input::Reset)
+        );
+        assert!(parse_line(r#"{"obs": "x"}"#, Format::Json).is_err());
+    }
+
+    #[test]
+    fn test_run_lines_resets_steps_and_reports_violations() {
+        let mut runner = runner(Format::Csv);
+        let input = "1,0\n0,0\n\nreset\ninf,0\n1\n";
+        let mut output = Vec::new();
+        runner.run_lines(input.as_bytes(), &mut output).unwrap();
+
+        let lines: Vec<Vec<String>> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| line.split(',').map(str::to_string).collect())
+            .collect();
+        assert_eq!(lines.len(), 4);
+
+        // episode, step, latency, verdict, action
+        assert_eq!(lines[0][..2], ["1", "0"]);
+        assert_eq!(lines[0][3], "ok");
+        assert_eq!(lines[0][4].parse::<f32>().unwrap(), 0.5f32.tanh());
+        assert_eq!(lines[1][..2], ["1", "1"]);
+        assert_eq!(lines[2][..2], ["2", "0"]);
+        assert_eq!(lines[2][3], "finite_observation");
+        assert!(lines[3][0].starts_with("error: "), "{:?}", lines[3]);
+
+        assert_eq!(runner.steps, 3);
+        assert_eq!(runner.violations, 1);
+        assert!(runner
+            .summary()
+            .starts_with("3 steps, 1 invariant violations"));
+    }
+
+    #[test]
+    fn test_datagram_results_are_json_lines() {
+        let mut runner = runner(Format::Json);
+        let results = runner.handle_datagram(b"[0, 0]\n{\"obs\": [1, 0]}\n[1]");
+
+        let values: Vec<serde_json::Value> = results
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[0]["step"], 0);
+        assert_eq!(values[0]["invariant"], "ok");
+        assert_eq!(values[1]["step"], 1);
+        assert!(values[1]["latency_us"].as_f64().unwrap() >= 0.0);
+        assert!(values[2]["error"].as_str().unwrap().contains("size"));
+    }
+}
@@ -0,0 +1,175 @@
+//! Named tensors and hyperparameters of a decoded weights blob
+
+use leanrl_core::weights::{DecodedWeights, DenseLayer};
+
+/// One parameter array of a policy; `values` are row-major over `shape`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tensor {
+    pub name: String,
+    pub shape: Vec<usize>,
+    pub values: Vec<f32>,
+}
+
+impl Tensor {
+    /// `shape` as `64x32`
+    pub fn shape_label(&self) -> String {
+        self.shape
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<_>>()
+            .join("x")
+    }
+}
+
+/// Tensors in blob order: `q_table`; `weights`, `bias`; or `layerN.weights`, `layerN.bias`
+pub fn tensors(decoded: &DecodedWeights) -> Vec<Tensor> {
+    match decoded {
+        DecodedWeights::TabularQLearning {
+            num_states,
+            num_actions,
+            q_table,
+            ..
+        } => vec![Tensor {
+            name: "q_table".to_string(),
+            shape: vec![*num_states, *num_actions],
+            values: q_table.clone(),
+        }],
+        DecodedWeights::LinearFA { weights, bias, .. } => vec![
+            Tensor {
+                name: "weights".to_string(),
+                shape: vec![bias.len(), weights.len() / bias.len().max(1)],
+                values: weights.clone(),
+            },
+            Tensor {
+                name: "bias".to_string(),
+                shape: vec![bias.len()],
+                values: bias.clone(),
+            },
+        ],
+        DecodedWeights::TinyNN { layers } => layers
+            .iter()
+            .enumerate()
+            .flat_map(|(i, DenseLayer { weights, bias, .. })| {
+                [
+                    Tensor {
+                        name: format!("layer{}.weights", i),
+                        shape: vec![bias.len(), weights.len() / bias.len().max(1)],
+                        values: weights.clone(),
+                    },
+                    Tensor {
+                        name: format!("layer{}.bias", i),
+                        shape: vec![bias.len()],
+                        values: bias.clone(),
+                    },
+                ]
+            })
+            .collect(),
+    }
+}
+
+/// Scalar hyperparameters stored in the blob header
+pub fn hyperparameters(decoded: &DecodedWeights) -> Vec<(&'static str, f32)> {
+    match decoded {
+        DecodedWeights::TabularQLearning { alpha, gamma, .. } => {
+            vec![("alpha", *alpha), ("gamma", *gamma)]
+        }
+        DecodedWeights::LinearFA { learning_rate, .. } => vec![("learning_rate", *learning_rate)],
+        DecodedWeights::TinyNN { .. } => Vec::new(),
+    }
+}
+
+/// Summary statistics of a tensor; min/max/mean/std/L2 cover the finite values only
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub std: f32,
+    pub l2: f32,
+    /// Largest finite `|value|`
+    pub max_abs: f32,
+    /// NaN and infinite values
+    pub non_finite: usize,
+}
+
+impl Stats {
+    pub fn of(values: &[f32]) -> Self {
+        let finite: Vec<f64> = values
+            .iter()
+            .filter(|v| v.is_finite())
+            .map(|&v| v as f64)
+            .collect();
+        let n = finite.len().max(1) as f64;
+        let mean = finite.iter().sum::<f64>() / n;
+        let variance = finite.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        let fold = |init: f64, f: fn(f64, f64) -> f64| {
+            if finite.is_empty() {
+                0.0
+            } else {
+                finite.iter().copied().fold(init, f)
+            }
+        };
+
+        Self {
+            min: fold(f64::INFINITY, f64::min) as f32,
+            max: fold(f64::NEG_INFINITY, f64::max) as f32,
+            mean: mean as f32,
+            std: variance.sqrt() as f32,
+            l2: finite.iter().map(|v| v * v).sum::<f64>().sqrt() as f32,
+            max_abs: finite.iter().fold(0.0, |acc: f64, v| acc.max(v.abs())) as f32,
+            non_finite: values.len() - finite.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leanrl_core::algorithms::tiny_nn::ActivationFunction;
+
+    #[test]
+    fn test_stats_skip_non_finite_values() {
+        let stats = Stats::of(&[3.0, -4.0, f32::NAN, f32::INFINITY]);
+        assert_eq!(stats.min, -4.0);
+        assert_eq!(stats.max, 3.0);
+        assert_eq!(stats.mean, -0.5);
+        assert_eq!(stats.std, 3.5);
+        assert_eq!(stats.l2, 5.0);
+        assert_eq!(stats.max_abs, 4.0);
+        assert_eq!(stats.non_finite, 2);
+
+        assert_eq!(Stats::of(&[]).max, 0.0);
+    }
+
+    #[test]
+    fn test_tiny_nn_tensor_names_and_shapes() {
+        let decoded = DecodedWeights::TinyNN {
+            layers: vec![
+                DenseLayer {
+                    weights: vec![0.0; 6],
+                    bias: vec![0.0; 2],
+                    activation: ActivationFunction::ReLU,
+                },
+                DenseLayer {
+                    weights: vec![0.0; 2],
+                    bias: vec![0.0; 1],
+                    activation: ActivationFunction::Tanh,
+                },
+            ],
+        };
+        let labels: Vec<_> = tensors(&decoded)
+            .iter()
+            .map(|tensor| format!("{} {}", tensor.name, tensor.shape_label()))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                "layer0.weights 2x3",
+                "layer0.bias 2",
+                "layer1.weights 1x2",
+                "layer1.bias 1"
+            ]
+        );
+        assert!(hyperparameters(&decoded).is_empty());
+    }
+}
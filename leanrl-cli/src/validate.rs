@@ -0,0 +1,183 @@
+use crate::inspect::read_weights;
+use crate::tensors::{hyperparameters, tensors, Stats};
+use anyhow::{bail, Context, Result};
+use leanrl_core::{dims::new_env, weights::decode_weights};
+use std::path::Path;
+
+/// Default bound on `|weight|`; trained policies sit far below it, so anything above is a
+/// diverged run or a corrupt export
+pub const DEFAULT_MAX_ABS: f32 = 1.0e3;
+
+/// One value check that failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// NaN or infinite values in a tensor or hyperparameter
+    NonFinite { name: String, count: usize },
+    /// Finite values above the magnitude bound
+    TooLarge {
+        name: String,
+        count: usize,
+        max_abs: f32,
+    },
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonFinite { name, count } => write!(f, "{}: {} NaN/Inf values", name, count),
+            Self::TooLarge {
+                name,
+                count,
+                max_abs,
+            } => write!(
+                f,
+                "{}: {} values above the bound (max |w| = {:e})",
+                name, count, max_abs
+            ),
+        }
+    }
+}
+
+/// Load weights through `Env::from_weights` at `obs_dim`/`action_dim` and check every
+/// parameter is finite and at most `max_abs` in magnitude
+///
+/// A blob the runtime rejects is an error; value problems are returned for reporting.
+pub fn validate_weights(
+    weights: &[u8],
+    obs_dim: usize,
+    action_dim: usize,
+    max_abs: f32,
+) -> Result<Vec<Problem>> {
+    new_env(weights, obs_dim, action_dim)?;
+    let decoded = decode_weights(weights, obs_dim, action_dim)?;
+
+    let scalars = hyperparameters(&decoded)
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), vec![value]));
+    let arrays = tensors(&decoded)
+        .into_iter()
+        .map(|tensor| (tensor.name, tensor.values));
+
+    let mut problems = Vec::new();
+    for (name, values) in scalars.chain(arrays) {
+        let stats = Stats::of(&values);
+        if stats.non_finite > 0 {
+            problems.push(Problem::NonFinite {
+                name: name.clone(),
+                count: stats.non_finite,
+            });
+        }
+        let count = values
+            .iter()
+            .filter(|v| v.is_finite() && v.abs() > max_abs)
+            .count();
+        if count > 0 {
+            problems.push(Problem::TooLarge {
+                name,
+                count,
+                max_abs: stats.max_abs,
+            });
+        }
+    }
+    Ok(problems)
+}
+
+/// `validate_weights` for a file, failing with every problem found
+pub fn validate_file(path: &Path, obs_dim: usize, action_dim: usize, max_abs: f32) -> Result<()> {
+    let weights = read_weights(path)?;
+    let problems = validate_weights(&weights, obs_dim, action_dim, max_abs)
+        .with_context(|| format!("Invalid weights {}", path.display()))?;
+
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("  {}", problem);
+        }
+        bail!(
+            "{} failed validation ({} problems)",
+            path.display(),
+            problems.len()
+        );
+    }
+
+    println!(
+        "{}: OK ({}x{}, all values finite and |w| <= {:e})",
+        path.display(),
+        obs_dim,
+        action_dim,
+        max_abs
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leanrl_core::weights::{linear_fa_weights, tabular_q_weights};
+
+    #[test]
+    fn test_validate_flags_non_finite_and_large_values() {
+        let clean = linear_fa_weights(2, 1, &[0.5, -0.5], &[0.1], 0.01).unwrap();
+        assert!(validate_weights(&clean, 2, 1, DEFAULT_MAX_ABS)
+            .unwrap()
+            .is_empty());
+
+        let bad = linear_fa_weights(2, 1, &[f32::NAN, 2e3], &[-5e3], f32::INFINITY).unwrap();
+        let problems = validate_weights(&bad, 2, 1, DEFAULT_MAX_ABS).unwrap();
+        assert_eq!(
+            problems,
+            [
+                Problem::NonFinite {
+                    name: "learning_rate".to_string(),
+                    count: 1
+                },
+                Problem::NonFinite {
+                    name: "weights".to_string(),
+                    count: 1
+                },
+                Problem::TooLarge {
+                    name: "weights".to_string(),
+                    count: 1,
+                    max_abs: 2e3
+                },
+                Problem::TooLarge {
+                    name: "bias".to_string(),
+                    count: 1,
+                    max_abs: 5e3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_wrong_dims() {
+        let weights = linear_fa_weights(2, 1, &[0.5, -0.5], &[0.1], 0.01).unwrap();
+        assert!(validate_weights(&weights, 4, 2, DEFAULT_MAX_ABS).is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_malformed_tabular_blobs() {
+        let header = |num_states: u32, num_actions: u32| {
+            let mut blob = tabular_q_weights(1, 1, &[0.0], 0.1, 0.9).unwrap();
+            blob.truncate(17);
+            blob[1..5].copy_from_slice(&num_states.to_le_bytes());
+            blob[5..9].copy_from_slice(&num_actions.to_le_bytes());
+            blob
+        };
+
+        // A header claiming u32::MAX x u32::MAX states must not reach the allocator
+        let hostile = header(u32::MAX, u32::MAX);
+        // A 0-state table would panic on the first step
+        let empty = header(0, 2);
+        // Declares 100x2 but carries 3 values
+        let mut truncated = header(100, 2);
+        truncated.extend([0.5f32, 0.25, -1.0].iter().flat_map(|v| v.to_le_bytes()));
+
+        for blob in [hostile, empty, truncated] {
+            let err = validate_weights(&blob, 4, 2, DEFAULT_MAX_ABS).unwrap_err();
+            assert!(
+                err.to_string().contains("Invalid weights data at byte"),
+                "{err}"
+            );
+        }
+    }
+}
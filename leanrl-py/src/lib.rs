@@ -0,0 +1,13 @@
+//! Python bindings for the LeanEdge-RL runtime
+//!
+//! The `leanrl` extension module (built with maturin, `extension-module` feature) exposes
+//! `Env`, `Policy`, weights encoders and `decode_weights` over NumPy arrays, running the same
+//! `leanrl_core` inference code that ships on the device. Observation and action dimensions
+//! are chosen at runtime through `leanrl_core::dims`, up to `MAX_OBS_DIM` x `MAX_ACTION_DIM`.
+
+#[cfg(feature = "extension-module")]
+mod python;
+
+pub use dims::{new_env, new_policy, DynEnv, DynPolicy, MAX_ACTION_DIM, MAX_OBS_DIM};
+pub use leanrl_core::{dims, weights};
+pub use weights::{decode_weights, DecodedWeights, DenseLayer};
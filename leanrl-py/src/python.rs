@@ -0,0 +1,457 @@
+//! The `leanrl` Python module
+
+use crate::{
+    dims::{self, DynEnv, DynPolicy, MAX_ACTION_DIM, MAX_OBS_DIM},
+    weights::{self, DecodedWeights, DenseLayer},
+};
+use leanrl_core::{signed, Error};
+use numpy::{
+    ndarray::Array2, AllowTypeChange, IntoPyArray, PyArray1, PyArray2, PyArrayLike1, PyArrayLike2,
+};
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyValueError},
+    prelude::*,
+    types::{PyBytes, PyDict},
+};
+
+create_exception!(
+    leanrl,
+    LeanRLError,
+    PyException,
+    "Error raised by the LeanEdge-RL runtime"
+);
+
+fn to_py_err(err: Error) -> PyErr {
+    LeanRLError::new_err(err.to_string())
+}
+
+/// Raise `ValueError` for dimensions outside the compiled range, before touching the weights
+fn supported_dims(obs_dim: usize, action_dim: usize) -> PyResult<()> {
+    dims::check_dims(obs_dim, action_dim).map_err(|_| {
+        PyValueError::new_err(format!(
+            "Unsupported dimensions {}x{}: obs_dim must be 1..={} and action_dim 1..={}",
+            obs_dim, action_dim, MAX_OBS_DIM, MAX_ACTION_DIM
+        ))
+    })
+}
+
+/// Copy a 1-D array-like into a contiguous `Vec` in logical order
+fn vector(values: &PyArrayLike1<'_, f32, AllowTypeChange>) -> Vec<f32> {
+    values.as_array().iter().copied().collect()
+}
+
+/// Copy a 2-D array-like into a row-major `Vec`, returning it with its shape
+fn matrix(values: &PyArrayLike2<'_, f32, AllowTypeChange>) -> (Vec<f32>, usize, usize) {
+    let view = values.as_array();
+    let (rows, cols) = view.dim();
+    (view.iter().copied().collect(), rows, cols)
+}
+
+/// Copy a `(batch, obs_dim)` observation batch, returning it with its row count
+///
+/// Raises `ValueError` naming the expected shape when the rows are not `obs_dim` wide, so no
+/// row is stepped or acted on unless the whole batch fits.
+fn obs_batch(
+    obs: &PyArrayLike2<'_, f32, AllowTypeChange>,
+    obs_dim: usize,
+) -> PyResult<(Vec<f32>, usize)> {
+    let (values, rows, cols) = matrix(obs);
+    if cols != obs_dim {
+        return Err(PyValueError::new_err(format!(
+            "Expected observations of shape (batch, {}), got ({}, {})",
+            obs_dim, rows, cols
+        )));
+    }
+    Ok((values, rows))
+}
+
+/// Wrap a row-major `Vec` as a `(rows, cols)` array
+fn array2<'py>(
+    py: Python<'py>,
+    values: Vec<f32>,
+    rows: usize,
+    cols: usize,
+) -> PyResult<Bound<'py, PyArray2<f32>>> {
+    let values = Array2::from_shape_vec((rows, cols), values)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(values.into_pyarray(py))
+}
+
+/// RL environment running the exact `leanrl_core` inference code
+///
+/// Dimensions are fixed at construction; observations must have `obs_dim` values.
+#[pyclass(name = "Env", module = "leanrl", unsendable)]
+pub struct PyEnv {
+    inner: Box<dyn DynEnv>,
+}
+
+#[pymethods]
+impl PyEnv {
+    #[new]
+    #[pyo3(signature = (weights, obs_dim = 4, action_dim = 2))]
+    fn new(weights: &[u8], obs_dim: usize, action_dim: usize) -> PyResult<Self> {
+        supported_dims(obs_dim, action_dim)?;
+        let inner = dims::new_env(weights, obs_dim, action_dim).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Create from a signed weights container, verified against a 32-byte Ed25519 public key
+    #[staticmethod]
+    #[pyo3(signature = (container, public_key, obs_dim = 4, action_dim = 2))]
+    fn from_signed(
+        container: &[u8],
+        public_key: &[u8],
+        obs_dim: usize,
+        action_dim: usize,
+    ) -> PyResult<Self> {
+        supported_dims(obs_dim, action_dim)?;
+        let public_key: &[u8; signed::PUBLIC_KEY_LEN] = public_key.try_into().map_err(|_| {
+            PyValueError::new_err(format!(
+                "public_key must be {} bytes",
+                signed::PUBLIC_KEY_LEN
+            ))
+        })?;
+        let inner =
+            dims::new_signed_env(container, public_key, obs_dim, action_dim).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Start a new episode and return the first action
+    fn reset<'py>(
+        &mut self,
+        py: Python<'py>,
+        obs: PyArrayLike1<'py, f32, AllowTypeChange>,
+    ) -> PyResult<Bound<'py, PyArray1<f32>>> {
+        let mut action = vec![0.0; self.inner.dims().1];
+        self.inner
+            .reset(&vector(&obs), &mut action)
+            .map_err(to_py_err)?;
+        Ok(PyArray1::from_vec(py, action))
+    }
+
+    /// Advance one step and return the action
+    fn step<'py>(
+        &mut self,
+        py: Python<'py>,
+        obs: PyArrayLike1<'py, f32, AllowTypeChange>,
+    ) -> PyResult<Bound<'py, PyArray1<f32>>> {
+        let mut action = vec![0.0; self.inner.dims().1];
+        self.inner
+            .step(&vector(&obs), &mut action)
+            .map_err(to_py_err)?;
+        Ok(PyArray1::from_vec(py, action))
+    }
+
+    /// Step once per row of a `(batch, obs_dim)` array, returning `(batch, action_dim)`
+    ///
+    /// The shape is checked before the first row is stepped: a `ValueError` leaves the step
+    /// count and any staged weights untouched.
+    fn step_batch<'py>(
+        &mut self,
+        py: Python<'py>,
+        obs: PyArrayLike2<'py, f32, AllowTypeChange>,
+    ) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        let (obs_dim, action_dim) = self.inner.dims();
+        let (obs, rows) = obs_batch(&obs, obs_dim)?;
+        let mut actions = vec![0.0; rows * action_dim];
+        self.inner
+            .step_batch(&obs, &mut actions)
+            .map_err(to_py_err)?;
+        array2(py, actions, rows, action_dim)
+    }
+
+    /// Whether `action` for `obs` satisfies the runtime safety invariant
+    fn check_invariant(
+        &self,
+        obs: PyArrayLike1<'_, f32, AllowTypeChange>,
+        action: PyArrayLike1<'_, f32, AllowTypeChange>,
+    ) -> PyResult<bool> {
+        match self.inner.check_invariant(&vector(&obs), &vector(&action)) {
+            Ok(()) => Ok(true),
+            Err(Error::InvariantViolation(_)) => Ok(false),
+            Err(err) => Err(to_py_err(err)),
+        }
+    }
+
+    /// Hot-swap the policy; on error the live policy is unchanged
+    fn update_weights(&mut self, weights: &[u8]) -> PyResult<()> {
+        self.inner.update_weights(weights).map_err(to_py_err)
+    }
+
+    /// Restore the policy that was live before the most recent swap
+    fn rollback_weights(&mut self) -> PyResult<()> {
+        self.inner.rollback_weights().map_err(to_py_err)
+    }
+
+    /// Serialize the live policy
+    fn get_weights<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let weights = self.inner.get_weights().map_err(to_py_err)?;
+        Ok(PyBytes::new(py, &weights))
+    }
+
+    #[getter]
+    fn obs_dim(&self) -> usize {
+        self.inner.dims().0
+    }
+
+    #[getter]
+    fn action_dim(&self) -> usize {
+        self.inner.dims().1
+    }
+
+    #[getter]
+    fn algorithm(&self) -> &'static str {
+        self.inner.algorithm_name()
+    }
+
+    #[getter]
+    fn parameter_count(&self) -> usize {
+        self.inner.parameter_count()
+    }
+
+    #[getter]
+    fn step_count(&self) -> u64 {
+        self.inner.state().step_count
+    }
+
+    #[getter]
+    fn episode_count(&self) -> u64 {
+        self.inner.state().episode_count
+    }
+
+    /// Number of policy swaps (hot-swaps and rollbacks) since construction
+    #[getter]
+    fn generation(&self) -> u64 {
+        self.inner.state().weights_generation
+    }
+
+    fn __repr__(&self) -> String {
+        let (obs_dim, action_dim) = self.inner.dims();
+        format!(
+            "Env(algorithm={}, obs_dim={}, action_dim={})",
+            self.inner.algorithm_name(),
+            obs_dim,
+            action_dim
+        )
+    }
+}
+
+/// Standalone policy for stateless inference over observations
+#[pyclass(name = "Policy", module = "leanrl", unsendable)]
+pub struct PyPolicy {
+    inner: Box<dyn DynPolicy>,
+}
+
+#[pymethods]
+impl PyPolicy {
+    #[new]
+    #[pyo3(signature = (weights, obs_dim = 4, action_dim = 2))]
+    fn new(weights: &[u8], obs_dim: usize, action_dim: usize) -> PyResult<Self> {
+        supported_dims(obs_dim, action_dim)?;
+        let inner = dims::new_policy(weights, obs_dim, action_dim).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Action for a single observation
+    fn act<'py>(
+        &self,
+        py: Python<'py>,
+        obs: PyArrayLike1<'py, f32, AllowTypeChange>,
+    ) -> PyResult<Bound<'py, PyArray1<f32>>> {
+        let mut action = vec![0.0; self.inner.dims().1];
+        self.inner
+            .act(&vector(&obs), &mut action)
+            .map_err(to_py_err)?;
+        Ok(PyArray1::from_vec(py, action))
+    }
+
+    /// Actions for every row of a `(batch, obs_dim)` array, as `(batch, action_dim)`
+    fn act_batch<'py>(
+        &self,
+        py: Python<'py>,
+        obs: PyArrayLike2<'py, f32, AllowTypeChange>,
+    ) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        let (obs_dim, action_dim) = self.inner.dims();
+        let (obs, rows) = obs_batch(&obs, obs_dim)?;
+        let mut actions = vec![0.0; rows * action_dim];
+        self.inner
+            .act_batch(&obs, &mut actions)
+            .map_err(to_py_err)?;
+        array2(py, actions, rows, action_dim)
+    }
+
+    /// Serialize the policy, algorithm byte included
+    fn get_weights<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let weights = self.inner.get_weights().map_err(to_py_err)?;
+        Ok(PyBytes::new(py, &weights))
+    }
+
+    #[getter]
+    fn obs_dim(&self) -> usize {
+        self.inner.dims().0
+    }
+
+    #[getter]
+    fn action_dim(&self) -> usize {
+        self.inner.dims().1
+    }
+
+    #[getter]
+    fn algorithm(&self) -> &'static str {
+        self.inner.algorithm_name()
+    }
+
+    #[getter]
+    fn parameter_count(&self) -> usize {
+        self.inner.parameter_count()
+    }
+
+    fn __repr__(&self) -> String {
+        let (obs_dim, action_dim) = self.inner.dims();
+        format!(
+            "Policy(algorithm={}, obs_dim={}, action_dim={})",
+            self.inner.algorithm_name(),
+            obs_dim,
+            action_dim
+        )
+    }
+}
+
+/// Encode LinearFA weights from a `(action_dim, obs_dim)` matrix and `action_dim` biases
+#[pyfunction]
+#[pyo3(signature = (weights, bias, learning_rate = 0.01))]
+fn linear_fa_weights<'py>(
+    py: Python<'py>,
+    weights: PyArrayLike2<'py, f32, AllowTypeChange>,
+    bias: PyArrayLike1<'py, f32, AllowTypeChange>,
+    learning_rate: f32,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let (weights, action_dim, obs_dim) = matrix(&weights);
+    let blob =
+        weights::linear_fa_weights(obs_dim, action_dim, &weights, &vector(&bias), learning_rate)
+            .map_err(to_py_err)?;
+    Ok(PyBytes::new(py, &blob))
+}
+
+/// Encode TabularQLearning weights from a `(num_states, num_actions)` Q-table
+#[pyfunction]
+#[pyo3(signature = (q_table, alpha = 0.1, gamma = 0.9))]
+fn tabular_q_weights<'py>(
+    py: Python<'py>,
+    q_table: PyArrayLike2<'py, f32, AllowTypeChange>,
+    alpha: f32,
+    gamma: f32,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let (q_table, num_states, num_actions) = matrix(&q_table);
+    let blob = weights::tabular_q_weights(num_states, num_actions, &q_table, alpha, gamma)
+        .map_err(to_py_err)?;
+    Ok(PyBytes::new(py, &blob))
+}
+
+/// Encode TinyNN weights from `(weights, bias, activation)` layers
+///
+/// Weights are `(outputs, inputs)`; the architecture is fixed to `[obs_dim, 64, 32, action_dim]`
+/// and activations are `"relu"`, `"tanh"`, `"sigmoid"` or `"linear"`.
+#[pyfunction]
+#[allow(clippy::type_complexity)]
+fn tiny_nn_weights<'py>(
+    py: Python<'py>,
+    layers: Vec<(
+        PyArrayLike2<'py, f32, AllowTypeChange>,
+        PyArrayLike1<'py, f32, AllowTypeChange>,
+        String,
+    )>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let (Some(first), Some(last)) = (layers.first(), layers.last()) else {
+        return Err(PyValueError::new_err("TinyNN needs at least one layer"));
+    };
+    let obs_dim = first.0.as_array().dim().1;
+    let action_dim = last.0.as_array().dim().0;
+
+    let layers = layers
+        .iter()
+        .map(|(weights, bias, activation)| {
+            Ok(DenseLayer {
+                weights: matrix(weights).0,
+                bias: vector(bias),
+                activation: weights::parse_activation(activation)?,
+            })
+        })
+        .collect::<leanrl_core::Result<Vec<_>>>()
+        .map_err(to_py_err)?;
+    let blob = weights::tiny_nn_weights(obs_dim, action_dim, &layers).map_err(to_py_err)?;
+    Ok(PyBytes::new(py, &blob))
+}
+
+/// Decode a weights blob into a dict of its parameters as NumPy arrays
+///
+/// The blob is loaded by the runtime first, so it decodes exactly what would run.
+#[pyfunction]
+#[pyo3(signature = (weights, obs_dim = 4, action_dim = 2))]
+fn decode_weights<'py>(
+    py: Python<'py>,
+    weights: &[u8],
+    obs_dim: usize,
+    action_dim: usize,
+) -> PyResult<Bound<'py, PyDict>> {
+    supported_dims(obs_dim, action_dim)?;
+    let decoded = weights::decode_weights(weights, obs_dim, action_dim).map_err(to_py_err)?;
+    let dict = PyDict::new(py);
+    match decoded {
+        DecodedWeights::TabularQLearning {
+            num_states,
+            num_actions,
+            alpha,
+            gamma,
+            q_table,
+        } => {
+            dict.set_item("algorithm", "TabularQLearning")?;
+            dict.set_item("alpha", alpha)?;
+            dict.set_item("gamma", gamma)?;
+            dict.set_item("q_table", array2(py, q_table, num_states, num_actions)?)?;
+        }
+        DecodedWeights::LinearFA {
+            learning_rate,
+            weights,
+            bias,
+        } => {
+            dict.set_item("algorithm", "LinearFA")?;
+            dict.set_item("learning_rate", learning_rate)?;
+            dict.set_item("weights", array2(py, weights, action_dim, obs_dim)?)?;
+            dict.set_item("bias", PyArray1::from_vec(py, bias))?;
+        }
+        DecodedWeights::TinyNN { layers } => {
+            let sizes = weights::tiny_nn_layer_sizes(obs_dim, action_dim);
+            let layers = layers
+                .into_iter()
+                .zip(sizes.windows(2))
+                .map(|(layer, io)| {
+                    Ok((
+                        array2(py, layer.weights, io[1], io[0])?,
+                        PyArray1::from_vec(py, layer.bias),
+                        weights::activation_name(layer.activation),
+                    ))
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            dict.set_item("algorithm", "TinyNN")?;
+            dict.set_item("layers", layers)?;
+        }
+    }
+    Ok(dict)
+}
+
+#[pymodule]
+fn leanrl(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyEnv>()?;
+    m.add_class::<PyPolicy>()?;
+    m.add_function(wrap_pyfunction!(linear_fa_weights, m)?)?;
+    m.add_function(wrap_pyfunction!(tabular_q_weights, m)?)?;
+    m.add_function(wrap_pyfunction!(tiny_nn_weights, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_weights, m)?)?;
+    m.add("LeanRLError", m.py().get_type::<LeanRLError>())?;
+    m.add("MAX_OBS_DIM", MAX_OBS_DIM)?;
+    m.add("MAX_ACTION_DIM", MAX_ACTION_DIM)?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
@@ -0,0 +1,98 @@
+import numpy as np
+import pytest
+
+import leanrl
+
+
+def linear_fa(obs_dim=4, action_dim=2, scale=0.1, bias=0.0):
+    weights = np.full((action_dim, obs_dim), scale, dtype=np.float32)
+    return leanrl.linear_fa_weights(weights, np.full(action_dim, bias, dtype=np.float32))
+
+
+def test_step_matches_reference_linear_fa():
+    rng = np.random.default_rng(0)
+    w = rng.normal(size=(3, 5)).astype(np.float32)
+    b = rng.normal(size=3).astype(np.float32)
+    env = leanrl.Env(leanrl.linear_fa_weights(w, b), obs_dim=5, action_dim=3)
+
+    obs = rng.normal(size=5).astype(np.float32)
+    action = env.reset(obs)
+    assert action.dtype == np.float32 and action.shape == (3,)
+    np.testing.assert_allclose(action, np.tanh(w @ obs + b), rtol=1e-5)
+    assert (env.step_count, env.episode_count) == (0, 1)
+
+
+def test_batched_step_and_policy_agree():
+    blob = linear_fa(obs_dim=4, action_dim=2)
+    env = leanrl.Env(blob)
+    policy = leanrl.Policy(blob)
+
+    obs = np.arange(12, dtype=np.float64).reshape(3, 4) / 10  # float64 is converted
+    stepped = env.step_batch(obs)
+    assert stepped.shape == (3, 2)
+    np.testing.assert_array_equal(stepped, policy.act_batch(obs))
+    np.testing.assert_array_equal(stepped[1], policy.act(obs[1]))
+    assert env.step_count == 3
+
+    with pytest.raises(ValueError, match=r"\(batch, 4\), got \(2, 3\)"):
+        env.step_batch(np.zeros((2, 3)))
+    with pytest.raises(ValueError, match=r"\(batch, 4\)"):
+        policy.act_batch(np.zeros((4, 2)))
+    assert env.step_count == 3
+
+
+def test_weights_round_trip():
+    blob = linear_fa(bias=0.25)
+    decoded = leanrl.decode_weights(blob)
+    assert decoded["algorithm"] == "LinearFA"
+    assert decoded["weights"].shape == (2, 4)
+    np.testing.assert_array_equal(decoded["bias"], [0.25, 0.25])
+    assert leanrl.linear_fa_weights(decoded["weights"], decoded["bias"]) == blob
+
+    q_table = np.arange(6, dtype=np.float32).reshape(2, 3)
+    tabular = leanrl.decode_weights(leanrl.tabular_q_weights(q_table), action_dim=3)
+    np.testing.assert_array_equal(tabular["q_table"], q_table)
+
+    layers = [
+        (np.full((64, 4), 0.01), np.zeros(64), "relu"),
+        (np.full((32, 64), 0.01), np.zeros(32), "tanh"),
+        (np.full((2, 32), 0.01), np.zeros(2), "linear"),
+    ]
+    nn = leanrl.tiny_nn_weights(layers)
+    assert leanrl.Env(nn).algorithm == "TinyNN"
+    assert [layer[2] for layer in leanrl.decode_weights(nn)["layers"]] == ["relu", "tanh", "linear"]
+
+
+def test_hot_swap_and_rollback():
+    env = leanrl.Env(linear_fa())
+    obs = np.ones(4, dtype=np.float32)
+    before = env.reset(obs)
+
+    env.update_weights(linear_fa(scale=0.0, bias=0.5))
+    assert env.generation == 1
+    np.testing.assert_allclose(env.step(obs), np.tanh([0.5, 0.5]), rtol=1e-6)
+
+    with pytest.raises(leanrl.LeanRLError):
+        env.update_weights(leanrl.tabular_q_weights(np.zeros((1, 1))))
+
+    env.rollback_weights()
+    np.testing.assert_array_equal(env.step(obs), before)
+    assert env.get_weights() == linear_fa()
+
+
+def test_invariant_and_errors():
+    env = leanrl.Env(linear_fa())
+    obs = np.ones(4, dtype=np.float32)
+    assert env.check_invariant(obs, env.reset(obs))
+    assert not env.check_invariant(obs, np.array([5.0, 0.0]))
+
+    with pytest.raises(leanrl.LeanRLError):
+        env.step(np.ones(3))
+    with pytest.raises(ValueError, match="Unsupported dimensions"):
+        leanrl.Env(linear_fa(), obs_dim=leanrl.MAX_OBS_DIM + 1)
+    with pytest.raises(ValueError, match="Unsupported dimensions"):
+        leanrl.Policy(linear_fa(), action_dim=0)
+    with pytest.raises(leanrl.LeanRLError):
+        leanrl.Env(b"")
+    with pytest.raises(ValueError):
+        leanrl.Env.from_signed(linear_fa(), b"short")
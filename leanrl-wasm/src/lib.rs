@@ -0,0 +1,185 @@
+//! WebAssembly bindings for browser HMIs and digital twins
+//!
+//! `WasmEnv` wraps `Env<LR_OBS_DIM, LR_ACTION_DIM>`, the shape the C API loads, and is exported
+//! with `wasm-bindgen` for `wasm32-unknown-unknown` (`wasm-pack build leanrl-wasm`). Errors
+//! stay `WasmError` on the Rust side and only become a JavaScript `Error` at the boundary, so
+//! the tests also run natively and under wasmtime (`--target wasm32-wasip1`).
+
+use leanrl_core::{
+    ffi::{LR_ACTION_DIM, LR_OBS_DIM},
+    signed, Action, Env, Error, Obs,
+};
+use wasm_bindgen::prelude::*;
+
+/// Runtime error, thrown to JavaScript as an `Error` carrying the runtime's message
+#[derive(Debug, Clone, PartialEq)]
+pub struct WasmError(pub Error);
+
+impl From<Error> for WasmError {
+    fn from(err: Error) -> Self {
+        Self(err)
+    }
+}
+
+impl From<WasmError> for JsValue {
+    fn from(err: WasmError) -> Self {
+        JsError::new(&err.0.to_string()).into()
+    }
+}
+
+/// Policy runtime running the same inference code as the device
+#[wasm_bindgen]
+pub struct WasmEnv {
+    env: Env<LR_OBS_DIM, LR_ACTION_DIM>,
+}
+
+#[wasm_bindgen]
+impl WasmEnv {
+    /// Load a policy from a weights blob (`Env::from_weights`)
+    #[wasm_bindgen(constructor)]
+    pub fn new(weights: &[u8]) -> Result<WasmEnv, WasmError> {
+        Ok(Self {
+            env: Env::from_weights(weights)?,
+        })
+    }
+
+    /// Load a signed weights container, verified against a 32-byte Ed25519 public key
+    #[wasm_bindgen(js_name = fromSignedWeights)]
+    pub fn from_signed_weights(container: &[u8], public_key: &[u8]) -> Result<WasmEnv, WasmError> {
+        let public_key: &[u8; signed::PUBLIC_KEY_LEN] = public_key.try_into().map_err(|_| {
+            Error::SignatureInvalid(format!(
+                "Public key must be {} bytes",
+                signed::PUBLIC_KEY_LEN
+            ))
+        })?;
+        Ok(Self {
+            env: Env::from_signed_weights(container, public_key)?,
+        })
+    }
+
+    /// Start a new episode and return the first action
+    pub fn reset(&mut self, obs: &[f32]) -> Result<Vec<f32>, WasmError> {
+        let obs = Obs::from_slice(obs)?;
+        Ok(self.env.reset(&obs).as_slice().to_vec())
+    }
+
+    /// Advance one step and return the action
+    pub fn step(&mut self, obs: &[f32]) -> Result<Vec<f32>, WasmError> {
+        let obs = Obs::from_slice(obs)?;
+        Ok(self.env.step(&obs).as_slice().to_vec())
+    }
+
+    /// Whether `action` for `obs` satisfies the runtime safety invariant
+    #[wasm_bindgen(js_name = checkInvariant)]
+    pub fn check_invariant(&self, obs: &[f32], action: &[f32]) -> Result<bool, WasmError> {
+        let obs = Obs::from_slice(obs)?;
+        let action = Action::from_slice(action)?;
+        match self.env.check_invariant(&obs, &action) {
+            Ok(()) => Ok(true),
+            Err(Error::InvariantViolation(_)) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Hot-swap the policy; on error the live policy is unchanged
+    #[wasm_bindgen(js_name = updateWeights)]
+    pub fn update_weights(&mut self, weights: &[u8]) -> Result<(), WasmError> {
+        Ok(self.env.update_weights(weights)?)
+    }
+
+    /// Restore the policy that was live before the most recent swap
+    #[wasm_bindgen(js_name = rollbackWeights)]
+    pub fn rollback_weights(&mut self) -> Result<(), WasmError> {
+        Ok(self.env.rollback_weights()?)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn algorithm(&self) -> String {
+        self.env.algorithm_name().to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = obsDim)]
+    pub fn obs_dim(&self) -> usize {
+        LR_OBS_DIM
+    }
+
+    #[wasm_bindgen(getter, js_name = actionDim)]
+    pub fn action_dim(&self) -> usize {
+        LR_ACTION_DIM
+    }
+
+    #[wasm_bindgen(getter, js_name = stepCount)]
+    pub fn step_count(&self) -> u64 {
+        self.env.state().step_count
+    }
+
+    #[wasm_bindgen(getter, js_name = episodeCount)]
+    pub fn episode_count(&self) -> u64 {
+        self.env.state().episode_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leanrl_core::weights;
+
+    fn linear_fa_weights(weight: f32, bias: f32) -> Vec<u8> {
+        weights::linear_fa_weights(
+            LR_OBS_DIM,
+            LR_ACTION_DIM,
+            &[weight; LR_OBS_DIM * LR_ACTION_DIM],
+            &[bias; LR_ACTION_DIM],
+            0.01,
+        )
+        .unwrap()
+    }
+
+    fn tabular_weights() -> Vec<u8> {
+        weights::tabular_q_weights(4, LR_ACTION_DIM, &[0.0; 4 * LR_ACTION_DIM], 0.1, 0.9).unwrap()
+    }
+
+    #[test]
+    fn test_step_and_invariant() {
+        let mut env = WasmEnv::new(&linear_fa_weights(0.1, 0.0)).unwrap();
+        let obs = [1.0; LR_OBS_DIM];
+        let action = env.reset(&obs).unwrap();
+        assert!((action[0] - 0.4f32.tanh()).abs() < 1e-6);
+        assert!(env.check_invariant(&obs, &action).unwrap());
+        assert!(!env.check_invariant(&obs, &[5.0, 0.0]).unwrap());
+
+        env.step(&obs).unwrap();
+        assert_eq!((env.step_count(), env.episode_count()), (1, 1));
+        assert_eq!(env.algorithm(), "LinearFA");
+        assert!(matches!(
+            env.step(&[1.0; 3]),
+            Err(WasmError(Error::InvalidObsSize { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_tabular_q_runs_without_a_clock() {
+        // TabularQ exploration used to read SystemTime, which panics on wasm32-unknown-unknown
+        let mut env = WasmEnv::new(&tabular_weights()).unwrap();
+        for _ in 0..16 {
+            let action = env.step(&[0.5, 0.0, 0.0, 0.0]).unwrap();
+            assert_eq!(action.iter().filter(|&&a| a == 1.0).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_hot_swap_and_errors() {
+        let mut env = WasmEnv::new(&linear_fa_weights(0.1, 0.0)).unwrap();
+        env.update_weights(&linear_fa_weights(0.0, 0.5)).unwrap();
+        let obs = [1.0; LR_OBS_DIM];
+        assert!((env.step(&obs).unwrap()[1] - 0.5f32.tanh()).abs() < 1e-6);
+        assert!(env.update_weights(&tabular_weights()).is_err());
+        env.rollback_weights().unwrap();
+
+        assert!(WasmEnv::new(&[]).is_err());
+        assert!(matches!(
+            WasmEnv::from_signed_weights(&linear_fa_weights(0.1, 0.0), &[0; 4]),
+            Err(WasmError(Error::SignatureInvalid(_)))
+        ));
+    }
+}
@@ -0,0 +1,10 @@
+#!/usr/bin/env bash
+# Fail if cshim/include/leanrl.h is stale or its functions differ from the lr_* symbols
+# exported by the compiled leanrl_core cdylib and staticlib (built with every C API feature).
+set -euo pipefail
+ROOT="${1:-.}"
+cd "$ROOT"
+cargo build --quiet -p leanrl_core --lib --features encryption,dyn-dims
+cargo run --quiet -p leanrl-bundle -- header --check \
+  --library target/debug/libleanrl_core.so \
+  --library target/debug/libleanrl_core.a
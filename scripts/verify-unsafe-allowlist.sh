@@ -4,12 +4,13 @@ set -euo pipefail
 ROOT="${1:-.}"
 cd "$ROOT"
 ALLOWED='^core/src/(ffi|simd)\.rs$'
-while IFS= read -r -d '' file; do
+# Comment lines (`//`, `//!`, `///`) may mention unsafe; code may not
+while IFS= read -r file; do
   rel="${file#./}"
   if [[ "$rel" =~ $ALLOWED ]]; then
     continue
   fi
   echo "Disallowed unsafe in: $rel (only core/src/ffi.rs and core/src/simd.rs are permitted)"
   exit 1
-done < <(grep -rlZ --include='*.rs' -E '\bunsafe\b' core/src || true)
+done < <(grep -rnE --include='*.rs' '\bunsafe\b' core/src | grep -vE '^[^:]+:[0-9]+:[[:space:]]*//' | cut -d: -f1 | sort -u || true)
 echo "Unsafe allowlist check passed."